@@ -9,23 +9,251 @@ pub struct WgConfig {
     pub address: String,
     pub listen_port: Option<String>,
     pub dns: Option<String>,
+    // DNS 搜索域,追加到 DNS 行 (wg-quick 语法)
+    #[serde(default)]
+    pub dns_search: Option<String>,
     pub peer_public_key: String,
     pub preshared_key: Option<String>,
     pub endpoint: String,
     pub allowed_ips: String,
     pub persistent_keepalive: Option<String>,
+    #[serde(default)]
+    pub mtu: Option<String>,
+    // 全局路由时从 AllowedIPs 中排除服务端地址,避免握手包进入隧道形成环路
+    #[serde(default)]
+    pub exclude_endpoint_route: bool,
+    // 每个 Peer 的限速 (Mbps),仅用于路由器配置
+    #[serde(default)]
+    pub rate_limit_up: Option<u32>,
+    #[serde(default)]
+    pub rate_limit_down: Option<u32>,
     pub peer_id: u32,
     pub peer_interface: String,
     pub peer_comment: String,
 }
 
+// 从 AllowedIPs 中排除服务端 endpoint 的主机地址
+//
+// 客户端使用 0.0.0.0/0 全局路由且与服务端处于同一局域网时,发往 endpoint 的握手包
+// 也会被路由进隧道,形成环路。各平台的处理方式:
+// - Linux wg-quick: 通过 fwmark + 策略路由自动绕过,无需处理
+// - macOS / Windows 官方客户端: 会自动为 endpoint 添加经默认网关的路由
+// - iOS / Android / 路由器以及直接使用 wireguard-go 的场景: 不会自动处理
+// 这里采用与平台无关的做法: 把包含 endpoint 的网段拆分为不包含 <endpoint_ip>/32
+// 的若干更小网段,使 endpoint 流量走系统默认网关。
+fn exclude_endpoint_from_allowed_ips(
+    allowed_ips: &str,
+    endpoint_ip: std::net::IpAddr,
+) -> Result<String, String> {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let (target, width) = match endpoint_ip {
+        IpAddr::V4(ip) => (u32::from(ip) as u128, 32u32),
+        IpAddr::V6(ip) => (u128::from(ip), 128u32),
+    };
+
+    let format_net = |bits: u128, prefix: u32| -> String {
+        let ip = if width == 32 {
+            IpAddr::V4(Ipv4Addr::from(bits as u32))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(bits))
+        };
+        format!("{}/{}", ip, prefix)
+    };
+
+    let mut result = Vec::new();
+
+    for cidr in allowed_ips
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    {
+        let parsed = cidr.split_once('/').and_then(|(ip, prefix)| {
+            Some((ip.parse::<IpAddr>().ok()?, prefix.parse::<u32>().ok()?))
+        });
+
+        let (net_bits, prefix) = match parsed {
+            Some((IpAddr::V4(ip), prefix)) if width == 32 && prefix <= 32 => {
+                (u32::from(ip) as u128, prefix)
+            }
+            Some((IpAddr::V6(ip), prefix)) if width == 128 && prefix <= 128 => {
+                (u128::from(ip), prefix)
+            }
+            // 地址族不同或无法解析的网段保持原样
+            _ => {
+                result.push(cidr.to_string());
+                continue;
+            }
+        };
+
+        let mask = |len: u32| -> u128 {
+            if len == 0 {
+                0
+            } else {
+                (!0u128 << (width - len)) & (!0u128 >> (128 - width))
+            }
+        };
+
+        // endpoint 不在该网段内,保持原样
+        if net_bits & mask(prefix) != target & mask(prefix) {
+            result.push(cidr.to_string());
+            continue;
+        }
+
+        // 逐级拆分: 每一级取不包含 endpoint 的那一半
+        for len in (prefix + 1)..=width {
+            let sibling = (target & mask(len)) ^ (1u128 << (width - len));
+            result.push(format_net(sibling, len));
+        }
+    }
+
+    Ok(result.join(", "))
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ExportFormat {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub file_extension: &'static str,
+}
+
+// 支持的配置导出格式,新增生成器时在此登记,前端据此生成导出菜单
+const EXPORT_FORMATS: [ExportFormat; 7] = [
+    ExportFormat {
+        id: "wg",
+        label: "WireGuard",
+        file_extension: "conf",
+    },
+    ExportFormat {
+        id: "surge",
+        label: "Surge",
+        file_extension: "conf",
+    },
+    ExportFormat {
+        id: "ikuai",
+        label: "爱快",
+        file_extension: "txt",
+    },
+    ExportFormat {
+        id: "mikrotik",
+        label: "MikroTik",
+        file_extension: "rsc",
+    },
+    ExportFormat {
+        id: "openwrt",
+        label: "OpenWrt",
+        file_extension: "sh",
+    },
+    ExportFormat {
+        id: "pfsense",
+        label: "pfSense",
+        file_extension: "xml",
+    },
+    ExportFormat {
+        id: "json",
+        label: "JSON",
+        file_extension: "json",
+    },
+];
+
+// 列出支持的配置导出格式
 #[command]
-pub fn generate_wg_config(config: WgConfig, _work_dir: String) -> Result<String, String> {
+pub fn list_export_formats() -> Vec<ExportFormat> {
+    EXPORT_FORMATS.to_vec()
+}
+
+// 解析 endpoint 的 IP,域名在阻塞线程池中解析,避免慢速 DNS 阻塞命令
+async fn resolve_endpoint_ip(endpoint: &str) -> Result<Option<std::net::IpAddr>, String> {
+    if endpoint.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let endpoint = endpoint.to_string();
+    let resolved = tokio::task::spawn_blocking(move || crate::tunnel::resolve_endpoint(&endpoint))
+        .await
+        .map_err(|e| format!("解析任务执行失败: {}", e))??;
+    resolved
+        .parse::<std::net::SocketAddr>()
+        .map(|addr| Some(addr.ip()))
+        .map_err(|e| format!("解析 endpoint 地址失败: {}", e))
+}
+
+// endpoint 为 IP 字面量时直接取出 IP,域名返回 None (预览时不做 DNS 解析)
+fn literal_endpoint_ip(endpoint: &str) -> Option<std::net::IpAddr> {
+    let endpoint = endpoint.trim();
+    endpoint
+        .parse::<std::net::SocketAddr>()
+        .map(|addr| addr.ip())
+        .or_else(|_| endpoint.trim_matches(['[', ']']).parse())
+        .ok()
+}
+
+#[command]
+pub async fn generate_wg_config(config: WgConfig, _work_dir: String) -> Result<String, String> {
+    let endpoint_ip = if config.exclude_endpoint_route {
+        resolve_endpoint_ip(&config.endpoint).await?
+    } else {
+        None
+    };
+    build_wg_config(&config, false, endpoint_ip).map(|preview| preview.content)
+}
+
+// 配置预览结果: 无效字段在 content 中以注释占位,并记录到 warnings
+#[derive(Serialize, Debug)]
+pub struct WgConfigPreview {
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+// 实时预览配置: 私钥未填写或字段尚未输入完整时以注释占位,不中断预览
+// 实际生成和保存仍使用 generate_wg_config 的严格校验
+// 预览不解析 endpoint 域名,只有 IP 字面量的 endpoint 会从 AllowedIPs 中排除
+#[command]
+pub fn preview_wg_config(config: WgConfig) -> Result<WgConfigPreview, String> {
+    let endpoint_ip = literal_endpoint_ip(&config.endpoint);
+    build_wg_config(&config, true, endpoint_ip)
+}
+
+// 预览时把字段错误写成注释占位并记录警告,生成时直接返回错误
+fn preview_issue(
+    preview: bool,
+    content: &mut String,
+    warnings: &mut Vec<String>,
+    field: &str,
+    error: String,
+) -> Result<(), String> {
+    if !preview {
+        return Err(error);
+    }
+    content.push_str(&format!("# {}: {}\n", field, error));
+    warnings.push(error);
+    Ok(())
+}
+
+// endpoint_ip 为已解析的 endpoint 地址,开启排除服务端地址时使用
+fn build_wg_config(
+    config: &WgConfig,
+    preview: bool,
+    endpoint_ip: Option<std::net::IpAddr>,
+) -> Result<WgConfigPreview, String> {
+    let mut warnings = Vec::new();
+
+    let public_key_line = match compute_public_key(&config.private_key) {
+        Ok(public_key) => format!("# 本地公钥 (提供给对端): {}", public_key),
+        Err(_) if preview && config.private_key.trim().is_empty() => {
+            "# 本地公钥 (提供给对端): 填写私钥后自动计算".to_string()
+        }
+        Err(e) if preview => {
+            let line = format!("# 本地公钥 (提供给对端): 无法计算 ({})", e);
+            warnings.push(e);
+            line
+        }
+        Err(e) => return Err(e),
+    };
+
     let mut content = format!(
-        "# 本地公钥 (提供给对端): {}\n\n[Interface]\nPrivateKey = {}\nAddress = {}\n",
-        compute_public_key(&config.private_key)?,
-        config.private_key,
-        config.address
+        "{}\n\n[Interface]\nPrivateKey = {}\nAddress = {}\n",
+        public_key_line, config.private_key, config.address
     );
 
     if let Some(port) = &config.listen_port {
@@ -34,11 +262,26 @@ pub fn generate_wg_config(config: WgConfig, _work_dir: String) -> Result<String,
         }
     }
 
-    if let Some(dns) = &config.dns {
-        if !dns.is_empty() {
-            content.push_str(&format!("DNS = {}\n", dns));
+    match parse_config_mtu(config) {
+        Ok(Some(mtu)) => content.push_str(&format!("MTU = {}\n", mtu)),
+        Ok(None) => {}
+        Err(e) => preview_issue(preview, &mut content, &mut warnings, "MTU", e)?,
+    }
+
+    let dns = config.dns.as_deref().unwrap_or("").trim();
+    let mut dns_search = config.dns_search.as_deref().unwrap_or("").trim();
+    if !dns_search.is_empty() {
+        if let Err(e) = crate::tunnel::validate_dns_search(dns_search) {
+            preview_issue(preview, &mut content, &mut warnings, "DNS 搜索域", e)?;
+            dns_search = "";
         }
     }
+    match (dns.is_empty(), dns_search.is_empty()) {
+        (false, false) => content.push_str(&format!("DNS = {}, {}\n", dns, dns_search)),
+        (false, true) => content.push_str(&format!("DNS = {}\n", dns)),
+        (true, false) => content.push_str(&format!("DNS = {}\n", dns_search)),
+        (true, true) => {}
+    }
 
     content.push_str(&format!(
         "\n[Peer]\nPublicKey = {}\n",
@@ -51,24 +294,105 @@ pub fn generate_wg_config(config: WgConfig, _work_dir: String) -> Result<String,
         }
     }
 
+    let allowed_ips = match endpoint_ip.filter(|_| config.exclude_endpoint_route) {
+        Some(endpoint_ip) => {
+            match exclude_endpoint_from_allowed_ips(&config.allowed_ips, endpoint_ip) {
+                Ok(allowed_ips) => allowed_ips,
+                Err(e) => {
+                    preview_issue(preview, &mut content, &mut warnings, "AllowedIPs", e)?;
+                    config.allowed_ips.clone()
+                }
+            }
+        }
+        None => {
+            if preview && config.exclude_endpoint_route && !config.endpoint.trim().is_empty() {
+                content.push_str("# Endpoint 为域名,预览未排除服务端地址,生成时解析后排除\n");
+            }
+            config.allowed_ips.clone()
+        }
+    };
+
     content.push_str(&format!(
         "Endpoint = {}\nAllowedIPs = {}\n",
-        config.endpoint, config.allowed_ips
+        config.endpoint, allowed_ips
     ));
 
-    if let Some(keepalive) = &config.persistent_keepalive {
-        if !keepalive.is_empty() {
-            content.push_str(&format!("PersistentKeepalive = {}\n", keepalive));
+    match crate::tunnel::parse_keepalive(config.persistent_keepalive.as_deref().unwrap_or("")) {
+        Ok(Some(keepalive)) => content.push_str(&format!("PersistentKeepalive = {}\n", keepalive)),
+        Ok(None) => {}
+        Err(e) => preview_issue(
+            preview,
+            &mut content,
+            &mut warnings,
+            "PersistentKeepalive",
+            e,
+        )?,
+    }
+
+    Ok(WgConfigPreview { content, warnings })
+}
+
+// 解析配置中的 MTU,未填写时返回 None
+fn parse_config_mtu(config: &WgConfig) -> Result<Option<u16>, String> {
+    match config
+        .mtu
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+    {
+        Some(mtu) => {
+            let value: u16 = mtu.parse().map_err(|_| format!("MTU 无效: {}", mtu))?;
+            if value < 576 {
+                return Err(format!("MTU 过小 (最小 576): {}", value));
+            }
+            Ok(Some(value))
         }
+        None => Ok(None),
     }
+}
 
-    Ok(content)
+// 检查生成配置的 MTU 在小 MTU 链路上是否可能分片,返回警告列表
+// endpoint 为域名时在阻塞线程池中解析,按解析出的地址族判断
+#[command]
+pub async fn check_config_mtu(config: WgConfig) -> Vec<String> {
+    let mtu = config.mtu.unwrap_or_default();
+    if mtu.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let endpoint = config.endpoint;
+    let endpoint_ip = tokio::task::spawn_blocking(move || crate::tunnel::endpoint_ip(&endpoint))
+        .await
+        .ok()
+        .flatten();
+    crate::tunnel::check_mtu(&mtu, endpoint_ip.as_slice())
+        .into_iter()
+        .collect()
+}
+
+// 校验限速值 (Mbps),必须为正数
+fn validate_rate_limits(config: &WgConfig) -> Result<(), String> {
+    if config.rate_limit_up == Some(0) {
+        return Err("上传限速必须大于 0".to_string());
+    }
+    if config.rate_limit_down == Some(0) {
+        return Err("下载限速必须大于 0".to_string());
+    }
+    Ok(())
 }
 
 #[command]
 pub fn generate_ikuai_config(config: WgConfig, _work_dir: String) -> Result<String, String> {
     let public_key = compute_public_key(&config.private_key)?;
 
+    // 爱快的 WireGuard Peer 导入格式没有限速字段,设置了限速时直接拒绝,避免限速被静默忽略
+    if config.rate_limit_up.is_some() || config.rate_limit_down.is_some() {
+        return Err(format!(
+            "爱快的 WireGuard Peer 配置不支持限速，请在流控分流中为 {} 单独设置",
+            config.address
+        ));
+    }
+
     let psk = config.preshared_key.unwrap_or_default();
     let keepalive = config
         .persistent_keepalive
@@ -105,7 +429,16 @@ pub fn generate_surge_config(config: WgConfig, _work_dir: String) -> Result<Stri
     surge_config.push_str(&format!("[WireGuard {}]\n", section_name));
     surge_config.push_str(&format!("private-key = {}\n", config.private_key));
     surge_config.push_str(&format!("self-ip = {}\n", self_ip));
-    surge_config.push_str("mtu = 1280\n");
+
+    // 未指定 MTU 时沿用 Surge 推荐的 1280
+    let mtu = parse_config_mtu(&config)?.unwrap_or(1280);
+    surge_config.push_str(&format!("mtu = {}\n", mtu));
+
+    if let Some(dns) = &config.dns {
+        if !dns.trim().is_empty() {
+            surge_config.push_str(&format!("dns-server = {}\n", dns.trim()));
+        }
+    }
 
     let mut peer_config = format!("peer = (public-key = {}", config.peer_public_key);
 
@@ -118,10 +451,10 @@ pub fn generate_surge_config(config: WgConfig, _work_dir: String) -> Result<Stri
         }
     }
 
-    if let Some(keepalive) = &config.persistent_keepalive {
-        if !keepalive.is_empty() {
-            peer_config.push_str(&format!(", keepalive = {}", keepalive));
-        }
+    if let Some(keepalive) =
+        crate::tunnel::parse_keepalive(config.persistent_keepalive.as_deref().unwrap_or(""))?
+    {
+        peer_config.push_str(&format!(", keepalive = {}", keepalive));
     }
 
     peer_config.push_str(")\n");
@@ -150,6 +483,23 @@ pub fn generate_mikrotik_config(config: WgConfig, _work_dir: String) -> Result<S
         }
     }
 
+    // 使用简单队列限速, max-limit 格式为 上传/下载,0 表示不限制
+    validate_rate_limits(&config)?;
+    if config.rate_limit_up.is_some() || config.rate_limit_down.is_some() {
+        let to_limit = |rate: Option<u32>| {
+            rate.map(|r| format!("{}M", r))
+                .unwrap_or_else(|| "0".to_string())
+        };
+        command.push_str(&format!(
+            "\n/queue/simple/add \\\n  name=\"wg-peer-{}\" \\\n  target={} \\\n  max-limit={}/{} \\\n  comment=\"{}\"",
+            config.peer_id,
+            allowed_address,
+            to_limit(config.rate_limit_up),
+            to_limit(config.rate_limit_down),
+            config.peer_comment
+        ));
+    }
+
     Ok(command)
 }
 
@@ -201,3 +551,408 @@ pub fn generate_openwrt_config(config: WgConfig, _work_dir: String) -> Result<St
 
     Ok(commands)
 }
+
+// 转义 XML 特殊字符
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// 生成 pfSense WireGuard 插件的 Peer 配置 (config.xml 中 <peers> 下的 <item>)
+#[command]
+pub fn generate_pfsense_config(config: WgConfig, _work_dir: String) -> Result<String, String> {
+    let public_key = compute_public_key(&config.private_key)?;
+
+    let mut xml = String::new();
+
+    xml.push_str("<item>\n");
+    xml.push_str("\t<enabled>yes</enabled>\n");
+    xml.push_str(&format!(
+        "\t<tun>{}</tun>\n",
+        escape_xml(&config.peer_interface)
+    ));
+    xml.push_str(&format!(
+        "\t<descr>{}</descr>\n",
+        escape_xml(&config.peer_comment)
+    ));
+
+    if let Some(keepalive) = &config.persistent_keepalive {
+        if !keepalive.is_empty() {
+            xml.push_str(&format!(
+                "\t<persistentkeepalive>{}</persistentkeepalive>\n",
+                escape_xml(keepalive)
+            ));
+        }
+    }
+
+    xml.push_str(&format!("\t<publickey>{}</publickey>\n", public_key));
+
+    if let Some(psk) = &config.preshared_key {
+        if !psk.is_empty() {
+            xml.push_str(&format!(
+                "\t<presharedkey>{}</presharedkey>\n",
+                escape_xml(psk)
+            ));
+        }
+    }
+
+    // pfSense 将允许的 IP 拆分为地址和掩码长度分别保存
+    xml.push_str("\t<allowedips>\n");
+    for cidr in config
+        .address
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    {
+        let (address, mask) = match cidr.split_once('/') {
+            Some((address, mask)) => (address, mask.to_string()),
+            None if cidr.contains(':') => (cidr, "128".to_string()),
+            None => (cidr, "32".to_string()),
+        };
+        xml.push_str("\t\t<row>\n");
+        xml.push_str(&format!(
+            "\t\t\t<address>{}</address>\n",
+            escape_xml(address)
+        ));
+        xml.push_str(&format!("\t\t\t<mask>{}</mask>\n", escape_xml(&mask)));
+        xml.push_str("\t\t\t<descr></descr>\n");
+        xml.push_str("\t\t</row>\n");
+    }
+    xml.push_str("\t</allowedips>\n");
+    xml.push_str("</item>");
+
+    Ok(xml)
+}
+
+// 供自动化脚本使用的 Peer JSON 导出格式,空的可选字段不输出
+#[derive(Serialize, Debug)]
+pub struct PeerJsonConfig {
+    pub public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preshared_key: Option<String>,
+    pub allowed_ips: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keepalive: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub peer_id: u32,
+}
+
+#[command]
+pub fn generate_json_config(config: WgConfig) -> Result<String, String> {
+    let non_empty = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+
+    let peer = PeerJsonConfig {
+        public_key: compute_public_key(&config.private_key)?,
+        preshared_key: config.preshared_key.as_deref().and_then(non_empty),
+        allowed_ips: config.address.clone(),
+        endpoint: non_empty(&config.endpoint),
+        keepalive: crate::tunnel::parse_keepalive(
+            config.persistent_keepalive.as_deref().unwrap_or(""),
+        )?
+        .filter(|k| *k > 0),
+        comment: non_empty(&config.peer_comment),
+        peer_id: config.peer_id,
+    };
+
+    serde_json::to_string_pretty(&peer).map_err(|e| format!("序列化 JSON 配置失败: {}", e))
+}
+
+// 解析 wg-quick 格式的配置文件 (只读取第一个 [Peer])
+#[command]
+pub fn import_wg_conf(content: String) -> Result<WgConfig, String> {
+    let mut config = WgConfig {
+        interface_name: String::new(),
+        private_key: String::new(),
+        address: String::new(),
+        listen_port: None,
+        dns: None,
+        dns_search: None,
+        peer_public_key: String::new(),
+        preshared_key: None,
+        endpoint: String::new(),
+        allowed_ips: String::new(),
+        persistent_keepalive: None,
+        mtu: None,
+        exclude_endpoint_route: false,
+        rate_limit_up: None,
+        rate_limit_down: None,
+        peer_id: 0,
+        peer_interface: String::new(),
+        peer_comment: String::new(),
+    };
+
+    let mut section = String::new();
+    let mut peer_count = 0;
+
+    for (line_no, raw_line) in content.lines().enumerate() {
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_ascii_lowercase();
+            if section == "peer" {
+                peer_count += 1;
+            }
+            continue;
+        }
+
+        // 多个 Peer 时只导入第一个
+        if section == "peer" && peer_count > 1 {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("第 {} 行格式错误: {}", line_no + 1, raw_line.trim()))?;
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+
+        match (section.as_str(), key.as_str()) {
+            ("interface", "privatekey") => config.private_key = value,
+            ("interface", "address") => config.address = value,
+            ("interface", "listenport") => config.listen_port = Some(value),
+            ("interface", "dns") => {
+                // wg-quick 将 DNS 行中的非 IP 项视为搜索域
+                let (servers, domains) = crate::tunnel::split_dns_line(&value);
+                config.dns = Some(servers.join(", ")).filter(|v| !v.is_empty());
+                config.dns_search = Some(domains.join(", ")).filter(|v| !v.is_empty());
+            }
+            ("interface", "mtu") => config.mtu = Some(value),
+            ("peer", "publickey") => config.peer_public_key = value,
+            ("peer", "presharedkey") => config.preshared_key = Some(value),
+            ("peer", "endpoint") => config.endpoint = value,
+            ("peer", "allowedips") => config.allowed_ips = value,
+            ("peer", "persistentkeepalive") => config.persistent_keepalive = Some(value),
+            ("interface", _) | ("peer", _) => {
+                log::info!("忽略不支持的配置项: {}", key);
+            }
+            _ => return Err(format!("第 {} 行不在任何配置段中", line_no + 1)),
+        }
+    }
+
+    if config.private_key.is_empty() {
+        return Err("配置缺少 [Interface] PrivateKey".to_string());
+    }
+
+    if peer_count == 0 {
+        return Err("配置缺少 [Peer] 段".to_string());
+    }
+
+    if peer_count > 1 {
+        log::warn!("配置包含 {} 个 Peer，仅导入第一个", peer_count);
+    }
+
+    Ok(config)
+}
+
+// 校验生成的配置能否被重新导入且 [Interface] 和 [Peer] 的所有字段保持一致
+// 一致时返回 true,否则返回第一个不一致的字段
+#[command]
+pub fn verify_config_roundtrip(config: WgConfig) -> Result<bool, String> {
+    // 排除 endpoint 路由会改写 AllowedIPs,校验时按原始网段生成
+    let mut config = config;
+    config.exclude_endpoint_route = false;
+
+    let content = build_wg_config(&config, false, None)?.content;
+    let imported = import_wg_conf(content)?;
+
+    // 空字符串与 None 视为相同
+    fn normalize(value: Option<&str>) -> String {
+        value.map(|v| v.trim().to_string()).unwrap_or_default()
+    }
+
+    // DNS 服务器和搜索域按列表比较,忽略分隔符差异
+    fn normalize_dns(value: Option<&str>) -> (String, String) {
+        let (servers, domains) = crate::tunnel::split_dns_line(value.unwrap_or(""));
+        (servers.join(", "), domains.join(", "))
+    }
+
+    let fields = [
+        (
+            "PrivateKey",
+            normalize(Some(&config.private_key)),
+            normalize(Some(&imported.private_key)),
+        ),
+        (
+            "Address",
+            normalize(Some(&config.address)),
+            normalize(Some(&imported.address)),
+        ),
+        (
+            "ListenPort",
+            normalize(config.listen_port.as_deref()),
+            normalize(imported.listen_port.as_deref()),
+        ),
+        (
+            "DNS",
+            normalize_dns(config.dns.as_deref()).0,
+            normalize_dns(imported.dns.as_deref()).0,
+        ),
+        (
+            "DNS 搜索域",
+            normalize_dns(config.dns_search.as_deref()).1,
+            normalize_dns(imported.dns_search.as_deref()).1,
+        ),
+        (
+            "MTU",
+            // 生成时按数值写出 (例如 "01420" 写为 1420),按解析后的值比较
+            parse_config_mtu(&config)?
+                .map(|mtu| mtu.to_string())
+                .unwrap_or_default(),
+            normalize(imported.mtu.as_deref()),
+        ),
+        (
+            "PublicKey",
+            normalize(Some(&config.peer_public_key)),
+            normalize(Some(&imported.peer_public_key)),
+        ),
+        (
+            "PresharedKey",
+            normalize(config.preshared_key.as_deref()),
+            normalize(imported.preshared_key.as_deref()),
+        ),
+        (
+            "Endpoint",
+            normalize(Some(&config.endpoint)),
+            normalize(Some(&imported.endpoint)),
+        ),
+        (
+            "AllowedIPs",
+            normalize(Some(&config.allowed_ips)),
+            normalize(Some(&imported.allowed_ips)),
+        ),
+        (
+            "PersistentKeepalive",
+            normalize(config.persistent_keepalive.as_deref()),
+            normalize(imported.persistent_keepalive.as_deref()),
+        ),
+    ];
+
+    for (name, expected, actual) in fields {
+        if expected != actual {
+            return Err(format!(
+                "字段 {} 不一致: 期望 \"{}\", 实际 \"{}\"",
+                name, expected, actual
+            ));
+        }
+    }
+
+    Ok(true)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MeshMember {
+    pub name: String,
+    pub address: String, // 成员的 VPN 地址,例如 10.0.0.1/24
+    #[serde(default)]
+    pub endpoint: String, // 其他成员连接该成员的地址 (host:port),为空表示无公网地址
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MeshConfig {
+    pub name: String,
+    pub conf: String,
+}
+
+// 生成全互联 (mesh) 配置: 每个成员生成一对密钥,配置中包含其他所有成员
+#[command]
+pub fn generate_mesh(members: Vec<MeshMember>) -> Result<Vec<MeshConfig>, String> {
+    if members.len() < 2 {
+        return Err("至少需要 2 个成员".to_string());
+    }
+
+    let mut names = std::collections::HashSet::new();
+    let mut hosts = std::collections::HashSet::new();
+    let mut prepared = Vec::with_capacity(members.len());
+
+    for member in &members {
+        let name = member.name.trim();
+        if name.is_empty() {
+            return Err("成员名称不能为空".to_string());
+        }
+        if !names.insert(name) {
+            return Err(format!("成员名称重复: {}", name));
+        }
+
+        // 其他成员通过 /32 (IPv6 为 /128) 路由到该成员
+        let address = member.address.trim();
+        let ip: std::net::IpAddr = address
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .parse()
+            .map_err(|_| format!("成员 {} 的地址无效: {}", name, address))?;
+        if !hosts.insert(ip) {
+            return Err(format!("成员地址重复: {}", ip));
+        }
+        let host_route = if ip.is_ipv4() {
+            format!("{}/32", ip)
+        } else {
+            format!("{}/128", ip)
+        };
+
+        let endpoint = member.endpoint.trim();
+        let listen_port = if endpoint.is_empty() {
+            None
+        } else {
+            Some(
+                crate::tunnel::parse_endpoint_port(endpoint)
+                    .map_err(|e| format!("成员 {} 的 {}", name, e))?,
+            )
+        };
+
+        let keypair = crate::commands::key_management::generate_keypair()?;
+        prepared.push((member, host_route, listen_port, keypair));
+    }
+
+    let configs = prepared
+        .iter()
+        .map(|(member, _, listen_port, keypair)| {
+            let mut conf = format!(
+                "# {} (公钥: {})\n\n[Interface]\nPrivateKey = {}\nAddress = {}\n",
+                member.name.trim(),
+                keypair.public_key,
+                keypair.private_key,
+                member.address.trim()
+            );
+            if let Some(port) = listen_port {
+                conf.push_str(&format!("ListenPort = {}\n", port));
+            }
+
+            for (other, host_route, _, other_keypair) in &prepared {
+                if other.name == member.name {
+                    continue;
+                }
+                conf.push_str(&format!(
+                    "\n# {}\n[Peer]\nPublicKey = {}\nAllowedIPs = {}\n",
+                    other.name.trim(),
+                    other_keypair.public_key,
+                    host_route
+                ));
+                if !other.endpoint.trim().is_empty() {
+                    conf.push_str(&format!("Endpoint = {}\n", other.endpoint.trim()));
+                }
+                // 自身没有公网地址时需要保持连接,否则对端无法主动连入
+                if listen_port.is_none() {
+                    conf.push_str("PersistentKeepalive = 25\n");
+                }
+            }
+
+            MeshConfig {
+                name: member.name.trim().to_string(),
+                conf,
+            }
+        })
+        .collect();
+
+    log::info!("已生成 {} 个成员的 mesh 配置", members.len());
+    Ok(configs)
+}
@@ -0,0 +1,1316 @@
+use crate::tunnel::{generate_interface_name, interface_exists, TunnelConfig, TUNNEL_PROCESSES};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use tauri::{command, AppHandle, Manager};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthIssue {
+    pub severity: String, // error, warning, info
+    pub category: String,
+    pub message: String,
+    pub target: Option<String>, // 相关的隧道/记录 ID 或文件名
+}
+
+impl HealthIssue {
+    fn new(severity: &str, category: &str, message: String, target: Option<String>) -> Self {
+        Self {
+            severity: severity.to_string(),
+            category: category.to_string(),
+            message,
+            target,
+        }
+    }
+}
+
+// 读取目录下所有 JSON 文件,解析失败的文件记录为问题
+fn load_json_dir<T: serde::de::DeserializeOwned>(
+    dir: &Path,
+    label: &str,
+    issues: &mut Vec<HealthIssue>,
+) -> Vec<T> {
+    let mut items = Vec::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return items,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        // 隧道的最后可用配置快照不是独立的配置
+        if path.to_string_lossy().ends_with(crate::tunnel::LKG_SUFFIX) {
+            continue;
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // 历史记录可能以 gzip 压缩存储
+        match crate::commands::history_service::read_json_file(&path)
+            .and_then(|content| serde_json::from_str::<T>(&content).map_err(|e| e.to_string()))
+        {
+            Ok(item) => items.push(item),
+            Err(e) => issues.push(HealthIssue::new(
+                "error",
+                "broken_file",
+                format!("{}文件 {} 无法解析: {}", label, file_name, e),
+                Some(file_name),
+            )),
+        }
+    }
+
+    items
+}
+
+// 解析 CIDR,返回 (地址, 前缀长度)
+pub fn parse_cidr(cidr: &str) -> Option<(IpAddr, u8)> {
+    let cidr = cidr.trim();
+    let (ip, prefix) = match cidr.split_once('/') {
+        Some((ip, prefix)) => (ip.parse::<IpAddr>().ok()?, prefix.parse::<u8>().ok()?),
+        None => {
+            let ip = cidr.parse::<IpAddr>().ok()?;
+            (ip, if ip.is_ipv4() { 32 } else { 128 })
+        }
+    };
+
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    if prefix > max {
+        return None;
+    }
+
+    Some((ip, prefix))
+}
+
+// 判断两个网段是否重叠
+pub fn cidr_overlaps(a: (IpAddr, u8), b: (IpAddr, u8)) -> bool {
+    let (a_bits, b_bits, width) = match (a.0, b.0) {
+        (IpAddr::V4(x), IpAddr::V4(y)) => (u32::from(x) as u128, u32::from(y) as u128, 32),
+        (IpAddr::V6(x), IpAddr::V6(y)) => (u128::from(x), u128::from(y), 128),
+        _ => return false,
+    };
+
+    let prefix = a.1.min(b.1) as u32;
+    if prefix == 0 {
+        return true;
+    }
+
+    let shift = width - prefix;
+    (a_bits >> shift) == (b_bits >> shift)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DnsAnalysis {
+    pub full_tunnel: bool,
+    pub dns_set: bool,
+    pub leak_risk: String, // high, medium, low
+    pub notes: Vec<String>,
+}
+
+// 判断路由是否覆盖指定地址族的全部流量 (/0 或两个 /1 网段)
+fn covers_all(routes: &[(IpAddr, u8)], v4: bool) -> bool {
+    let family: Vec<&(IpAddr, u8)> = routes.iter().filter(|(ip, _)| ip.is_ipv4() == v4).collect();
+    if family.iter().any(|(_, prefix)| *prefix == 0) {
+        return true;
+    }
+
+    let (low, high): (IpAddr, IpAddr) = if v4 {
+        ([0, 0, 0, 0].into(), [128, 0, 0, 0].into())
+    } else {
+        (
+            [0u16, 0, 0, 0, 0, 0, 0, 0].into(),
+            [0x8000u16, 0, 0, 0, 0, 0, 0, 0].into(),
+        )
+    };
+    [low, high].iter().all(|half| {
+        family
+            .iter()
+            .any(|net| net.1 <= 1 && cidr_overlaps(**net, (*half, 1)))
+    })
+}
+
+// 分析隧道配置的 DNS 泄露风险
+// 全局路由但未设置 DNS 时,系统解析器可能绕过隧道;
+// 设置了 DNS 但 DNS 服务器不在路由范围内时,查询会走物理网卡
+#[command]
+pub fn analyze_dns_config(config: TunnelConfig) -> Result<DnsAnalysis, String> {
+    let allowed_ips: Vec<&str> = if config.peers.is_empty() {
+        vec![config.allowed_ips.as_str()]
+    } else {
+        config
+            .peers
+            .iter()
+            .map(|p| p.allowed_ips.as_str())
+            .collect()
+    };
+    let routes: Vec<(IpAddr, u8)> = allowed_ips
+        .iter()
+        .flat_map(|ips| ips.split(','))
+        .filter_map(parse_cidr)
+        .collect();
+
+    let dns_servers: Vec<IpAddr> = config
+        .dns
+        .split(',')
+        .filter_map(|s| s.trim().parse::<IpAddr>().ok())
+        .collect();
+
+    let full_v4 = covers_all(&routes, true);
+    let full_v6 = covers_all(&routes, false);
+    let full_tunnel = full_v4 || full_v6;
+    let dns_set = !dns_servers.is_empty();
+
+    let mut notes = Vec::new();
+    let mut leak_risk = "low";
+
+    if config.mode == "server" {
+        notes
+            .push("服务端模式下 DNS 仅影响本机解析，客户端的 DNS 需在客户端配置中设置".to_string());
+    }
+
+    if full_tunnel && !dns_set {
+        leak_risk = "high";
+        notes.push(
+            "已启用全局路由但未设置 DNS，系统解析器可能继续使用本地网络的 DNS 服务器导致泄露"
+                .to_string(),
+        );
+    }
+
+    if dns_set {
+        let unrouted: Vec<String> = dns_servers
+            .iter()
+            .filter(|dns| {
+                let host = (**dns, if dns.is_ipv4() { 32 } else { 128 });
+                !routes.iter().any(|net| cidr_overlaps(*net, host))
+            })
+            .map(|dns| dns.to_string())
+            .collect();
+        if !unrouted.is_empty() {
+            leak_risk = "medium";
+            notes.push(format!(
+                "DNS 服务器 {} 不在 AllowedIPs 路由范围内，查询不会经过隧道",
+                unrouted.join(", ")
+            ));
+        }
+    }
+
+    if full_v4 && !full_v6 {
+        if leak_risk == "low" {
+            leak_risk = "medium";
+        }
+        notes.push(
+            "只路由了 IPv4 全部流量，IPv6 流量和 IPv6 DNS 查询仍可能走本地网络 (可添加 ::/0)"
+                .to_string(),
+        );
+    }
+
+    if !config.dns.trim().is_empty() && !dns_set {
+        notes.push("DNS 中未找到有效的 IP 地址".to_string());
+    }
+
+    Ok(DnsAnalysis {
+        full_tunnel,
+        dns_set,
+        leak_risk: leak_risk.to_string(),
+        notes,
+    })
+}
+
+// 运行配置健康检查,返回按严重程度标记的问题列表
+#[command]
+pub async fn run_health_checks(app: AppHandle) -> Result<Vec<HealthIssue>, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let running: Vec<String> = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.keys().cloned().collect()
+    };
+
+    // 读取配置文件、检查接口和端口占用都是阻塞操作,放到阻塞线程池中执行
+    let issues =
+        tokio::task::spawn_blocking(move || collect_health_issues(&app, &app_data_dir, &running))
+            .await
+            .map_err(|e| format!("健康检查任务执行失败: {}", e))?;
+
+    log::info!("健康检查完成，发现 {} 个问题", issues.len());
+
+    Ok(issues)
+}
+
+// 执行各项健康检查,running 为应用正在管理的隧道 ID
+fn collect_health_issues(
+    app: &AppHandle,
+    app_data_dir: &Path,
+    running: &[String],
+) -> Vec<HealthIssue> {
+    let mut issues = Vec::new();
+
+    // 1. 损坏的 JSON 文件
+    let tunnels: Vec<TunnelConfig> =
+        load_json_dir(&app_data_dir.join("tunnels"), "隧道配置", &mut issues);
+    let _servers: Vec<crate::commands::server_service::ServerConfig> =
+        load_json_dir(&app_data_dir.join("servers"), "服务端配置", &mut issues);
+    let _history: Vec<crate::commands::history_service::HistoryEntry> =
+        load_json_dir(&app_data_dir.join("history"), "历史记录", &mut issues);
+
+    // 2. 重复的密钥
+    let mut private_keys: HashMap<&str, &TunnelConfig> = HashMap::new();
+    for tunnel in &tunnels {
+        let key = tunnel.private_key.trim();
+        if key.is_empty() || crate::keychain::is_keychain_ref(key) {
+            continue;
+        }
+        if let Some(other) = private_keys.insert(key, tunnel) {
+            issues.push(HealthIssue::new(
+                "error",
+                "duplicate_key",
+                format!(
+                    "隧道 \"{}\" 与 \"{}\" 使用了相同的私钥",
+                    tunnel.name, other.name
+                ),
+                Some(tunnel.id.clone()),
+            ));
+        }
+
+        let mut peer_keys: HashMap<&str, usize> = HashMap::new();
+        for peer in &tunnel.peers {
+            *peer_keys.entry(peer.public_key.trim()).or_insert(0) += 1;
+        }
+        for (key, count) in peer_keys {
+            if count > 1 && !key.is_empty() {
+                issues.push(HealthIssue::new(
+                    "error",
+                    "duplicate_key",
+                    format!(
+                        "隧道 \"{}\" 中有 {} 个 Peer 使用相同的公钥 {}",
+                        tunnel.name, count, key
+                    ),
+                    Some(tunnel.id.clone()),
+                ));
+            }
+        }
+    }
+
+    // 3. 监听端口冲突
+    let mut ports: HashMap<&str, &TunnelConfig> = HashMap::new();
+    for tunnel in &tunnels {
+        let port = tunnel.listen_port.trim();
+        if port.is_empty() {
+            continue;
+        }
+        if let Some(other) = ports.insert(port, tunnel) {
+            issues.push(HealthIssue::new(
+                "warning",
+                "port_conflict",
+                format!(
+                    "隧道 \"{}\" 与 \"{}\" 使用了相同的监听端口 {}，无法同时运行",
+                    tunnel.name, other.name, port
+                ),
+                Some(tunnel.id.clone()),
+            ));
+        }
+    }
+
+    // 4. 不同隧道之间的路由重叠
+    let routes: Vec<(&TunnelConfig, &str, (IpAddr, u8))> = tunnels
+        .iter()
+        .flat_map(|tunnel| {
+            tunnel.peers.iter().flat_map(move |peer| {
+                peer.allowed_ips
+                    .split(',')
+                    .filter_map(move |cidr| parse_cidr(cidr).map(|net| (tunnel, cidr.trim(), net)))
+            })
+        })
+        .collect();
+
+    for (i, (tunnel_a, cidr_a, net_a)) in routes.iter().enumerate() {
+        for (tunnel_b, cidr_b, net_b) in routes.iter().skip(i + 1) {
+            if tunnel_a.id == tunnel_b.id {
+                continue;
+            }
+            // 默认路由 (0.0.0.0/0, ::/0) 的重叠是全局代理的正常情况,只作提示
+            let severity = if net_a.1 == 0 || net_b.1 == 0 {
+                "info"
+            } else {
+                "warning"
+            };
+            if cidr_overlaps(*net_a, *net_b) {
+                issues.push(HealthIssue::new(
+                    severity,
+                    "route_overlap",
+                    format!(
+                        "隧道 \"{}\" 的 {} 与隧道 \"{}\" 的 {} 路由重叠，同时运行时可能冲突",
+                        tunnel_a.name, cidr_a, tunnel_b.name, cidr_b
+                    ),
+                    Some(tunnel_a.id.clone()),
+                ));
+            }
+        }
+    }
+
+    // 5. 历史记录公钥与私钥不一致
+    match crate::commands::history_service::verify_stored_public_keys(app.clone()) {
+        Ok(mismatches) => {
+            for mismatch in mismatches {
+                issues.push(HealthIssue::new(
+                    "warning",
+                    "public_key_mismatch",
+                    format!(
+                        "历史记录的公钥 {} 与私钥推导结果 {} 不一致",
+                        mismatch.stored, mismatch.computed
+                    ),
+                    Some(mismatch.id),
+                ));
+            }
+        }
+        Err(e) => log::warn!("校验历史记录公钥失败: {}", e),
+    }
+
+    // 6. 残留接口 (接口存在但应用未管理)
+    for tunnel in &tunnels {
+        let interface_name = generate_interface_name(&tunnel.id);
+        if !running.contains(&tunnel.id) && interface_exists(&interface_name) {
+            issues.push(HealthIssue::new(
+                "warning",
+                "orphaned_interface",
+                format!(
+                    "接口 {} 仍然存在但隧道 \"{}\" 未由应用管理，可能是残留进程",
+                    interface_name, tunnel.name
+                ),
+                Some(tunnel.id.clone()),
+            ));
+        }
+    }
+
+    // 7. 未运行隧道的监听端口被其他程序占用,启动时会在 UAPI 阶段失败
+    for tunnel in &tunnels {
+        let Ok(port) = tunnel.listen_port.trim().parse::<u16>() else {
+            continue;
+        };
+        if port == 0 || running.contains(&tunnel.id) {
+            continue;
+        }
+        if let Some(conflict) = listen_port_conflict(port) {
+            issues.push(HealthIssue::new(
+                "warning",
+                "port_in_use",
+                format!("隧道 \"{}\" 无法启动: {}", tunnel.name, conflict),
+                Some(tunnel.id.clone()),
+            ));
+        }
+    }
+
+    issues
+}
+
+// JSON 值的类型名称,用于错误提示
+fn json_type_name(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "null".to_string(),
+        serde_json::Value::Bool(_) => "布尔值".to_string(),
+        serde_json::Value::Number(n) => format!("数字 {}", n),
+        serde_json::Value::String(_) => "字符串".to_string(),
+        serde_json::Value::Array(_) => "数组".to_string(),
+        serde_json::Value::Object(_) => "对象".to_string(),
+    }
+}
+
+// 按目标结构体反序列化配置内容,返回 serde 报告的问题
+// 逐字段校验未发现问题时兜底使用,覆盖字段校验未列出的约束
+fn deserialize_messages<T: serde::de::DeserializeOwned>(content: &str) -> Vec<String> {
+    match serde_json::from_str::<T>(content) {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![format!("解析失败: {}", e)],
+    }
+}
+
+// 配置字段的 JSON 类型,整数附带取值范围
+#[derive(Clone, Copy)]
+enum FieldType {
+    Str,
+    Bool,
+    Int(i128, i128),
+    Array,
+}
+
+impl FieldType {
+    fn name(self) -> &'static str {
+        match self {
+            FieldType::Str => "字符串",
+            FieldType::Bool => "布尔值",
+            FieldType::Int(..) => "整数",
+            FieldType::Array => "数组",
+        }
+    }
+}
+
+// 字段是否可以缺失或为 null,与结构体的 serde 定义对应
+#[derive(Clone, Copy, PartialEq)]
+enum Presence {
+    Required, // 必填且不能为 null
+    Default,  // #[serde(default)]: 可以缺失,不能为 null
+    Nullable, // Option<T>: 可以缺失或为 null
+}
+
+// 收集配置文件中的问题,每条问题带有字段路径 (例如 "peers[0].endpoint")
+struct FieldChecker<'a> {
+    object: &'a serde_json::Map<String, serde_json::Value>,
+    prefix: String,
+    errors: &'a mut Vec<String>,
+}
+
+impl<'a> FieldChecker<'a> {
+    fn path(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    // 检查字段是否存在以及类型,通过时返回字段值 (缺失或 null 时返回 None)
+    fn field(
+        &mut self,
+        name: &str,
+        ty: FieldType,
+        presence: Presence,
+    ) -> Option<&'a serde_json::Value> {
+        let object = self.object;
+        let value = match object.get(name) {
+            None if presence == Presence::Required => {
+                self.errors
+                    .push(format!("{}: 缺少必填字段", self.path(name)));
+                return None;
+            }
+            None => return None,
+            Some(serde_json::Value::Null) if presence == Presence::Nullable => return None,
+            Some(value) => value,
+        };
+
+        let type_ok = match (ty, value) {
+            (FieldType::Str, serde_json::Value::String(_)) => true,
+            (FieldType::Bool, serde_json::Value::Bool(_)) => true,
+            (FieldType::Array, serde_json::Value::Array(_)) => true,
+            (FieldType::Int(min, max), serde_json::Value::Number(n)) => {
+                let Some(n) = n.as_i64().map(i128::from).or(n.as_u64().map(i128::from)) else {
+                    self.errors.push(format!(
+                        "{}: 类型错误，应为整数，实际为{}",
+                        self.path(name),
+                        json_type_name(value)
+                    ));
+                    return None;
+                };
+                if n < min || n > max {
+                    self.errors.push(format!(
+                        "{}: 超出范围 ({}-{}): {}",
+                        self.path(name),
+                        min,
+                        max,
+                        n
+                    ));
+                    return None;
+                }
+                true
+            }
+            _ => false,
+        };
+
+        if !type_ok {
+            self.errors.push(format!(
+                "{}: 类型错误，应为{}，实际为{}",
+                self.path(name),
+                ty.name(),
+                json_type_name(value)
+            ));
+            return None;
+        }
+
+        Some(value)
+    }
+
+    // 检查字符串字段,非空时再用 validate 校验内容
+    fn string(&mut self, name: &str, presence: Presence, validate: fn(&str) -> Result<(), String>) {
+        let Some(value) = self.field(name, FieldType::Str, presence) else {
+            return;
+        };
+        let value = value.as_str().unwrap_or("").trim();
+        if value.is_empty() {
+            return;
+        }
+        if let Err(e) = validate(value) {
+            self.errors.push(format!("{}: {}", self.path(name), e));
+        }
+    }
+
+    fn check(&mut self, name: &str, ty: FieldType, presence: Presence) {
+        self.field(name, ty, presence);
+    }
+}
+
+const U16: FieldType = FieldType::Int(0, u16::MAX as i128);
+const U32: FieldType = FieldType::Int(0, u32::MAX as i128);
+const U64: FieldType = FieldType::Int(0, u64::MAX as i128);
+const I64: FieldType = FieldType::Int(i64::MIN as i128, i64::MAX as i128);
+
+fn no_check(_: &str) -> Result<(), String> {
+    Ok(())
+}
+
+fn check_key(value: &str) -> Result<(), String> {
+    crate::commands::key_management::normalize_key(value).map(|_| ())
+}
+
+// 私钥可能是钥匙串引用,引用不做密钥格式校验
+fn check_private_key(value: &str) -> Result<(), String> {
+    if crate::keychain::is_keychain_ref(value) {
+        return Ok(());
+    }
+    check_key(value)
+}
+
+// 逗号分隔的 IP 或 CIDR 列表
+fn check_cidr_list(value: &str) -> Result<(), String> {
+    match value
+        .split(',')
+        .map(str::trim)
+        .find(|cidr| !cidr.is_empty() && parse_cidr(cidr).is_none())
+    {
+        Some(invalid) => Err(format!("地址格式无效: {}", invalid)),
+        None => Ok(()),
+    }
+}
+
+fn check_endpoint(value: &str) -> Result<(), String> {
+    crate::tunnel::parse_endpoint_port(value).map(|_| ())
+}
+
+fn check_listen_port(value: &str) -> Result<(), String> {
+    value
+        .parse::<u16>()
+        .map(|_| ())
+        .map_err(|_| format!("监听端口无效 (应为 0-65535): {}", value))
+}
+
+fn check_mtu_value(value: &str) -> Result<(), String> {
+    match value.parse::<u16>() {
+        Ok(mtu) if mtu >= 576 => Ok(()),
+        Ok(mtu) => Err(format!("MTU 过小 (最小 576): {}", mtu)),
+        Err(_) => Err(format!("MTU 无效: {}", value)),
+    }
+}
+
+fn check_keepalive(value: &str) -> Result<(), String> {
+    crate::tunnel::parse_keepalive(value).map(|_| ())
+}
+
+// 按 TunnelPeerConfig 的字段逐项校验
+fn check_tunnel_peer(checker: &mut FieldChecker) {
+    use Presence::*;
+
+    checker.string("public_key", Required, check_key);
+    checker.string("client_private_key", Nullable, check_key);
+    checker.string("preshared_key", Nullable, check_key);
+    checker.string("endpoint", Nullable, check_endpoint);
+    checker.string("address", Nullable, check_cidr_list);
+    checker.string("allowed_ips", Required, check_cidr_list);
+    checker.check("persistent_keepalive", U16, Nullable);
+    checker.string("remark", Nullable, no_check);
+    checker.string("name", Nullable, no_check);
+    checker.check("tx_bytes", U64, Default);
+    checker.check("rx_bytes", U64, Default);
+    checker.check("last_handshake", I64, Nullable);
+    checker.check("expires_at", I64, Nullable);
+}
+
+// 按 TunnelConfig 的字段逐项校验,包括每个 Peer
+fn check_tunnel_config(checker: &mut FieldChecker) {
+    use Presence::*;
+
+    checker.string("id", Required, no_check);
+    checker.string("name", Required, no_check);
+    checker.string("mode", Default, no_check);
+    checker.string("private_key", Required, check_private_key);
+    checker.check("store_key_in_keychain", FieldType::Bool, Default);
+    checker.string("interface_name", Nullable, no_check);
+    checker.string("address", Required, check_cidr_list);
+    checker.string("listen_port", Required, check_listen_port);
+    checker.string("dns", Required, no_check);
+    checker.string("dns_search", Nullable, crate::tunnel::validate_dns_search);
+    checker.string("mtu", Required, check_mtu_value);
+    checker.check("route_table", U32, Nullable);
+    checker.check("fwmark", U32, Nullable);
+    checker.check("kill_switch", FieldType::Bool, Default);
+    checker.check("auto_start", FieldType::Bool, Default);
+    checker.check("reconnect_on_network_change", FieldType::Bool, Default);
+    checker.string("server_endpoint", Default, no_check);
+    checker.string("server_allowed_ips", Default, check_cidr_list);
+    checker.string("peer_public_key", Default, check_key);
+    checker.string("preshared_key", Default, check_key);
+    checker.string("endpoint", Default, check_endpoint);
+    checker.string("allowed_ips", Default, check_cidr_list);
+    checker.string("persistent_keepalive", Default, check_keepalive);
+    checker.check("created_at", I64, Required);
+
+    let Some(peers) = checker
+        .field("peers", FieldType::Array, Default)
+        .and_then(|p| p.as_array())
+    else {
+        return;
+    };
+    for (index, peer) in peers.iter().enumerate() {
+        let prefix = format!("peers[{}]", index);
+        match peer.as_object() {
+            Some(object) => check_tunnel_peer(&mut FieldChecker {
+                object,
+                prefix: format!("{}.", prefix),
+                errors: &mut *checker.errors,
+            }),
+            None => checker.errors.push(format!(
+                "{}: 类型错误，应为对象，实际为{}",
+                prefix,
+                json_type_name(peer)
+            )),
+        }
+    }
+}
+
+// 按 ServerConfig 的字段逐项校验
+fn check_server_config(checker: &mut FieldChecker) {
+    use Presence::*;
+
+    checker.string("id", Required, no_check);
+    checker.string("name", Required, no_check);
+    checker.string("peer_public_key", Required, check_key);
+    checker.string("preshared_key", Required, check_key);
+    checker.string("endpoint", Required, check_endpoint);
+    checker.string("allowed_ips", Required, check_cidr_list);
+    checker.string("persistent_keepalive", Required, check_keepalive);
+    checker.string("peer_interface", Required, no_check);
+    checker.check("next_peer_id", U32, Required);
+    checker.check("created_at", I64, Required);
+    checker.string("peer_address_range", Default, check_cidr_list);
+}
+
+// 校验隧道配置或服务端配置文件的字段和类型,返回具体的问题列表 (为空表示文件有效)
+// 逐字段校验密钥、地址、endpoint、端口、MTU 和每个 Peer,返回全部问题及字段路径
+// 便于修复在列表中消失的配置文件
+// 根据所在目录 (tunnels / servers) 或字段判断配置类型
+#[command]
+pub fn validate_config_file(path: String) -> Result<Vec<String>, String> {
+    let path = Path::new(&path);
+    let content = crate::commands::history_service::read_json_file(path)?;
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("JSON 格式错误: {}", e))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| format!("配置文件应为 JSON 对象，实际为{}", json_type_name(&value)))?;
+
+    let dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str());
+    let is_server = match dir {
+        Some("servers") => true,
+        Some("tunnels") => false,
+        _ if object.contains_key("next_peer_id") => true,
+        _ if object.contains_key("private_key") => false,
+        _ => return Err("无法识别配置类型，仅支持隧道配置和服务端配置".to_string()),
+    };
+
+    let mut messages = Vec::new();
+    let mut checker = FieldChecker {
+        object,
+        prefix: String::new(),
+        errors: &mut messages,
+    };
+    if is_server {
+        check_server_config(&mut checker);
+    } else {
+        check_tunnel_config(&mut checker);
+    }
+
+    if messages.is_empty() {
+        messages = if is_server {
+            deserialize_messages::<crate::commands::server_service::ServerConfig>(&content)
+        } else {
+            deserialize_messages::<TunnelConfig>(&content)
+        };
+    }
+
+    Ok(messages)
+}
+
+// 取地址列表中与目标同一协议族的第一个 IP (去掉前缀长度)
+fn first_ip_of_family(addresses: &str, target: IpAddr) -> Option<IpAddr> {
+    addresses
+        .split(',')
+        .filter_map(|addr| addr.trim().split('/').next()?.trim().parse::<IpAddr>().ok())
+        .find(|ip| ip.is_ipv4() == target.is_ipv4())
+}
+
+// 检查本机是否开启了 IP 转发,客户端之间的流量需要服务端转发
+fn ip_forwarding_enabled(ipv6: bool) -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let path = if ipv6 {
+            "/proc/sys/net/ipv6/conf/all/forwarding"
+        } else {
+            "/proc/sys/net/ipv4/ip_forward"
+        };
+        fs::read_to_string(path).ok().map(|v| v.trim() == "1")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let key = if ipv6 {
+            "net.inet6.ip6.forwarding"
+        } else {
+            "net.inet.ip.forwarding"
+        };
+        std::process::Command::new("sysctl")
+            .args(["-n", key])
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "1")
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = ipv6;
+        None
+    }
+}
+
+// 从指定源地址 ping 目标地址一次 (超时 2 秒)
+fn ping_from(source: IpAddr, target: IpAddr) -> bool {
+    let source = source.to_string();
+    let target = target.to_string();
+
+    #[cfg(target_os = "linux")]
+    let args = vec!["-c", "1", "-W", "2", "-I", &source, &target];
+    #[cfg(target_os = "macos")]
+    let args = vec!["-c", "1", "-t", "2", "-S", &source, &target];
+    #[cfg(target_os = "windows")]
+    let args = vec!["-n", "1", "-w", "2000", "-S", &source, &target];
+
+    // macOS 的 IPv6 需要使用 ping6
+    #[cfg(target_os = "macos")]
+    let program = if target.contains(':') {
+        "ping6"
+    } else {
+        "ping"
+    };
+    #[cfg(not(target_os = "macos"))]
+    let program = "ping";
+
+    std::process::Command::new(program)
+        .args(&args)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+// 客户端互通测试中单个方向 (from -> to) 的检查结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerToPeerLeg {
+    pub from: String,
+    pub to: String,
+    // 目标客户端的 VPN 地址
+    pub target_ip: String,
+    // 发起方客户端的 AllowedIPs 是否包含目标地址 (否则流量不会进入隧道)
+    pub client_allowed: bool,
+    // 本机为目标客户端配置的 AllowedIPs 是否包含其地址 (否则本机不会转发给它)
+    pub server_routed: bool,
+    // 目标客户端是否在线 (最近 3 分钟内有握手)
+    pub online: bool,
+    // 从本机隧道地址 ping 目标客户端是否可达
+    pub reachable: bool,
+    pub issues: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerToPeerResult {
+    pub legs: Vec<PeerToPeerLeg>,
+    // 本机是否开启 IP 转发,无法检测时为 None
+    pub ip_forwarding: Option<bool>,
+    // 两个方向的检查全部通过
+    pub success: bool,
+}
+
+// 测试服务端模式隧道下两个客户端能否经由本机互通 (hub-and-spoke)
+// client_a / client_b 为 Peer 公钥;按 A -> B、B -> A 两个方向分别检查:
+// 发起方 AllowedIPs 是否包含对方地址、本机是否为对方配置了路由、对方是否在线、本机能否 ping 通对方
+// 隧道未运行、客户端不存在等无法测试的情况返回 Err
+// 注意: 客户端防火墙屏蔽 ICMP 时 reachable 也会为 false
+#[command]
+pub async fn test_peer_to_peer(
+    app: AppHandle,
+    server_id: String,
+    client_a: String,
+    client_b: String,
+) -> Result<PeerToPeerResult, String> {
+    {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        if !processes.contains_key(&server_id) {
+            return Err("服务端隧道未运行".to_string());
+        }
+    }
+
+    let config = crate::tunnel::get_tunnel_config(app, server_id.clone()).await?;
+    if config.mode != "server" {
+        return Err("仅支持服务端模式的隧道".to_string());
+    }
+
+    // 查找两个客户端的 VPN 地址和本机为其配置的 AllowedIPs
+    let mut clients = Vec::new();
+    for public_key in [&client_a, &client_b] {
+        let peer = config
+            .peers
+            .iter()
+            .find(|p| &p.public_key == public_key)
+            .ok_or_else(|| format!("隧道中不存在该客户端: {}", public_key))?;
+        let label = peer.remark.clone().unwrap_or_else(|| public_key.clone());
+        let address = peer.address.as_deref().unwrap_or(&peer.allowed_ips);
+        let ip = address
+            .split(',')
+            .find_map(|a| a.trim().split('/').next()?.trim().parse::<IpAddr>().ok())
+            .ok_or_else(|| format!("客户端 {} 没有有效的 VPN 地址", label))?;
+        let server_side: Vec<(IpAddr, u8)> =
+            peer.allowed_ips.split(',').filter_map(parse_cidr).collect();
+        clients.push((label, public_key.clone(), ip, server_side));
+    }
+
+    // 客户端配置中的 AllowedIPs 由本隧道的 server_allowed_ips 下发
+    let client_allowed: Vec<(IpAddr, u8)> = config
+        .server_allowed_ips
+        .split(',')
+        .filter_map(parse_cidr)
+        .collect();
+
+    let interface_name = generate_interface_name(&server_id);
+    let stats = crate::tunnel::get_peer_stats_impl(&server_id, &interface_name).await;
+    let now = chrono::Utc::now().timestamp();
+
+    let ip_forwarding = ip_forwarding_enabled(clients[0].2.is_ipv6());
+
+    let mut legs = Vec::new();
+    for (from, to) in [(0, 1), (1, 0)] {
+        let (from_label, _, _, _) = &clients[from];
+        let (to_label, to_key, to_ip, to_server_side) = &clients[to];
+        let host = (*to_ip, if to_ip.is_ipv4() { 32 } else { 128 });
+        let mut issues = Vec::new();
+
+        let client_allowed_ok = client_allowed.iter().any(|net| cidr_overlaps(*net, host));
+        if !client_allowed_ok {
+            issues.push(format!(
+                "{} 的 AllowedIPs ({}) 不包含 {} 的地址 {}，流量不会进入隧道",
+                from_label, config.server_allowed_ips, to_label, to_ip
+            ));
+        }
+
+        let server_routed = to_server_side.iter().any(|net| cidr_overlaps(*net, host));
+        if !server_routed {
+            issues.push(format!(
+                "本机为 {} 配置的 AllowedIPs 不包含其地址 {}，本机不会将流量转发给它",
+                to_label, to_ip
+            ));
+        }
+
+        let online = stats
+            .iter()
+            .find(|s| &s.public_key == to_key)
+            .and_then(|s| s.last_handshake)
+            .is_some_and(|ts| ts > 0 && now - ts <= 180);
+        if !online {
+            issues.push(format!("{} 未连接 (最近 3 分钟内没有握手)", to_label));
+        }
+
+        if ip_forwarding == Some(false) {
+            issues.push("本机未开启 IP 转发，客户端之间的流量无法经由服务端转发".to_string());
+        }
+
+        // 从本机隧道地址 (与客户端同一网段) 探测目标
+        let reachable = match first_ip_of_family(&config.address, *to_ip) {
+            Some(source) if online => {
+                let target = *to_ip;
+                let ok = tokio::task::spawn_blocking(move || ping_from(source, target))
+                    .await
+                    .map_err(|e| format!("探测任务执行失败: {}", e))?;
+                log::info!("从 {} 探测 {} ({}): {}", source, to_label, to_ip, ok);
+                if !ok {
+                    issues.push(format!("从本机无法 ping 通 {} ({})", to_label, to_ip));
+                }
+                ok
+            }
+            Some(_) => false,
+            None => {
+                issues.push(format!("隧道没有与 {} 同协议族的地址", to_ip));
+                false
+            }
+        };
+
+        legs.push(PeerToPeerLeg {
+            from: from_label.clone(),
+            to: to_label.clone(),
+            target_ip: to_ip.to_string(),
+            client_allowed: client_allowed_ok,
+            server_routed,
+            online,
+            reachable,
+            issues,
+        });
+    }
+
+    let success = legs.iter().all(|leg| leg.issues.is_empty());
+    Ok(PeerToPeerResult {
+        legs,
+        ip_forwarding,
+        success,
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EffectiveAllowedIps {
+    pub public_key: String,
+    pub name: Option<String>,
+    // 配置中声明的 AllowedIPs
+    pub claimed: Vec<String>,
+    // 路由表中实际指向隧道网卡的部分
+    pub effective: Vec<String>,
+}
+
+// Linux: 解析 `ip route show` 的一行,返回目标网段
+// 带 local / broadcast 等类型前缀的路由不是发往网卡的流量,跳过
+#[cfg(target_os = "linux")]
+fn parse_ip_route_line(line: &str, v4: bool) -> Option<(IpAddr, u8)> {
+    let mut fields = line.split_whitespace();
+    let mut destination = fields.next()?;
+    if destination == "unicast" {
+        destination = fields.next()?;
+    } else if matches!(
+        destination,
+        "local" | "broadcast" | "multicast" | "anycast" | "unreachable" | "blackhole" | "prohibit"
+    ) {
+        return None;
+    }
+
+    if destination == "default" {
+        let any: IpAddr = if v4 {
+            [0, 0, 0, 0].into()
+        } else {
+            [0u16; 8].into()
+        };
+        return Some((any, 0));
+    }
+    parse_cidr(destination)
+}
+
+// macOS: 解析 `netstat -rn` 的目标列
+// IPv4 网段可能省略末尾的 0 (如 10.8/16),省略前缀时按给出的字节数推算;IPv6 可能带 %utunN 后缀
+#[cfg(target_os = "macos")]
+fn parse_netstat_destination(destination: &str, v4: bool) -> Option<(IpAddr, u8)> {
+    if destination == "default" {
+        let any: IpAddr = if v4 {
+            [0, 0, 0, 0].into()
+        } else {
+            [0u16; 8].into()
+        };
+        return Some((any, 0));
+    }
+
+    let (address, prefix) = match destination.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix.parse::<u8>().ok()?)),
+        None => (destination, None),
+    };
+    let address = address.split('%').next()?;
+
+    if !v4 {
+        return parse_cidr(&format!("{}/{}", address, prefix.unwrap_or(128)));
+    }
+
+    let mut octets: Vec<&str> = address.split('.').collect();
+    if octets.is_empty() || octets.len() > 4 {
+        return None;
+    }
+    let implied_prefix = (octets.len() * 8) as u8;
+    octets.resize(4, "0");
+    parse_cidr(&format!(
+        "{}/{}",
+        octets.join("."),
+        prefix.unwrap_or(implied_prefix)
+    ))
+}
+
+// 列出系统路由表中指向指定网卡的路由 (目标网段)
+fn list_interface_routes(interface: &str) -> Result<Vec<(IpAddr, u8)>, String> {
+    let mut routes = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    {
+        // table all: 隧道可能使用自定义路由表
+        for (family, v4) in [("-4", true), ("-6", false)] {
+            let output = std::process::Command::new("ip")
+                .args([family, "route", "show", "table", "all", "dev", interface])
+                .output()
+                .map_err(|e| format!("读取路由表失败: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "读取路由表失败: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            routes.extend(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| parse_ip_route_line(line, v4)),
+            );
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("netstat")
+            .args(["-rn"])
+            .output()
+            .map_err(|e| format!("读取路由表失败: {}", e))?;
+        if !output.status.success() {
+            return Err("读取路由表失败".to_string());
+        }
+
+        // 输出分为 Internet: 和 Internet6: 两段,列依次为 Destination Gateway Flags Netif
+        let mut v4 = true;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            match line.trim() {
+                "Internet:" => v4 = true,
+                "Internet6:" => v4 = false,
+                _ => {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    if fields.get(3) == Some(&interface) {
+                        routes.extend(parse_netstat_destination(fields[0], v4));
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // CREATE_NO_WINDOW: 隐藏控制台窗口
+        let script = format!(
+            "Get-NetRoute -InterfaceAlias '{}' -ErrorAction Stop | Select-Object -ExpandProperty DestinationPrefix",
+            interface
+        );
+        let output = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(0x08000000)
+            .output()
+            .map_err(|e| format!("读取路由表失败: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "读取路由表失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        routes.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(parse_cidr),
+        );
+    }
+
+    Ok(routes)
+}
+
+// 对照系统路由表,列出每个 Peer 声明的 AllowedIPs 中实际路由到隧道网卡的部分
+// 路由比声明的网段更小时 (如 0.0.0.0/0 拆分为两个 /1) 返回路由本身,否则返回声明的网段
+// 声明了但没有出现在 effective 中的网段,流量不会进入隧道
+#[command]
+pub async fn get_effective_allowed_ips(
+    app: AppHandle,
+    tunnel_id: String,
+) -> Result<Vec<EffectiveAllowedIps>, String> {
+    {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        if !processes.contains_key(&tunnel_id) {
+            return Err("隧道未运行".to_string());
+        }
+    }
+
+    let config = crate::tunnel::get_tunnel_config(app, tunnel_id.clone()).await?;
+    let interface_name = generate_interface_name(&tunnel_id);
+    let routes = tokio::task::spawn_blocking(move || list_interface_routes(&interface_name))
+        .await
+        .map_err(|e| format!("读取路由表任务执行失败: {}", e))??;
+
+    // 本机隧道地址的主机路由不是发往 Peer 的流量
+    let local_addresses: Vec<IpAddr> = config
+        .address
+        .split(',')
+        .filter_map(|addr| addr.trim().split('/').next()?.trim().parse::<IpAddr>().ok())
+        .collect();
+    let routes: Vec<(IpAddr, u8)> = routes
+        .into_iter()
+        .filter(|(ip, prefix)| {
+            let host_prefix = if ip.is_ipv4() { 32 } else { 128 };
+            !(*prefix == host_prefix && local_addresses.contains(ip))
+        })
+        .collect();
+
+    let result = config
+        .peers
+        .iter()
+        .map(|peer| {
+            let claimed: Vec<String> = peer
+                .allowed_ips
+                .split(',')
+                .map(|cidr| cidr.trim().to_string())
+                .filter(|cidr| !cidr.is_empty())
+                .collect();
+
+            let mut effective = Vec::new();
+            for net in claimed.iter().filter_map(|cidr| parse_cidr(cidr)) {
+                for route in routes.iter().filter(|route| cidr_overlaps(**route, net)) {
+                    let (ip, prefix) = if route.1 >= net.1 { *route } else { net };
+                    let entry = format!("{}/{}", ip, prefix);
+                    if !effective.contains(&entry) {
+                        effective.push(entry);
+                    }
+                }
+            }
+
+            EffectiveAllowedIps {
+                public_key: peer.public_key.clone(),
+                name: peer.name.clone(),
+                claimed,
+                effective,
+            }
+        })
+        .collect();
+
+    Ok(result)
+}
+
+// Linux: 从 `ss -lunp` 的输出中提取进程,形如 users:(("wireguard-go",pid=1234,fd=7))
+// 非 root 用户看不到其他用户进程的信息,此时返回 None
+#[cfg(target_os = "linux")]
+fn parse_ss_owner(output: &str) -> Option<String> {
+    let users = output.split("users:((\"").nth(1)?;
+    let (name, rest) = users.split_once('"')?;
+    let pid: String = rest
+        .split("pid=")
+        .nth(1)?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    Some(format!("{} (PID {})", name, pid))
+}
+
+// macOS: 从 `lsof -iUDP:<port>` 的输出中提取进程 (第一行为表头)
+#[cfg(target_os = "macos")]
+fn parse_lsof_owner(output: &str) -> Option<String> {
+    let line = output.lines().nth(1)?;
+    let mut fields = line.split_whitespace();
+    let name = fields.next()?;
+    let pid = fields.next()?;
+    Some(format!("{} (PID {})", name, pid))
+}
+
+// Windows: 从 `netstat -ano -p UDP` 的输出中查找绑定该端口的 PID
+#[cfg(target_os = "windows")]
+fn parse_netstat_pid(output: &str, port: u16) -> Option<String> {
+    let suffix = format!(":{}", port);
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [proto, local, .., pid] if proto.starts_with("UDP") && local.ends_with(&suffix) => {
+                Some(pid.to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
+// 查找绑定指定 UDP 端口的进程,返回 "名称 (PID x)";无法确定时返回 None
+pub fn udp_port_owner(port: u16) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("ss")
+            .args(["-H", "-lunp", "sport", "=", &format!(":{}", port)])
+            .output()
+            .ok()?;
+        parse_ss_owner(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("lsof")
+            .args(["-nP", &format!("-iUDP:{}", port)])
+            .output()
+            .ok()?;
+        parse_lsof_owner(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // CREATE_NO_WINDOW: 隐藏控制台窗口
+        let output = std::process::Command::new("netstat")
+            .args(["-ano", "-p", "UDP"])
+            .creation_flags(0x08000000)
+            .output()
+            .ok()?;
+        let pid = parse_netstat_pid(&String::from_utf8_lossy(&output.stdout), port)?;
+
+        // tasklist 的 CSV 输出第一列为进程名
+        let output = std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .creation_flags(0x08000000)
+            .output()
+            .ok()?;
+        let name = String::from_utf8_lossy(&output.stdout)
+            .split(',')
+            .next()
+            .map(|n| n.trim().trim_matches('"').to_string())
+            .filter(|n| !n.is_empty() && !n.starts_with("INFO:"));
+        Some(match name {
+            Some(name) => format!("{} (PID {})", name, pid),
+            None => format!("PID {}", pid),
+        })
+    }
+}
+
+// 检查 UDP 监听端口能否绑定,被占用时返回说明 (尽量包含占用进程)
+pub fn listen_port_conflict(port: u16) -> Option<String> {
+    if std::net::UdpSocket::bind(("0.0.0.0", port)).is_ok() {
+        return None;
+    }
+
+    Some(match udp_port_owner(port) {
+        Some(owner) => format!(
+            "端口 {} 已被 {} 占用，请停止该进程或更换监听端口",
+            port, owner
+        ),
+        None => format!("端口 {} 已被占用", port),
+    })
+}
+
+// 检查监听端口是否可用,可用时返回 None,被占用时返回占用进程的说明
+#[command]
+pub async fn check_listen_port(port: u16) -> Result<Option<String>, String> {
+    if port == 0 {
+        return Err("监听端口必须在 1-65535 之间".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || listen_port_conflict(port))
+        .await
+        .map_err(|e| format!("检查端口任务执行失败: {}", e))
+}
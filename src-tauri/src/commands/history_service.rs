@@ -1,6 +1,10 @@
 use crate::sync::SyncManager;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
 use tauri::{command, AppHandle, Manager};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,9 +20,19 @@ pub struct HistoryEntry {
     pub surge_config: Option<String>,
     pub mikrotik_config: Option<String>,
     pub openwrt_config: Option<String>,
+    pub pfsense_config: Option<String>,
+    pub json_config: Option<String>,
     pub public_key: String,
     pub server_id: String,
     pub server_name: String,
+    // 到期时间 (毫秒时间戳),到期后标记为已过期
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub expired: bool,
+    // 用户自定义标签,用于筛选
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -32,6 +46,58 @@ pub struct HistoryListItem {
     pub public_key: String,
     pub server_id: String,
     pub server_name: String,
+    // 到期时间 (毫秒时间戳),到期后标记为已过期
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub expired: bool,
+    // 用户自定义标签,用于筛选
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+// gzip 文件头
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// 读取 JSON 文件,以 gzip 文件头识别并解压压缩存储的文件
+pub fn read_json_file(path: &Path) -> Result<String, String> {
+    use std::io::Read;
+
+    let bytes = fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8(bytes).map_err(|e| format!("文件编码无效: {}", e));
+    }
+
+    let mut content = String::new();
+    flate2::read::GzDecoder::new(bytes.as_slice())
+        .read_to_string(&mut content)
+        .map_err(|e| format!("解压文件失败: {}", e))?;
+
+    Ok(content)
+}
+
+// 写入历史记录文件,按偏好设置决定是否 gzip 压缩
+fn write_history_file(app: &AppHandle, path: &Path, json: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let compress = crate::commands::persistence::load_preferences(app.clone())
+        .map(|p| p.compress_history)
+        .unwrap_or(false);
+
+    if !compress {
+        return fs::write(path, json).map_err(|e| format!("保存历史记录失败: {}", e));
+    }
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(json.as_bytes())
+        .map_err(|e| format!("压缩历史记录失败: {}", e))?;
+    let bytes = encoder
+        .finish()
+        .map_err(|e| format!("压缩历史记录失败: {}", e))?;
+
+    fs::write(path, bytes).map_err(|e| format!("保存历史记录失败: {}", e))
 }
 
 #[command]
@@ -59,9 +125,9 @@ pub fn save_to_history(app: AppHandle, entry: HistoryEntry) -> Result<(), String
         format!("序列化历史记录失败: {}", e)
     })?;
 
-    fs::write(&file_path, json).map_err(|e| {
-        log::error!("保存历史记录失败: {}", e);
-        format!("保存历史记录失败: {}", e)
+    write_history_file(&app, &file_path, &json).map_err(|e| {
+        log::error!("{}", e);
+        e
     })?;
 
     log::info!("历史记录保存成功: {}", entry.id);
@@ -88,19 +154,9 @@ pub fn get_history_list(app: AppHandle) -> Result<Vec<HistoryListItem>, String>
         if let Ok(entry) = entry {
             let path = entry.path();
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(content) = read_json_file(&path) {
                     if let Ok(history_entry) = serde_json::from_str::<HistoryEntry>(&content) {
-                        items.push(HistoryListItem {
-                            id: history_entry.id,
-                            timestamp: history_entry.timestamp,
-                            interface_name: history_entry.interface_name,
-                            peer_comment: history_entry.peer_comment,
-                            peer_id: history_entry.peer_id,
-                            address: history_entry.address,
-                            public_key: history_entry.public_key,
-                            server_id: history_entry.server_id,
-                            server_name: history_entry.server_name,
-                        });
+                        items.push(history_list_item(history_entry));
                     }
                 }
             }
@@ -112,6 +168,140 @@ pub fn get_history_list(app: AppHandle) -> Result<Vec<HistoryListItem>, String>
     Ok(items)
 }
 
+// 历史记录索引: 文件名 -> (文件修改时间, 记录时间戳, 标签)
+// 分页时按索引排序,只读取修改过的文件和当前页的文件
+type HistoryIndex = HashMap<String, (SystemTime, i64, Vec<String>)>;
+
+lazy_static::lazy_static! {
+    static ref HISTORY_INDEX: Mutex<HistoryIndex> = Mutex::new(HashMap::new());
+}
+
+// 按历史目录当前内容更新索引,只重新读取修改过的文件
+fn refresh_history_index(history_dir: &Path, index: &mut HistoryIndex) -> Result<(), String> {
+    let entries = fs::read_dir(history_dir).map_err(|e| format!("读取历史目录失败: {}", e))?;
+    let mut updated: HistoryIndex = HashMap::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+            continue;
+        };
+
+        // 文件未修改时沿用索引中的时间戳和标签
+        if let Some((cached_modified, timestamp, tags)) = index.remove(filename) {
+            if cached_modified == modified {
+                updated.insert(filename.to_string(), (modified, timestamp, tags));
+                continue;
+            }
+        }
+
+        if let Ok(content) = read_json_file(&path) {
+            if let Ok(history_entry) = serde_json::from_str::<HistoryEntry>(&content) {
+                updated.insert(
+                    filename.to_string(),
+                    (modified, history_entry.timestamp, history_entry.tags),
+                );
+            }
+        }
+    }
+
+    // 已删除的文件随之从索引中移除
+    *index = updated;
+    Ok(())
+}
+
+// 分页获取历史记录,按时间倒序,返回当前页和记录总数
+#[command]
+pub fn get_history_page(
+    app: AppHandle,
+    offset: usize,
+    limit: usize,
+) -> Result<(Vec<HistoryListItem>, usize), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let history_dir = app_data_dir.join("history");
+
+    if !history_dir.exists() {
+        return Ok((Vec::new(), 0));
+    }
+
+    let mut index = HISTORY_INDEX
+        .lock()
+        .map_err(|_| "历史记录索引状态异常".to_string())?;
+    refresh_history_index(&history_dir, &mut index)?;
+
+    let mut sorted: Vec<(&String, i64)> = index
+        .iter()
+        .map(|(filename, (_, timestamp, _))| (filename, *timestamp))
+        .collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let total = sorted.len();
+    let items = sorted
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .filter_map(|(filename, _)| read_json_file(&history_dir.join(filename)).ok())
+        .filter_map(|content| serde_json::from_str::<HistoryEntry>(&content).ok())
+        .map(history_list_item)
+        .collect();
+
+    Ok((items, total))
+}
+
+// 获取所有历史记录中出现过的标签 (去重并排序),分页加载时用于标签筛选
+#[command]
+pub fn list_history_tags(app: AppHandle) -> Result<Vec<String>, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let history_dir = app_data_dir.join("history");
+
+    if !history_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut index = HISTORY_INDEX
+        .lock()
+        .map_err(|_| "历史记录索引状态异常".to_string())?;
+    refresh_history_index(&history_dir, &mut index)?;
+
+    let tags: std::collections::BTreeSet<String> = index
+        .values()
+        .flat_map(|(_, _, tags)| tags.iter().cloned())
+        .collect();
+
+    Ok(tags.into_iter().collect())
+}
+
+fn history_list_item(entry: HistoryEntry) -> HistoryListItem {
+    HistoryListItem {
+        id: entry.id,
+        timestamp: entry.timestamp,
+        interface_name: entry.interface_name,
+        peer_comment: entry.peer_comment,
+        peer_id: entry.peer_id,
+        address: entry.address,
+        public_key: entry.public_key,
+        server_id: entry.server_id,
+        server_name: entry.server_name,
+        expires_at: entry.expires_at,
+        expired: entry.expired,
+        tags: entry.tags,
+    }
+}
+
 #[command]
 pub fn get_history_detail(app: AppHandle, id: String) -> Result<HistoryEntry, String> {
     let app_data_dir = app
@@ -125,7 +315,7 @@ pub fn get_history_detail(app: AppHandle, id: String) -> Result<HistoryEntry, St
         return Err("历史记录不存在".to_string());
     }
 
-    let content = fs::read_to_string(&file_path).map_err(|e| format!("读取历史记录失败: {}", e))?;
+    let content = read_json_file(&file_path).map_err(|e| format!("读取历史记录失败: {}", e))?;
 
     let entry: HistoryEntry =
         serde_json::from_str(&content).map_err(|e| format!("解析历史记录失败: {}", e))?;
@@ -217,7 +407,7 @@ pub fn export_all_configs_zip(app: AppHandle, zip_path: String) -> Result<(), St
         if let Ok(entry) = entry {
             let path = entry.path();
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(content) = read_json_file(&path) {
                     if let Ok(history_entry) = serde_json::from_str::<HistoryEntry>(&content) {
                         let base_name = format!(
                             "{}-{}",
@@ -239,6 +429,14 @@ pub fn export_all_configs_zip(app: AppHandle, zip_path: String) -> Result<(), St
                                 .map_err(|e| format!("写入 Surge 文件到 ZIP 失败: {}", e))?;
                         }
 
+                        // 完整记录,供 import_history_zip 导入
+                        let json = serde_json::to_string_pretty(&history_entry)
+                            .map_err(|e| format!("序列化历史记录失败: {}", e))?;
+                        zip.start_file(format!("history/{}.json", history_entry.id), options)
+                            .map_err(|e| format!("添加历史记录到 ZIP 失败: {}", e))?;
+                        zip.write_all(json.as_bytes())
+                            .map_err(|e| format!("写入历史记录到 ZIP 失败: {}", e))?;
+
                         all_peers.push(history_entry.ikuai_config);
                         config_count += 1;
                     }
@@ -263,6 +461,162 @@ pub fn export_all_configs_zip(app: AppHandle, zip_path: String) -> Result<(), St
     Ok(())
 }
 
+// 从 export_all_configs_zip 导出的 ZIP 中导入历史记录,返回导入的条数
+// ID 相同的记录保留时间戳较新的一条
+#[command]
+pub fn import_history_zip(app: AppHandle, zip_path: String) -> Result<usize, String> {
+    use std::io::Read;
+
+    let file = fs::File::open(&zip_path).map_err(|e| format!("打开 ZIP 文件失败: {}", e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("读取 ZIP 文件失败: {}", e))?;
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+    let history_dir = app_data_dir.join("history");
+    fs::create_dir_all(&history_dir).map_err(|e| format!("创建历史目录失败: {}", e))?;
+
+    let mut found = 0;
+    let mut imported = 0;
+
+    for i in 0..archive.len() {
+        let mut zip_file = archive
+            .by_index(i)
+            .map_err(|e| format!("读取 ZIP 条目失败: {}", e))?;
+
+        let name = zip_file.name().to_string();
+        if !name.starts_with("history/") || !name.ends_with(".json") {
+            continue;
+        }
+
+        let mut content = String::new();
+        zip_file
+            .read_to_string(&mut content)
+            .map_err(|e| format!("读取 {} 失败: {}", name, e))?;
+
+        let entry: HistoryEntry = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("跳过无效的历史记录 {}: {}", name, e);
+                continue;
+            }
+        };
+        found += 1;
+
+        // ID 用作文件名,不允许包含路径
+        if entry.id.is_empty() || entry.id.contains(|c| matches!(c, '/' | '\\' | '.')) {
+            log::warn!("跳过 ID 无效的历史记录: {}", name);
+            continue;
+        }
+
+        let file_path = history_dir.join(format!("{}.json", entry.id));
+        if file_path.exists() {
+            let existing = read_json_file(&file_path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<HistoryEntry>(&content).ok());
+            if let Some(existing) = existing {
+                if existing.timestamp >= entry.timestamp {
+                    continue;
+                }
+            }
+        }
+
+        write_history_file(&app, &file_path, &content)?;
+        imported += 1;
+    }
+
+    if found == 0 {
+        return Err("ZIP 中没有可导入的历史记录，请使用新版本重新导出".to_string());
+    }
+
+    log::info!("从 ZIP 导入了 {} 条历史记录 (共 {} 条)", imported, found);
+    Ok(imported)
+}
+
+// CSV 字段转义: 含逗号、引号或换行时用双引号包裹,内部引号加倍
+// 以 = + - @ 开头的值加 ' 前缀,避免在 Excel 等表格软件中被当作公式执行
+pub(crate) fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[command]
+pub fn export_history_csv(app: AppHandle, csv_path: String) -> Result<(), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let history_dir = app_data_dir.join("history");
+
+    if !history_dir.exists() {
+        return Err("没有历史记录可导出".to_string());
+    }
+
+    let entries = fs::read_dir(&history_dir).map_err(|e| format!("读取历史目录失败: {}", e))?;
+
+    let mut history_entries: Vec<HistoryEntry> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|path| read_json_file(&path).ok())
+        .filter_map(|content| serde_json::from_str::<HistoryEntry>(&content).ok())
+        .collect();
+
+    if history_entries.is_empty() {
+        return Err("没有找到有效的配置".to_string());
+    }
+
+    history_entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    // 加 UTF-8 BOM,避免 Excel 打开时中文乱码
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(
+        "timestamp,server_name,interface_name,ikuai_comment,ikuai_id,address,public_key\r\n",
+    );
+
+    for entry in &history_entries {
+        let timestamp = chrono::DateTime::from_timestamp_millis(entry.timestamp)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        let row = [
+            timestamp,
+            entry.server_name.clone(),
+            entry.interface_name.clone(),
+            entry.peer_comment.clone(),
+            entry.peer_id.to_string(),
+            entry.address.clone(),
+            entry.public_key.clone(),
+        ];
+        csv.push_str(
+            &row.iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        csv.push_str("\r\n");
+    }
+
+    fs::write(&csv_path, csv).map_err(|e| format!("写入 CSV 文件失败: {}", e))?;
+
+    Ok(())
+}
+
 #[command]
 pub fn get_history_list_by_server(
     app: AppHandle,
@@ -278,60 +632,348 @@ pub fn get_history_list_by_server(
     Ok(filtered)
 }
 
+// 搜索历史记录: 按备注、接口名称、地址、公钥和标签做不区分大小写的子串匹配
+// server_id 限定服务端,tags 中的标签需要全部匹配
 #[command]
-pub fn generate_next_client_ip(app: AppHandle, peer_address_range: String, server_id: String) -> Result<String, String> {
-    // 解析 CIDR 格式：10.2.3.0/24
-    let parts: Vec<&str> = peer_address_range.split('/').collect();
-    if parts.len() != 2 {
-        return Err("无效的 CIDR 格式".to_string());
+pub fn search_history(
+    app: AppHandle,
+    query: String,
+    server_id: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Vec<HistoryListItem>, String> {
+    let query = query.trim().to_lowercase();
+    let tags: Vec<String> = tags
+        .unwrap_or_default()
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+
+    let results = get_history_list(app)?
+        .into_iter()
+        .filter(|item| match server_id.as_deref() {
+            Some(id) if !id.is_empty() => item.server_id == id,
+            _ => true,
+        })
+        .filter(|item| {
+            tags.iter()
+                .all(|tag| item.tags.iter().any(|t| t.to_lowercase() == *tag))
+        })
+        .filter(|item| {
+            query.is_empty()
+                || [
+                    &item.peer_comment,
+                    &item.interface_name,
+                    &item.address,
+                    &item.public_key,
+                ]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&query))
+                || item.tags.iter().any(|t| t.to_lowercase().contains(&query))
+        })
+        .collect();
+
+    Ok(results)
+}
+
+// 设置历史记录的标签,去除空白和重复 (不区分大小写) 的标签
+#[command]
+pub fn set_history_tags(app: AppHandle, id: String, tags: Vec<String>) -> Result<(), String> {
+    let mut entry = get_history_detail(app.clone(), id)?;
+
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
     }
 
-    let base_ip = parts[0];
-    let mask: u8 = parts[1]
-        .parse()
-        .map_err(|_| "掩码格式错误".to_string())?;
+    entry.tags = normalized;
+    save_to_history(app, entry)
+}
+
+// 生成服务端地址池中下一个可用的客户端 IP,分配逻辑与 allocate_peer_address 相同
+#[command]
+pub fn generate_next_client_ip(app: AppHandle, peer_address_range: String, server_id: String) -> Result<String, String> {
+    crate::commands::server_service::allocate_peer_address(app, server_id, peer_address_range)
+}
+
+// HTML 转义
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// 生成客户端接入说明页（自包含 HTML，可直接打印或另存为 PDF）
+#[command]
+pub fn generate_onboarding_sheet(app: AppHandle, history_id: String) -> Result<String, String> {
+    let entry = get_history_detail(app, history_id)?;
+
+    let qrcode = crate::commands::misc_commands::generate_qrcode(entry.wg_config.clone())?;
+
+    let title = if entry.peer_comment.is_empty() {
+        entry.interface_name.clone()
+    } else {
+        entry.peer_comment.clone()
+    };
+
+    let created_at = chrono::DateTime::from_timestamp_millis(entry.timestamp)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>WireGuard 接入说明 - {title}</title>
+<style>
+  body {{ font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif; margin: 32px; color: #222; }}
+  h1 {{ font-size: 22px; margin-bottom: 4px; }}
+  .meta {{ color: #666; font-size: 13px; margin-bottom: 24px; }}
+  .qr {{ text-align: center; margin: 24px 0; }}
+  .qr img {{ width: 240px; height: 240px; }}
+  pre {{ background: #f5f5f5; padding: 12px; border-radius: 6px; font-size: 12px; white-space: pre-wrap; word-break: break-all; }}
+  ol li {{ margin-bottom: 6px; }}
+  @media print {{ body {{ margin: 12mm; }} }}
+</style>
+</head>
+<body>
+<h1>WireGuard 接入说明 - {title}</h1>
+<div class="meta">服务端: {server} · 客户端地址: {address} · 生成时间: {created_at}</div>
+
+<h2>方式一: 扫码导入（手机）</h2>
+<ol>
+  <li>在应用商店安装 WireGuard 官方客户端</li>
+  <li>打开 WireGuard，点击 "+" 并选择 "扫描二维码"</li>
+  <li>扫描下方二维码，为隧道命名后保存并开启</li>
+</ol>
+<div class="qr"><img src="{qrcode}" alt="WireGuard 配置二维码"></div>
+
+<h2>方式二: 导入配置文件（电脑）</h2>
+<ol>
+  <li>从 https://www.wireguard.com/install/ 下载并安装 WireGuard 客户端</li>
+  <li>将下方内容保存为 <code>{filename}.conf</code></li>
+  <li>在 WireGuard 中选择 "从文件导入隧道"，导入后点击 "连接"</li>
+</ol>
+<pre>{config}</pre>
+
+<p class="meta">配置中包含私钥，请妥善保管，不要转发给他人。</p>
+</body>
+</html>
+"#,
+        title = escape_html(&title),
+        server = escape_html(&entry.server_name),
+        address = escape_html(&entry.address),
+        created_at = created_at,
+        qrcode = qrcode,
+        filename = escape_html(&entry.interface_name),
+        config = escape_html(&entry.wg_config),
+    );
+
+    Ok(html)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublicKeyMismatch {
+    pub id: String,
+    pub stored: String,
+    pub computed: String,
+}
+
+// 从 wg-quick 配置中提取 [Interface] 的私钥
+fn extract_private_key(wg_config: &str) -> Option<String> {
+    wg_config.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("PrivateKey") {
+            // Base64 私钥末尾的 '=' 会被 split_once 保留在 value 中
+            Some(value.trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+// 扫描历史记录中公钥与私钥不一致的条目,fix 为 true 时同时写回正确的公钥
+fn scan_public_key_mismatches(
+    app: &AppHandle,
+    fix: bool,
+) -> Result<Vec<PublicKeyMismatch>, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
 
-    if mask != 24 {
-        return Err("目前仅支持 /24 掩码".to_string());
+    let history_dir = app_data_dir.join("history");
+
+    if !history_dir.exists() {
+        return Ok(Vec::new());
     }
 
-    // 解析基础 IP（前 3 段）
-    let ip_parts: Vec<&str> = base_ip.split('.').collect();
-    if ip_parts.len() != 4 {
-        return Err("IP 地址格式错误".to_string());
+    let mut mismatches = Vec::new();
+    let entries = fs::read_dir(&history_dir).map_err(|e| format!("读取历史目录失败: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+
+        let Ok(content) = read_json_file(&path) else {
+            continue;
+        };
+        let Ok(mut history_entry) = serde_json::from_str::<HistoryEntry>(&content) else {
+            continue;
+        };
+
+        let Some(private_key) = extract_private_key(&history_entry.wg_config) else {
+            continue;
+        };
+
+        let computed = match crate::commands::key_management::compute_public_key(&private_key) {
+            Ok(key) => key,
+            Err(e) => {
+                log::warn!("历史记录 {} 的私钥无效: {}", history_entry.id, e);
+                continue;
+            }
+        };
+
+        if history_entry.public_key.trim() == computed {
+            continue;
+        }
+
+        mismatches.push(PublicKeyMismatch {
+            id: history_entry.id.clone(),
+            stored: history_entry.public_key.clone(),
+            computed: computed.clone(),
+        });
+
+        if fix {
+            history_entry.public_key = computed;
+            let json = serde_json::to_string_pretty(&history_entry)
+                .map_err(|e| format!("序列化历史记录失败: {}", e))?;
+            write_history_file(app, &path, &json)?;
+            log::info!("已修正历史记录公钥: {}", history_entry.id);
+        }
     }
 
-    let first_three = format!("{}.{}.{}", ip_parts[0], ip_parts[1], ip_parts[2]);
+    Ok(mismatches)
+}
+
+// 校验历史记录中保存的公钥是否与私钥推导结果一致
+#[command]
+pub fn verify_stored_public_keys(app: AppHandle) -> Result<Vec<PublicKeyMismatch>, String> {
+    scan_public_key_mismatches(&app, false)
+}
 
-    // 获取该服务端的所有历史记录，提取已使用的客户端 IP
-    let all_history = get_history_list(app)?;
-    let server_history: Vec<HistoryListItem> = all_history
-        .into_iter()
-        .filter(|item| item.server_id == server_id)
-        .collect();
+// 用私钥推导结果修正不一致的公钥,返回修正的条目
+#[command]
+pub fn fix_stored_public_keys(app: AppHandle) -> Result<Vec<PublicKeyMismatch>, String> {
+    scan_public_key_mismatches(&app, true)
+}
 
-    let mut used_ips = std::collections::HashSet::new();
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerPeerAllowedIp {
+    pub public_key: String,
+    pub allowed_ip: String,
+}
 
-    for history_item in server_history {
-        // 提取 address 中的 IP（格式为 IP/32）
-        if let Some(ip) = history_item.address.split('/').next() {
-            // 检查是否与基础 IP 同一网段
-            if ip.starts_with(&first_three) {
-                if let Some(last_octet_str) = ip.split('.').last() {
-                    if let Ok(last_octet) = last_octet_str.parse::<u32>() {
-                        used_ips.insert(last_octet);
+// 汇总某个服务端下所有客户端的地址,生成服务端 Peer 需要的 AllowedIPs (单主机路由)
+#[command]
+pub fn compute_server_peer_allowed_ips(
+    app: AppHandle,
+    server_id: String,
+) -> Result<Vec<ServerPeerAllowedIp>, String> {
+    let mut server_history = get_history_list_by_server(app, server_id)?;
+    server_history.sort_by_key(|item| item.peer_id);
+
+    let mut result = Vec::new();
+
+    for item in server_history {
+        if item.public_key.is_empty() {
+            continue;
+        }
+
+        // 客户端可能配置了多个地址 (IPv4 + IPv6)
+        let allowed_ip = item
+            .address
+            .split(',')
+            .filter_map(|addr| {
+                let ip = addr.trim().split('/').next()?.trim();
+                match ip.parse::<std::net::IpAddr>() {
+                    Ok(std::net::IpAddr::V4(v4)) => Some(format!("{}/32", v4)),
+                    Ok(std::net::IpAddr::V6(v6)) => Some(format!("{}/128", v6)),
+                    Err(_) => {
+                        log::warn!("历史记录 {} 的地址无效: {}", item.id, addr);
+                        None
                     }
                 }
-            }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if allowed_ip.is_empty() {
+            continue;
         }
+
+        result.push(ServerPeerAllowedIp {
+            public_key: item.public_key,
+            allowed_ip,
+        });
     }
 
-    // 生成下一个可用的 IP（从 2 开始，1 被服务端占用）
-    for i in 2..=254 {
-        if !used_ips.contains(&i) {
-            return Ok(format!("{}.{}/32", first_three, i));
+    Ok(result)
+}
+
+// 将已到期的历史记录标记为已过期,返回新标记的数量
+pub fn mark_expired_history(app: &AppHandle) -> Result<usize, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let history_dir = app_data_dir.join("history");
+
+    if !history_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    let entries = fs::read_dir(&history_dir).map_err(|e| format!("读取历史目录失败: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+
+        let Ok(content) = read_json_file(&path) else {
+            continue;
+        };
+        let Ok(mut history_entry) = serde_json::from_str::<HistoryEntry>(&content) else {
+            continue;
+        };
+
+        if history_entry.expired || !crate::tunnel::is_peer_expired(history_entry.expires_at) {
+            continue;
         }
+
+        history_entry.expired = true;
+        let json = serde_json::to_string_pretty(&history_entry)
+            .map_err(|e| format!("序列化历史记录失败: {}", e))?;
+        write_history_file(app, &path, &json)?;
+
+        log::info!("历史记录已过期: {}", history_entry.id);
+        count += 1;
     }
 
-    Err("地址池已满，无法生成新的客户端 IP".to_string())
+    Ok(count)
 }
@@ -81,6 +81,66 @@ pub fn compute_public_key(private_key: &str) -> Result<String, String> {
     Ok(BASE64.encode(&public_bytes))
 }
 
+// 校验单个密钥 (key_type: private / public / preshared)
+// 先按 normalize_key 去除空白并补齐填充,与保存时的规范化保持一致
+// 格式错误返回 Err 并说明原因;私钥未经 clamp 时返回 Ok(false),通常说明误填了公钥
+#[command]
+pub fn validate_wireguard_key(key: String, key_type: String) -> Result<bool, String> {
+    if !matches!(key_type.as_str(), "private" | "public" | "preshared") {
+        return Err(format!("未知的密钥类型: {}", key_type));
+    }
+
+    // 被截断的密钥通常是字符数不对,一并提示去除空白后的字符数
+    let length = key.chars().filter(|c| !c.is_whitespace()).count();
+    let normalized = normalize_key(&key).map_err(|e| {
+        if length == 0 || length == 44 {
+            e
+        } else {
+            format!("{} (共 {} 个字符，应为 44 个字符)", e, length)
+        }
+    })?;
+    let bytes = BASE64
+        .decode(&normalized)
+        .map_err(|e| format!("密钥不是有效的 Base64: {}", e))?;
+
+    if key_type == "private" && (bytes[0] & 7 != 0 || bytes[31] & 0xc0 != 0x40) {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+// 规范化密钥: 去除所有空白字符 (含中间的空格和换行)、补齐 Base64 填充并重新编码
+// 从聊天软件复制的密钥常带有多余空白或丢失末尾的 '=',规范化后仍不是 32 字节才返回错误
+pub fn normalize_key(key: &str) -> Result<String, String> {
+    let compact: String = key.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err("密钥不能为空".to_string());
+    }
+
+    let unpadded = compact.trim_end_matches('=');
+    let padding = (4 - unpadded.len() % 4) % 4;
+    let padded = format!("{}{}", unpadded, "=".repeat(padding));
+
+    let bytes = BASE64
+        .decode(&padded)
+        .map_err(|e| format!("密钥不是有效的 Base64: {}", e))?;
+    if bytes.len() != 32 {
+        return Err(format!(
+            "密钥长度错误: 实际为 {} 字节，应为 32 字节",
+            bytes.len()
+        ));
+    }
+
+    Ok(BASE64.encode(bytes))
+}
+
+// 供前端在粘贴密钥时调用,返回规范化后的密钥
+#[command]
+pub fn normalize_wireguard_key(key: String) -> Result<String, String> {
+    normalize_key(&key)
+}
+
 fn clamp_private_key(key: &mut [u8; 32]) {
     key[0] &= 248;
     key[31] &= 127;
@@ -1,6 +1,7 @@
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::Serialize;
 use std::fs;
-use tauri::command;
+use tauri::{command, AppHandle};
 
 #[command]
 pub fn get_platform() -> String {
@@ -24,6 +25,42 @@ pub fn generate_qrcode(content: String) -> Result<String, String> {
     Ok(data_url)
 }
 
+// PNG 二维码的默认和最大边长 (像素)
+const QRCODE_PNG_DEFAULT_SIZE: u32 = 400;
+const QRCODE_PNG_MAX_SIZE: u32 = 2048;
+
+// 生成 PNG 格式的二维码,用于不支持内联 SVG 的场景 (部分手机、打印预览)
+// size 为 0 时使用默认尺寸;内容较多时实际尺寸可能略大于 size
+#[command]
+pub fn generate_qrcode_png(content: String, size: u32) -> Result<String, String> {
+    use qrcode::QrCode;
+
+    if size > QRCODE_PNG_MAX_SIZE {
+        return Err(format!(
+            "二维码尺寸过大: {} (最大 {})",
+            size, QRCODE_PNG_MAX_SIZE
+        ));
+    }
+    let size = if size == 0 {
+        QRCODE_PNG_DEFAULT_SIZE
+    } else {
+        size
+    };
+
+    let code = QrCode::new(content.as_bytes()).map_err(|e| format!("生成二维码失败: {}", e))?;
+    let image = code
+        .render::<image::Luma<u8>>()
+        .min_dimensions(size, size)
+        .build();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("编码 PNG 失败: {}", e))?;
+
+    Ok(format!("data:image/png;base64,{}", BASE64.encode(&png)))
+}
+
 #[command]
 pub fn save_config_to_path(content: String, file_path: String) -> Result<(), String> {
     fs::write(&file_path, content).map_err(|e| format!("保存文件失败: {}", e))?;
@@ -43,6 +80,30 @@ pub fn read_file_as_base64(file_path: String) -> Result<String, String> {
     Ok(BASE64.encode(&data))
 }
 
+// 识别图片中的二维码并返回内容 (通常为 wg-quick 配置)
+#[command]
+pub fn decode_qrcode_from_image(file_path: String) -> Result<String, String> {
+    let data = fs::read(&file_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let image = image::load_from_memory(&data)
+        .map_err(|e| format!("无法识别图片格式: {}", e))?
+        .to_luma8();
+
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        image.width() as usize,
+        image.height() as usize,
+        |x, y| image.get_pixel(x as u32, y as u32).0[0],
+    );
+
+    for grid in prepared.detect_grids() {
+        match grid.decode() {
+            Ok((_, content)) => return Ok(content),
+            Err(e) => log::warn!("二维码解码失败: {:?}", e),
+        }
+    }
+
+    Err("未能识别二维码，请确保图像清晰且包含有效的二维码".to_string())
+}
+
 #[command]
 pub fn get_local_ip() -> Result<String, String> {
     // 获取设备的本地局域网 IP 地址
@@ -232,3 +293,244 @@ pub async fn get_public_ip() -> Result<String, String> {
 
     Err("无法获取公网 IP，请检查网络连接".to_string())
 }
+
+// 推荐的监听端口: 从 51820 起第一个未被其他隧道使用且可以绑定的 UDP 端口
+async fn suggest_listen_port(app: &AppHandle) -> u16 {
+    let used: Vec<u16> = crate::tunnel::get_all_tunnel_configs(app.clone())
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|t| t.listen_port)
+        .collect();
+
+    (51820..=51920)
+        .find(|port| !used.contains(port) && std::net::UdpSocket::bind(("0.0.0.0", *port)).is_ok())
+        .unwrap_or(51820)
+}
+
+// 为服务端配置推荐候选 Endpoint: 公网 IP 和各个非虚拟网卡的局域网 IP,均附带推荐的监听端口
+// 公网 IP 排在最前,获取失败时只返回局域网地址
+#[command]
+pub async fn suggest_server_endpoint(app: AppHandle) -> Result<Vec<String>, String> {
+    let port = suggest_listen_port(&app).await;
+
+    let mut hosts = Vec::new();
+    match get_public_ip().await {
+        Ok(ip) => hosts.push(ip),
+        Err(e) => log::warn!("推荐 Endpoint 时获取公网 IP 失败: {}", e),
+    }
+    match get_all_local_ips() {
+        Ok(ips) => hosts.extend(ips),
+        Err(e) => log::warn!("推荐 Endpoint 时获取局域网 IP 失败: {}", e),
+    }
+
+    let mut candidates: Vec<String> = Vec::new();
+    for host in hosts {
+        let endpoint = if host.contains(':') {
+            format!("[{}]:{}", host, port)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        if !candidates.contains(&endpoint) {
+            candidates.push(endpoint);
+        }
+    }
+
+    if candidates.is_empty() {
+        return Err("未能获取可用的公网或局域网地址".to_string());
+    }
+
+    Ok(candidates)
+}
+
+// 测试 endpoint 的连通性和延迟,返回毫秒
+// ICMP 需要特权,这里改为计时 TCP 连接; WireGuard 本身走 UDP,端口不接受 TCP 时会返回拒绝连接的错误
+#[command]
+pub async fn ping_endpoint(endpoint: String) -> Result<u64, String> {
+    let endpoint = endpoint.trim().to_string();
+    if endpoint.is_empty() {
+        return Err("Endpoint 不能为空".to_string());
+    }
+
+    // DNS 解析是阻塞调用
+    let resolved = tokio::task::spawn_blocking(move || crate::tunnel::resolve_endpoint(&endpoint))
+        .await
+        .map_err(|e| format!("解析任务执行失败: {}", e))??;
+    let addr: std::net::SocketAddr = resolved
+        .parse()
+        .map_err(|e| format!("解析地址 {} 失败: {}", resolved, e))?;
+
+    let start = std::time::Instant::now();
+    let result = tokio::time::timeout(
+        std::time::Duration::from_secs(3),
+        tokio::net::TcpStream::connect(addr),
+    )
+    .await;
+    let elapsed = start.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(Ok(_)) => {
+            log::info!("连接 {} 耗时 {} ms", addr, elapsed);
+            Ok(elapsed)
+        }
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => Err(format!(
+            "{} 拒绝连接 ({} ms)，主机可达但该端口未接受 TCP 连接",
+            addr, elapsed
+        )),
+        Ok(Err(e)) => Err(format!("连接 {} 失败: {}", addr, e)),
+        Err(_) => Err(format!("连接 {} 超时 (3 秒)", addr)),
+    }
+}
+
+// 无法探测路径 MTU 时建议的隧道 MTU (1500 - 80)
+const DEFAULT_TUNNEL_MTU: u16 = 1420;
+
+// 探测到 endpoint 的路径 MTU,减去 WireGuard 开销后作为建议的隧道 MTU
+// 以禁止分片的 ping 二分查找能通过的最大包;目标不响应 ICMP 或平台不支持时返回 1420
+#[command]
+pub async fn detect_optimal_mtu(endpoint: String) -> Result<u16, String> {
+    let endpoint = endpoint.trim().to_string();
+    if endpoint.is_empty() {
+        return Err("Endpoint 不能为空".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        // endpoint 可以不带端口
+        let resolved = crate::tunnel::resolve_endpoint(&endpoint)
+            .or_else(|_| crate::tunnel::resolve_endpoint(&format!("{}:0", endpoint)))?;
+        let addr: std::net::SocketAddr = resolved
+            .parse()
+            .map_err(|e| format!("解析地址 {} 失败: {}", resolved, e))?;
+
+        let Some(path_mtu) = probe_path_mtu(addr.ip()) else {
+            log::info!("无法探测到 {} 的路径 MTU,使用默认值", addr.ip());
+            return Ok(DEFAULT_TUNNEL_MTU);
+        };
+
+        // 与 wg-quick 一致,IPv4 也按 IPv6 的 80 字节开销保守计算
+        let mtu = path_mtu.saturating_sub(crate::tunnel::WG_OVERHEAD_V6 as u16);
+        log::info!(
+            "到 {} 的路径 MTU 为 {},建议隧道 MTU {}",
+            addr.ip(),
+            path_mtu,
+            mtu
+        );
+        Ok(mtu)
+    })
+    .await
+    .map_err(|e| format!("MTU 探测任务执行失败: {}", e))?
+}
+
+// 二分查找能以禁止分片方式到达目标的最大 IP 包 (576 ~ 1500 字节)
+fn probe_path_mtu(ip: std::net::IpAddr) -> Option<u16> {
+    // 外层 IP 头 + ICMP 头
+    let header: u16 = if ip.is_ipv4() { 28 } else { 48 };
+
+    let (mut low, mut high) = (576 - header, 1500 - header);
+    // 最小的包也不通时,目标不可达或不响应 ICMP
+    if !ping_dont_fragment(ip, low)? {
+        return None;
+    }
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if ping_dont_fragment(ip, mid)? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Some(low + header)
+}
+
+// 发送一个禁止分片的 ping (超时 1 秒),平台不支持时返回 None
+fn ping_dont_fragment(ip: std::net::IpAddr, payload: u16) -> Option<bool> {
+    let target = ip.to_string();
+    let size = payload.to_string();
+
+    #[cfg(target_os = "linux")]
+    let output = std::process::Command::new("ping")
+        .args(["-c", "1", "-W", "1", "-M", "do", "-s", &size, &target])
+        .output();
+
+    // macOS 的 ping -D 只支持 IPv4
+    #[cfg(target_os = "macos")]
+    let output = if ip.is_ipv4() {
+        std::process::Command::new("ping")
+            .args(["-c", "1", "-t", "1", "-D", "-s", &size, &target])
+            .output()
+    } else {
+        return None;
+    };
+
+    // Windows 的 ping -f 只支持 IPv4
+    #[cfg(target_os = "windows")]
+    let output = if ip.is_ipv4() {
+        use std::os::windows::process::CommandExt;
+
+        std::process::Command::new("ping")
+            .args(["-n", "1", "-w", "1000", "-f", "-l", &size, &target])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+    } else {
+        return None;
+    };
+
+    let output = output.ok()?;
+
+    // Windows 收到 "目标主机无法访问" 等 ICMP 错误时也返回 0,以回复中的 TTL= 判断是否收到应答
+    #[cfg(target_os = "windows")]
+    return Some(
+        output.status.success() && String::from_utf8_lossy(&output.stdout).contains("TTL="),
+    );
+
+    #[cfg(not(target_os = "windows"))]
+    Some(output.status.success())
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ElevationBackend {
+    pub backend: String, // osascript, pkexec, sudo, uac, none
+    pub available: bool,
+}
+
+// 检查命令是否存在
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn which(cmd: &str) -> bool {
+    std::process::Command::new("which")
+        .arg(cmd)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+// 检查提权方式是否可用,避免首次启动隧道时才在 osascript/pkexec 中失败
+#[command]
+pub fn check_elevation_backend() -> Result<ElevationBackend, String> {
+    #[cfg(target_os = "macos")]
+    let (backend, available) = ("osascript", which("osascript"));
+
+    // 与 Linux 启动隧道的逻辑保持一致: 优先 pkexec,其次 sudo
+    #[cfg(target_os = "linux")]
+    let (backend, available) = if which("pkexec") {
+        ("pkexec", true)
+    } else if which("sudo") {
+        ("sudo", true)
+    } else {
+        ("none", false)
+    };
+
+    // Windows 通过 UAC 以管理员身份运行
+    #[cfg(target_os = "windows")]
+    let (backend, available) = ("uac", true);
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    let (backend, available) = ("none", false);
+
+    log::info!("提权方式: {}, 可用: {}", backend, available);
+
+    Ok(ElevationBackend {
+        backend: backend.to_string(),
+        available,
+    })
+}
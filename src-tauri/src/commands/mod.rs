@@ -1,4 +1,5 @@
 pub mod config_templates;
+pub mod diagnostics;
 pub mod env_config;
 pub mod history_service;
 pub mod key_management;
@@ -78,3 +78,233 @@ pub fn clear_cached_config(app: AppHandle) -> Result<(), String> {
 
     Ok(())
 }
+
+// 应用偏好设置
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AppPreferences {
+    // 历史记录使用 gzip 压缩存储
+    #[serde(default)]
+    pub compress_history: bool,
+    // 受保护网段 (如 SSH 所在的管理网络),AllowedIPs 包含这些网段时校验配置会发出警告
+    #[serde(default)]
+    pub protected_subnets: Vec<String>,
+    // 日志级别 (error / warn / info / debug / trace),为空时使用 info
+    #[serde(default)]
+    pub log_level: Option<String>,
+}
+
+// 按偏好设置调整日志级别,无效或未设置时使用 info
+pub fn apply_log_level(preferences: &AppPreferences) {
+    let level = preferences
+        .log_level
+        .as_deref()
+        .and_then(|level| level.parse::<log::LevelFilter>().ok())
+        .unwrap_or(log::LevelFilter::Info);
+    log::set_max_level(level);
+}
+
+#[command]
+pub fn save_preferences(app: AppHandle, preferences: AppPreferences) -> Result<(), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    fs::create_dir_all(&app_data_dir).map_err(|e| format!("创建应用数据目录失败: {}", e))?;
+
+    let json = serde_json::to_string_pretty(&preferences)
+        .map_err(|e| format!("序列化偏好设置失败: {}", e))?;
+
+    fs::write(app_data_dir.join("preferences.json"), json)
+        .map_err(|e| format!("保存偏好设置失败: {}", e))?;
+
+    apply_log_level(&preferences);
+    Ok(())
+}
+
+#[command]
+pub fn load_preferences(app: AppHandle) -> Result<AppPreferences, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let preferences_path = app_data_dir.join("preferences.json");
+
+    if !preferences_path.exists() {
+        return Ok(AppPreferences::default());
+    }
+
+    let content =
+        fs::read_to_string(&preferences_path).map_err(|e| format!("读取偏好设置失败: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("解析偏好设置失败: {}", e))
+}
+
+// 应用设置导出包 (不含隧道、服务端和历史记录数据)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub exported_at: i64,
+    #[serde(default)]
+    pub webdav: Option<crate::webdav::WebDavConfig>,
+    #[serde(default)]
+    pub preferences: Option<AppPreferences>,
+    // 标记为开机自动启动的隧道 ID
+    #[serde(default)]
+    pub autostart_tunnels: Option<Vec<String>>,
+}
+
+// 读取所有隧道配置,返回标记为开机自动启动的隧道 ID
+fn autostart_tunnel_ids(app: &AppHandle) -> Result<Vec<String>, String> {
+    let tunnels_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?
+        .join("tunnels");
+    let Ok(entries) = fs::read_dir(&tunnels_dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<crate::tunnel::TunnelConfig>(&content).ok())
+        .filter(|config| config.auto_start)
+        .map(|config| config.id)
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+// 导出应用设置为单个 JSON
+// 默认不包含 WebDAV 密码、同步加密密码和 SSH 私钥路径,include_secrets 为 true 时一并导出
+#[command]
+pub fn export_settings(app: AppHandle, include_secrets: Option<bool>) -> Result<String, String> {
+    let mut webdav = crate::commands::webdav_commands::load_webdav_config(app.clone())?;
+    if !include_secrets.unwrap_or(false) {
+        webdav.password.clear();
+        webdav.encryption_password = None;
+        webdav.ssh_key_path.clear();
+    }
+
+    let bundle = SettingsBundle {
+        version: 1,
+        exported_at: chrono::Utc::now().timestamp(),
+        webdav: Some(webdav),
+        preferences: Some(load_preferences(app.clone())?),
+        autostart_tunnels: Some(autostart_tunnel_ids(&app)?),
+    };
+
+    serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化设置失败: {}", e))
+}
+
+// 从导出的 JSON 导入应用设置
+// 导出文件中未包含的密码等敏感信息保留本机已保存的值
+#[command]
+pub async fn import_settings(app: AppHandle, json: String) -> Result<(), String> {
+    let bundle: SettingsBundle =
+        serde_json::from_str(&json).map_err(|e| format!("解析设置文件失败: {}", e))?;
+
+    if bundle.version != 1 {
+        return Err(format!("不支持的设置文件版本: {}", bundle.version));
+    }
+
+    if let Some(mut webdav) = bundle.webdav {
+        let current = crate::commands::webdav_commands::load_webdav_config(app.clone())?;
+        let same_account =
+            current.server_url == webdav.server_url && current.username == webdav.username;
+        if webdav.password.is_empty() && same_account {
+            webdav.password = current.password;
+        }
+        if webdav.encryption_password.is_none() {
+            webdav.encryption_password = current.encryption_password;
+        }
+        if webdav.ssh_key_path.is_empty() {
+            webdav.ssh_key_path = current.ssh_key_path;
+        }
+        crate::commands::webdav_commands::save_webdav_config(app.clone(), webdav)?;
+    }
+
+    if let Some(preferences) = bundle.preferences {
+        save_preferences(app.clone(), preferences)?;
+    }
+
+    // 只调整本机已存在的隧道,开机自动启动需要系统权限,失败时记录警告继续导入
+    if let Some(autostart) = bundle.autostart_tunnels {
+        let current = autostart_tunnel_ids(&app)?;
+        let tunnels_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("获取应用数据目录失败: {}", e))?
+            .join("tunnels");
+        let changed = autostart
+            .iter()
+            .filter(|id| !current.contains(id))
+            .map(|id| (id, true))
+            .chain(
+                current
+                    .iter()
+                    .filter(|id| !autostart.contains(id))
+                    .map(|id| (id, false)),
+            );
+        for (id, enabled) in changed {
+            if !tunnels_dir.join(format!("{}.json", id)).exists() {
+                continue;
+            }
+            if let Err(e) =
+                crate::tunnel::set_tunnel_autostart(app.clone(), id.clone(), enabled).await
+            {
+                log::warn!("导入隧道 {} 的开机自动启动设置失败: {}", id, e);
+            }
+        }
+    }
+
+    log::info!("应用设置导入完成");
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DataDirStatus {
+    pub writable: bool,
+    pub free_bytes: Option<u64>, // 所在卷的可用空间,获取失败时为 None
+}
+
+// 检查应用数据目录是否可写,并返回所在卷的可用空间
+// 启动时调用,提前发现只读或空间不足的卷,避免保存时才报错
+#[command]
+pub fn check_data_dir_writable(app: AppHandle) -> Result<DataDirStatus, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    fs::create_dir_all(&app_data_dir).map_err(|e| format!("创建应用数据目录失败: {}", e))?;
+
+    // 写入并删除探测文件
+    let probe_path = app_data_dir.join(".write_probe");
+    let writable = match fs::write(&probe_path, b"probe") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe_path);
+            true
+        }
+        Err(e) => {
+            log::warn!("应用数据目录不可写: {:?}, {}", app_data_dir, e);
+            false
+        }
+    };
+
+    let free_bytes = match fs2::available_space(&app_data_dir) {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            log::warn!("获取可用空间失败: {}", e);
+            None
+        }
+    };
+
+    Ok(DataDirStatus {
+        writable,
+        free_bytes,
+    })
+}
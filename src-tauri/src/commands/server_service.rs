@@ -1,7 +1,9 @@
+use crate::commands::key_management::normalize_key;
 use crate::commands::persistence::PersistentConfig;
 use crate::sync::SyncManager;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
 use tauri::{command, AppHandle, Manager};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -21,9 +23,23 @@ pub struct ServerConfig {
 }
 
 #[command]
-pub fn save_server_config(app: AppHandle, config: ServerConfig) -> Result<(), String> {
+pub fn save_server_config(
+    app: AppHandle,
+    mut config: ServerConfig,
+    allow_duplicate: Option<bool>,
+) -> Result<(), String> {
     log::info!("保存服务端配置: id={}, name={}", config.id, config.name);
 
+    // 规范化密钥,避免复制时带入的空白或缺失的填充导致后续生成配置失败
+    if !config.peer_public_key.trim().is_empty() {
+        config.peer_public_key =
+            normalize_key(&config.peer_public_key).map_err(|e| format!("服务端公钥无效: {}", e))?;
+    }
+    if !config.preshared_key.trim().is_empty() {
+        config.preshared_key =
+            normalize_key(&config.preshared_key).map_err(|e| format!("预共享密钥无效: {}", e))?;
+    }
+
     let app_data_dir = app.path().app_data_dir().map_err(|e| {
         log::error!("获取应用数据目录失败: {}", e);
         format!("获取应用数据目录失败: {}", e)
@@ -35,6 +51,10 @@ pub fn save_server_config(app: AppHandle, config: ServerConfig) -> Result<(), St
         format!("创建服务端目录失败: {}", e)
     })?;
 
+    if !allow_duplicate.unwrap_or(false) {
+        check_server_name_unique(&servers_dir, &config)?;
+    }
+
     let file_path = servers_dir.join(format!("{}.json", config.id));
     let json = serde_json::to_string_pretty(&config).map_err(|e| {
         log::error!("序列化服务端配置失败: {}", e);
@@ -50,6 +70,38 @@ pub fn save_server_config(app: AppHandle, config: ServerConfig) -> Result<(), St
     Ok(())
 }
 
+// 检查服务端名称是否与其他服务端重复
+// 名称未修改时不检查,避免已存在的重名配置无法更新
+fn check_server_name_unique(servers_dir: &Path, config: &ServerConfig) -> Result<(), String> {
+    let name = config.name.trim();
+    let Ok(entries) = fs::read_dir(servers_dir) else {
+        return Ok(());
+    };
+
+    let existing: Vec<ServerConfig> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    if existing
+        .iter()
+        .any(|s| s.id == config.id && s.name.trim() == name)
+    {
+        return Ok(());
+    }
+
+    match existing
+        .iter()
+        .find(|s| s.id != config.id && s.name.trim() == name)
+    {
+        Some(other) => Err(format!("已存在同名服务端: {} (ID: {})", name, other.id)),
+        None => Ok(()),
+    }
+}
+
 #[command]
 pub fn get_server_list(app: AppHandle) -> Result<Vec<ServerConfig>, String> {
     let app_data_dir = app
@@ -85,6 +137,44 @@ pub fn get_server_list(app: AppHandle) -> Result<Vec<ServerConfig>, String> {
     Ok(servers)
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerFingerprint {
+    pub id: String,
+    pub peer_public_key_fp: String,
+}
+
+// 计算公钥指纹: SHA-256 的前 4 个字节,以十六进制显示
+fn public_key_fingerprint(public_key: &str) -> String {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use sha2::{Digest, Sha256};
+
+    // 无法解码的公钥直接对原始字符串计算哈希
+    let bytes = BASE64
+        .decode(public_key.trim())
+        .unwrap_or_else(|_| public_key.trim().as_bytes().to_vec());
+    let digest = Sha256::digest(&bytes);
+
+    digest[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+// 获取所有服务端公钥的短指纹,便于区分名称相近的服务端
+#[command]
+pub fn get_server_fingerprints(app: AppHandle) -> Result<Vec<ServerFingerprint>, String> {
+    let servers = get_server_list(app)?;
+
+    Ok(servers
+        .into_iter()
+        .map(|server| ServerFingerprint {
+            peer_public_key_fp: public_key_fingerprint(&server.peer_public_key),
+            id: server.id,
+        })
+        .collect())
+}
+
 #[command]
 pub fn get_server_detail(app: AppHandle, id: String) -> Result<ServerConfig, String> {
     let app_data_dir = app
@@ -107,6 +197,181 @@ pub fn get_server_detail(app: AppHandle, id: String) -> Result<ServerConfig, Str
     Ok(server)
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerPortProbe {
+    pub port_open: bool,
+    pub resolved_ip: String,
+    pub port: u16,
+    pub tested: String, // 实际执行的检测及结果说明
+}
+
+// 从本机探测服务端 Endpoint 的 UDP 端口
+//
+// 仅为本机检测: 探测包从运行本应用的机器发出,不经过外部检测服务,
+// 本机与服务端在同一内网或 NAT 支持回环时,结果不能代表外网客户端的可达性。
+// UDP 无连接,WireGuard 对无法识别的数据包静默丢弃,因此无法完全确认端口可达:
+// 只有收到 ICMP 端口不可达时才能确定端口关闭,未收到拒绝只说明端口开放或被防火墙过滤。
+#[command]
+pub async fn probe_server_port_locally(
+    app: AppHandle,
+    server_id: String,
+) -> Result<ServerPortProbe, String> {
+    use std::net::{IpAddr, SocketAddr};
+    use std::time::Duration;
+
+    let server = get_server_detail(app, server_id)?;
+    let endpoint = server.endpoint.trim().to_string();
+    if endpoint.is_empty() {
+        return Err("服务端未配置 Endpoint".to_string());
+    }
+
+    let port = crate::tunnel::parse_endpoint_port(&endpoint)?;
+
+    // DNS 解析可能阻塞,放到阻塞线程池中执行
+    let resolved = tokio::task::spawn_blocking(move || crate::tunnel::resolve_endpoint(&endpoint))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))??;
+    let addr: SocketAddr = resolved
+        .parse()
+        .map_err(|e| format!("解析地址失败: {}", e))?;
+
+    let bind_addr = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = tokio::net::UdpSocket::bind(bind_addr)
+        .await
+        .map_err(|e| format!("创建 UDP socket 失败: {}", e))?;
+    socket
+        .connect(addr)
+        .await
+        .map_err(|e| format!("连接服务端失败: {}", e))?;
+
+    // 已连接的 UDP socket 在收到 ICMP 端口不可达后,send/recv 会返回 ConnectionRefused
+    let mut refused = false;
+    for _ in 0..3 {
+        match socket.send(&[0u8; 4]).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                refused = true;
+                break;
+            }
+            Err(e) => return Err(format!("发送探测包失败: {}", e)),
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    if !refused {
+        let mut buf = [0u8; 128];
+        match tokio::time::timeout(Duration::from_secs(3), socket.recv(&mut buf)).await {
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => refused = true,
+            Ok(Err(e)) => return Err(format!("接收响应失败: {}", e)),
+            Ok(Ok(_)) | Err(_) => {}
+        }
+    }
+
+    let mut tested = format!(
+        "从本机向 {} 发送了 3 个 UDP 探测包 (仅本机检测，未从外网验证)。",
+        addr
+    );
+    if refused {
+        tested.push_str("收到 ICMP 端口不可达，端口未开放或端口转发未生效。");
+    } else {
+        tested.push_str(
+            "未收到拒绝响应，端口开放或被防火墙过滤。UDP 无法完全确认可达，请以客户端实际握手结果为准。",
+        );
+    }
+
+    let is_private = match addr.ip() {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_loopback() || (v6.segments()[0] & 0xfe00) == 0xfc00,
+    };
+    if is_private {
+        tested.push_str("Endpoint 解析为内网地址，检测结果不代表外网可达性。");
+    }
+
+    log::info!("服务端端口本机检测: {}", tested);
+
+    Ok(ServerPortProbe {
+        port_open: !refused,
+        resolved_ip: addr.ip().to_string(),
+        port,
+        tested,
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerEndpointResolution {
+    pub server_id: String,
+    pub endpoint: String,
+    pub resolved_ip: Option<String>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+// 单个 Endpoint 的 DNS 解析超时时间
+const RESOLVE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// 并发解析所有服务端的 Endpoint,用于检查各服务端的 DNS 是否正常
+#[command]
+pub async fn resolve_all_server_endpoints(
+    app: AppHandle,
+) -> Result<Vec<ServerEndpointResolution>, String> {
+    let servers = get_server_list(app)?;
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, server) in servers.into_iter().enumerate() {
+        tasks.spawn(async move {
+            let endpoint = server.endpoint.trim().to_string();
+            let result = if endpoint.is_empty() {
+                Err("未配置 Endpoint".to_string())
+            } else {
+                let target = endpoint.clone();
+                let lookup =
+                    tokio::task::spawn_blocking(move || crate::tunnel::resolve_endpoint(&target));
+                match tokio::time::timeout(RESOLVE_TIMEOUT, lookup).await {
+                    Ok(Ok(result)) => result,
+                    Ok(Err(e)) => Err(format!("任务执行失败: {}", e)),
+                    Err(_) => Err("DNS 解析超时".to_string()),
+                }
+            };
+
+            // resolve_endpoint 返回 IP:端口,这里只保留 IP
+            let resolved_ip = result.as_ref().ok().map(|addr| {
+                addr.parse::<std::net::SocketAddr>()
+                    .map(|a| a.ip().to_string())
+                    .unwrap_or_else(|_| addr.clone())
+            });
+
+            let resolution = ServerEndpointResolution {
+                server_id: server.id,
+                endpoint,
+                resolved_ip,
+                ok: result.is_ok(),
+                error: result.err(),
+            };
+            (index, resolution)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        results.push(joined.map_err(|e| format!("解析任务失败: {}", e))?);
+    }
+
+    // 按服务端列表顺序返回
+    results.sort_by_key(|(index, _)| *index);
+    let results: Vec<ServerEndpointResolution> = results.into_iter().map(|(_, r)| r).collect();
+
+    log::info!(
+        "服务端 Endpoint 解析完成: {}/{} 个成功",
+        results.iter().filter(|r| r.ok).count(),
+        results.len()
+    );
+    Ok(results)
+}
+
 #[command]
 pub async fn delete_server(app: AppHandle, id: String) -> Result<(), String> {
     let app_data_dir = app
@@ -178,10 +443,127 @@ pub fn update_server_peer_id(
 ) -> Result<(), String> {
     let mut server = get_server_detail(app.clone(), server_id)?;
     server.next_peer_id = next_peer_id;
-    save_server_config(app, server)?;
+    save_server_config(app, server, None)?;
     Ok(())
 }
 
+// 根据历史记录重新计算服务端的 next_peer_id
+// 多台设备同步后各自分配的 peer_id 可能超过本地记录的 next_peer_id,取已使用的最大值 + 1;
+// 本地记录更大时保持不变,避免复用已删除记录的 peer_id
+#[command]
+pub fn reconcile_peer_ids(app: AppHandle, server_id: String) -> Result<u32, String> {
+    let mut server = get_server_detail(app.clone(), server_id.clone())?;
+    let history = crate::commands::history_service::get_history_list_by_server(
+        app.clone(),
+        server_id.clone(),
+    )?;
+
+    let mut seen = std::collections::HashSet::new();
+    for item in &history {
+        if !seen.insert(item.peer_id) {
+            log::warn!(
+                "服务端 {} 存在重复的 peer_id: {}",
+                server.name,
+                item.peer_id
+            );
+        }
+    }
+
+    let max_used = history.iter().map(|item| item.peer_id).max().unwrap_or(0);
+    let next_peer_id = server.next_peer_id.max(max_used + 1);
+
+    if next_peer_id != server.next_peer_id {
+        log::info!(
+            "服务端 {} 的 next_peer_id 由 {} 调整为 {}",
+            server.name,
+            server.next_peer_id,
+            next_peer_id
+        );
+        server.next_peer_id = next_peer_id;
+        save_server_config(app, server, None)?;
+    }
+
+    Ok(next_peer_id)
+}
+
+// 同步完成后对所有服务端执行 reconcile_peer_ids,单个服务端失败不影响其他服务端
+pub fn reconcile_all_peer_ids(app: &AppHandle) {
+    let servers = match get_server_list(app.clone()) {
+        Ok(servers) => servers,
+        Err(e) => {
+            log::error!("读取服务端列表失败: {}", e);
+            return;
+        }
+    };
+
+    for server in servers {
+        if let Err(e) = reconcile_peer_ids(app.clone(), server.id.clone()) {
+            log::error!("校正服务端 {} 的 peer_id 失败: {}", server.name, e);
+        }
+    }
+}
+
+// 从服务端的地址池中分配一个未使用的客户端地址,返回 "IP/32" (IPv6 为 "IP/128")
+// 网段第一个主机地址保留给服务端;IPv4 跳过网络地址和广播地址,IPv6 跳过子网路由器任播地址
+#[command]
+pub fn allocate_peer_address(
+    app: AppHandle,
+    server_id: String,
+    subnet: String,
+) -> Result<String, String> {
+    use crate::commands::diagnostics::parse_cidr;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let (ip, prefix) = parse_cidr(&subnet).ok_or_else(|| format!("无效的网段: {}", subnet))?;
+
+    let to_bits = |ip: IpAddr| -> u128 {
+        match ip {
+            IpAddr::V4(v4) => u32::from(v4) as u128,
+            IpAddr::V6(v6) => u128::from(v6),
+        }
+    };
+
+    let width: u32 = if ip.is_ipv4() { 32 } else { 128 };
+    let host_bits = width - prefix as u32;
+    if host_bits < 2 {
+        return Err(format!("网段 {} 过小，没有可分配的客户端地址", subnet));
+    }
+
+    let host_mask = if host_bits == 128 {
+        u128::MAX
+    } else {
+        (1u128 << host_bits) - 1
+    };
+    let network = to_bits(ip) & !host_mask;
+    let first = network + 2;
+    let last = if ip.is_ipv4() {
+        network + host_mask - 1
+    } else {
+        network + host_mask
+    };
+
+    let history = crate::commands::history_service::get_history_list_by_server(app, server_id)?;
+    let used: std::collections::HashSet<u128> = history
+        .iter()
+        .flat_map(|item| item.address.split(','))
+        .filter_map(parse_cidr)
+        .filter(|(addr, _)| addr.is_ipv4() == ip.is_ipv4())
+        .map(|(addr, _)| to_bits(addr))
+        .collect();
+
+    let free = (first..=last)
+        .find(|candidate| !used.contains(candidate))
+        .ok_or_else(|| format!("地址池 {} 已满，无法分配新的客户端地址", subnet))?;
+
+    let address = if ip.is_ipv4() {
+        IpAddr::V4(Ipv4Addr::from(free as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(free))
+    };
+
+    Ok(format!("{}/{}", address, width))
+}
+
 #[command]
 pub fn migrate_old_config_to_server(app: AppHandle) -> Result<Option<String>, String> {
     let app_data_dir = app
@@ -226,10 +608,50 @@ pub fn migrate_old_config_to_server(app: AppHandle) -> Result<Option<String>, St
         peer_address_range: String::new(),
     };
 
-    save_server_config(app.clone(), server_config)?;
+    save_server_config(app.clone(), server_config, None)?;
 
     fs::rename(&old_config_path, app_data_dir.join("config.json.bak"))
         .map_err(|e| format!("备份旧配置失败: {}", e))?;
 
     Ok(Some(server_id))
 }
+
+// 将旧版工作目录中的 wg.env 导入为服务端配置,返回新服务端 ID
+// wg.env 中缺少对端公钥或 Endpoint 时返回 None;wg.env 位于用户目录,导入后保留原文件
+#[command]
+pub fn import_env_as_server(work_dir: String, app: AppHandle) -> Result<Option<String>, String> {
+    let env = crate::commands::env_config::load_env_config(work_dir)?;
+
+    let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+    let (Some(peer_public_key), Some(endpoint)) =
+        (non_empty(env.peer_public_key), non_empty(env.endpoint))
+    else {
+        return Ok(None);
+    };
+
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let server_id = format!("env_{}", timestamp);
+    let name = match non_empty(env.interface_name) {
+        Some(interface_name) => format!("{}（wg.env 导入）", interface_name),
+        None => "默认服务端（wg.env 导入）".to_string(),
+    };
+
+    let server_config = ServerConfig {
+        id: server_id.clone(),
+        name,
+        peer_public_key,
+        preshared_key: env.preshared_key.unwrap_or_default(),
+        endpoint,
+        allowed_ips: non_empty(env.allowed_ips).unwrap_or_else(|| "0.0.0.0/0,::/0".to_string()),
+        persistent_keepalive: non_empty(env.keepalive).unwrap_or_else(|| "25".to_string()),
+        peer_interface: non_empty(env.peer_interface).unwrap_or_else(|| "wg_0".to_string()),
+        next_peer_id: 1,
+        created_at: timestamp,
+        peer_address_range: String::new(),
+    };
+
+    save_server_config(app, server_config, None)?;
+
+    log::info!("已从 wg.env 导入服务端配置: {}", server_id);
+    Ok(Some(server_id))
+}
@@ -0,0 +1,81 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+// 需要监听的配置目录
+const WATCHED_DIRS: [&str; 3] = ["tunnels", "servers", "history"];
+
+// 合并短时间内的多次文件变化,避免前端频繁刷新
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+// 持有 watcher,防止被释放后停止监听
+pub struct ConfigWatcher(#[allow(dead_code)] Mutex<RecommendedWatcher>);
+
+// 启动配置目录监听,文件新增/修改/删除时向前端发送 configs-changed 事件
+// 事件内容为发生变化的目录名列表,例如 ["tunnels", "history"]
+pub fn start_config_watcher(app: &AppHandle) -> Result<(), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("创建文件监听失败: {}", e))?;
+
+    for dir in WATCHED_DIRS {
+        let path = app_data_dir.join(dir);
+        std::fs::create_dir_all(&path).map_err(|e| format!("创建 {} 目录失败: {}", dir, e))?;
+        watcher
+            .watch(&path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("监听 {} 目录失败: {}", dir, e))?;
+    }
+
+    let app_handle = app.clone();
+    std::thread::spawn(move || {
+        // 发送端随 watcher 一起释放后 recv 返回错误,线程退出
+        while let Ok(first) = rx.recv() {
+            let mut changed = BTreeSet::new();
+            let mut pending = Some(first);
+
+            while let Some(result) = pending.take() {
+                match result {
+                    // 只读访问不算变化
+                    Ok(event) if !event.kind.is_access() => {
+                        for path in &event.paths {
+                            let dir = path
+                                .parent()
+                                .and_then(|p| p.file_name())
+                                .and_then(|n| n.to_str());
+                            if let Some(dir) = dir.filter(|d| WATCHED_DIRS.contains(d)) {
+                                changed.insert(dir.to_string());
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("文件监听出错: {}", e),
+                }
+                pending = rx.recv_timeout(DEBOUNCE).ok();
+            }
+
+            if changed.is_empty() {
+                continue;
+            }
+
+            let dirs: Vec<String> = changed.into_iter().collect();
+            log::info!("检测到配置目录变化: {:?}", dirs);
+            if let Err(e) = app_handle.emit("configs-changed", dirs) {
+                log::error!("发出 configs-changed 事件失败: {}", e);
+            }
+        }
+    });
+
+    app.manage(ConfigWatcher(Mutex::new(watcher)));
+
+    log::info!("已启动配置目录监听");
+    Ok(())
+}
@@ -2,9 +2,10 @@
 // 以 root 权限运行,管理 WireGuard 隧道
 
 use crate::daemon_ipc::{
-    IpcRequest, IpcResponse, PeerConfigIpc, PeerStatsIpc, TunnelConfigIpc, TunnelStatusIpc,
-    DAEMON_SOCKET_PATH,
+    DaemonStateIpc, DaemonTunnelStateIpc, IpcRequest, IpcResponse, PeerConfigIpc, PeerStatsIpc,
+    PingResponseIpc, TunnelConfigIpc, TunnelStatusIpc, DAEMON_PROTOCOL_VERSION, DAEMON_SOCKET_PATH,
 };
+use crate::tunnel::{redact_uapi_dump, uapi_error_message};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
@@ -14,6 +15,11 @@ use std::process::{Child, Command};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+// 流式推送 peer 统计的间隔范围 (毫秒)
+const STREAM_MIN_INTERVAL_MS: u64 = 500;
+const STREAM_MAX_INTERVAL_MS: u64 = 60_000;
+const STREAM_DEFAULT_INTERVAL_MS: u64 = 1_000;
+
 // 全局隧道进程管理
 lazy_static::lazy_static! {
     static ref DAEMON_TUNNELS: Arc<Mutex<HashMap<String, TunnelProcess>>> = Arc::new(Mutex::new(HashMap::new()));
@@ -24,8 +30,11 @@ struct TunnelProcess {
     tunnel_id: String,
     interface_name: String,
     socket_path: String, // 实际的 WireGuard UAPI socket 路径
+    wireguard_go_path: String,
     process: Child,
     config: TunnelConfigIpc,
+    // kill switch 当前放行的 endpoint (wireguard-go 中实际使用的地址)
+    kill_switch_endpoints: Vec<std::net::SocketAddr>,
 }
 
 /// 守护进程主循环
@@ -53,6 +62,12 @@ pub async fn run_daemon() -> Result<(), String> {
 
     log::info!("守护进程监听在: {}", DAEMON_SOCKET_PATH);
 
+    // 上次异常退出时隧道未被停止,清理残留的策略路由规则
+    cleanup_stale_policy_rules();
+
+    // 启动标记为开机自动启动的隧道
+    tokio::spawn(start_autostart_tunnels());
+
     // 处理连接
     for stream in listener.incoming() {
         match stream {
@@ -89,13 +104,29 @@ async fn handle_client(stream: UnixStream) -> Result<(), String> {
 
     log::info!("收到请求: method={}, id={}", request.method, request.id);
 
+    // 流式请求会持续写入多帧响应,直到客户端断开
+    if request.method == "stream_peer_stats" {
+        drop(reader);
+        return handle_stream_peer_stats(request.id, request.params, stream).await;
+    }
+
     // 处理请求
     let response = match request.method.as_str() {
         "start_tunnel" => handle_start_tunnel(request.id.clone(), request.params).await,
         "stop_tunnel" => handle_stop_tunnel(request.id.clone(), request.params).await,
         "get_tunnel_status" => handle_get_tunnel_status(request.id.clone(), request.params).await,
         "get_peer_stats" => handle_get_peer_stats(request.id.clone(), request.params).await,
+        "set_listen_port" => handle_set_listen_port(request.id.clone(), request.params).await,
+        "remove_peer" => handle_remove_peer(request.id.clone(), request.params).await,
+        "update_tunnel_peers" => {
+            handle_update_tunnel_peers(request.id.clone(), request.params).await
+        }
+        "dump_uapi_state" => handle_dump_uapi_state(request.id.clone(), request.params).await,
+        "set_autostart" => handle_set_autostart(request.id.clone(), request.params).await,
+        "get_interface_mtu" => handle_get_interface_mtu(request.id.clone(), request.params).await,
+        "apply_config" => handle_apply_config(request.id.clone(), request.params).await,
         "list_tunnels" => handle_list_tunnels(request.id.clone()).await,
+        "dump_state" => handle_dump_state(request.id.clone()).await,
         "ping" => handle_ping(request.id.clone()).await,
         _ => IpcResponse {
             id: request.id.clone(),
@@ -201,10 +232,25 @@ async fn start_tunnel_internal(config: TunnelConfigIpc) -> Result<(), String> {
         wg_go_path
     );
 
+    // wireguard-go 的输出写入按接口区分的日志文件,供 GUI 查看
+    // 日志位于 /tmp,先删除旧文件再以 create_new 创建,避免跟随他人预先放置的符号链接
+    let log_path = crate::tunnel::wireguard_go_log_path(&config.interface_name);
+    let _ = std::fs::remove_file(&log_path);
+    let log_file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&log_path)
+        .map_err(|e| format!("创建 wireguard-go 日志文件失败: {}", e))?;
+    let log_file_err = log_file
+        .try_clone()
+        .map_err(|e| format!("创建 wireguard-go 日志文件失败: {}", e))?;
+
     // 启动 wireguard-go (使用引用避免所有权转移)
     let mut child = Command::new(&wg_go_path)
         .arg("-f")
         .arg(&config.interface_name)
+        .stdout(log_file)
+        .stderr(log_file_err)
         .spawn()
         .map_err(|e| format!("启动 wireguard-go 失败: {}", e))?;
 
@@ -262,18 +308,47 @@ async fn start_tunnel_internal(config: TunnelConfigIpc) -> Result<(), String> {
     }
 
     // 使用 netlink 配置路由
+    // 指定路由表时默认路由也写入该表,由策略路由规则决定哪些流量使用
     for peer in &config.peers {
         for allowed_ip in &peer.allowed_ips {
-            if allowed_ip == "0.0.0.0/0" || allowed_ip == "::/0" {
+            if config.route_table.is_none() && (allowed_ip == "0.0.0.0/0" || allowed_ip == "::/0") {
                 continue; // 跳过默认路由
             }
 
-            let _ = configure_route(&config.interface_name, allowed_ip).await;
+            let _ = configure_route(&config.interface_name, allowed_ip, config.route_table).await;
+        }
+    }
+
+    // 同时指定路由表和 fwmark 时添加策略路由规则
+    if let Err(e) = configure_policy_rules(&config, true) {
+        let _ = child.kill();
+        return Err(e);
+    }
+
+    // 启用 kill switch 时添加防火墙规则,放行 UAPI 中实际配置的 endpoint (域名已解析为 IP)
+    let kill_switch_endpoints = if config.kill_switch {
+        match configured_endpoints(&socket_path) {
+            Ok(endpoints) => endpoints,
+            Err(e) => {
+                let _ = child.kill();
+                let _ = configure_policy_rules(&config, false);
+                return Err(e);
+            }
         }
+    } else {
+        Vec::new()
+    };
+    if let Err(e) = configure_kill_switch(&config, &kill_switch_endpoints, true) {
+        let _ = child.kill();
+        let _ = configure_policy_rules(&config, false);
+        return Err(e);
     }
 
     log::info!("隧道 {} 启动成功", config.tunnel_id);
 
+    // 动态 DNS: 定期重新解析 Peer 的 endpoint 域名
+    start_endpoint_refresh_task(config.tunnel_id.clone(), child.id(), socket_path.clone());
+
     // 保存进程信息
     tunnels.insert(
         config.tunnel_id.clone(),
@@ -281,43 +356,25 @@ async fn start_tunnel_internal(config: TunnelConfigIpc) -> Result<(), String> {
             tunnel_id: config.tunnel_id.clone(),
             interface_name: config.interface_name.clone(),
             socket_path: socket_path.clone(),
+            wireguard_go_path: wg_go_path,
             process: child,
             config,
+            kill_switch_endpoints,
         },
     );
 
     Ok(())
 }
 
-/// 配置 WireGuard 接口 (通过 UAPI)
-async fn configure_interface(config: &TunnelConfigIpc, socket_path: &str) -> Result<(), String> {
-    use std::io::Read;
-    use std::os::unix::net::UnixStream;
-
-    // 连接到 UAPI socket
-    let mut stream = UnixStream::connect(&socket_path)
-        .map_err(|e| format!("连接 WireGuard socket 失败: {}", e))?;
-
-    // 构建配置命令
-    let mut uapi_config = String::from("set=1\n");
-
-    // 私钥
-    let private_key_hex = base64_to_hex(&config.private_key)?;
-    uapi_config.push_str(&format!("private_key={}\n", private_key_hex));
-
-    // 监听端口
-    if let Some(port) = config.listen_port {
-        uapi_config.push_str(&format!("listen_port={}\n", port));
-    }
-
-    uapi_config.push_str("replace_peers=true\n");
+/// 生成 Peer 部分的 UAPI 配置 (endpoint 中的域名会被解析为 IP)
+async fn build_peers_uapi(peers: &[PeerConfigIpc]) -> Result<String, String> {
+    let mut uapi = String::new();
 
-    // Peer 配置
-    log::info!("配置 {} 个 peer(s)", config.peers.len());
-    for (i, peer) in config.peers.iter().enumerate() {
+    log::info!("配置 {} 个 peer(s)", peers.len());
+    for (i, peer) in peers.iter().enumerate() {
         log::info!("配置 peer #{}: endpoint={:?}", i, peer.endpoint);
         let public_key_hex = base64_to_hex(&peer.public_key)?;
-        uapi_config.push_str(&format!("public_key={}\n", public_key_hex));
+        uapi.push_str(&format!("public_key={}\n", public_key_hex));
 
         if let Some(ref endpoint) = peer.endpoint {
             if !endpoint.is_empty() {
@@ -333,7 +390,7 @@ async fn configure_interface(config: &TunnelConfigIpc, socket_path: &str) -> Res
                 match resolved {
                     Ok(resolved_endpoint) => {
                         log::info!("成功解析 endpoint: {} -> {}", endpoint, resolved_endpoint);
-                        uapi_config.push_str(&format!("endpoint={}\n", resolved_endpoint));
+                        uapi.push_str(&format!("endpoint={}\n", resolved_endpoint));
                     }
                     Err(e) => {
                         // DNS 解析失败，返回错误
@@ -357,7 +414,7 @@ async fn configure_interface(config: &TunnelConfigIpc, socket_path: &str) -> Res
                 // 预共享密钥也需要转换为十六进制
                 match base64_to_hex(psk) {
                     Ok(psk_hex) => {
-                        uapi_config.push_str(&format!("preshared_key={}\n", psk_hex));
+                        uapi.push_str(&format!("preshared_key={}\n", psk_hex));
                     }
                     Err(e) => {
                         log::warn!("警告: 预共享密钥格式无效，已跳过: {}", e);
@@ -368,14 +425,146 @@ async fn configure_interface(config: &TunnelConfigIpc, socket_path: &str) -> Res
         }
 
         if let Some(keepalive) = peer.persistent_keepalive {
-            uapi_config.push_str(&format!("persistent_keepalive_interval={}\n", keepalive));
+            uapi.push_str(&format!("persistent_keepalive_interval={}\n", keepalive));
         }
 
         for allowed_ip in &peer.allowed_ips {
-            uapi_config.push_str(&format!("allowed_ip={}\n", allowed_ip));
+            uapi.push_str(&format!("allowed_ip={}\n", allowed_ip));
+        }
+    }
+
+    Ok(uapi)
+}
+
+/// endpoint 刷新间隔
+const ENDPOINT_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// 定期重新解析每个 Peer 的原始 endpoint (域名),IP 变化时通过 UAPI 更新
+/// 与 macOS 的 start_endpoint_refresh_task 逻辑一致; 隧道停止或以新进程重启后任务结束
+fn start_endpoint_refresh_task(tunnel_id: String, pid: u32, socket_path: String) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ENDPOINT_REFRESH_INTERVAL);
+        // 第一次 tick 立即返回,启动时已解析过 endpoint
+        interval.tick().await;
+
+        // 保存每个 peer 上次解析的 endpoint,避免重复更新
+        // 以启动时已下发的 endpoint 作为初始值,避免第一次刷新误报变化
+        let mut last_resolved: HashMap<String, String> =
+            tokio::task::spawn_blocking(move || uapi_get_blocking(&socket_path))
+                .await
+                .ok()
+                .and_then(Result::ok)
+                .map(|uapi| parse_peer_endpoints(&uapi))
+                .unwrap_or_default();
+
+        loop {
+            interval.tick().await;
+
+            let current = {
+                let tunnels = DAEMON_TUNNELS.lock().await;
+                tunnels
+                    .get(&tunnel_id)
+                    .filter(|t| t.process.id() == pid)
+                    .map(|t| (t.socket_path.clone(), t.config.peers.clone()))
+            };
+            let Some((socket_path, peers)) = current else {
+                log::info!("隧道 {} 已停止,结束 endpoint 刷新任务", tunnel_id);
+                break;
+            };
+
+            for peer in peers {
+                let Some(endpoint) = peer.endpoint.filter(|e| !e.is_empty()) else {
+                    continue;
+                };
+
+                let endpoint_clone = endpoint.clone();
+                let resolved = match tokio::task::spawn_blocking(move || {
+                    resolve_endpoint_blocking(&endpoint_clone)
+                })
+                .await
+                {
+                    Ok(Ok(resolved)) => resolved,
+                    Ok(Err(e)) => {
+                        log::warn!("解析 endpoint {} 失败: {}", endpoint, e);
+                        continue;
+                    }
+                    Err(e) => {
+                        log::warn!("解析任务失败: {}", e);
+                        continue;
+                    }
+                };
+
+                let last = last_resolved.get(&peer.public_key);
+                if last == Some(&resolved) {
+                    continue;
+                }
+
+                log::info!(
+                    "隧道 {}: endpoint {} 解析结果变化: {} -> {}",
+                    tunnel_id,
+                    endpoint,
+                    last.map(String::as_str).unwrap_or("(首次)"),
+                    resolved
+                );
+
+                let public_key_hex = match base64_to_hex(&peer.public_key) {
+                    Ok(hex) => hex,
+                    Err(e) => {
+                        log::error!("解析公钥失败: {}", e);
+                        continue;
+                    }
+                };
+
+                let command = format!("public_key={}\nendpoint={}\n", public_key_hex, resolved);
+                let socket_path = socket_path.clone();
+                match tokio::task::spawn_blocking(move || uapi_set_blocking(&socket_path, &command))
+                    .await
+                {
+                    Ok(Ok(())) => {
+                        log::info!("成功更新 endpoint: {}", resolved);
+                        last_resolved.insert(peer.public_key.clone(), resolved);
+                        refresh_kill_switch_endpoints(&tunnel_id).await;
+                    }
+                    Ok(Err(e)) => log::warn!("更新 endpoint 失败: {}", e),
+                    Err(e) => log::warn!("任务执行失败: {}", e),
+                }
+            }
         }
+    });
+}
+
+/// 配置 WireGuard 接口 (通过 UAPI)
+async fn configure_interface(config: &TunnelConfigIpc, socket_path: &str) -> Result<(), String> {
+    use std::io::Read;
+    use std::os::unix::net::UnixStream;
+
+    // 连接到 UAPI socket
+    let mut stream = UnixStream::connect(&socket_path)
+        .map_err(|e| format!("连接 WireGuard socket 失败: {}", e))?;
+
+    // 构建配置命令
+    let mut uapi_config = String::from("set=1\n");
+
+    // 私钥
+    let private_key_hex = base64_to_hex(&config.private_key)?;
+    uapi_config.push_str(&format!("private_key={}\n", private_key_hex));
+
+    // 监听端口
+    if let Some(port) = config.listen_port {
+        uapi_config.push_str(&format!("listen_port={}\n", port));
+    }
+
+    if let Some(fwmark) = config.fwmark {
+        uapi_config.push_str(&format!("fwmark={}\n", fwmark));
+    }
+
+    if config.replace_peers {
+        uapi_config.push_str("replace_peers=true\n");
     }
 
+    // Peer 配置
+    uapi_config.push_str(&build_peers_uapi(&config.peers).await?);
+
     uapi_config.push_str("\n");
 
     log::info!("发送 UAPI 配置:\n{}", uapi_config);
@@ -419,8 +608,8 @@ async fn configure_interface(config: &TunnelConfigIpc, socket_path: &str) -> Res
 
     log::info!("UAPI 响应:\n{}", response);
 
-    if response.contains("errno=") && !response.contains("errno=0") {
-        return Err(format!("配置失败: {}", response));
+    if let Some(message) = uapi_error_message(&response) {
+        return Err(format!("配置失败: {}", message));
     }
 
     Ok(())
@@ -503,7 +692,17 @@ async fn stop_tunnel_internal(tunnel_id: &str) -> Result<(), String> {
             }
         }
 
-        // 4. 清理 socket 文件（通常 wireguard-go 会自动清理，但以防万一）
+        // 4. 删除策略路由规则 (路由表中的路由随接口删除)
+        if let Err(e) = configure_policy_rules(&tunnel.config, false) {
+            log::warn!("警告: {}", e);
+        }
+
+        // 5. 删除 kill switch 防火墙规则
+        if let Err(e) = configure_kill_switch(&tunnel.config, &[], false) {
+            log::warn!("警告: {}", e);
+        }
+
+        // 6. 清理 socket 文件（通常 wireguard-go 会自动清理，但以防万一）
         if std::path::Path::new(&tunnel.socket_path).exists() {
             log::info!("清理残留的 socket 文件: {}", tunnel.socket_path);
             if let Err(e) = std::fs::remove_file(&tunnel.socket_path) {
@@ -597,27 +796,23 @@ async fn get_tunnel_status_internal(tunnel_id: &str) -> Result<TunnelStatusIpc,
     })
 }
 
-/// 处理获取 per-peer 统计信息请求
-async fn handle_get_peer_stats(request_id: String, params: serde_json::Value) -> IpcResponse {
-    log::info!("收到获取 peer 统计请求: params={:?}", params);
-    let tunnel_id: String =
-        match serde_json::from_value(params.get("tunnel_id").cloned().unwrap_or_default()) {
-            Ok(id) => id,
-            Err(e) => {
-                log::error!("解析 tunnel_id 失败: {}", e);
-                return IpcResponse {
-                    id: request_id,
-                    result: None,
-                    error: Some(format!("解析 tunnel_id 失败: {}", e)),
-                };
-            }
-        };
+/// 处理下发配置请求
+async fn handle_apply_config(request_id: String, params: serde_json::Value) -> IpcResponse {
+    let config: TunnelConfigIpc = match serde_json::from_value(params) {
+        Ok(c) => c,
+        Err(e) => {
+            return IpcResponse {
+                id: request_id,
+                result: None,
+                error: Some(format!("解析配置失败: {}", e)),
+            };
+        }
+    };
 
-    log::info!("查询 peer 统计: tunnel_id={}", tunnel_id);
-    match get_peer_stats_internal(&tunnel_id).await {
-        Ok(stats) => IpcResponse {
+    match apply_config_internal(config).await {
+        Ok(_) => IpcResponse {
             id: request_id,
-            result: Some(serde_json::to_value(&stats).unwrap()),
+            result: Some(serde_json::json!({"status": "ok"})),
             error: None,
         },
         Err(e) => IpcResponse {
@@ -628,71 +823,130 @@ async fn handle_get_peer_stats(request_id: String, params: serde_json::Value) ->
     }
 }
 
-/// 内部获取 per-peer 统计信息逻辑
-async fn get_peer_stats_internal(tunnel_id: &str) -> Result<Vec<PeerStatsIpc>, String> {
-    log::info!("开始获取隧道 {} 的 peer 统计", tunnel_id);
+/// 内部下发配置逻辑
+async fn apply_config_internal(config: TunnelConfigIpc) -> Result<(), String> {
     let socket_path = {
         let tunnels = DAEMON_TUNNELS.lock().await;
-        log::info!("当前运行中的隧道: {:?}", tunnels.keys().collect::<Vec<_>>());
+        tunnels
+            .get(&config.tunnel_id)
+            .map(|t| t.socket_path.clone())
+            .ok_or_else(|| format!("隧道 {} 未运行", config.tunnel_id))?
+    };
 
-        if let Some(tunnel) = tunnels.get(tunnel_id) {
-            log::info!("找到隧道，socket 路径: {}", tunnel.socket_path);
-            tunnel.socket_path.clone()
+    log::info!(
+        "下发配置到隧道 {} (replace_peers={})",
+        config.tunnel_id,
+        config.replace_peers
+    );
+
+    configure_interface(&config, &socket_path).await?;
+
+    // 同步更新守护进程中保存的配置
+    let mut tunnels = DAEMON_TUNNELS.lock().await;
+    if let Some(tunnel) = tunnels.get_mut(&config.tunnel_id) {
+        let previous = tunnel.config.clone();
+        if config.replace_peers {
+            tunnel.config.peers = config.peers.clone();
         } else {
-            log::error!("隧道 {} 未在运行列表中", tunnel_id);
-            return Err(format!("隧道 {} 未运行", tunnel_id));
+            for peer in &config.peers {
+                tunnel
+                    .config
+                    .peers
+                    .retain(|p| p.public_key != peer.public_key);
+                tunnel.config.peers.push(peer.clone());
+            }
         }
-    };
+        tunnel.config.listen_port = config.listen_port;
+        tunnel.config.route_table = config.route_table;
+        tunnel.config.fwmark = config.fwmark;
+
+        // 路由表、fwmark 或地址族变化时重新添加策略路由规则,避免旧规则残留
+        let rule_key = |c: &TunnelConfigIpc| (c.route_table, c.fwmark, policy_rule_families(c));
+        if rule_key(&previous) != rule_key(&tunnel.config) {
+            if let Err(e) = configure_policy_rules(&previous, false) {
+                log::warn!("警告: {}", e);
+            }
+            if tunnel.config.route_table != previous.route_table {
+                for peer in &tunnel.config.peers {
+                    for allowed_ip in &peer.allowed_ips {
+                        let is_default = allowed_ip == "0.0.0.0/0" || allowed_ip == "::/0";
+                        if tunnel.config.route_table.is_none() && is_default {
+                            continue; // 跳过默认路由
+                        }
+                        let _ = configure_route(
+                            &tunnel.config.interface_name,
+                            allowed_ip,
+                            tunnel.config.route_table,
+                        )
+                        .await;
+                    }
+                }
+            }
+            configure_policy_rules(&tunnel.config, true)?;
+        }
+    }
+    drop(tunnels);
 
-    // 在阻塞线程池中获取 peer 统计信息
-    log::info!("准备获取 peer 统计信息...");
-    let socket_path_clone = socket_path.clone();
-    let peer_stats = tokio::task::spawn_blocking(move || {
-        log::info!("在阻塞线程中获取 peer 统计: {}", socket_path_clone);
-        get_peer_stats_from_uapi(&socket_path_clone)
-    })
-    .await
-    .map_err(|e| format!("获取统计任务失败: {}", e))??;
+    // 重新下发时 endpoint 会重新解析,同步 kill switch 放行的地址
+    refresh_kill_switch_endpoints(&config.tunnel_id).await;
 
-    log::info!("获取到 {} 个 peer 的统计信息", peer_stats.len());
+    Ok(())
+}
 
-    Ok(peer_stats)
+/// 处理修改监听端口请求
+async fn handle_set_listen_port(request_id: String, params: serde_json::Value) -> IpcResponse {
+    #[derive(serde::Deserialize)]
+    struct SetListenPortParams {
+        tunnel_id: String,
+        listen_port: u16,
+    }
+
+    let params: SetListenPortParams = match serde_json::from_value(params) {
+        Ok(p) => p,
+        Err(e) => {
+            return IpcResponse {
+                id: request_id,
+                result: None,
+                error: Some(format!("解析参数失败: {}", e)),
+            };
+        }
+    };
+
+    match set_listen_port_internal(&params.tunnel_id, params.listen_port).await {
+        Ok(_) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::json!({"status": "ok"})),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
 }
 
-/// 从 UAPI 获取 per-peer 统计信息
-fn get_peer_stats_from_uapi(socket_path: &str) -> Result<Vec<PeerStatsIpc>, String> {
+/// 向 UAPI socket 发送 set 命令并检查 errno (阻塞)
+fn uapi_set_blocking(socket_path: &str, command: &str) -> Result<(), String> {
     use std::io::Read;
-    use std::os::unix::net::UnixStream;
-
-    log::info!("连接到 socket: {}", socket_path);
-    let mut stream = UnixStream::connect(socket_path)
-        .map_err(|e| format!("连接 socket {} 失败: {}", socket_path, e))?;
 
-    // 设置读取超时
+    let mut stream =
+        UnixStream::connect(socket_path).map_err(|e| format!("无法连接到 socket: {}", e))?;
     stream
         .set_read_timeout(Some(std::time::Duration::from_secs(2)))
         .map_err(|e| format!("设置超时失败: {}", e))?;
-
-    log::info!("发送 get 命令");
     stream
-        .write_all(b"get=1\n\n")
-        .map_err(|e| format!("发送请求失败: {}", e))?;
+        .write_all(format!("set=1\n{}\n", command).as_bytes())
+        .map_err(|e| format!("发送配置失败: {}", e))?;
 
-    // 读取响应
     let mut response = String::new();
-    let mut buffer = [0u8; 4096];
-
-    log::info!("开始读取响应");
+    let mut buffer = [0u8; 1024];
     loop {
         match stream.read(&mut buffer) {
-            Ok(0) => {
-                log::info!("EOF");
-                break;
-            }
+            Ok(0) => break,
             Ok(n) => {
                 response.push_str(&String::from_utf8_lossy(&buffer[..n]));
-                if response.contains("\n\n") {
-                    log::info!("检测到双换行符，停止读取");
+                if response.contains("\n\n") || response.contains("errno=") {
                     break;
                 }
             }
@@ -701,50 +955,644 @@ fn get_peer_stats_from_uapi(socket_path: &str) -> Result<Vec<PeerStatsIpc>, Stri
                     || e.kind() == std::io::ErrorKind::TimedOut =>
             {
                 if !response.is_empty() {
-                    log::info!("超时但已有数据，停止读取");
                     break;
                 }
-                return Err("读取超时".to_string());
+                return Err("读取响应超时".to_string());
             }
-            Err(e) => return Err(format!("读取失败: {}", e)),
+            Err(e) => return Err(format!("读取响应失败: {}", e)),
         }
     }
 
-    // 解析 UAPI 响应获取 per-peer 统计
-    parse_peer_stats(&response)
+    match uapi_error_message(&response) {
+        Some(message) => Err(message),
+        None => Ok(()),
+    }
 }
 
-/// 解析 UAPI 响应,提取每个 peer 的统计信息
-fn parse_peer_stats(uapi_response: &str) -> Result<Vec<PeerStatsIpc>, String> {
-    let mut peer_stats = Vec::new();
-    let mut current_public_key: Option<String> = None;
-    let mut current_tx_bytes = 0u64;
-    let mut current_rx_bytes = 0u64;
-    let mut current_last_handshake: Option<i64> = None;
+/// 获取运行中隧道的 UAPI socket 路径
+async fn tunnel_socket_path(tunnel_id: &str) -> Result<String, String> {
+    let tunnels = DAEMON_TUNNELS.lock().await;
+    tunnels
+        .get(tunnel_id)
+        .map(|t| t.socket_path.clone())
+        .ok_or_else(|| format!("隧道 {} 未运行", tunnel_id))
+}
 
-    for line in uapi_response.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+/// 内部修改监听端口逻辑
+async fn set_listen_port_internal(tunnel_id: &str, port: u16) -> Result<(), String> {
+    let socket_path = tunnel_socket_path(tunnel_id).await?;
 
-        if line.starts_with("public_key=") {
-            // 保存上一个 peer 的数据
-            if let Some(public_key) = current_public_key.take() {
-                peer_stats.push(PeerStatsIpc {
-                    public_key: hex_to_base64(&public_key)?,
-                    tx_bytes: current_tx_bytes,
-                    rx_bytes: current_rx_bytes,
-                    last_handshake: current_last_handshake,
-                });
-                // 重置计数器
-                current_tx_bytes = 0;
-                current_rx_bytes = 0;
-                current_last_handshake = None;
-            }
+    log::info!("修改隧道 {} 的监听端口为 {}", tunnel_id, port);
 
-            // 开始新 peer
-            if let Some(hex_key) = line.strip_prefix("public_key=") {
+    let command = format!("listen_port={}\n", port);
+    tokio::task::spawn_blocking(move || uapi_set_blocking(&socket_path, &command))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+        .map_err(|e| format!("修改监听端口失败: {}", e))?;
+
+    // 同步更新守护进程中保存的配置
+    let mut tunnels = DAEMON_TUNNELS.lock().await;
+    if let Some(tunnel) = tunnels.get_mut(tunnel_id) {
+        tunnel.config.listen_port = Some(port);
+    }
+
+    Ok(())
+}
+
+/// 处理移除 Peer 请求
+async fn handle_remove_peer(request_id: String, params: serde_json::Value) -> IpcResponse {
+    #[derive(serde::Deserialize)]
+    struct RemovePeerParams {
+        tunnel_id: String,
+        public_key: String,
+    }
+
+    let params: RemovePeerParams = match serde_json::from_value(params) {
+        Ok(p) => p,
+        Err(e) => {
+            return IpcResponse {
+                id: request_id,
+                result: None,
+                error: Some(format!("解析参数失败: {}", e)),
+            };
+        }
+    };
+
+    match remove_peer_internal(&params.tunnel_id, &params.public_key).await {
+        Ok(_) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::json!({"status": "ok"})),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 内部移除 Peer 逻辑
+async fn remove_peer_internal(tunnel_id: &str, public_key: &str) -> Result<(), String> {
+    let socket_path = tunnel_socket_path(tunnel_id).await?;
+
+    log::info!("从隧道 {} 移除 Peer {}", tunnel_id, public_key);
+
+    let command = format!("public_key={}\nremove=true\n", base64_to_hex(public_key)?);
+    tokio::task::spawn_blocking(move || uapi_set_blocking(&socket_path, &command))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+        .map_err(|e| format!("移除 Peer 失败: {}", e))?;
+
+    // 同步更新守护进程中保存的配置
+    let mut tunnels = DAEMON_TUNNELS.lock().await;
+    if let Some(tunnel) = tunnels.get_mut(tunnel_id) {
+        tunnel.config.peers.retain(|p| p.public_key != public_key);
+    }
+
+    Ok(())
+}
+
+/// 处理在线更新 Peer 列表请求
+async fn handle_update_tunnel_peers(request_id: String, params: serde_json::Value) -> IpcResponse {
+    #[derive(serde::Deserialize)]
+    struct UpdatePeersParams {
+        tunnel_id: String,
+        peers: Vec<PeerConfigIpc>,
+    }
+
+    let params: UpdatePeersParams = match serde_json::from_value(params) {
+        Ok(p) => p,
+        Err(e) => {
+            return IpcResponse {
+                id: request_id,
+                result: None,
+                error: Some(format!("解析参数失败: {}", e)),
+            };
+        }
+    };
+
+    match update_tunnel_peers_internal(&params.tunnel_id, params.peers).await {
+        Ok(_) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::json!({"status": "ok"})),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 内部更新 Peer 列表逻辑: 通过 UAPI 的 replace_peers 替换全部 Peer,不重启 wireguard-go
+async fn update_tunnel_peers_internal(
+    tunnel_id: &str,
+    peers: Vec<PeerConfigIpc>,
+) -> Result<(), String> {
+    let socket_path = tunnel_socket_path(tunnel_id).await?;
+
+    log::info!(
+        "在线更新隧道 {} 的 Peer 列表 ({} 个)",
+        tunnel_id,
+        peers.len()
+    );
+
+    let command = format!("replace_peers=true\n{}", build_peers_uapi(&peers).await?);
+    tokio::task::spawn_blocking(move || uapi_set_blocking(&socket_path, &command))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+        .map_err(|e| format!("更新 Peer 失败: {}", e))?;
+
+    // 同步更新守护进程中保存的配置
+    let mut tunnels = DAEMON_TUNNELS.lock().await;
+    if let Some(tunnel) = tunnels.get_mut(tunnel_id) {
+        tunnel.config.peers = peers;
+    }
+
+    Ok(())
+}
+
+// 开机自动启动的隧道配置目录,每个隧道一个 <tunnel_id>.json (仅 root 可读,包含私钥)
+const AUTOSTART_DIR: &str = "/etc/wire-vault/autostart";
+// 开机时网络可能尚未就绪,启动失败后重试
+const AUTOSTART_RETRIES: u32 = 5;
+const AUTOSTART_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
+
+fn autostart_config_path(tunnel_id: &str) -> Result<std::path::PathBuf, String> {
+    // tunnel_id 用作文件名,拒绝路径分隔符等字符
+    if tunnel_id.is_empty()
+        || !tunnel_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("无效的隧道 ID: {}", tunnel_id));
+    }
+    Ok(std::path::Path::new(AUTOSTART_DIR).join(format!("{}.json", tunnel_id)))
+}
+
+/// 处理设置开机自动启动请求: config 为空时取消自动启动
+async fn handle_set_autostart(request_id: String, params: serde_json::Value) -> IpcResponse {
+    #[derive(serde::Deserialize)]
+    struct SetAutostartParams {
+        tunnel_id: String,
+        config: Option<TunnelConfigIpc>,
+    }
+
+    let result = serde_json::from_value::<SetAutostartParams>(params)
+        .map_err(|e| format!("解析参数失败: {}", e))
+        .and_then(|p| set_autostart_internal(&p.tunnel_id, p.config));
+
+    match result {
+        Ok(_) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::json!({"status": "ok"})),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+fn set_autostart_internal(tunnel_id: &str, config: Option<TunnelConfigIpc>) -> Result<(), String> {
+    let path = autostart_config_path(tunnel_id)?;
+
+    let Some(config) = config else {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| format!("删除自动启动配置失败: {}", e))?;
+            log::info!("已取消隧道 {} 的开机自动启动", tunnel_id);
+        }
+        return Ok(());
+    };
+
+    if config.tunnel_id != tunnel_id {
+        return Err("自动启动配置与隧道 ID 不一致".to_string());
+    }
+
+    // 不保存应用传入的 wireguard-go 路径: AppImage 的挂载点重启后失效,开发版路径对用户可写
+    // 开机时由 find_wireguard_go 使用安装守护进程时复制的副本
+    let mut config = config;
+    config.wireguard_go_path.clear();
+
+    std::fs::create_dir_all(AUTOSTART_DIR).map_err(|e| format!("创建自动启动目录失败: {}", e))?;
+    std::fs::set_permissions(AUTOSTART_DIR, std::fs::Permissions::from_mode(0o700))
+        .map_err(|e| format!("设置自动启动目录权限失败: {}", e))?;
+
+    let json =
+        serde_json::to_string_pretty(&config).map_err(|e| format!("序列化配置失败: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("保存自动启动配置失败: {}", e))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("设置自动启动配置权限失败: {}", e))?;
+
+    log::info!("已设置隧道 {} 开机自动启动", tunnel_id);
+    Ok(())
+}
+
+/// 守护进程启动时拉起所有自动启动的隧道,不依赖 GUI 是否打开
+async fn start_autostart_tunnels() {
+    let entries = match std::fs::read_dir(AUTOSTART_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+
+        let mut config = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<TunnelConfigIpc>(&c).map_err(|e| e.to_string()))
+        {
+            Ok(config) => config,
+            Err(e) => {
+                log::error!("读取自动启动配置 {:?} 失败: {}", path, e);
+                continue;
+            }
+        };
+        // 旧版本保存的配置可能包含应用传入的路径,同样忽略
+        config.wireguard_go_path.clear();
+
+        tokio::spawn(async move {
+            let tunnel_id = config.tunnel_id.clone();
+            for attempt in 1..=AUTOSTART_RETRIES {
+                match start_tunnel_internal(config.clone()).await {
+                    Ok(_) => {
+                        log::info!("已自动启动隧道 {}", tunnel_id);
+                        return;
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "自动启动隧道 {} 失败 (第 {}/{} 次): {}",
+                            tunnel_id,
+                            attempt,
+                            AUTOSTART_RETRIES,
+                            e
+                        );
+                        // 隧道已由 GUI 启动时不再重试
+                        if DAEMON_TUNNELS.lock().await.contains_key(&tunnel_id) {
+                            return;
+                        }
+                    }
+                }
+                tokio::time::sleep(AUTOSTART_RETRY_DELAY).await;
+            }
+        });
+    }
+}
+
+/// 处理获取 per-peer 统计信息请求
+async fn handle_get_peer_stats(request_id: String, params: serde_json::Value) -> IpcResponse {
+    log::info!("收到获取 peer 统计请求: params={:?}", params);
+    let tunnel_id: String =
+        match serde_json::from_value(params.get("tunnel_id").cloned().unwrap_or_default()) {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("解析 tunnel_id 失败: {}", e);
+                return IpcResponse {
+                    id: request_id,
+                    result: None,
+                    error: Some(format!("解析 tunnel_id 失败: {}", e)),
+                };
+            }
+        };
+
+    log::info!("查询 peer 统计: tunnel_id={}", tunnel_id);
+    match get_peer_stats_internal(&tunnel_id).await {
+        Ok(stats) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::to_value(&stats).unwrap()),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 内部获取 per-peer 统计信息逻辑
+async fn get_peer_stats_internal(tunnel_id: &str) -> Result<Vec<PeerStatsIpc>, String> {
+    log::info!("开始获取隧道 {} 的 peer 统计", tunnel_id);
+    let socket_path = {
+        let tunnels = DAEMON_TUNNELS.lock().await;
+        log::info!("当前运行中的隧道: {:?}", tunnels.keys().collect::<Vec<_>>());
+
+        if let Some(tunnel) = tunnels.get(tunnel_id) {
+            log::info!("找到隧道，socket 路径: {}", tunnel.socket_path);
+            tunnel.socket_path.clone()
+        } else {
+            log::error!("隧道 {} 未在运行列表中", tunnel_id);
+            return Err(format!("隧道 {} 未运行", tunnel_id));
+        }
+    };
+
+    // 在阻塞线程池中获取 peer 统计信息
+    log::info!("准备获取 peer 统计信息...");
+    let socket_path_clone = socket_path.clone();
+    let peer_stats = tokio::task::spawn_blocking(move || {
+        log::info!("在阻塞线程中获取 peer 统计: {}", socket_path_clone);
+        get_peer_stats_from_uapi(&socket_path_clone)
+    })
+    .await
+    .map_err(|e| format!("获取统计任务失败: {}", e))??;
+
+    log::info!("获取到 {} 个 peer 的统计信息", peer_stats.len());
+
+    Ok(peer_stats)
+}
+
+/// 处理流式 peer 统计请求
+/// 每隔 interval_ms 写入一帧 IpcResponse (以换行分隔),客户端断开后停止;
+/// 隧道中途停止时写入一帧带 error 的响应后结束
+async fn handle_stream_peer_stats(
+    request_id: String,
+    params: serde_json::Value,
+    mut stream: UnixStream,
+) -> Result<(), String> {
+    let tunnel_id = match params.get("tunnel_id").and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => {
+            let response = IpcResponse {
+                id: request_id,
+                result: None,
+                error: Some("缺少 tunnel_id 参数".to_string()),
+            };
+            return write_stream_frame(&mut stream, &response);
+        }
+    };
+
+    let interval_ms = params
+        .get("interval_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(STREAM_DEFAULT_INTERVAL_MS)
+        .clamp(STREAM_MIN_INTERVAL_MS, STREAM_MAX_INTERVAL_MS);
+
+    // 客户端卡住不读取时避免一直阻塞
+    stream
+        .set_write_timeout(Some(std::time::Duration::from_secs(10)))
+        .map_err(|e| format!("设置写入超时失败: {}", e))?;
+
+    log::info!(
+        "开始流式推送 peer 统计: tunnel_id={}, interval={}ms",
+        tunnel_id,
+        interval_ms
+    );
+
+    loop {
+        let (response, finished) = match get_peer_stats_internal(&tunnel_id).await {
+            Ok(stats) => (
+                IpcResponse {
+                    id: request_id.clone(),
+                    result: Some(serde_json::to_value(&stats).unwrap()),
+                    error: None,
+                },
+                false,
+            ),
+            Err(e) => (
+                IpcResponse {
+                    id: request_id.clone(),
+                    result: None,
+                    error: Some(e),
+                },
+                true,
+            ),
+        };
+
+        if let Err(e) = write_stream_frame(&mut stream, &response) {
+            log::info!(
+                "流式推送结束 (客户端已断开): tunnel_id={}, {}",
+                tunnel_id,
+                e
+            );
+            return Ok(());
+        }
+
+        if finished {
+            log::info!("流式推送结束 (隧道已停止): tunnel_id={}", tunnel_id);
+            return Ok(());
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(interval_ms)).await;
+    }
+}
+
+/// 写入一帧流式响应 (JSON + 换行)
+fn write_stream_frame(stream: &mut UnixStream, response: &IpcResponse) -> Result<(), String> {
+    let response_json =
+        serde_json::to_string(response).map_err(|e| format!("序列化响应失败: {}", e))?;
+    stream
+        .write_all(format!("{}\n", response_json).as_bytes())
+        .map_err(|e| format!("发送响应失败: {}", e))
+}
+
+/// 处理读取原始 UAPI 响应请求
+async fn handle_dump_uapi_state(request_id: String, params: serde_json::Value) -> IpcResponse {
+    let tunnel_id: String =
+        match serde_json::from_value(params.get("tunnel_id").cloned().unwrap_or_default()) {
+            Ok(id) => id,
+            Err(e) => {
+                return IpcResponse {
+                    id: request_id,
+                    result: None,
+                    error: Some(format!("解析 tunnel_id 失败: {}", e)),
+                };
+            }
+        };
+
+    match dump_uapi_state_internal(&tunnel_id).await {
+        Ok(dump) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::Value::String(dump)),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 内部读取原始 UAPI 响应逻辑,私钥不会离开守护进程
+async fn dump_uapi_state_internal(tunnel_id: &str) -> Result<String, String> {
+    let socket_path = tunnel_socket_path(tunnel_id).await?;
+
+    let raw = tokio::task::spawn_blocking(move || uapi_get_blocking(&socket_path))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))??;
+
+    Ok(redact_uapi_dump(&raw))
+}
+
+/// 处理读取接口实际 MTU 请求
+async fn handle_get_interface_mtu(request_id: String, params: serde_json::Value) -> IpcResponse {
+    let tunnel_id: String =
+        match serde_json::from_value(params.get("tunnel_id").cloned().unwrap_or_default()) {
+            Ok(id) => id,
+            Err(e) => {
+                return IpcResponse {
+                    id: request_id,
+                    result: None,
+                    error: Some(format!("解析 tunnel_id 失败: {}", e)),
+                };
+            }
+        };
+
+    let interface_name = {
+        let tunnels = DAEMON_TUNNELS.lock().await;
+        tunnels.get(&tunnel_id).map(|t| t.interface_name.clone())
+    };
+
+    let result = match interface_name {
+        Some(interface_name) => get_link_mtu(&interface_name).await,
+        None => Err(format!("隧道 {} 未运行", tunnel_id)),
+    };
+
+    match result {
+        Ok(mtu) => IpcResponse {
+            id: request_id,
+            result: Some(serde_json::json!(mtu)),
+            error: None,
+        },
+        Err(e) => IpcResponse {
+            id: request_id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 通过 netlink 读取接口当前生效的 MTU
+async fn get_link_mtu(interface: &str) -> Result<u32, String> {
+    use futures::stream::TryStreamExt;
+    use netlink_packet_route::link::LinkAttribute;
+    use rtnetlink::new_connection;
+
+    let (connection, handle, _) =
+        new_connection().map_err(|e| format!("创建 netlink 连接失败: {}", e))?;
+
+    tokio::spawn(connection);
+
+    let mut links = handle
+        .link()
+        .get()
+        .match_name(interface.to_string())
+        .execute();
+    let link = links
+        .try_next()
+        .await
+        .map_err(|e| format!("获取接口失败: {}", e))?
+        .ok_or_else(|| format!("接口不存在: {}", interface))?;
+
+    link.attributes
+        .iter()
+        .find_map(|attr| match attr {
+            LinkAttribute::Mtu(mtu) => Some(*mtu),
+            _ => None,
+        })
+        .ok_or_else(|| format!("接口 {} 缺少 MTU 信息", interface))
+}
+
+/// 从 UAPI 获取 per-peer 统计信息
+fn get_peer_stats_from_uapi(socket_path: &str) -> Result<Vec<PeerStatsIpc>, String> {
+    let response = uapi_get_blocking(socket_path)?;
+
+    // 解析 UAPI 响应获取 per-peer 统计
+    parse_peer_stats(&response)
+}
+
+/// 向 UAPI socket 发送 get 命令并返回原始响应 (阻塞)
+fn uapi_get_blocking(socket_path: &str) -> Result<String, String> {
+    use std::io::Read;
+    use std::os::unix::net::UnixStream;
+
+    log::info!("连接到 socket: {}", socket_path);
+    let mut stream = UnixStream::connect(socket_path)
+        .map_err(|e| format!("连接 socket {} 失败: {}", socket_path, e))?;
+
+    // 设置读取超时
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+        .map_err(|e| format!("设置超时失败: {}", e))?;
+
+    log::info!("发送 get 命令");
+    stream
+        .write_all(b"get=1\n\n")
+        .map_err(|e| format!("发送请求失败: {}", e))?;
+
+    // 读取响应
+    let mut response = String::new();
+    let mut buffer = [0u8; 4096];
+
+    log::info!("开始读取响应");
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => {
+                log::info!("EOF");
+                break;
+            }
+            Ok(n) => {
+                response.push_str(&String::from_utf8_lossy(&buffer[..n]));
+                if response.contains("\n\n") {
+                    log::info!("检测到双换行符，停止读取");
+                    break;
+                }
+            }
+            Err(ref e)
+                if e.kind() == std::io::ErrorKind::WouldBlock
+                    || e.kind() == std::io::ErrorKind::TimedOut =>
+            {
+                if !response.is_empty() {
+                    log::info!("超时但已有数据，停止读取");
+                    break;
+                }
+                return Err("读取超时".to_string());
+            }
+            Err(e) => return Err(format!("读取失败: {}", e)),
+        }
+    }
+
+    Ok(response)
+}
+
+/// 解析 UAPI 响应,提取每个 peer 的统计信息
+fn parse_peer_stats(uapi_response: &str) -> Result<Vec<PeerStatsIpc>, String> {
+    let mut peer_stats = Vec::new();
+    let mut current_public_key: Option<String> = None;
+    let mut current_tx_bytes = 0u64;
+    let mut current_rx_bytes = 0u64;
+    let mut current_last_handshake: Option<i64> = None;
+
+    for line in uapi_response.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with("public_key=") {
+            // 保存上一个 peer 的数据
+            if let Some(public_key) = current_public_key.take() {
+                peer_stats.push(PeerStatsIpc {
+                    public_key: hex_to_base64(&public_key)?,
+                    tx_bytes: current_tx_bytes,
+                    rx_bytes: current_rx_bytes,
+                    last_handshake: current_last_handshake,
+                });
+                // 重置计数器
+                current_tx_bytes = 0;
+                current_rx_bytes = 0;
+                current_last_handshake = None;
+            }
+
+            // 开始新 peer
+            if let Some(hex_key) = line.strip_prefix("public_key=") {
                 current_public_key = Some(hex_key.to_string());
             }
         } else if line.starts_with("tx_bytes=") {
@@ -779,6 +1627,24 @@ fn parse_peer_stats(uapi_response: &str) -> Result<Vec<PeerStatsIpc>, String> {
     Ok(peer_stats)
 }
 
+/// 从 UAPI get 响应中解析每个 peer 当前的 endpoint,返回 Base64 公钥 -> endpoint
+fn parse_peer_endpoints(uapi_response: &str) -> HashMap<String, String> {
+    let mut endpoints = HashMap::new();
+    let mut current_public_key: Option<String> = None;
+
+    for line in uapi_response.lines().map(str::trim) {
+        if let Some(hex_key) = line.strip_prefix("public_key=") {
+            current_public_key = hex_to_base64(hex_key).ok();
+        } else if let Some(endpoint) = line.strip_prefix("endpoint=") {
+            if let Some(public_key) = current_public_key.clone() {
+                endpoints.insert(public_key, endpoint.to_string());
+            }
+        }
+    }
+
+    endpoints
+}
+
 /// 将十六进制密钥转换为 Base64
 fn hex_to_base64(hex: &str) -> Result<String, String> {
     let bytes = hex::decode(hex).map_err(|e| format!("十六进制解码失败: {}", e))?;
@@ -885,11 +1751,59 @@ async fn handle_list_tunnels(request_id: String) -> IpcResponse {
     }
 }
 
-/// 处理 ping 请求
+/// 处理导出守护进程状态请求,供问题反馈使用,不包含私钥
+async fn handle_dump_state(request_id: String) -> IpcResponse {
+    let (tunnels, running_wg_go_path) = {
+        let tunnels = DAEMON_TUNNELS.lock().await;
+        let mut states: Vec<DaemonTunnelStateIpc> = tunnels
+            .iter()
+            .map(|(tunnel_id, tunnel)| DaemonTunnelStateIpc {
+                tunnel_id: tunnel_id.clone(),
+                interface_name: tunnel.interface_name.clone(),
+                socket_path: tunnel.socket_path.clone(),
+                pid: tunnel.process.id(),
+                peer_count: tunnel.config.peers.len(),
+                listen_port: tunnel.config.listen_port,
+                wireguard_go_path: tunnel.wireguard_go_path.clone(),
+            })
+            .collect();
+        states.sort_by(|a, b| a.tunnel_id.cmp(&b.tunnel_id));
+        let path = states.first().map(|t| t.wireguard_go_path.clone());
+        (states, path)
+    };
+
+    // 没有运行中的隧道时,报告新启动隧道将使用的 wireguard-go
+    let wireguard_go_path = running_wg_go_path.or_else(|| find_wireguard_go().ok());
+    let wireguard_go_version = wireguard_go_path
+        .as_deref()
+        .and_then(|path| crate::daemon_install::wireguard_go_version(std::path::Path::new(path)));
+
+    let state = DaemonStateIpc {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        protocol_version: DAEMON_PROTOCOL_VERSION,
+        wireguard_go_path,
+        wireguard_go_version,
+        tunnels,
+    };
+
+    IpcResponse {
+        id: request_id,
+        result: serde_json::to_value(state).ok(),
+        error: None,
+    }
+}
+
+/// 处理 ping 请求,同时返回守护进程版本和 IPC 协议版本,供应用检查兼容性
 async fn handle_ping(request_id: String) -> IpcResponse {
+    let pong = PingResponseIpc {
+        status: "pong".to_string(),
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        protocol_version: DAEMON_PROTOCOL_VERSION,
+    };
+
     IpcResponse {
         id: request_id,
-        result: Some(serde_json::json!({"status": "pong"})),
+        result: serde_json::to_value(pong).ok(),
         error: None,
     }
 }
@@ -940,7 +1854,7 @@ fn interface_exists(name: &str) -> bool {
 }
 
 /// 查找 wireguard-go 可执行文件
-fn find_wireguard_go() -> Result<String, String> {
+pub(crate) fn find_wireguard_go() -> Result<String, String> {
     // 尝试常见路径（优先级顺序）
     let paths = vec![
         "/opt/wire-vault/wireguard-go", // 安装守护进程时复制的位置（优先使用）
@@ -1064,8 +1978,391 @@ async fn configure_interface_ip(interface: &str, address: &str) -> Result<(), St
     Ok(())
 }
 
-/// 使用 netlink 配置路由
-async fn configure_route(interface: &str, destination: &str) -> Result<(), String> {
+/// 策略路由规则记录目录,每个接口一个文件,内容为 "<table> <fwmark> <地址族...>"
+/// 位于 tmpfs,与 ip rule 一样重启后清空;守护进程异常退出后据此清理残留规则
+const POLICY_RULES_DIR: &str = "/var/run/wire-vault/policy-rules";
+
+/// main 表中除默认路由外的路由优先于隧道路由表 (多个隧道共用)
+const SUPPRESS_RULE: [&str; 4] = ["table", "main", "suppress_prefixlength", "0"];
+
+/// 添加或删除策略路由规则: 未带 fwmark 的流量查询隧道路由表
+/// 与 wg-quick 的做法一致:
+/// - ip rule add not fwmark <fwmark> table <table>: WireGuard 自身的加密包带有 fwmark,仍走 main 表,避免路由环路
+/// - ip rule add table main suppress_prefixlength 0: 局域网和直连路由不被隧道路由表中的默认路由覆盖
+fn configure_policy_rules(config: &TunnelConfigIpc, add: bool) -> Result<(), String> {
+    let (Some(table), Some(fwmark)) = (config.route_table, config.fwmark) else {
+        return Ok(());
+    };
+    let families = policy_rule_families(config);
+
+    if !add {
+        remove_policy_rules(&config.interface_name, table, fwmark, families)
+            .map_err(|e| format!("删除策略路由规则失败: {}", e))?;
+        log::info!("已删除策略路由规则: not fwmark {} table {}", fwmark, table);
+        return Ok(());
+    }
+
+    // 清理上次异常退出时残留的规则,并在添加前记录,失败时可按记录回滚
+    remove_recorded_policy_rules(&config.interface_name);
+    record_policy_rules(&config.interface_name, table, fwmark, families)?;
+
+    let fwmark_str = fwmark.to_string();
+    let table_str = table.to_string();
+    let result = families.iter().try_for_each(|family| {
+        run_ip_rule(
+            family,
+            "add",
+            &["not", "fwmark", &fwmark_str, "table", &table_str],
+        )?;
+        // 其他隧道已添加时规则已存在
+        match run_ip_rule(family, "add", &SUPPRESS_RULE) {
+            Err(e) if !e.contains("File exists") => Err(e),
+            _ => Ok(()),
+        }
+    });
+    if let Err(e) = result {
+        remove_recorded_policy_rules(&config.interface_name);
+        return Err(format!("添加策略路由规则失败: {}", e));
+    }
+
+    log::info!("已添加策略路由规则: not fwmark {} table {}", fwmark, table);
+    Ok(())
+}
+
+/// 需要添加策略路由规则的地址族
+fn policy_rule_families(config: &TunnelConfigIpc) -> &'static [&'static str] {
+    let has_ipv6 = config.address.contains(':')
+        || config
+            .peers
+            .iter()
+            .any(|p| p.allowed_ips.iter().any(|ip| ip.contains(':')));
+    if has_ipv6 {
+        &["-4", "-6"]
+    } else {
+        &["-4"]
+    }
+}
+
+/// 执行一条 ip rule 命令
+fn run_ip_rule(family: &str, action: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new("ip")
+        .args([family, "rule", action])
+        .args(args)
+        .output()
+        .map_err(|e| format!("执行 ip rule 失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ip {} rule {} {}: {}",
+            family,
+            action,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn policy_record_path(interface: &str) -> std::path::PathBuf {
+    std::path::Path::new(POLICY_RULES_DIR).join(interface)
+}
+
+/// 记录接口已添加的策略路由规则
+fn record_policy_rules(
+    interface: &str,
+    table: u32,
+    fwmark: u32,
+    families: &[&str],
+) -> Result<(), String> {
+    std::fs::create_dir_all(POLICY_RULES_DIR)
+        .map_err(|e| format!("创建策略路由记录目录失败: {}", e))?;
+    std::fs::write(
+        policy_record_path(interface),
+        format!("{} {} {}", table, fwmark, families.join(" ")),
+    )
+    .map_err(|e| format!("保存策略路由记录失败: {}", e))
+}
+
+/// 读取策略路由记录: (table, fwmark, 地址族)
+fn read_policy_record(path: &std::path::Path) -> Option<(u32, u32, Vec<String>)> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut fields = content.split_whitespace();
+    let table = fields.next()?.parse().ok()?;
+    let fwmark = fields.next()?.parse().ok()?;
+    let families = fields
+        .filter(|f| *f == "-4" || *f == "-6")
+        .map(str::to_string)
+        .collect();
+    Some((table, fwmark, families))
+}
+
+/// 删除接口的策略路由规则和记录,没有其他隧道使用时同时删除 suppress 规则
+fn remove_policy_rules(
+    interface: &str,
+    table: u32,
+    fwmark: u32,
+    families: &[&str],
+) -> Result<(), String> {
+    let _ = std::fs::remove_file(policy_record_path(interface));
+
+    // 其他隧道仍在使用的地址族
+    let in_use: Vec<String> = std::fs::read_dir(POLICY_RULES_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| read_policy_record(&entry.path()))
+                .flat_map(|(_, _, families)| families)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let fwmark_str = fwmark.to_string();
+    let table_str = table.to_string();
+    let mut results = Vec::new();
+    for family in families {
+        results.push(run_ip_rule(
+            family,
+            "del",
+            &["not", "fwmark", &fwmark_str, "table", &table_str],
+        ));
+        if !in_use.iter().any(|f| f == family) {
+            let _ = run_ip_rule(family, "del", &SUPPRESS_RULE);
+        }
+    }
+    results.into_iter().collect()
+}
+
+/// 按记录删除接口残留的策略路由规则
+fn remove_recorded_policy_rules(interface: &str) {
+    let path = policy_record_path(interface);
+    let Some((table, fwmark, families)) = read_policy_record(&path) else {
+        let _ = std::fs::remove_file(&path);
+        return;
+    };
+
+    let families: Vec<&str> = families.iter().map(String::as_str).collect();
+    match remove_policy_rules(interface, table, fwmark, &families) {
+        Ok(_) => log::info!("已清理接口 {} 残留的策略路由规则", interface),
+        Err(e) => log::warn!("清理接口 {} 残留的策略路由规则失败: {}", interface, e),
+    }
+}
+
+/// 守护进程启动时清理上次异常退出残留的策略路由规则
+fn cleanup_stale_policy_rules() {
+    let Ok(entries) = std::fs::read_dir(POLICY_RULES_DIR) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if let Some(interface) = entry.file_name().to_str() {
+            remove_recorded_policy_rules(interface);
+        }
+    }
+}
+
+/// kill switch 使用的 iptables 链名 (链名最长 28 个字符,接口名最长 15 个字符)
+fn kill_switch_chain(interface: &str) -> String {
+    format!("WV-KS-{}", interface)
+}
+
+/// 执行一条 iptables / ip6tables 命令
+fn run_iptables(cmd: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| format!("执行 {} 失败: {}", cmd, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} {} 失败: {}",
+            cmd,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// 删除 kill switch 链及其在 OUTPUT 中的跳转规则,返回遇到的第一个错误
+fn remove_kill_switch_chain(cmd: &str, chain: &str) -> Result<(), String> {
+    let results = [
+        run_iptables(cmd, &["-D", "OUTPUT", "-j", chain]),
+        run_iptables(cmd, &["-F", chain]),
+        run_iptables(cmd, &["-X", chain]),
+    ];
+    results.into_iter().collect()
+}
+
+/// 添加或删除 kill switch 防火墙规则
+/// 在 OUTPUT 链最前面跳转到独立的链,除回环接口、隧道接口、发往 Peer endpoint 的 UDP 流量
+/// 以及带隧道 fwmark 的数据包外,拒绝所有出站流量。
+/// wireguard-go 意外退出时规则保留,直到隧道被停止,从而避免流量绕过隧道泄露;
+/// endpoints 为 wireguard-go 中实际配置的地址,解析结果变化后由 refresh_kill_switch_endpoints 更新
+fn configure_kill_switch(
+    config: &TunnelConfigIpc,
+    endpoints: &[std::net::SocketAddr],
+    add: bool,
+) -> Result<(), String> {
+    if !config.kill_switch {
+        return Ok(());
+    }
+
+    let chain = kill_switch_chain(&config.interface_name);
+
+    // 删除时 IPv4 和 IPv6 规则都要尝试,返回遇到的第一个错误
+    if !add {
+        let results = [
+            remove_kill_switch_chain("iptables", &chain),
+            remove_kill_switch_chain("ip6tables", &chain),
+        ];
+        results
+            .into_iter()
+            .collect::<Result<(), String>>()
+            .map_err(|e| format!("删除 kill switch 规则失败: {}", e))?;
+        log::info!("已删除 kill switch 规则: {}", chain);
+        return Ok(());
+    }
+
+    for (cmd, ipv6) in [("iptables", false), ("ip6tables", true)] {
+        // 清理上次异常退出时残留的规则
+        let _ = remove_kill_switch_chain(cmd, &chain);
+
+        let mut rules: Vec<Vec<String>> = vec![
+            vec!["-o".into(), "lo".into()],
+            vec!["-o".into(), config.interface_name.clone()],
+        ];
+        for endpoint in endpoints.iter().filter(|e| e.is_ipv6() == ipv6) {
+            rules.push(kill_switch_endpoint_rule(endpoint));
+        }
+        if let Some(fwmark) = config.fwmark {
+            rules.push(vec![
+                "-m".into(),
+                "mark".into(),
+                "--mark".into(),
+                fwmark.to_string(),
+            ]);
+        }
+
+        let result = run_iptables(cmd, &["-N", &chain]).and_then(|_| {
+            for rule in &rules {
+                let mut args = vec!["-A", chain.as_str()];
+                args.extend(rule.iter().map(String::as_str));
+                args.extend(["-j", "ACCEPT"]);
+                run_iptables(cmd, &args)?;
+            }
+            run_iptables(cmd, &["-A", &chain, "-j", "REJECT"])?;
+            run_iptables(cmd, &["-I", "OUTPUT", "-j", &chain])
+        });
+
+        if let Err(e) = result {
+            let _ = remove_kill_switch_chain("iptables", &chain);
+            let _ = remove_kill_switch_chain("ip6tables", &chain);
+            return Err(format!("添加 kill switch 规则失败: {}", e));
+        }
+    }
+
+    log::info!("已添加 kill switch 规则: {}", chain);
+    Ok(())
+}
+
+/// kill switch 中放行发往某个 endpoint 的 UDP 流量的匹配条件
+fn kill_switch_endpoint_rule(endpoint: &std::net::SocketAddr) -> Vec<String> {
+    vec![
+        "-d".into(),
+        endpoint.ip().to_string(),
+        "-p".into(),
+        "udp".into(),
+        "--dport".into(),
+        endpoint.port().to_string(),
+    ]
+}
+
+/// 读取 wireguard-go 中各 peer 实际使用的 endpoint
+fn configured_endpoints(socket_path: &str) -> Result<Vec<std::net::SocketAddr>, String> {
+    let uapi = uapi_get_blocking(socket_path)?;
+    let mut endpoints: Vec<std::net::SocketAddr> = parse_peer_endpoints(&uapi)
+        .into_values()
+        .filter_map(|endpoint| endpoint.parse().ok())
+        .collect();
+    endpoints.sort();
+    endpoints.dedup();
+    Ok(endpoints)
+}
+
+/// endpoint 解析结果变化后更新 kill switch 放行的地址
+/// 先在链首插入新地址再删除旧地址,更新期间握手包不会被拒绝
+fn update_kill_switch_endpoints(
+    config: &TunnelConfigIpc,
+    old: &[std::net::SocketAddr],
+    new: &[std::net::SocketAddr],
+) -> Result<(), String> {
+    let chain = kill_switch_chain(&config.interface_name);
+    let cmd_for = |endpoint: &std::net::SocketAddr| {
+        if endpoint.is_ipv6() {
+            "ip6tables"
+        } else {
+            "iptables"
+        }
+    };
+
+    for endpoint in new.iter().filter(|e| !old.contains(e)) {
+        let rule = kill_switch_endpoint_rule(endpoint);
+        let mut args = vec!["-I", chain.as_str(), "1"];
+        args.extend(rule.iter().map(String::as_str));
+        args.extend(["-j", "ACCEPT"]);
+        run_iptables(cmd_for(endpoint), &args)
+            .map_err(|e| format!("更新 kill switch 规则失败: {}", e))?;
+        log::info!("kill switch 放行新的 endpoint: {}", endpoint);
+    }
+
+    for endpoint in old.iter().filter(|e| !new.contains(e)) {
+        let rule = kill_switch_endpoint_rule(endpoint);
+        let mut args = vec!["-D", chain.as_str()];
+        args.extend(rule.iter().map(String::as_str));
+        args.extend(["-j", "ACCEPT"]);
+        if let Err(e) = run_iptables(cmd_for(endpoint), &args) {
+            log::warn!("删除旧的 kill switch 放行规则失败: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// 按 wireguard-go 当前使用的 endpoint 同步隧道的 kill switch 放行规则
+async fn refresh_kill_switch_endpoints(tunnel_id: &str) {
+    let mut tunnels = DAEMON_TUNNELS.lock().await;
+    let Some(tunnel) = tunnels.get_mut(tunnel_id) else {
+        return;
+    };
+    if !tunnel.config.kill_switch {
+        return;
+    }
+
+    let socket_path = tunnel.socket_path.clone();
+    let endpoints =
+        match tokio::task::spawn_blocking(move || configured_endpoints(&socket_path)).await {
+            Ok(Ok(endpoints)) => endpoints,
+            Ok(Err(e)) => {
+                log::warn!("读取隧道 {} 的 endpoint 失败: {}", tunnel_id, e);
+                return;
+            }
+            Err(e) => {
+                log::warn!("任务执行失败: {}", e);
+                return;
+            }
+        };
+
+    match update_kill_switch_endpoints(&tunnel.config, &tunnel.kill_switch_endpoints, &endpoints) {
+        Ok(()) => tunnel.kill_switch_endpoints = endpoints,
+        Err(e) => log::warn!("警告: {}", e),
+    }
+}
+
+/// 使用 netlink 配置路由,table 为 None 时写入 main 表
+async fn configure_route(
+    interface: &str,
+    destination: &str,
+    table: Option<u32>,
+) -> Result<(), String> {
     use futures::stream::TryStreamExt;
     use rtnetlink::new_connection;
     use std::net::IpAddr;
@@ -1106,29 +2403,42 @@ async fn configure_route(interface: &str, destination: &str) -> Result<(), Strin
     // 添加路由
     match ip {
         IpAddr::V4(addr) => {
-            handle
+            let mut request = handle
                 .route()
                 .add()
                 .v4()
                 .destination_prefix(addr, prefix_len)
-                .output_interface(index)
+                .output_interface(index);
+            if let Some(table) = table {
+                request = request.table_id(table);
+            }
+            request
                 .execute()
                 .await
                 .map_err(|e| format!("添加 IPv4 路由失败: {}", e))?;
         }
         IpAddr::V6(addr) => {
-            handle
+            let mut request = handle
                 .route()
                 .add()
                 .v6()
                 .destination_prefix(addr, prefix_len)
-                .output_interface(index)
+                .output_interface(index);
+            if let Some(table) = table {
+                request = request.table_id(table);
+            }
+            request
                 .execute()
                 .await
                 .map_err(|e| format!("添加 IPv6 路由失败: {}", e))?;
         }
     }
 
-    log::info!("已添加路由: {} -> {}", destination, interface);
+    log::info!(
+        "已添加路由: {} -> {} (table={})",
+        destination,
+        interface,
+        table.map_or("main".to_string(), |t| t.to_string())
+    );
     Ok(())
 }
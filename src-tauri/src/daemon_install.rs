@@ -2,11 +2,13 @@
 // 通过 pkexec 获取权限执行安装操作
 
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::Path;
 use std::process::Command;
 use tauri::Manager;
 
+use crate::daemon_ipc::{DaemonSocketState, IpcClient};
+
 const SYSTEMD_SERVICE_CONTENT: &str = r#"[Unit]
 Description=WireVault 守护进程
 Documentation=https://github.com/mrtian2016/wire-vault
@@ -14,7 +16,7 @@ After=network.target
 
 [Service]
 Type=simple
-ExecStart=/usr/local/bin/wire-vault daemon
+ExecStart={exec_path} daemon
 Restart=on-failure
 RestartSec=5s
 
@@ -33,6 +35,32 @@ StandardError=journal
 WantedBy=multi-user.target
 "#;
 
+const OPENRC_SCRIPT_CONTENT: &str = r#"#!/sbin/openrc-run
+# WireVault 守护进程
+
+name="wire-vault-daemon"
+description="WireVault 守护进程"
+command="/usr/local/bin/wire-vault"
+command_args="daemon"
+command_background=true
+pidfile="/run/wire-vault-daemon.pid"
+output_log="/var/log/wire-vault-daemon.log"
+error_log="/var/log/wire-vault-daemon.log"
+
+depend() {
+    need net
+    after firewall
+}
+"#;
+
+// 安装后守护进程可执行文件的位置
+const INSTALLED_BINARY_PATH: &str = "/usr/local/bin/wire-vault";
+
+/// 生成指定可执行文件路径的 systemd service 文件内容
+fn systemd_service_content(exec_path: &str) -> String {
+    SYSTEMD_SERVICE_CONTENT.replace("{exec_path}", exec_path)
+}
+
 /// 守护进程状态
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct DaemonStatus {
@@ -40,19 +68,71 @@ pub struct DaemonStatus {
     pub running: bool,
     pub enabled: bool,
     pub version: Option<String>,
+    // socket 状态: missing / stale / healthy
+    pub socket_state: DaemonSocketState,
+}
+
+/// 检查命令是否存在于 PATH 中
+fn command_exists(cmd: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(cmd).is_file()))
+        .unwrap_or(false)
+}
+
+/// 检测当前系统使用的 init 系统
+pub fn init_system() -> &'static str {
+    if Path::new("/run/systemd/system").exists() {
+        "systemd"
+    } else if Path::new("/run/openrc").exists() || command_exists("rc-service") {
+        "openrc"
+    } else if Path::new("/run/runit").exists() || command_exists("sv") {
+        "runit"
+    } else {
+        "unknown"
+    }
+}
+
+/// 检测 init 系统: systemd / openrc / runit / unknown
+#[tauri::command]
+pub fn detect_init_system() -> Result<String, String> {
+    let init = init_system();
+    log::info!("检测到 init 系统: {}", init);
+    Ok(init.to_string())
 }
 
 /// 检查守护进程状态
 #[tauri::command]
 pub async fn check_daemon_status() -> Result<DaemonStatus, String> {
-    // 检查是否安装 (检查可执行文件和 systemd service)
-    let installed = Path::new("/usr/local/bin/wire-vault").exists()
-        && Path::new("/etc/systemd/system/wire-vault-daemon.service").exists();
+    let is_openrc = init_system() == "openrc";
+
+    // 检查是否安装 (检查可执行文件和 systemd service / OpenRC init 脚本)
+    let service_file = if is_openrc {
+        "/etc/init.d/wire-vault-daemon"
+    } else {
+        "/etc/systemd/system/wire-vault-daemon.service"
+    };
+    let installed =
+        Path::new("/usr/local/bin/wire-vault").exists() && Path::new(service_file).exists();
 
     let mut running = false;
     let mut enabled = false;
 
-    if installed {
+    if installed && is_openrc {
+        // 检查是否运行
+        if let Ok(output) = Command::new("rc-service")
+            .args(["wire-vault-daemon", "status"])
+            .output()
+        {
+            running = output.status.success();
+        }
+
+        // 检查是否加入默认运行级别
+        if let Ok(output) = Command::new("rc-update").args(["show", "default"]).output() {
+            enabled = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.trim_start().starts_with("wire-vault-daemon"));
+        }
+    } else if installed {
         // 检查是否运行
         if let Ok(output) = Command::new("systemctl")
             .args(["is-active", "wire-vault-daemon"])
@@ -89,6 +169,7 @@ pub async fn check_daemon_status() -> Result<DaemonStatus, String> {
         running,
         enabled,
         version,
+        socket_state: IpcClient::socket_state(),
     })
 }
 
@@ -98,6 +179,17 @@ pub async fn check_daemon_status() -> Result<DaemonStatus, String> {
 pub async fn install_daemon(app: tauri::AppHandle) -> Result<String, String> {
     log::info!("========== 开始安装守护进程 ==========");
 
+    // 守护进程依赖 systemd 或 OpenRC 管理，其他 init 系统下安装必然失败
+    let init = init_system();
+    if init != "systemd" && init != "openrc" {
+        let msg = format!(
+            "当前系统未使用 systemd 或 OpenRC (检测到: {})，暂不支持安装守护进程",
+            init
+        );
+        log::error!("{}", msg);
+        return Err(msg);
+    }
+
     // 检查运行环境
     let appimage = std::env::var("APPIMAGE").ok();
     let appimage_str = appimage.as_deref().unwrap_or("未检测");
@@ -243,8 +335,8 @@ pub async fn install_daemon(app: tauri::AppHandle) -> Result<String, String> {
             msg
         })?;
 
-    // 创建临时安装脚本
-    let script_content = format!(
+    // 创建临时安装脚本 (步骤 1-2 通用，步骤 3-5 按 init 系统区分)
+    let script_head = format!(
         r#"#!/bin/bash
 set -e
 
@@ -299,7 +391,62 @@ else
     exit 1
 fi
 
-# 3. 创建 systemd service 文件
+"#,
+        temp_dir, temp_sidecar, temp_app
+    );
+
+    let script_tail = if init == "openrc" {
+        format!(
+            r#"# 3. 创建 OpenRC init 脚本
+log_info "[3/5] 创建 OpenRC init 脚本..."
+if cat > /etc/init.d/wire-vault-daemon << 'SERVICEEOF'
+{}SERVICEEOF
+then
+    log_info "  ✓ OpenRC init 脚本已创建"
+    chmod 755 /etc/init.d/wire-vault-daemon
+    log_info "  文件权限: $(stat -c '%a' /etc/init.d/wire-vault-daemon)"
+else
+    log_error "  ✗ 创建 OpenRC init 脚本失败"
+    exit 1
+fi
+
+# 4. 添加到默认运行级别
+log_info "[4/5] 启用守护进程..."
+if rc-update add wire-vault-daemon default; then
+    log_info "  ✓ 守护进程已启用"
+else
+    log_error "  ✗ 启用守护进程失败"
+    exit 1
+fi
+
+# 5. 启动守护进程
+log_info "[5/5] 启动守护进程..."
+if rc-service wire-vault-daemon start; then
+    log_info "  ✓ 守护进程已启动"
+else
+    log_error "  ✗ 启动守护进程失败"
+    exit 1
+fi
+
+# 验证
+log_info "验证守护进程状态..."
+sleep 2
+
+if rc-service wire-vault-daemon status; then
+    log_info "✓ 守护进程安装并启动成功!"
+    exit 0
+else
+    log_error "✗ 守护进程启动失败"
+    log_error "最近 30 条日志:"
+    tail -n 30 /var/log/wire-vault-daemon.log || true
+    exit 1
+fi
+"#,
+            OPENRC_SCRIPT_CONTENT
+        )
+    } else {
+        format!(
+            r#"# 3. 创建 systemd service 文件
 log_info "[3/5] 创建 systemd service..."
 if cat > /etc/systemd/system/wire-vault-daemon.service << 'SERVICEEOF'
 {}SERVICEEOF
@@ -355,8 +502,11 @@ else
     exit 1
 fi
 "#,
-        temp_dir, temp_sidecar, temp_app, SYSTEMD_SERVICE_CONTENT
-    );
+            systemd_service_content(INSTALLED_BINARY_PATH)
+        )
+    };
+
+    let script_content = format!("{}{}", script_head, script_tail);
 
     log::info!("安装脚本已生成，长度: {} 字节", script_content.len());
 
@@ -430,10 +580,8 @@ fi
     Ok(stdout.to_string())
 }
 
-/// 卸载守护进程
-#[tauri::command]
-pub async fn uninstall_daemon() -> Result<String, String> {
-    let script_content = r#"#!/bin/bash
+// systemd 卸载脚本
+const SYSTEMD_UNINSTALL_SCRIPT: &str = r#"#!/bin/bash
 set -e
 
 echo "=== WireVault 守护进程卸载 ==="
@@ -465,6 +613,45 @@ rm -f /var/run/wire-vault-daemon.sock
 echo "✓ 守护进程已卸载"
 "#;
 
+// OpenRC 卸载脚本
+const OPENRC_UNINSTALL_SCRIPT: &str = r#"#!/bin/sh
+set -e
+
+echo "=== WireVault 守护进程卸载 ==="
+
+# 1. 停止服务并移出运行级别
+echo "[1/4] 停止服务..."
+rc-service wire-vault-daemon stop || true
+rc-update del wire-vault-daemon default || true
+
+# 2. 删除 OpenRC init 脚本
+echo "[2/4] 删除 OpenRC init 脚本..."
+rm -f /etc/init.d/wire-vault-daemon
+
+# 3. 删除可执行文件
+echo "[3/4] 删除可执行文件..."
+rm -f /usr/local/bin/wire-vault
+
+# 4. 清理 /opt/wire-vault 目录
+echo "[4/4] 清理配置目录..."
+rm -rf /opt/wire-vault
+
+# 清理 socket 和日志文件
+rm -f /var/run/wire-vault-daemon.sock
+rm -f /var/log/wire-vault-daemon.log
+
+echo "✓ 守护进程已卸载"
+"#;
+
+/// 卸载守护进程
+#[tauri::command]
+pub async fn uninstall_daemon() -> Result<String, String> {
+    let script_content = if init_system() == "openrc" {
+        OPENRC_UNINSTALL_SCRIPT
+    } else {
+        SYSTEMD_UNINSTALL_SCRIPT
+    };
+
     // 写入临时脚本
     let script_path = "/tmp/wire-vault-uninstall-daemon.sh";
     fs::write(script_path, script_content).map_err(|e| format!("创建卸载脚本失败: {}", e))?;
@@ -496,15 +683,186 @@ echo "✓ 守护进程已卸载"
     Ok(stdout.to_string())
 }
 
+/// 应用自带与守护进程使用的 wireguard-go 版本对比
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct WireguardGoVersions {
+    pub bundled: Option<String>,
+    pub daemon_installed: Option<String>,
+    #[serde(rename = "match")]
+    pub matches: bool,
+}
+
+/// 执行 wireguard-go --version,返回第一行输出
+pub(crate) fn wireguard_go_version(path: &Path) -> Option<String> {
+    let output = Command::new(path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+/// 对比应用自带的 wireguard-go 与守护进程实际使用的版本
+/// 版本不一致时应重新安装守护进程
+#[tauri::command]
+pub async fn compare_wireguard_go_versions(
+    app: tauri::AppHandle,
+) -> Result<WireguardGoVersions, String> {
+    let bundled_path = app
+        .path()
+        .resolve("wireguard-go", tauri::path::BaseDirectory::Resource)
+        .ok()
+        .filter(|p| p.exists())
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(|p| p.join("wireguard-go")))
+                .filter(|p| p.exists())
+        });
+
+    let bundled = bundled_path.as_deref().and_then(wireguard_go_version);
+    let daemon_installed = crate::daemon::find_wireguard_go()
+        .ok()
+        .and_then(|path| wireguard_go_version(Path::new(&path)));
+
+    let matches = matches!((&bundled, &daemon_installed), (Some(a), Some(b)) if a == b);
+
+    log::info!(
+        "wireguard-go 版本: 应用自带={:?}, 守护进程={:?}",
+        bundled,
+        daemon_installed
+    );
+
+    Ok(WireguardGoVersions {
+        bundled,
+        daemon_installed,
+        matches,
+    })
+}
+
+/// 获取当前应用可执行文件路径 (AppImage 下使用 AppImage 文件本身而非临时挂载点)
+fn resolve_current_binary() -> Result<String, String> {
+    if let Ok(appimage) = std::env::var("APPIMAGE") {
+        return Ok(appimage);
+    }
+
+    let current_exe =
+        std::env::current_exe().map_err(|e| format!("获取当前执行文件路径失败: {}", e))?;
+    current_exe
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "无效的可执行文件路径".to_string())
+}
+
+/// 创建仅当前用户可读写的临时脚本文件 (mktemp 随机文件名 + 0600 权限,避免符号链接和替换竞争)
+fn create_private_script(prefix: &str, content: &str) -> Result<String, String> {
+    let output = Command::new("mktemp")
+        .arg("-t")
+        .arg(format!("{}.XXXXXXXX", prefix))
+        .output()
+        .map_err(|e| format!("创建临时脚本失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "创建临时脚本失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let script_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    fs::write(&script_path, content).map_err(|e| {
+        let _ = fs::remove_file(&script_path);
+        format!("写入临时脚本失败: {}", e)
+    })?;
+    Ok(script_path)
+}
+
+/// 修复 systemd service 文件: 重新复制当前可执行文件到安装目录并重写服务
+/// 用于应用更新后路径变化导致守护进程无法启动的情况,无需完整卸载重装
+/// service 始终指向 root 拥有的安装路径,不直接执行用户可写的文件
+#[tauri::command]
+pub async fn repair_daemon_unit() -> Result<String, String> {
+    if init_system() != "systemd" {
+        return Err("仅支持修复 systemd 管理的守护进程".to_string());
+    }
+
+    let source_path = resolve_current_binary()?;
+    log::info!("修复守护进程 service 文件，源文件: {}", source_path);
+
+    let script_content = format!(
+        r#"#!/bin/sh
+set -e
+
+install -m 755 '{}' {}
+echo "✓ 应用已复制到 {}"
+
+cat > /etc/systemd/system/wire-vault-daemon.service << 'SERVICEEOF'
+{}SERVICEEOF
+chmod 644 /etc/systemd/system/wire-vault-daemon.service
+echo "✓ service 文件已更新"
+
+systemctl daemon-reload
+echo "✓ systemd 已重新加载"
+
+systemctl restart wire-vault-daemon
+echo "✓ 守护进程已重启"
+"#,
+        source_path.replace('\'', "'\\''"),
+        INSTALLED_BINARY_PATH,
+        INSTALLED_BINARY_PATH,
+        systemd_service_content(INSTALLED_BINARY_PATH)
+    );
+
+    let script_path = create_private_script("wire-vault-repair-daemon", &script_content)?;
+
+    log::info!("请求管理员权限以修复守护进程...");
+
+    let script_arg = script_path.clone();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::new("pkexec").arg("sh").arg(script_arg).output()
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(|e| format!("执行修复脚本失败: {}", e));
+
+    // 清理临时脚本
+    let _ = fs::remove_file(&script_path);
+    let output = output?;
+
+    if !output.status.success() {
+        let error_msg = String::from_utf8_lossy(&output.stderr);
+        if error_msg.contains("dismissed") || error_msg.contains("canceled") {
+            return Err("用户取消了授权".to_string());
+        }
+        return Err(format!("修复失败: {}", error_msg));
+    }
+
+    log::info!("守护进程 service 文件修复完成");
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
 /// 辅助函数: 执行 pkexec 命令并确保环境变量正确
-fn run_pkexec_systemctl(
+/// 根据 init 系统选择 systemctl 或 rc-service/rc-update
+fn run_pkexec_service(
     action: &str,
     service: &str,
 ) -> Result<std::process::Output, std::io::Error> {
-    log::info!("执行 pkexec systemctl {} {}", action, service);
+    let args: Vec<&str> = if init_system() == "openrc" {
+        match action {
+            "enable" => vec!["rc-update", "add", service, "default"],
+            "disable" => vec!["rc-update", "del", service, "default"],
+            _ => vec!["rc-service", service, action],
+        }
+    } else {
+        vec!["systemctl", action, service]
+    };
+
+    log::info!("执行 pkexec {}", args.join(" "));
 
     let mut cmd = Command::new("pkexec");
-    cmd.args(["systemctl", action, service]);
+    cmd.args(&args);
 
     // 确保环境变量传递 (用于图形化认证对话框)
     if let Ok(display) = std::env::var("DISPLAY") {
@@ -534,7 +892,7 @@ pub async fn start_daemon_service() -> Result<(), String> {
     log::info!("start_daemon_service 被调用");
 
     // 使用 spawn_blocking 避免阻塞异步运行时
-    let output = tokio::task::spawn_blocking(|| run_pkexec_systemctl("start", "wire-vault-daemon"))
+    let output = tokio::task::spawn_blocking(|| run_pkexec_service("start", "wire-vault-daemon"))
         .await
         .map_err(|e| format!("任务执行失败: {}", e))?
         .map_err(|e| format!("启动服务失败: {}", e))?;
@@ -561,7 +919,7 @@ pub async fn stop_daemon_service() -> Result<(), String> {
     log::info!("stop_daemon_service 被调用");
 
     // 使用 spawn_blocking 避免阻塞异步运行时
-    let output = tokio::task::spawn_blocking(|| run_pkexec_systemctl("stop", "wire-vault-daemon"))
+    let output = tokio::task::spawn_blocking(|| run_pkexec_service("stop", "wire-vault-daemon"))
         .await
         .map_err(|e| format!("任务执行失败: {}", e))?
         .map_err(|e| format!("停止服务失败: {}", e))?;
@@ -588,7 +946,7 @@ pub async fn restart_daemon_service() -> Result<(), String> {
     log::info!("restart_daemon_service 被调用");
 
     // 使用 spawn_blocking 避免阻塞异步运行时
-    let output = tokio::task::spawn_blocking(|| run_pkexec_systemctl("restart", "wire-vault-daemon"))
+    let output = tokio::task::spawn_blocking(|| run_pkexec_service("restart", "wire-vault-daemon"))
         .await
         .map_err(|e| format!("任务执行失败: {}", e))?
         .map_err(|e| format!("重启服务失败: {}", e))?;
@@ -615,7 +973,7 @@ pub async fn enable_daemon_service() -> Result<(), String> {
     log::info!("enable_daemon_service 被调用");
 
     // 使用 spawn_blocking 避免阻塞异步运行时
-    let output = tokio::task::spawn_blocking(|| run_pkexec_systemctl("enable", "wire-vault-daemon"))
+    let output = tokio::task::spawn_blocking(|| run_pkexec_service("enable", "wire-vault-daemon"))
         .await
         .map_err(|e| format!("任务执行失败: {}", e))?
         .map_err(|e| format!("启用服务失败: {}", e))?;
@@ -642,7 +1000,7 @@ pub async fn disable_daemon_service() -> Result<(), String> {
     log::info!("disable_daemon_service 被调用");
 
     // 使用 spawn_blocking 避免阻塞异步运行时
-    let output = tokio::task::spawn_blocking(|| run_pkexec_systemctl("disable", "wire-vault-daemon"))
+    let output = tokio::task::spawn_blocking(|| run_pkexec_service("disable", "wire-vault-daemon"))
         .await
         .map_err(|e| format!("任务执行失败: {}", e))?
         .map_err(|e| format!("禁用服务失败: {}", e))?;
@@ -668,6 +1026,15 @@ pub async fn disable_daemon_service() -> Result<(), String> {
 pub async fn get_daemon_logs(lines: Option<usize>) -> Result<String, String> {
     let line_count = lines.unwrap_or(50);
 
+    // OpenRC 下守护进程输出写入日志文件
+    if init_system() == "openrc" {
+        let content = fs::read_to_string("/var/log/wire-vault-daemon.log")
+            .map_err(|e| format!("获取日志失败: {}", e))?;
+        let all_lines: Vec<&str> = content.lines().collect();
+        let start = all_lines.len().saturating_sub(line_count);
+        return Ok(all_lines[start..].join("\n"));
+    }
+
     let output = Command::new("journalctl")
         .args([
             "-u",
@@ -688,3 +1055,113 @@ pub async fn get_daemon_logs(lines: Option<usize>) -> Result<String, String> {
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
+
+/// 递归收集数据目录下需要修复权限的文件
+/// 返回 (属主不是当前用户的路径, 属于当前用户但不可读写的路径)
+fn collect_permission_issues(
+    dir: &Path,
+    uid: u32,
+    foreign: &mut Vec<std::path::PathBuf>,
+    unwritable: &mut Vec<std::path::PathBuf>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        // 目录本身不可读时无法继续遍历,交给上层按属主修复
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接,避免修改数据目录之外的文件
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+
+        let required = if metadata.is_dir() { 0o700 } else { 0o600 };
+        if metadata.uid() != uid {
+            foreign.push(path.clone());
+        } else if !metadata.file_type().is_symlink()
+            && metadata.permissions().mode() & required != required
+        {
+            unwritable.push(path.clone());
+        }
+
+        if metadata.is_dir() {
+            collect_permission_issues(&path, uid, foreign, unwritable);
+        }
+    }
+}
+
+/// 检查并修复数据目录的文件权限
+/// 以 root 运行过 GUI 后,数据目录中可能残留 root 所有的文件,普通用户模式下无法读写。
+/// 属主不是当前用户的文件通过 pkexec chown 改回当前用户,返回已修复的路径列表
+#[tauri::command]
+pub async fn check_and_fix_data_permissions(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let uid = nix::unistd::Uid::current();
+    if uid.is_root() {
+        log::info!("当前以 root 运行,无需修复数据目录权限");
+        return Ok(Vec::new());
+    }
+    let gid = nix::unistd::Gid::current();
+
+    let mut foreign = Vec::new();
+    let mut unwritable = Vec::new();
+    if let Ok(metadata) = fs::metadata(&app_data_dir) {
+        if metadata.uid() != uid.as_raw() {
+            foreign.push(app_data_dir.clone());
+        }
+    }
+    collect_permission_issues(&app_data_dir, uid.as_raw(), &mut foreign, &mut unwritable);
+
+    if !foreign.is_empty() {
+        log::info!(
+            "数据目录中有 {} 个文件不属于当前用户,请求管理员权限修复...",
+            foreign.len()
+        );
+
+        // -R: 当前用户无法读取的目录内容无法遍历,一并修复属主
+        let owner = format!("{}:{}", uid.as_raw(), gid.as_raw());
+        let paths = foreign.clone();
+        let output = tokio::task::spawn_blocking(move || {
+            Command::new("pkexec")
+                .args(["chown", "-R", "-h", &owner, "--"])
+                .args(&paths)
+                .output()
+        })
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+        .map_err(|e| format!("执行 chown 失败: {}", e))?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            if error_msg.contains("dismissed") || error_msg.contains("canceled") {
+                return Err("用户取消了授权".to_string());
+            }
+            return Err(format!("修复文件属主失败: {}", error_msg));
+        }
+    }
+
+    // 属主修复后,补上当前用户的读写权限 (目录还需要执行权限),不需要 root
+    let mut repaired = Vec::new();
+    for path in foreign.iter().chain(unwritable.iter()) {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            continue;
+        };
+        if !metadata.file_type().is_symlink() {
+            let extra = if metadata.is_dir() { 0o700 } else { 0o600 };
+            let mode = metadata.permissions().mode() | extra;
+            if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(mode)) {
+                log::warn!("设置 {} 的权限失败: {}", path.display(), e);
+                continue;
+            }
+        }
+        repaired.push(path.display().to_string());
+    }
+
+    log::info!("数据目录权限修复完成,共 {} 个文件", repaired.len());
+    Ok(repaired)
+}
@@ -2,12 +2,15 @@
 // 定义 GUI 和守护进程之间的通信协议
 
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::os::unix::net::UnixStream;
 
 // Unix Socket 路径
 pub const DAEMON_SOCKET_PATH: &str = "/var/run/wire-vault-daemon.sock";
 
+// IPC 协议版本,请求或响应格式出现不兼容的修改时递增
+pub const DAEMON_PROTOCOL_VERSION: u32 = 1;
+
 // IPC 请求
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct IpcRequest {
@@ -35,6 +38,22 @@ pub struct TunnelConfigIpc {
     pub peers: Vec<PeerConfigIpc>,
     pub wireguard_go_path: String,  // wireguard-go 可执行文件的完整路径
     pub socket_dir: Option<String>, // WireGuard socket 目录 (默认 /var/run/wireguard)
+    // 是否替换现有 Peer (false 时追加/更新,保留其他方式添加的 Peer)
+    #[serde(default = "default_replace_peers")]
+    pub replace_peers: bool,
+    // WireGuard 出站数据包的 fwmark
+    #[serde(default)]
+    pub fwmark: Option<u32>,
+    // 路由写入的路由表 (None 为 main 表)
+    #[serde(default)]
+    pub route_table: Option<u32>,
+    // 是否启用 kill switch (由守护进程维护防火墙规则)
+    #[serde(default)]
+    pub kill_switch: bool,
+}
+
+fn default_replace_peers() -> bool {
+    true
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -66,15 +85,88 @@ pub struct PeerStatsIpc {
     pub last_handshake: Option<i64>,
 }
 
+// 心跳响应 (旧版守护进程只返回 status,协议版本视为 0)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PingResponseIpc {
+    pub status: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub protocol_version: u32,
+}
+
+// 守护进程中单个隧道的状态 (不含私钥)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DaemonTunnelStateIpc {
+    pub tunnel_id: String,
+    pub interface_name: String,
+    pub socket_path: String,
+    pub pid: u32,
+    pub peer_count: usize,
+    pub listen_port: Option<u16>,
+    pub wireguard_go_path: String,
+}
+
+// 守护进程完整状态,用于问题反馈
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DaemonStateIpc {
+    pub version: String,
+    pub protocol_version: u32,
+    pub wireguard_go_path: Option<String>,
+    pub wireguard_go_version: Option<String>,
+    pub tunnels: Vec<DaemonTunnelStateIpc>,
+}
+
+// 守护进程 Socket 状态
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DaemonSocketState {
+    Missing, // socket 文件不存在: 守护进程未运行
+    Stale,   // socket 文件存在但拒绝连接: 守护进程异常退出后残留
+    Healthy, // 可以正常连接
+}
+
 // IPC 客户端 (GUI 使用)
 pub struct IpcClient;
 
 impl IpcClient {
+    /// 连接守护进程,区分 socket 不存在、残留 socket 和其他连接错误
+    fn connect() -> Result<UnixStream, String> {
+        if !std::path::Path::new(DAEMON_SOCKET_PATH).exists() {
+            return Err(
+                "守护进程未运行: socket 文件不存在。请启动守护进程 (sudo systemctl start wire-vault-daemon)"
+                    .to_string(),
+            );
+        }
+
+        UnixStream::connect(DAEMON_SOCKET_PATH).map_err(|e| match e.kind() {
+            std::io::ErrorKind::ConnectionRefused => format!(
+                "守护进程 socket 已失效 (可能异常退出后残留): {}。请删除 {} 后重启守护进程 (sudo systemctl restart wire-vault-daemon)",
+                e, DAEMON_SOCKET_PATH
+            ),
+            _ => format!(
+                "无法连接到守护进程: {}。请确保守护进程正在运行 (sudo systemctl status wire-vault-daemon)",
+                e
+            ),
+        })
+    }
+
+    /// 检查守护进程 socket 状态
+    pub fn socket_state() -> DaemonSocketState {
+        if !std::path::Path::new(DAEMON_SOCKET_PATH).exists() {
+            return DaemonSocketState::Missing;
+        }
+
+        match UnixStream::connect(DAEMON_SOCKET_PATH) {
+            Ok(_) => DaemonSocketState::Healthy,
+            Err(_) => DaemonSocketState::Stale,
+        }
+    }
+
     /// 发送请求到守护进程
     pub fn send_request(method: &str, params: serde_json::Value) -> Result<IpcResponse, String> {
         // 连接到守护进程
-        let mut stream = UnixStream::connect(DAEMON_SOCKET_PATH)
-            .map_err(|e| format!("无法连接到守护进程: {}。请确保守护进程正在运行 (sudo systemctl status wire-vault-daemon)", e))?;
+        let mut stream = Self::connect()?;
 
         // 设置读写超时（30秒，足够启动隧道）
         stream
@@ -178,6 +270,151 @@ impl IpcClient {
         Ok(stats)
     }
 
+    /// 将配置下发到运行中的隧道
+    pub fn apply_config(config: TunnelConfigIpc) -> Result<(), String> {
+        let params = serde_json::to_value(&config).map_err(|e| format!("序列化配置失败: {}", e))?;
+        let response = Self::send_request("apply_config", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// 在线修改隧道监听端口
+    pub fn set_listen_port(tunnel_id: &str, port: u16) -> Result<(), String> {
+        let params = serde_json::json!({ "tunnel_id": tunnel_id, "listen_port": port });
+        let response = Self::send_request("set_listen_port", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// 从运行中的隧道移除 Peer
+    pub fn remove_peer(tunnel_id: &str, public_key: &str) -> Result<(), String> {
+        let params = serde_json::json!({ "tunnel_id": tunnel_id, "public_key": public_key });
+        let response = Self::send_request("remove_peer", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// 设置隧道开机自动启动,config 为 None 时取消
+    /// 守护进程保存一份配置副本,开机后无需打开 GUI 即可启动隧道
+    pub fn set_autostart(tunnel_id: &str, config: Option<&TunnelConfigIpc>) -> Result<(), String> {
+        let params = serde_json::json!({ "tunnel_id": tunnel_id, "config": config });
+        let response = Self::send_request("set_autostart", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// 读取隧道的原始 UAPI 响应 (私钥已隐藏)
+    pub fn dump_uapi_state(tunnel_id: &str) -> Result<String, String> {
+        let params = serde_json::json!({ "tunnel_id": tunnel_id });
+        let response = Self::send_request("dump_uapi_state", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        let result = response.result.ok_or("响应缺少结果")?;
+        serde_json::from_value(result).map_err(|e| format!("解析 UAPI 响应失败: {}", e))
+    }
+
+    /// 读取隧道接口当前生效的 MTU
+    pub fn get_interface_mtu(tunnel_id: &str) -> Result<u32, String> {
+        let params = serde_json::json!({ "tunnel_id": tunnel_id });
+        let response = Self::send_request("get_interface_mtu", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        let result = response.result.ok_or("响应缺少结果")?;
+        serde_json::from_value(result).map_err(|e| format!("解析 MTU 失败: {}", e))
+    }
+
+    /// 导出守护进程中的隧道状态 (不含私钥)
+    pub fn dump_state() -> Result<DaemonStateIpc, String> {
+        let params = serde_json::json!({});
+        let response = Self::send_request("dump_state", params)?;
+
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        let result = response.result.ok_or("响应缺少结果")?;
+        serde_json::from_value(result).map_err(|e| format!("解析守护进程状态失败: {}", e))
+    }
+
+    /// 流式获取隧道的 per-peer 统计信息
+    /// 每收到一帧调用一次 on_stats,返回 false 时断开连接;隧道停止时返回守护进程的错误
+    pub fn stream_peer_stats(
+        tunnel_id: &str,
+        interval_ms: u64,
+        mut on_stats: impl FnMut(Vec<PeerStatsIpc>) -> bool,
+    ) -> Result<(), String> {
+        let mut stream = Self::connect()?;
+
+        // 守护进程的推送间隔最长 60 秒,超过 90 秒没有数据视为连接异常
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(90)))
+            .map_err(|e| format!("设置读取超时失败: {}", e))?;
+
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let request = IpcRequest {
+            id: request_id.clone(),
+            method: "stream_peer_stats".to_string(),
+            params: serde_json::json!({ "tunnel_id": tunnel_id, "interval_ms": interval_ms }),
+        };
+        let request_json =
+            serde_json::to_string(&request).map_err(|e| format!("序列化请求失败: {}", e))?;
+        stream
+            .write_all(format!("{}\n", request_json).as_bytes())
+            .map_err(|e| format!("发送请求失败: {}", e))?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .map_err(|e| format!("读取响应失败（可能超时）: {}", e))?;
+            if read == 0 {
+                // 守护进程关闭了连接
+                return Ok(());
+            }
+
+            let response: IpcResponse =
+                serde_json::from_str(&line).map_err(|e| format!("解析响应失败: {}", e))?;
+            if response.id != request_id {
+                return Err("响应 ID 不匹配".to_string());
+            }
+            if let Some(error) = response.error {
+                return Err(error);
+            }
+
+            let result = response.result.ok_or("响应缺少结果")?;
+            let stats: Vec<PeerStatsIpc> =
+                serde_json::from_value(result).map_err(|e| format!("解析 peer 统计失败: {}", e))?;
+
+            if !on_stats(stats) {
+                return Ok(());
+            }
+        }
+    }
+
     /// 列出所有运行中的隧道
     pub fn list_tunnels() -> Result<Vec<String>, String> {
         let params = serde_json::json!({});
@@ -194,8 +431,8 @@ impl IpcClient {
         Ok(tunnel_ids)
     }
 
-    /// 心跳检测
-    pub fn ping() -> Result<(), String> {
+    /// 心跳检测,返回守护进程的版本信息
+    pub fn ping() -> Result<PingResponseIpc, String> {
         let params = serde_json::json!({});
         let response = Self::send_request("ping", params)?;
 
@@ -203,12 +440,32 @@ impl IpcClient {
             return Err(error);
         }
 
-        Ok(())
+        let result = response.result.ok_or("响应缺少结果")?;
+        serde_json::from_value(result).map_err(|e| format!("解析心跳响应失败: {}", e))
     }
 
     /// 检查守护进程是否正在运行
     pub fn is_daemon_running() -> bool {
-        Self::ping().is_ok()
+        Self::socket_state() == DaemonSocketState::Healthy && Self::ping().is_ok()
+    }
+
+    /// 检查守护进程与应用的 IPC 协议版本是否一致
+    /// 只升级了应用或守护进程时,不兼容的请求格式会导致难以理解的解析错误
+    pub fn check_daemon_compatibility() -> Result<(), String> {
+        let pong = Self::ping()?;
+        let daemon_version = pong.version.as_deref().unwrap_or("未知");
+
+        match pong.protocol_version.cmp(&DAEMON_PROTOCOL_VERSION) {
+            std::cmp::Ordering::Equal => Ok(()),
+            std::cmp::Ordering::Less => Err(format!(
+                "守护进程版本过旧，请重新安装 (守护进程 {}，协议版本 {}，应用需要协议版本 {})",
+                daemon_version, pong.protocol_version, DAEMON_PROTOCOL_VERSION
+            )),
+            std::cmp::Ordering::Greater => Err(format!(
+                "应用版本过旧，请更新应用 (守护进程 {}，协议版本 {}，应用支持协议版本 {})",
+                daemon_version, pong.protocol_version, DAEMON_PROTOCOL_VERSION
+            )),
+        }
     }
 }
 
@@ -0,0 +1,75 @@
+// keychain.rs - 系统钥匙串私钥存储
+// macOS Keychain / Windows Credential Manager / Linux Secret Service
+
+use crate::tunnel::TunnelConfig;
+
+// 钥匙串服务名
+const KEYCHAIN_SERVICE: &str = "wire-vault";
+
+// JSON 中保存的私钥引用前缀,实际私钥存放在钥匙串中
+pub const KEYCHAIN_REF_PREFIX: &str = "keychain:";
+
+// 判断私钥字段是否为钥匙串引用
+pub fn is_keychain_ref(private_key: &str) -> bool {
+    private_key.starts_with(KEYCHAIN_REF_PREFIX)
+}
+
+fn keychain_entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|e| format!("打开钥匙串失败: {}", e))
+}
+
+// 将隧道私钥写入钥匙串,成功后 JSON 中只保留引用
+// 钥匙串不可用时回退为明文存储,并关闭该选项
+pub fn store_private_key(config: &mut TunnelConfig) {
+    if !config.store_key_in_keychain {
+        return;
+    }
+
+    if config.private_key.is_empty() || is_keychain_ref(&config.private_key) {
+        return;
+    }
+
+    let result = keychain_entry(&config.id).and_then(|entry| {
+        entry
+            .set_password(config.private_key.trim())
+            .map_err(|e| format!("写入钥匙串失败: {}", e))
+    });
+
+    match result {
+        Ok(_) => {
+            log::info!("私钥已保存到系统钥匙串: {}", config.id);
+            config.private_key = format!("{}{}", KEYCHAIN_REF_PREFIX, config.id);
+        }
+        Err(e) => {
+            log::warn!("系统钥匙串不可用,回退为明文存储: {}", e);
+            config.store_key_in_keychain = false;
+        }
+    }
+}
+
+// 将私钥引用解析为实际私钥
+pub fn resolve_private_key(config: &mut TunnelConfig) -> Result<(), String> {
+    let account = match config.private_key.strip_prefix(KEYCHAIN_REF_PREFIX) {
+        Some(account) => account.to_string(),
+        None => return Ok(()),
+    };
+
+    let private_key = keychain_entry(&account)?
+        .get_password()
+        .map_err(|e| format!("从钥匙串读取私钥失败: {}", e))?;
+
+    config.private_key = private_key;
+    Ok(())
+}
+
+// 删除隧道在钥匙串中的私钥
+pub fn delete_private_key(tunnel_id: &str) {
+    match keychain_entry(tunnel_id).and_then(|entry| {
+        entry
+            .delete_credential()
+            .map_err(|e| format!("删除钥匙串条目失败: {}", e))
+    }) {
+        Ok(_) => log::info!("已删除钥匙串中的私钥: {}", tunnel_id),
+        Err(e) => log::warn!("{}", e),
+    }
+}
@@ -1,5 +1,10 @@
 mod commands;
+mod config_watcher;
+mod keychain;
+mod network_monitor;
+mod sftp;
 mod sync;
+mod sync_crypto;
 mod tunnel;
 mod webdav;
 
@@ -41,7 +46,8 @@ pub fn run() {
                     }),
                     Target::new(TargetKind::Webview),
                 ])
-                .level(log::LevelFilter::Info)
+                // 实际日志级别在启动后按偏好设置调整
+                .level(log::LevelFilter::Trace)
                 .build(),
         )
         .plugin(tauri_plugin_cli::init())
@@ -55,6 +61,34 @@ pub fn run() {
             log::info!("应用日志目录: {:?}", app.path().app_log_dir());
             log::info!("=====================================");
 
+            // 按偏好设置调整日志级别
+            if let Ok(preferences) = commands::persistence::load_preferences(app.handle().clone()) {
+                commands::persistence::apply_log_level(&preferences);
+            }
+
+            // 监听配置目录,WebDAV 同步或手动修改文件后通知前端刷新
+            if let Err(e) = config_watcher::start_config_watcher(app.handle()) {
+                log::warn!("启动配置目录监听失败: {}", e);
+            }
+
+            // 加载隧道配置中保存的接口名称,状态监控和停止隧道时使用
+            tunnel::load_interface_names(app.handle());
+
+            // 到期的 Peer 由应用负责移除
+            tunnel::start_peer_expiry_task(app.handle().clone());
+
+            // 运行中隧道的状态变化通过事件推送给前端
+            tunnel::start_tunnel_status_monitor(app.handle().clone());
+
+            // 网络切换后重连标记为自动重连的隧道
+            network_monitor::start_network_monitor(app.handle().clone());
+
+            // 由登录项启动时拉起标记为开机自动启动的隧道
+            #[cfg(target_os = "macos")]
+            if std::env::args().any(|arg| arg == "--autostart") {
+                tunnel::start_autostart_tunnels(app.handle().clone());
+            }
+
             let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
                 .title("")
                 .fullscreen(false)
@@ -94,45 +128,98 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             commands::misc_commands::get_platform,
+            commands::misc_commands::check_elevation_backend,
             commands::misc_commands::get_local_ip,
             commands::misc_commands::get_all_local_ips,
             commands::misc_commands::get_public_ip,
+            commands::misc_commands::suggest_server_endpoint,
+            commands::misc_commands::ping_endpoint,
+            commands::misc_commands::detect_optimal_mtu,
             commands::key_management::generate_keypair,
             commands::key_management::generate_preshared_key,
             commands::key_management::private_key_to_public,
+            commands::key_management::validate_wireguard_key,
+            commands::key_management::normalize_wireguard_key,
             commands::env_config::load_env_config,
             commands::persistence::get_next_peer_id,
+            commands::config_templates::list_export_formats,
             commands::config_templates::generate_wg_config,
+            commands::config_templates::preview_wg_config,
+            commands::config_templates::check_config_mtu,
             commands::config_templates::generate_ikuai_config,
             commands::config_templates::generate_surge_config,
             commands::config_templates::generate_mikrotik_config,
             commands::config_templates::generate_openwrt_config,
+            commands::config_templates::generate_pfsense_config,
+            commands::config_templates::generate_json_config,
+            commands::config_templates::import_wg_conf,
+            commands::config_templates::verify_config_roundtrip,
+            commands::config_templates::generate_mesh,
             commands::persistence::save_persistent_config,
             commands::persistence::load_persistent_config,
             commands::misc_commands::generate_qrcode,
+            commands::misc_commands::generate_qrcode_png,
+            commands::misc_commands::decode_qrcode_from_image,
             commands::misc_commands::save_config_to_path,
             commands::misc_commands::read_file_content,
             commands::misc_commands::read_file_as_base64,
             commands::history_service::save_to_history,
             commands::history_service::get_history_list,
+            commands::history_service::get_history_page,
+            commands::history_service::list_history_tags,
             commands::history_service::get_history_detail,
             commands::history_service::delete_history,
             commands::history_service::clear_all_history,
             commands::persistence::clear_cached_config,
+            commands::persistence::check_data_dir_writable,
+            commands::persistence::save_preferences,
+            commands::persistence::load_preferences,
+            commands::persistence::export_settings,
+            commands::persistence::import_settings,
             commands::history_service::export_all_configs_zip,
+            commands::history_service::import_history_zip,
+            commands::history_service::export_history_csv,
+            commands::history_service::generate_onboarding_sheet,
+            commands::history_service::verify_stored_public_keys,
+            commands::history_service::fix_stored_public_keys,
+            commands::history_service::compute_server_peer_allowed_ips,
+            commands::diagnostics::run_health_checks,
+            commands::diagnostics::analyze_dns_config,
+            commands::diagnostics::validate_config_file,
+            commands::diagnostics::test_peer_to_peer,
+            commands::diagnostics::get_effective_allowed_ips,
+            commands::diagnostics::check_listen_port,
             commands::server_service::save_server_config,
             commands::server_service::get_server_list,
+            commands::server_service::get_server_fingerprints,
+            commands::server_service::probe_server_port_locally,
+            commands::server_service::resolve_all_server_endpoints,
             commands::server_service::get_server_detail,
             commands::server_service::delete_server,
             commands::server_service::clear_all_servers,
             commands::server_service::get_next_peer_id_for_server,
             commands::server_service::update_server_peer_id,
+            commands::server_service::reconcile_peer_ids,
+            commands::server_service::allocate_peer_address,
             commands::history_service::get_history_list_by_server,
+            commands::history_service::search_history,
+            commands::history_service::set_history_tags,
             commands::server_service::migrate_old_config_to_server,
+            commands::server_service::import_env_as_server,
             commands::history_service::generate_next_client_ip,
             commands::webdav_commands::save_webdav_config,
             commands::webdav_commands::load_webdav_config,
+            commands::webdav_commands::parse_webdav_url,
+            commands::webdav_commands::validate_webdav_config,
             commands::webdav_commands::test_webdav_connection,
+            commands::webdav_commands::test_all_webdav_profiles,
+            commands::webdav_commands::list_webdav_profiles,
+            commands::webdav_commands::save_webdav_profile,
+            commands::webdav_commands::load_webdav_profile,
+            commands::webdav_commands::delete_webdav_profile,
+            commands::webdav_commands::list_remote_files,
+            commands::webdav_commands::inspect_deletion_records,
+            commands::webdav_commands::clear_deletion_records_manual,
             commands::webdav_commands::sync_to_webdav,
             commands::webdav_commands::sync_from_webdav,
             commands::webdav_commands::sync_bidirectional_webdav,
@@ -140,17 +227,55 @@ pub fn run() {
             commands::webdav_commands::load_last_sync_info,
             tunnel::start_tunnel,
             tunnel::stop_tunnel,
+            tunnel::reset_tunnel_stats,
+            tunnel::export_status_html,
             tunnel::get_tunnel_list,
             tunnel::get_tunnel_details,
             tunnel::save_tunnel_config,
+            tunnel::import_tunnel_from_conf,
+            tunnel::duplicate_tunnel_config,
+            tunnel::export_tunnel_to_conf,
             tunnel::delete_tunnel_config,
             tunnel::get_all_tunnel_configs,
+            tunnel::migrate_all_tunnels_to_peers_array,
+            tunnel::validate_endpoint,
+            tunnel::validate_tunnel_config,
+            tunnel::check_prerequisites,
+            tunnel::update_listen_port,
+            tunnel::set_tunnel_autostart,
+            tunnel::set_reconnect_on_network_change,
+            tunnel::list_config_profiles,
+            tunnel::apply_config_profile,
+            tunnel::apply_tunnel_config,
+            tunnel::mark_last_known_good,
+            tunnel::rollback_tunnel,
+            tunnel::dump_uapi_state,
+            tunnel::get_tunnel_peer_stats,
+            tunnel::set_peer_name,
+            tunnel::export_peer_traffic_csv,
+            tunnel::get_interface_mtu,
+            tunnel::get_tunnel_reliability,
+            #[cfg(any(target_os = "macos", target_os = "linux"))]
+            tunnel::get_wireguard_go_log,
+            tunnel::list_wireguard_processes,
+            tunnel::kill_wireguard_process,
+            tunnel::next_interface_name,
             tunnel::get_tunnel_config,
             tunnel::start_peer_stats_watcher,
             tunnel::stop_peer_stats_watcher,
             #[cfg(target_os = "linux")]
+            tunnel_linux::start_peer_stats_stream,
+            #[cfg(target_os = "linux")]
+            tunnel_linux::stop_peer_stats_stream,
+            #[cfg(target_os = "linux")]
+            tunnel_linux::sync_daemon_state,
+            #[cfg(target_os = "linux")]
+            tunnel_linux::get_daemon_state,
+            #[cfg(target_os = "linux")]
             daemon_install::check_daemon_status,
             #[cfg(target_os = "linux")]
+            daemon_install::detect_init_system,
+            #[cfg(target_os = "linux")]
             daemon_install::install_daemon,
             #[cfg(target_os = "linux")]
             daemon_install::uninstall_daemon,
@@ -161,10 +286,16 @@ pub fn run() {
             #[cfg(target_os = "linux")]
             daemon_install::restart_daemon_service,
             #[cfg(target_os = "linux")]
+            daemon_install::repair_daemon_unit,
+            #[cfg(target_os = "linux")]
+            daemon_install::compare_wireguard_go_versions,
+            #[cfg(target_os = "linux")]
             daemon_install::enable_daemon_service,
             #[cfg(target_os = "linux")]
             daemon_install::disable_daemon_service,
             #[cfg(target_os = "linux")]
+            daemon_install::check_and_fix_data_permissions,
+            #[cfg(target_os = "linux")]
             daemon_install::get_daemon_logs
         ])
         .build(tauri::generate_context!())
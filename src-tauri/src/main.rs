@@ -42,6 +42,8 @@ fn print_help() {
     println!("选项:");
     println!("  -h, --help            显示此帮助信息");
     println!("  -V, --version         显示版本号");
+    #[cfg(target_os = "macos")]
+    println!("  --autostart           启动并拉起开机自动启动的隧道 (供登录项使用)");
     println!();
     #[cfg(target_os = "linux")]
     {
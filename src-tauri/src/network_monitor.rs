@@ -0,0 +1,266 @@
+use std::process::Command;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::AppHandle;
+
+// 合并切换网络时短时间内的多次路由变化,等网络稳定后再处理
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+// 无法订阅系统通知时,定期检查默认路由
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+// 启动网络变化监听,默认网关变化后重连标记为 reconnect_on_network_change 的运行中隧道
+// Linux 订阅 netlink 路由通知,macOS 订阅 SCDynamicStore 网络状态通知,Windows 等待路由表变化通知
+pub fn start_network_monitor(app: AppHandle) {
+    let (tx, rx) = mpsc::channel::<()>();
+    spawn_change_listener(tx);
+
+    std::thread::spawn(move || {
+        let mut last = default_route_fingerprint();
+        log::info!("已启动网络变化监听,当前默认路由: {:?}", last);
+
+        // 所有发送端释放后 recv 返回错误,线程退出
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+            // 隧道自身的路由变化也会触发通知,只有默认网关变化才算切换网络
+            let current = default_route_fingerprint();
+            if current == last {
+                continue;
+            }
+            log::info!("默认路由变化: {:?} -> {:?}", last, current);
+
+            // 网络断开时不重连,等新网络可用后再处理
+            let reconnect = current.is_some();
+            last = current;
+            if reconnect {
+                tauri::async_runtime::block_on(
+                    crate::tunnel::reconnect_tunnels_after_network_change(&app),
+                );
+            }
+        }
+    });
+}
+
+// 定期发出检查信号,由默认路由对比判断网络是否变化
+fn poll_changes(tx: mpsc::Sender<()>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if tx.send(()).is_err() {
+            break;
+        }
+    });
+}
+
+// Linux: ip monitor 订阅 netlink 的链路和路由变化通知,每条消息输出一行
+#[cfg(target_os = "linux")]
+fn spawn_change_listener(tx: mpsc::Sender<()>) {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    std::thread::spawn(move || {
+        let child = Command::new("ip")
+            .args(["-o", "monitor", "link", "route"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                log::warn!("启动 ip monitor 失败,改为定期检查: {}", e);
+                poll_changes(tx);
+                return;
+            }
+        };
+
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if !line.trim().is_empty() && tx.send(()).is_err() {
+                    let _ = child.kill();
+                    return;
+                }
+            }
+        }
+
+        let _ = child.wait();
+        log::warn!("ip monitor 已退出,改为定期检查");
+        poll_changes(tx);
+    });
+}
+
+// macOS: 订阅 SCDynamicStore 的全局和各接口 IPv4/IPv6 状态变化
+// 切换网络时主服务、路由器地址或接口地址会变化,通知在监听线程的 RunLoop 中回调
+#[cfg(target_os = "macos")]
+fn spawn_change_listener(tx: mpsc::Sender<()>) {
+    use core_foundation::array::CFArray;
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use core_foundation::string::CFString;
+    use system_configuration::dynamic_store::{
+        SCDynamicStore, SCDynamicStoreBuilder, SCDynamicStoreCallBackContext,
+    };
+
+    fn on_change(_store: SCDynamicStore, _keys: CFArray<CFString>, tx: &mut mpsc::Sender<()>) {
+        let _ = tx.send(());
+    }
+
+    std::thread::spawn(move || {
+        let poll_tx = tx.clone();
+        let store = SCDynamicStoreBuilder::new("WireVault network monitor")
+            .callback_context(SCDynamicStoreCallBackContext {
+                callout: on_change,
+                info: tx,
+            })
+            .build();
+
+        let keys = CFArray::from_CFTypes(&[
+            CFString::from("State:/Network/Global/IPv4"),
+            CFString::from("State:/Network/Global/IPv6"),
+        ]);
+        let patterns = CFArray::from_CFTypes(&[
+            CFString::from("State:/Network/Interface/.*/IPv4"),
+            CFString::from("State:/Network/Interface/.*/IPv6"),
+        ]);
+        if !store.set_notification_keys(&keys, &patterns) {
+            log::warn!("订阅 SCDynamicStore 网络通知失败,改为定期检查");
+            poll_changes(poll_tx);
+            return;
+        }
+        drop(poll_tx);
+
+        let source = store.create_run_loop_source();
+        CFRunLoop::get_current().add_source(&source, unsafe { kCFRunLoopCommonModes });
+        CFRunLoop::run_current();
+
+        log::warn!("SCDynamicStore 监听已退出");
+    });
+}
+
+// Windows: NotifyRouteChange 不传句柄时同步阻塞,直到 IPv4 路由表发生变化
+#[cfg(target_os = "windows")]
+fn spawn_change_listener(tx: mpsc::Sender<()>) {
+    use windows_sys::Win32::NetworkManagement::IpHelper::NotifyRouteChange;
+
+    std::thread::spawn(move || loop {
+        // SAFETY: 两个参数都为空指针时为同步调用,不会保存任何指针
+        let result = unsafe { NotifyRouteChange(std::ptr::null_mut(), std::ptr::null()) };
+        if result != 0 {
+            log::warn!("等待路由变化通知失败 (错误码 {}),改为定期检查", result);
+            poll_changes(tx);
+            return;
+        }
+        if tx.send(()).is_err() {
+            return;
+        }
+    });
+}
+
+// 当前默认路由的网关和出口接口,无默认路由 (网络断开) 时返回 None
+// 忽略隧道接口上的默认路由,全局隧道启动后不会被当作网络切换
+#[cfg(target_os = "linux")]
+fn default_route_fingerprint() -> Option<String> {
+    let mut routes = Vec::new();
+    for family in ["-4", "-6"] {
+        let output = Command::new("ip")
+            .args([family, "route", "show", "default"])
+            .output()
+            .ok()?;
+        routes.extend(parse_ip_default_routes(&String::from_utf8_lossy(
+            &output.stdout,
+        )));
+    }
+    fingerprint(routes)
+}
+
+#[cfg(target_os = "macos")]
+fn default_route_fingerprint() -> Option<String> {
+    let output = Command::new("netstat").arg("-rn").output().ok()?;
+    fingerprint(parse_netstat_default_routes(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+#[cfg(target_os = "windows")]
+fn default_route_fingerprint() -> Option<String> {
+    use std::os::windows::process::CommandExt;
+
+    let output = Command::new("route")
+        .args(["print", "-4", "0.0.0.0"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    fingerprint(parse_route_print_default_routes(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn fingerprint(mut routes: Vec<String>) -> Option<String> {
+    if routes.is_empty() {
+        return None;
+    }
+    routes.sort();
+    routes.dedup();
+    Some(routes.join(", "))
+}
+
+// 解析 ip route show default 输出,例如:
+// default via 192.168.1.1 dev wlan0 proto dhcp src 192.168.1.5 metric 600
+#[cfg(target_os = "linux")]
+fn parse_ip_default_routes(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let value = |key: &str| {
+                fields
+                    .iter()
+                    .position(|f| *f == key)
+                    .and_then(|i| fields.get(i + 1))
+                    .copied()
+            };
+            let dev = value("dev")?;
+            // 隧道接口 (tun 前缀)
+            if dev.starts_with("tun") {
+                return None;
+            }
+            Some(format!("{} {}", value("via").unwrap_or("-"), dev))
+        })
+        .collect()
+}
+
+// 解析 netstat -rn 中的默认路由行,例如:
+// default            192.168.1.1        UGScg                 en0
+#[cfg(target_os = "macos")]
+fn parse_netstat_default_routes(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 4 || fields[0] != "default" {
+                return None;
+            }
+            // 隧道接口 (utun 前缀)
+            if fields[3].starts_with("utun") {
+                return None;
+            }
+            Some(format!("{} {}", fields[1], fields[3]))
+        })
+        .collect()
+}
+
+// 解析 route print 中的默认路由行,例如:
+// 0.0.0.0          0.0.0.0      192.168.1.1    192.168.1.10     25
+// 隧道的默认路由网关为 "On-link" (中文系统为 "在链路上"),不是 IP 地址
+#[cfg(target_os = "windows")]
+fn parse_route_print_default_routes(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 5 || fields[0] != "0.0.0.0" || fields[1] != "0.0.0.0" {
+                return None;
+            }
+            fields[2].parse::<std::net::IpAddr>().ok()?;
+            Some(format!("{} {}", fields[2], fields[3]))
+        })
+        .collect()
+}
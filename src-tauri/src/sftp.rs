@@ -0,0 +1,350 @@
+use crate::sync::SyncBackend;
+use crate::webdav::{RemoteFileInfo, WebDavConfig};
+use ssh2::{CheckResult, KnownHostFileKind, Session, Sftp};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+// 连接和读写超时
+const SFTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// SFTP 连接参数和复用的会话
+struct SftpInner {
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+    key_path: String,
+    base_path: String,
+    // Session 需要与 Sftp 一起保留,否则连接会被关闭
+    session: Mutex<Option<(Session, Sftp)>>,
+}
+
+/// SFTP 客户端，作为 WebDAV 之外的同步后端
+/// ssh2 为阻塞 API，所有操作都在阻塞线程中执行
+pub struct SftpClient {
+    inner: Arc<SftpInner>,
+}
+
+impl SftpClient {
+    /// 创建新的 SFTP 客户端 (server_url 格式: sftp://host[:port]/path)
+    pub fn new(config: WebDavConfig) -> Result<Self, String> {
+        let url = Url::parse(config.server_url.trim()).map_err(|e| format!("无效的 URL: {}", e))?;
+        if url.scheme() != "sftp" {
+            return Err("SFTP 地址应以 sftp:// 开头".to_string());
+        }
+
+        let host = url
+            .host_str()
+            .filter(|h| !h.is_empty())
+            .ok_or_else(|| "URL 缺少服务器地址".to_string())?
+            .to_string();
+
+        let username = if config.username.trim().is_empty() {
+            url.username().to_string()
+        } else {
+            config.username.trim().to_string()
+        };
+        if username.is_empty() {
+            return Err("SFTP 用户名不能为空".to_string());
+        }
+
+        // 路径为空时使用登录用户的主目录
+        let base_path = match url.path().trim_end_matches('/') {
+            "" => ".".to_string(),
+            path => path.to_string(),
+        };
+
+        Ok(Self {
+            inner: Arc::new(SftpInner {
+                host,
+                port: url.port().unwrap_or(22),
+                username,
+                password: config.password,
+                key_path: config.ssh_key_path.trim().to_string(),
+                base_path,
+                session: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// 在阻塞线程中执行 SFTP 操作，操作失败后丢弃连接，下次重新连接
+    async fn run<T, F>(&self, op: F) -> Result<T, String>
+    where
+        F: FnOnce(&Sftp, &SftpInner) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut guard = inner
+                .session
+                .lock()
+                .map_err(|_| "SFTP 连接状态异常".to_string())?;
+
+            if guard.is_none() {
+                *guard = Some(inner.connect()?);
+            }
+
+            let result = match guard.as_ref() {
+                Some((_, sftp)) => op(sftp, &inner),
+                None => Err("SFTP 未连接".to_string()),
+            };
+            if result.is_err() {
+                *guard = None;
+            }
+            result
+        })
+        .await
+        .map_err(|e| format!("SFTP 任务执行失败: {}", e))?
+    }
+}
+
+impl SftpInner {
+    /// 建立 SSH 连接并打开 SFTP 通道
+    fn connect(&self) -> Result<(Session, Sftp), String> {
+        use std::net::ToSocketAddrs;
+
+        let addr = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| format!("解析服务器地址失败: {}", e))?
+            .next()
+            .ok_or_else(|| format!("无法解析服务器地址: {}", self.host))?;
+        let tcp = TcpStream::connect_timeout(&addr, SFTP_TIMEOUT)
+            .map_err(|e| format!("连接失败: {}", e))?;
+
+        let mut session = Session::new().map_err(|e| format!("创建 SSH 会话失败: {}", e))?;
+        session.set_timeout(SFTP_TIMEOUT.as_millis() as u32);
+        session.set_tcp_stream(tcp);
+        session
+            .handshake()
+            .map_err(|e| format!("SSH 握手失败: {}", e))?;
+
+        self.verify_host_key(&session)?;
+
+        if self.key_path.is_empty() {
+            session
+                .userauth_password(&self.username, &self.password)
+                .map_err(|e| format!("SSH 认证失败: {}", e))?;
+        } else {
+            let passphrase = Some(self.password.as_str()).filter(|p| !p.is_empty());
+            session
+                .userauth_pubkey_file(&self.username, None, Path::new(&self.key_path), passphrase)
+                .map_err(|e| format!("SSH 私钥认证失败: {}", e))?;
+        }
+
+        if !session.authenticated() {
+            return Err("SSH 认证失败".to_string());
+        }
+
+        let sftp = session
+            .sftp()
+            .map_err(|e| format!("打开 SFTP 通道失败: {}", e))?;
+
+        log::info!("SFTP 已连接: {}@{}:{}", self.username, self.host, self.port);
+        Ok((session, sftp))
+    }
+
+    /// 校验服务器公钥是否与 ~/.ssh/known_hosts 中的记录一致，防止中间人攻击
+    fn verify_host_key(&self, session: &Session) -> Result<(), String> {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .ok_or_else(|| "无法确定用户主目录".to_string())?;
+        let known_hosts_path = PathBuf::from(home).join(".ssh").join("known_hosts");
+
+        let mut known_hosts = session
+            .known_hosts()
+            .map_err(|e| format!("读取 known_hosts 失败: {}", e))?;
+        if known_hosts_path.exists() {
+            known_hosts
+                .read_file(&known_hosts_path, KnownHostFileKind::OpenSSH)
+                .map_err(|e| format!("读取 known_hosts 失败: {}", e))?;
+        }
+
+        let (key, _) = session
+            .host_key()
+            .ok_or_else(|| "无法获取服务器公钥".to_string())?;
+
+        match known_hosts.check_port(&self.host, self.port, key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::NotFound => Err(format!(
+                "服务器 {} 不在 known_hosts 中，请先使用 ssh 命令连接一次并确认主机指纹",
+                self.host
+            )),
+            CheckResult::Mismatch => Err(format!(
+                "服务器 {} 的公钥与 known_hosts 记录不一致，可能存在中间人攻击",
+                self.host
+            )),
+            CheckResult::Failure => Err("校验服务器公钥失败".to_string()),
+        }
+    }
+
+    /// 转换为服务器上的完整路径
+    fn full_path(&self, remote_path: &str) -> PathBuf {
+        let remote_path = remote_path.trim_matches('/');
+        if remote_path.is_empty() {
+            PathBuf::from(&self.base_path)
+        } else {
+            PathBuf::from(format!("{}/{}", self.base_path, remote_path))
+        }
+    }
+
+    /// 逐级创建目录，已存在的目录跳过
+    fn create_dir_all(&self, sftp: &Sftp, remote_path: &str) -> Result<(), String> {
+        let mut current = String::new();
+        for part in remote_path.split('/').filter(|p| !p.is_empty()) {
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(part);
+
+            let path = self.full_path(&current);
+            if sftp.stat(&path).is_err() {
+                sftp.mkdir(&path, 0o755)
+                    .map_err(|e| format!("创建目录失败: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SyncBackend for SftpClient {
+    async fn test_connection(&self) -> Result<(), String> {
+        self.run(|sftp, inner| {
+            sftp.stat(&inner.full_path(""))
+                .map(|_| ())
+                .map_err(|e| format!("远程目录不可访问: {}", e))
+        })
+        .await
+    }
+
+    async fn upload_file(&self, local_path: &Path, remote_path: &str) -> Result<(), String> {
+        let content = tokio::fs::read(local_path)
+            .await
+            .map_err(|e| format!("读取本地文件失败: {}", e))?;
+
+        // 远程文件使用本地的修改时间,避免双向同步时被误判为远程更新
+        let modified = tokio::fs::metadata(local_path)
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+
+        let remote_path = remote_path.to_string();
+        self.run(move |sftp, inner| {
+            if let Some((parent, _)) = remote_path.trim_matches('/').rsplit_once('/') {
+                inner.create_dir_all(sftp, parent)?;
+            }
+
+            let path = inner.full_path(&remote_path);
+            let mut file = sftp
+                .create(&path)
+                .map_err(|e| format!("上传文件失败: {}", e))?;
+            file.write_all(&content)
+                .map_err(|e| format!("上传文件失败: {}", e))?;
+            drop(file);
+
+            if let Some(mtime) = modified {
+                let stat = ssh2::FileStat {
+                    size: None,
+                    uid: None,
+                    gid: None,
+                    perm: None,
+                    atime: Some(mtime),
+                    mtime: Some(mtime),
+                };
+                if let Err(e) = sftp.setstat(&path, stat) {
+                    log::warn!("设置远程文件修改时间失败: {}", e);
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn download_file(&self, remote_path: &str, local_path: &Path) -> Result<(), String> {
+        let remote_path = remote_path.to_string();
+        let content = self
+            .run(move |sftp, inner| {
+                let mut file = sftp
+                    .open(&inner.full_path(&remote_path))
+                    .map_err(|e| format!("下载文件失败: {}", e))?;
+                let mut content = Vec::new();
+                file.read_to_end(&mut content)
+                    .map_err(|e| format!("读取远程文件失败: {}", e))?;
+                Ok(content)
+            })
+            .await?;
+
+        // 确保本地目录存在
+        if let Some(parent) = local_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("创建本地目录失败: {}", e))?;
+        }
+
+        tokio::fs::write(local_path, content)
+            .await
+            .map_err(|e| format!("保存文件失败: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn delete_file(&self, remote_path: &str) -> Result<(), String> {
+        let remote_path = remote_path.to_string();
+        self.run(move |sftp, inner| {
+            sftp.unlink(&inner.full_path(&remote_path))
+                .map_err(|e| format!("删除文件失败: {}", e))
+        })
+        .await
+    }
+
+    async fn create_directory(&self, remote_path: &str) -> Result<(), String> {
+        let remote_path = remote_path.to_string();
+        self.run(move |sftp, inner| inner.create_dir_all(sftp, &remote_path))
+            .await
+    }
+
+    async fn list_directory_detailed(
+        &self,
+        remote_path: &str,
+    ) -> Result<Vec<RemoteFileInfo>, String> {
+        let remote_path = remote_path.to_string();
+        self.run(move |sftp, inner| {
+            let entries = sftp
+                .readdir(&inner.full_path(&remote_path))
+                .map_err(|e| format!("列出目录失败: {}", e))?;
+
+            Ok(entries
+                .into_iter()
+                .filter(|(_, stat)| stat.is_file())
+                .filter_map(|(path, stat)| {
+                    let name = path.file_name()?.to_str()?.to_string();
+                    Some(RemoteFileInfo {
+                        name,
+                        size: stat.size,
+                        modified: stat.mtime.map(|t| t as i64),
+                    })
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn get_last_modified(&self, remote_path: &str) -> Result<Option<i64>, String> {
+        let remote_path = remote_path.to_string();
+        self.run(move |sftp, inner| {
+            // 文件不存在时与 WebDAV 一致返回 None
+            Ok(sftp
+                .stat(&inner.full_path(&remote_path))
+                .ok()
+                .and_then(|stat| stat.mtime)
+                .map(|t| t as i64))
+        })
+        .await
+    }
+}
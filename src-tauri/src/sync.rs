@@ -1,21 +1,91 @@
-use crate::webdav::{WebDavClient, WebDavConfig};
+use crate::sftp::SftpClient;
+use crate::sync_crypto::SyncCipher;
+use crate::webdav::{ConflictStrategy, RemoteFileInfo, SyncFileState, WebDavClient, WebDavConfig};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 
+/// 同步后端 (WebDAV、SFTP),路径均为相对于远程根目录的路径
+#[async_trait::async_trait]
+pub trait SyncBackend: Send + Sync {
+    /// 测试连接
+    async fn test_connection(&self) -> Result<(), String>;
+
+    /// 上传文件
+    async fn upload_file(&self, local_path: &Path, remote_path: &str) -> Result<(), String>;
+
+    /// 下载文件
+    async fn download_file(&self, remote_path: &str, local_path: &Path) -> Result<(), String>;
+
+    /// 删除文件
+    async fn delete_file(&self, remote_path: &str) -> Result<(), String>;
+
+    /// 创建目录 (已存在时视为成功)
+    async fn create_directory(&self, remote_path: &str) -> Result<(), String>;
+
+    /// 列出目录内容，包含文件大小和修改时间
+    async fn list_directory_detailed(
+        &self,
+        remote_path: &str,
+    ) -> Result<Vec<RemoteFileInfo>, String>;
+
+    /// 列出目录中的文件名
+    async fn list_directory(&self, remote_path: &str) -> Result<Vec<String>, String> {
+        let files = self.list_directory_detailed(remote_path).await?;
+        Ok(files.into_iter().map(|f| f.name).collect())
+    }
+
+    /// 获取文件修改时间 (秒级时间戳)
+    async fn get_last_modified(&self, remote_path: &str) -> Result<Option<i64>, String>;
+}
+
+/// 根据配置中的 backend_type 创建同步后端
+pub fn create_sync_backend(config: WebDavConfig) -> Result<Box<dyn SyncBackend>, String> {
+    match config.backend_type.as_str() {
+        "" | "webdav" => Ok(Box::new(WebDavClient::new(config)?)),
+        "sftp" => Ok(Box::new(SftpClient::new(config)?)),
+        other => Err(format!("不支持的同步方式: {}", other)),
+    }
+}
+
 /// 删除追踪记录
-#[derive(Serialize, Deserialize, Default)]
-struct DeletedFiles {
-    servers: HashSet<String>,
-    history: HashSet<String>,
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DeletedFiles {
+    #[serde(default)]
+    pub servers: HashSet<String>,
+    #[serde(default)]
+    pub history: HashSet<String>,
+}
+
+// 冲突时保留的远程副本文件名标记 (<name>.remote-<timestamp>.json)
+const REMOTE_COPY_MARKER: &str = ".remote-";
+
+// 冲突副本保存目录 (conflicts/servers、conflicts/history),不与正常记录混在一起,也不参与同步
+const CONFLICTS_DIR: &str = "conflicts";
+
+/// 同步进度事件 (sync-progress)
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgress {
+    pub current: usize,
+    pub total: usize,
+    pub filename: String,
+    pub direction: String, // upload / download
 }
 
 /// 同步管理器
 pub struct SyncManager {
-    client: Arc<Mutex<Option<WebDavClient>>>,
+    client: Arc<Mutex<Option<Box<dyn SyncBackend>>>>,
+    // 设置了加密密码时,上传前加密、下载后解密
+    cipher: Arc<Mutex<Option<Arc<SyncCipher>>>>,
+    conflict_strategy: Arc<Mutex<ConflictStrategy>>,
+    // 上次双向同步后各文件的状态,同步过程中更新
+    file_states: Arc<Mutex<HashMap<String, SyncFileState>>>,
     app_data_dir: PathBuf,
+    // 设置后在双向同步时向前端发送进度事件
+    app: Option<AppHandle>,
 }
 
 impl SyncManager {
@@ -23,18 +93,56 @@ impl SyncManager {
     pub fn new(app_data_dir: PathBuf) -> Self {
         Self {
             client: Arc::new(Mutex::new(None)),
+            cipher: Arc::new(Mutex::new(None)),
+            conflict_strategy: Arc::new(Mutex::new(ConflictStrategy::default())),
+            file_states: Arc::new(Mutex::new(HashMap::new())),
             app_data_dir,
+            app: None,
+        }
+    }
+
+    /// 启用同步进度事件
+    pub fn with_progress(mut self, app: AppHandle) -> Self {
+        self.app = Some(app);
+        self
+    }
+
+    /// 设置上次双向同步的文件状态,用于区分单端修改和冲突
+    pub fn with_file_states(mut self, states: HashMap<String, SyncFileState>) -> Self {
+        self.file_states = Arc::new(Mutex::new(states));
+        self
+    }
+
+    fn emit_progress(&self, current: usize, total: usize, filename: &str, direction: &str) {
+        if let Some(app) = &self.app {
+            let progress = SyncProgress {
+                current,
+                total,
+                filename: filename.to_string(),
+                direction: direction.to_string(),
+            };
+            if let Err(e) = app.emit("sync-progress", progress) {
+                log::warn!("发送同步进度事件失败: {}", e);
+            }
         }
     }
 
-    /// 初始化 WebDAV 客户端
+    /// 初始化同步客户端 (WebDAV 或 SFTP)
     pub async fn init_client(&self, config: WebDavConfig) -> Result<(), String> {
         if !config.enabled {
             *self.client.lock().await = None;
+            *self.cipher.lock().await = None;
             return Ok(());
         }
 
-        let client = WebDavClient::new(config)?;
+        *self.cipher.lock().await = config
+            .encryption_password
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(|p| Arc::new(SyncCipher::new(p)));
+        *self.conflict_strategy.lock().await = config.conflict_strategy;
+
+        let client = create_sync_backend(config)?;
         *self.client.lock().await = Some(client);
         Ok(())
     }
@@ -44,7 +152,7 @@ impl SyncManager {
     pub async fn test_connection(&self) -> Result<(), String> {
         let client_guard = self.client.lock().await;
         let client = client_guard
-            .as_ref()
+            .as_deref()
             .ok_or_else(|| "WebDAV 未配置".to_string())?;
 
         client.test_connection().await
@@ -54,7 +162,7 @@ impl SyncManager {
     pub async fn sync_to_remote(&self) -> Result<SyncResult, String> {
         let client_guard = self.client.lock().await;
         let client = client_guard
-            .as_ref()
+            .as_deref()
             .ok_or_else(|| "WebDAV 未配置".to_string())?;
 
         let mut result = SyncResult::default();
@@ -67,7 +175,7 @@ impl SyncManager {
         let servers_dir = self.app_data_dir.join("servers");
         if servers_dir.exists() {
             result.servers_uploaded += self
-                .sync_directory_to_remote(&client, &servers_dir, "servers")
+                .sync_directory_to_remote(client, &servers_dir, "servers")
                 .await?;
         }
 
@@ -75,7 +183,7 @@ impl SyncManager {
         let history_dir = self.app_data_dir.join("history");
         if history_dir.exists() {
             result.history_uploaded += self
-                .sync_directory_to_remote(&client, &history_dir, "history")
+                .sync_directory_to_remote(client, &history_dir, "history")
                 .await?;
         }
 
@@ -86,7 +194,7 @@ impl SyncManager {
     pub async fn sync_from_remote(&self) -> Result<SyncResult, String> {
         let client_guard = self.client.lock().await;
         let client = client_guard
-            .as_ref()
+            .as_deref()
             .ok_or_else(|| "WebDAV 未配置".to_string())?;
 
         let mut result = SyncResult::default();
@@ -98,7 +206,7 @@ impl SyncManager {
             .map_err(|e| format!("创建 servers 目录失败: {}", e))?;
 
         result.servers_downloaded += self
-            .sync_directory_from_remote(&client, "servers", &servers_dir)
+            .sync_directory_from_remote(client, "servers", &servers_dir)
             .await?;
 
         // 同步历史记录
@@ -108,7 +216,7 @@ impl SyncManager {
             .map_err(|e| format!("创建 history 目录失败: {}", e))?;
 
         result.history_downloaded += self
-            .sync_directory_from_remote(&client, "history", &history_dir)
+            .sync_directory_from_remote(client, "history", &history_dir)
             .await?;
 
         Ok(result)
@@ -118,7 +226,7 @@ impl SyncManager {
     pub async fn sync_bidirectional(&self) -> Result<SyncResult, String> {
         let client_guard = self.client.lock().await;
         let client = client_guard
-            .as_ref()
+            .as_deref()
             .ok_or_else(|| "WebDAV 未配置".to_string())?;
 
         let mut result = SyncResult::default();
@@ -134,7 +242,7 @@ impl SyncManager {
             .map_err(|e| format!("创建 servers 目录失败: {}", e))?;
 
         let (uploaded, downloaded) = self
-            .sync_directory_bidirectional(&client, &servers_dir, "servers")
+            .sync_directory_bidirectional(client, &servers_dir, "servers")
             .await?;
         result.servers_uploaded += uploaded;
         result.servers_downloaded += downloaded;
@@ -146,7 +254,7 @@ impl SyncManager {
             .map_err(|e| format!("创建 history 目录失败: {}", e))?;
 
         let (uploaded, downloaded) = self
-            .sync_directory_bidirectional(&client, &history_dir, "history")
+            .sync_directory_bidirectional(client, &history_dir, "history")
             .await?;
         result.history_uploaded += uploaded;
         result.history_downloaded += downloaded;
@@ -157,6 +265,7 @@ impl SyncManager {
             log::error!("清除删除记录失败: {}", e);
         }
 
+        result.file_states = self.file_states.lock().await.clone();
         Ok(result)
     }
 
@@ -178,8 +287,13 @@ impl SyncManager {
         Ok(())
     }
 
-    /// 清除删除记录（同步完成后调用）
-    async fn clear_deletion_records(&self) -> Result<(), String> {
+    /// 查看待同步的删除记录
+    pub async fn inspect_deletion_records(&self) -> Result<DeletedFiles, String> {
+        self.load_deleted_files().await
+    }
+
+    /// 清除删除记录（同步完成后调用，也可由用户手动重置）
+    pub async fn clear_deletion_records(&self) -> Result<(), String> {
         let deleted_file = self.app_data_dir.join(".deleted_files.json");
         if deleted_file.exists() {
             tokio::fs::remove_file(&deleted_file)
@@ -191,6 +305,173 @@ impl SyncManager {
 
     // === 私有辅助方法 ===
 
+    /// 上传文件,设置了加密密码时先加密
+    async fn upload(
+        &self,
+        client: &dyn SyncBackend,
+        local_path: &Path,
+        remote_path: &str,
+    ) -> Result<(), String> {
+        let Some(cipher) = self.cipher.lock().await.clone() else {
+            return client.upload_file(local_path, remote_path).await;
+        };
+
+        let plaintext = tokio::fs::read(local_path)
+            .await
+            .map_err(|e| format!("读取本地文件失败: {}", e))?;
+        let encrypted = tokio::task::spawn_blocking(move || cipher.encrypt(&plaintext))
+            .await
+            .map_err(|e| format!("加密任务执行失败: {}", e))??;
+
+        // 写入临时文件后上传,保留原文件的修改时间供双向同步比较
+        let temp_path = self.sync_temp_path(local_path)?;
+        tokio::fs::write(&temp_path, encrypted)
+            .await
+            .map_err(|e| format!("写入临时文件失败: {}", e))?;
+        if let Ok(modified) = std::fs::metadata(local_path).and_then(|m| m.modified()) {
+            if let Ok(file) = std::fs::File::options().write(true).open(&temp_path) {
+                let _ = file.set_modified(modified);
+            }
+        }
+
+        let result = client.upload_file(&temp_path, remote_path).await;
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        result
+    }
+
+    /// 下载文件,设置了加密密码时解密后再写入本地
+    /// 先下载到临时文件,解密失败或缺少密码时返回错误,不会覆盖本地文件
+    async fn download(
+        &self,
+        client: &dyn SyncBackend,
+        remote_path: &str,
+        local_path: &Path,
+    ) -> Result<(), String> {
+        let temp_path = self.sync_temp_path(local_path)?;
+        client.download_file(remote_path, &temp_path).await?;
+        let data = tokio::fs::read(&temp_path)
+            .await
+            .map_err(|e| format!("读取下载文件失败: {}", e));
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        let data = data?;
+
+        let cipher = self.cipher.lock().await.clone();
+        let plaintext = match (cipher, SyncCipher::is_encrypted(&data)) {
+            (Some(cipher), true) => tokio::task::spawn_blocking(move || cipher.decrypt(&data))
+                .await
+                .map_err(|e| format!("解密任务执行失败: {}", e))?
+                .map_err(|e| format!("{}: {}", remote_path, e))?,
+            (None, true) => {
+                return Err(format!(
+                    "远程文件已加密，请在同步设置中填写加密密码: {}",
+                    remote_path
+                ))
+            }
+            (Some(_), false) => {
+                // 启用加密前上传的文件仍为明文
+                log::warn!("远程文件未加密，将在下次上传时加密: {}", remote_path);
+                data
+            }
+            (None, false) => data,
+        };
+
+        if let Some(parent) = local_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("创建本地目录失败: {}", e))?;
+        }
+        tokio::fs::write(local_path, plaintext)
+            .await
+            .map_err(|e| format!("保存文件失败: {}", e))
+    }
+
+    /// 将远程文件下载为 conflicts/<dir>/<name>.remote-<timestamp>.json 副本,不覆盖本地文件
+    /// 内容与本地文件相同时删除副本并返回 false
+    async fn keep_remote_copy(
+        &self,
+        client: &dyn SyncBackend,
+        remote_path: &str,
+        local_path: &Path,
+    ) -> Result<bool, String> {
+        let stem = local_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| format!("无效的文件路径: {}", local_path.display()))?;
+        let dir_name = local_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .ok_or_else(|| format!("无效的文件路径: {}", local_path.display()))?;
+        let copy_path = self
+            .app_data_dir
+            .join(CONFLICTS_DIR)
+            .join(dir_name)
+            .join(format!(
+                "{}{}{}.json",
+                stem,
+                REMOTE_COPY_MARKER,
+                chrono::Utc::now().timestamp()
+            ));
+
+        self.download(client, remote_path, &copy_path).await?;
+
+        let same = match (
+            tokio::fs::read(&copy_path).await,
+            tokio::fs::read(local_path).await,
+        ) {
+            (Ok(remote), Ok(local)) => remote == local,
+            _ => false,
+        };
+        if same {
+            let _ = tokio::fs::remove_file(&copy_path).await;
+            return Ok(false);
+        }
+
+        log::warn!(
+            "同步冲突: {} 本地和远程内容不同，远程版本已保存为 {}",
+            remote_path,
+            copy_path.display()
+        );
+        Ok(true)
+    }
+
+    /// 上传文件并记录同步后两端的修改时间
+    async fn upload_tracked(
+        &self,
+        client: &dyn SyncBackend,
+        local_path: &Path,
+        remote_path: &str,
+        states: &mut HashMap<String, SyncFileState>,
+    ) -> Result<(), String> {
+        self.upload(client, local_path, remote_path).await?;
+        let remote_modified = client.get_last_modified(remote_path).await?;
+        record_file_state(states, remote_path, local_path, remote_modified);
+        Ok(())
+    }
+
+    /// 下载文件并记录同步后两端的修改时间
+    async fn download_tracked(
+        &self,
+        client: &dyn SyncBackend,
+        remote_path: &str,
+        local_path: &Path,
+        remote_modified: Option<i64>,
+        states: &mut HashMap<String, SyncFileState>,
+    ) -> Result<(), String> {
+        self.download(client, remote_path, local_path).await?;
+        record_file_state(states, remote_path, local_path, remote_modified);
+        Ok(())
+    }
+
+    /// 加解密使用的临时文件路径
+    fn sync_temp_path(&self, local_path: &Path) -> Result<PathBuf, String> {
+        let temp_dir = self.app_data_dir.join(".sync_tmp");
+        std::fs::create_dir_all(&temp_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+        let filename = local_path
+            .file_name()
+            .ok_or_else(|| format!("无效的文件路径: {}", local_path.display()))?;
+        Ok(temp_dir.join(filename))
+    }
+
     /// 加载删除追踪文件
     async fn load_deleted_files(&self) -> Result<DeletedFiles, String> {
         let deleted_file = self.app_data_dir.join(".deleted_files.json");
@@ -203,7 +484,16 @@ impl SyncManager {
             .await
             .map_err(|e| format!("读取删除记录失败: {}", e))?;
 
-        serde_json::from_str(&content).map_err(|e| format!("解析删除记录失败: {}", e))
+        // 记录损坏时重置为空,避免整个同步失败
+        match serde_json::from_str(&content) {
+            Ok(deleted) => Ok(deleted),
+            Err(e) => {
+                log::warn!("删除记录已损坏，已重置: {}", e);
+                let deleted = DeletedFiles::default();
+                self.save_deleted_files(&deleted).await?;
+                Ok(deleted)
+            }
+        }
     }
 
     /// 保存删除追踪文件
@@ -223,7 +513,7 @@ impl SyncManager {
     /// 同步目录到远程
     async fn sync_directory_to_remote(
         &self,
-        client: &WebDavClient,
+        client: &dyn SyncBackend,
         local_dir: &Path,
         remote_dir: &str,
     ) -> Result<usize, String> {
@@ -242,7 +532,7 @@ impl SyncManager {
             if path.is_file() {
                 if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
                     let remote_path = format!("{}/{}", remote_dir, filename);
-                    client.upload_file(&path, &remote_path).await?;
+                    self.upload(client, &path, &remote_path).await?;
                     count += 1;
                 }
             }
@@ -254,7 +544,7 @@ impl SyncManager {
     /// 从远程同步目录
     async fn sync_directory_from_remote(
         &self,
-        client: &WebDavClient,
+        client: &dyn SyncBackend,
         remote_dir: &str,
         local_dir: &Path,
     ) -> Result<usize, String> {
@@ -274,7 +564,7 @@ impl SyncManager {
             if filename.ends_with(".json") {
                 let remote_path = format!("{}/{}", remote_dir, filename);
                 let local_path = local_dir.join(&filename);
-                client.download_file(&remote_path, &local_path).await?;
+                self.download(client, &remote_path, &local_path).await?;
                 count += 1;
             }
         }
@@ -282,15 +572,23 @@ impl SyncManager {
         Ok(count)
     }
 
-    /// 双向同步目录（基于时间戳，支持删除同步）
+    /// 双向同步目录（基于上次同步的文件状态，支持删除同步）
+    /// 只有一端在上次同步后被修改时以该端为准，两端都被修改 (或没有同步记录) 时按冲突策略处理
     async fn sync_directory_bidirectional(
         &self,
-        client: &WebDavClient,
+        client: &dyn SyncBackend,
         local_dir: &Path,
         remote_dir: &str,
     ) -> Result<(usize, usize), String> {
         let mut uploaded = 0;
         let mut downloaded = 0;
+        let strategy = *self.conflict_strategy.lock().await;
+
+        // 本目录的状态在同步过程中重新记录,已删除的文件不再保留
+        let mut states = self.file_states.lock().await;
+        let baseline = states.clone();
+        let prefix = format!("{}/", remote_dir);
+        states.retain(|key, _| !key.starts_with(&prefix));
 
         // 加载删除记录
         let deleted = self.load_deleted_files().await?;
@@ -315,15 +613,8 @@ impl SyncManager {
             if path.is_file() {
                 if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
                     if filename.ends_with(".json") {
-                        if let Ok(metadata) = tokio::fs::metadata(&path).await {
-                            if let Ok(modified) = metadata.modified() {
-                                if let Ok(timestamp) =
-                                    modified.duration_since(std::time::UNIX_EPOCH)
-                                {
-                                    local_files
-                                        .insert(filename.to_string(), timestamp.as_secs() as i64);
-                                }
-                            }
+                        if let Some(timestamp) = local_modified_secs(&path) {
+                            local_files.insert(filename.to_string(), timestamp);
                         }
                     }
                 }
@@ -335,21 +626,33 @@ impl SyncManager {
             Ok(files) => files,
             Err(_) => {
                 // 远程目录不存在，上传所有本地文件
-                for filename in local_files.keys() {
+                let total = local_files.len();
+                for (index, filename) in local_files.keys().enumerate() {
                     let local_path = local_dir.join(filename);
                     let remote_path = format!("{}/{}", remote_dir, filename);
-                    client.upload_file(&local_path, &remote_path).await?;
+                    self.emit_progress(index + 1, total, filename, "upload");
+                    self.upload_tracked(client, &local_path, &remote_path, &mut states)
+                        .await?;
                     uploaded += 1;
                 }
                 return Ok((uploaded, downloaded));
             }
         };
 
+        // 进度总数: 远程文件 + 仅本地存在的文件
+        let total = remote_files.iter().filter(|f| f.ends_with(".json")).count()
+            + local_files
+                .keys()
+                .filter(|f| !remote_files.contains(*f))
+                .count();
+        let mut current = 0;
+
         // 处理每个远程文件
         for filename in &remote_files {
             if !filename.ends_with(".json") {
                 continue;
             }
+            current += 1;
 
             let remote_path = format!("{}/{}", remote_dir, filename);
             let local_path = local_dir.join(filename);
@@ -367,37 +670,100 @@ impl SyncManager {
             // 获取远程文件的修改时间
             let remote_modified = client.get_last_modified(&remote_path).await?;
 
-            if local_files.contains_key(filename) {
-                // 本地和远程都存在，比较时间戳
-                let local_modified = local_files[filename];
+            let Some(local_modified) = local_files.remove(filename) else {
+                // 仅远程存在，下载
+                self.emit_progress(current, total, filename, "download");
+                self.download_tracked(
+                    client,
+                    &remote_path,
+                    &local_path,
+                    remote_modified,
+                    &mut states,
+                )
+                .await?;
+                downloaded += 1;
+                continue;
+            };
+
+            let last = baseline.get(&remote_path).copied();
 
+            // 无法获取远程修改时间或时间相同时，不做任何操作
+            let Some(remote_time) = remote_modified.filter(|t| *t != local_modified) else {
                 if let Some(remote_time) = remote_modified {
-                    if remote_time > local_modified {
-                        // 远程更新，下载
-                        client.download_file(&remote_path, &local_path).await?;
-                        downloaded += 1;
-                    } else if local_modified > remote_time {
-                        // 本地更新，上传
-                        client.upload_file(&local_path, &remote_path).await?;
-                        uploaded += 1;
-                    }
-                    // 如果时间相同，不做任何操作
+                    record_file_state(&mut states, &remote_path, &local_path, Some(remote_time));
+                } else if let Some(last) = last {
+                    states.insert(remote_path, last);
+                }
+                continue;
+            };
+
+            // 与上次同步后记录的修改时间比较，判断哪一端被修改过
+            let local_changed = !matches!(last, Some(s) if s.local_modified == local_modified);
+            let remote_changed = !matches!(last, Some(s) if s.remote_modified == remote_time);
+
+            if !local_changed && !remote_changed {
+                if let Some(last) = last {
+                    states.insert(remote_path, last);
                 }
+                continue;
+            }
+
+            let conflict = local_changed && remote_changed;
+            if conflict && strategy == ConflictStrategy::KeepBoth {
+                // 远程版本另存为副本后以本地文件为准
+                self.emit_progress(current, total, filename, "download");
+                if self
+                    .keep_remote_copy(client, &remote_path, &local_path)
+                    .await?
+                {
+                    downloaded += 1;
+                }
+                self.emit_progress(current, total, filename, "upload");
+                self.upload_tracked(client, &local_path, &remote_path, &mut states)
+                    .await?;
+                uploaded += 1;
+                continue;
+            }
 
-                // 从列表中移除已处理的文件
-                local_files.remove(filename);
+            let remote_wins = if conflict {
+                match strategy {
+                    ConflictStrategy::NewerWins => remote_time > local_modified,
+                    ConflictStrategy::RemoteWins => true,
+                    ConflictStrategy::LocalWins | ConflictStrategy::KeepBoth => false,
+                }
             } else {
-                // 仅远程存在，下载
-                client.download_file(&remote_path, &local_path).await?;
+                remote_changed
+            };
+
+            if remote_wins {
+                // 远程被修改 (或冲突时以远程为准)，下载
+                self.emit_progress(current, total, filename, "download");
+                self.download_tracked(
+                    client,
+                    &remote_path,
+                    &local_path,
+                    Some(remote_time),
+                    &mut states,
+                )
+                .await?;
                 downloaded += 1;
+            } else {
+                // 本地被修改 (或冲突时以本地为准)，上传
+                self.emit_progress(current, total, filename, "upload");
+                self.upload_tracked(client, &local_path, &remote_path, &mut states)
+                    .await?;
+                uploaded += 1;
             }
         }
 
         // 处理仅本地存在的文件，上传
         for filename in local_files.keys() {
+            current += 1;
             let local_path = local_dir.join(filename);
             let remote_path = format!("{}/{}", remote_dir, filename);
-            client.upload_file(&local_path, &remote_path).await?;
+            self.emit_progress(current, total, filename, "upload");
+            self.upload_tracked(client, &local_path, &remote_path, &mut states)
+                .await?;
             uploaded += 1;
         }
 
@@ -405,6 +771,36 @@ impl SyncManager {
     }
 }
 
+/// 本地文件的修改时间 (秒)
+fn local_modified_secs(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let timestamp = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(timestamp.as_secs() as i64)
+}
+
+/// 记录文件同步后两端的修改时间,任一端无法获取时不记录 (下次按冲突处理)
+fn record_file_state(
+    states: &mut HashMap<String, SyncFileState>,
+    remote_path: &str,
+    local_path: &Path,
+    remote_modified: Option<i64>,
+) {
+    match (local_modified_secs(local_path), remote_modified) {
+        (Some(local_modified), Some(remote_modified)) => {
+            states.insert(
+                remote_path.to_string(),
+                SyncFileState {
+                    local_modified,
+                    remote_modified,
+                },
+            );
+        }
+        _ => {
+            states.remove(remote_path);
+        }
+    }
+}
+
 /// 同步结果
 #[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct SyncResult {
@@ -412,6 +808,9 @@ pub struct SyncResult {
     pub servers_downloaded: usize,
     pub history_uploaded: usize,
     pub history_downloaded: usize,
+    // 双向同步后各文件的状态,由调用方保存到 LastSyncInfo
+    #[serde(skip)]
+    pub file_states: HashMap<String, SyncFileState>,
 }
 
 impl SyncResult {
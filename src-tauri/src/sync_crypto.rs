@@ -0,0 +1,93 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// 加密文件格式: 魔数 + 盐 (16 字节) + 随机数 (24 字节) + 密文 (含 16 字节认证标签)
+const MAGIC: &[u8] = b"WVENC1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// 同步文件加密 (XChaCha20-Poly1305,密钥由 Argon2 从密码派生)
+pub struct SyncCipher {
+    password: String,
+    // 本次同步上传使用的盐,同一批文件只需派生一次密钥
+    salt: [u8; SALT_LEN],
+    // 按盐缓存已派生的密钥,Argon2 派生较慢
+    keys: Mutex<HashMap<[u8; SALT_LEN], [u8; 32]>>,
+}
+
+impl SyncCipher {
+    pub fn new(password: &str) -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+
+        Self {
+            password: password.to_string(),
+            salt,
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 数据是否为加密格式
+    pub fn is_encrypted(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    /// 加密文件内容
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let key = self.derive_key(&self.salt)?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(XNonce::from_slice(&nonce), plaintext)
+            .map_err(|_| "加密文件失败".to_string())?;
+
+        let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    /// 解密文件内容,密码错误或内容被篡改时返回错误
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let body = data
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| "文件未加密".to_string())?;
+        if body.len() < SALT_LEN + NONCE_LEN {
+            return Err("加密文件已损坏".to_string());
+        }
+
+        let (salt, rest) = body.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let salt: [u8; SALT_LEN] = salt.try_into().map_err(|_| "加密文件已损坏".to_string())?;
+
+        let key = self.derive_key(&salt)?;
+        XChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "解密失败: 加密密码错误或文件已损坏".to_string())
+    }
+
+    fn derive_key(&self, salt: &[u8; SALT_LEN]) -> Result<[u8; 32], String> {
+        let mut keys = self
+            .keys
+            .lock()
+            .map_err(|_| "密钥缓存状态异常".to_string())?;
+        if let Some(key) = keys.get(salt) {
+            return Ok(*key);
+        }
+
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(self.password.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("派生加密密钥失败: {}", e))?;
+        keys.insert(*salt, key);
+        Ok(key)
+    }
+}
@@ -26,7 +26,10 @@ mod platform {
 }
 
 // 重新导出平台特定的函数
-pub use platform::{cleanup_stale_tunnel, get_tunnel_status_impl, start_tunnel_platform};
+pub use platform::{
+    apply_interface_config, cleanup_stale_tunnel, get_interface_mtu_impl, get_peer_stats_impl,
+    get_tunnel_status_impl, read_uapi_dump, remove_peer, set_listen_port, start_tunnel_platform,
+};
 
 // 进程包装器，用于统一管理不同类型的子进程
 pub enum ProcessHandle {
@@ -48,9 +51,10 @@ impl ProcessHandle {
                 child.kill().map_err(|e| format!("杀死进程失败: {}", e))
             }
             #[cfg(target_os = "macos")]
-            ProcessHandle::PrivilegedProcess(pid) => {
-                crate::tunnel_macos::stop_wireguard_macos(*pid)
-            }
+            ProcessHandle::PrivilegedProcess(pid) => crate::tunnel_macos::stop_wireguard_macos(
+                *pid,
+                Some(generate_interface_name(_tunnel_id).as_str()),
+            ),
             #[cfg(target_os = "linux")]
             ProcessHandle::PrivilegedProcess(pid) => {
                 crate::tunnel_linux::stop_wireguard_linux(*pid, _tunnel_id)
@@ -76,6 +80,14 @@ lazy_static::lazy_static! {
     pub static ref TUNNEL_CONFIGS: Mutex<HashMap<String, (String, InterfaceConfig)>> = Mutex::new(HashMap::new());
     // 管理 peer 统计推送线程
     pub static ref PEER_STATS_WATCHERS: Mutex<HashMap<String, std::thread::JoinHandle<()>>> = Mutex::new(HashMap::new());
+    // 已从运行中接口移除的过期 Peer ("隧道ID:公钥"),避免重复移除
+    static ref REMOVED_EXPIRED_PEERS: Mutex<std::collections::HashSet<String>> = Mutex::new(std::collections::HashSet::new());
+    // 流量统计重置时记录的基线 (tx, rx),显示时减去基线,隧道重启后清除
+    static ref STATS_BASELINES: Mutex<HashMap<String, (u64, u64)>> = Mutex::new(HashMap::new());
+    // 状态监控任务采集的握手时间戳 (秒),用于统计连接稳定性,隧道重新启动后清除
+    static ref HANDSHAKE_HISTORY: Mutex<HashMap<String, std::collections::VecDeque<i64>>> = Mutex::new(HashMap::new());
+    // 隧道配置中保存的接口名称 (隧道ID -> 接口名),未分配的隧道按 ID 哈希生成
+    static ref INTERFACE_NAMES: std::sync::Mutex<HashMap<String, String>> = std::sync::Mutex::new(HashMap::new());
 }
 
 // Windows 创建进程标志：CREATE_NO_WINDOW = 0x08000000
@@ -135,7 +147,88 @@ pub fn interface_exists(name: &str) -> bool {
 }
 
 // 生成接口名称的辅助函数
+// 配置中保存了接口名称的隧道使用保存的名称,否则按隧道 ID 哈希生成
 pub fn generate_interface_name(tunnel_id: &str) -> String {
+    let assigned = INTERFACE_NAMES
+        .lock()
+        .ok()
+        .and_then(|names| names.get(tunnel_id).cloned());
+    assigned.unwrap_or_else(|| hashed_interface_name(tunnel_id))
+}
+
+// 记录隧道配置中保存的接口名称
+fn register_interface_name(config: &TunnelConfig) {
+    if let Ok(mut names) = INTERFACE_NAMES.lock() {
+        match &config.interface_name {
+            Some(name) => names.insert(config.id.clone(), name.clone()),
+            None => names.remove(&config.id),
+        };
+    }
+}
+
+// 启动时加载所有隧道配置中保存的接口名称
+pub fn load_interface_names(app: &tauri::AppHandle) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(app_data_dir.join("tunnels")) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !is_tunnel_config_file(&path) {
+            continue;
+        }
+        let config = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<TunnelConfig>(&content).ok());
+        if let Some(config) = config {
+            register_interface_name(&config);
+        }
+    }
+}
+
+// 为隧道分配不与其他隧道和系统中已有接口冲突的接口名称
+// 优先使用按 ID 哈希得到的名称,冲突时依次尝试后续编号 (Windows 接口名由隧道 ID 生成,不会冲突)
+fn allocate_interface_name(
+    tunnels_dir: &std::path::Path,
+    tunnel_id: &str,
+) -> Result<String, String> {
+    let preferred = hashed_interface_name(tunnel_id);
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = tunnels_dir;
+        Ok(preferred)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // 其他已保存隧道占用的接口名称
+        let mut used_names = std::collections::HashSet::new();
+        if let Ok(entries) = std::fs::read_dir(tunnels_dir) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if !is_tunnel_config_file(&path) {
+                    continue;
+                }
+                if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                    if id != tunnel_id {
+                        used_names.insert(generate_interface_name(id));
+                    }
+                }
+            }
+        }
+
+        let prefix = preferred.trim_end_matches(|c: char| c.is_ascii_digit());
+        let start: u32 = preferred[prefix.len()..].parse().unwrap_or(0);
+        (0..1000)
+            .map(|offset| format!("{}{}", prefix, start + offset))
+            .find(|name| !used_names.contains(name) && !interface_exists(name))
+            .ok_or_else(|| "没有可用的接口名称".to_string())
+    }
+}
+
+// 按隧道 ID 哈希生成接口名称
+fn hashed_interface_name(tunnel_id: &str) -> String {
     #[cfg(target_os = "windows")]
     {
         crate::tunnel_windows::sanitize_identifier(tunnel_id)
@@ -183,6 +276,493 @@ pub fn base64_to_hex(base64_key: &str) -> Result<String, String> {
     Ok(hex::encode(&bytes))
 }
 
+// 从 endpoint 中提取端口并校验范围 (1-65535)
+// 支持 host:port、IPv4:port 以及 [IPv6]:port 格式
+pub fn parse_endpoint_port(endpoint: &str) -> Result<u16, String> {
+    let endpoint = endpoint.trim();
+
+    let port_str = if let Some(rest) = endpoint.strip_prefix('[') {
+        // IPv6 格式: [addr]:port
+        let (_, after) = rest
+            .split_once(']')
+            .ok_or_else(|| format!("Endpoint 格式错误: {} (缺少 ']')", endpoint))?;
+        after
+            .strip_prefix(':')
+            .ok_or_else(|| format!("Endpoint 缺少端口: {}", endpoint))?
+    } else {
+        let (host, port) = endpoint
+            .rsplit_once(':')
+            .ok_or_else(|| format!("Endpoint 缺少端口: {}", endpoint))?;
+        // 未加方括号的 IPv6 地址无法区分端口
+        if host.contains(':') {
+            return Err(format!(
+                "Endpoint 格式错误: {} (IPv6 地址需使用 [地址]:端口 格式)",
+                endpoint
+            ));
+        }
+        port
+    };
+
+    let port: u32 = port_str
+        .trim()
+        .parse()
+        .map_err(|_| format!("Endpoint 端口无效: {}", endpoint))?;
+
+    if port == 0 || port > 65535 {
+        return Err(format!("Endpoint 端口超出范围 (1-65535): {}", endpoint));
+    }
+
+    Ok(port as u16)
+}
+
+// Peer 的显示名称: 优先使用备注,否则使用序号
+fn peer_label(peer: &TunnelPeerConfig, index: usize) -> String {
+    peer.remark
+        .as_deref()
+        .filter(|r| !r.is_empty())
+        .map(|r| r.to_string())
+        .unwrap_or_else(|| format!("#{}", index + 1))
+}
+
+// 保存前规范化隧道中的所有密钥 (去除空白、补齐填充、重新编码为标准 Base64)
+// 钥匙串引用和留空的可选密钥保持不变
+pub fn normalize_tunnel_keys(config: &mut TunnelConfig) -> Result<(), String> {
+    use crate::commands::key_management::normalize_key;
+
+    if !config.private_key.trim().is_empty()
+        && !crate::keychain::is_keychain_ref(&config.private_key)
+    {
+        config.private_key =
+            normalize_key(&config.private_key).map_err(|e| format!("私钥无效: {}", e))?;
+    }
+
+    for (index, peer) in config.peers.iter_mut().enumerate() {
+        let label = peer_label(peer, index);
+        if !peer.public_key.trim().is_empty() {
+            peer.public_key = normalize_key(&peer.public_key)
+                .map_err(|e| format!("Peer {} 公钥无效: {}", label, e))?;
+        }
+        for (name, key) in [
+            ("预共享密钥", &mut peer.preshared_key),
+            ("客户端私钥", &mut peer.client_private_key),
+        ] {
+            if let Some(value) = key.as_mut().filter(|v| !v.trim().is_empty()) {
+                *value = normalize_key(value)
+                    .map_err(|e| format!("Peer {} {}无效: {}", label, name, e))?;
+            }
+        }
+    }
+
+    // 向后兼容的单个 Peer 字段
+    if !config.peer_public_key.trim().is_empty() {
+        config.peer_public_key =
+            normalize_key(&config.peer_public_key).map_err(|e| format!("Peer 公钥无效: {}", e))?;
+    }
+    if !config.preshared_key.trim().is_empty() {
+        config.preshared_key =
+            normalize_key(&config.preshared_key).map_err(|e| format!("预共享密钥无效: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// 校验隧道配置中所有 Peer 的 endpoint 端口
+pub fn validate_tunnel_endpoints(config: &TunnelConfig) -> Result<(), String> {
+    for (index, peer) in config.peers.iter().enumerate() {
+        if let Some(endpoint) = &peer.endpoint {
+            if endpoint.trim().is_empty() {
+                continue;
+            }
+            parse_endpoint_port(endpoint)
+                .map_err(|e| format!("Peer {} 配置错误: {}", peer_label(peer, index), e))?;
+        }
+    }
+
+    // 向后兼容的单个 Peer 字段
+    if !config.endpoint.trim().is_empty() {
+        parse_endpoint_port(&config.endpoint).map_err(|e| format!("Peer 配置错误: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// 解析 PersistentKeepalive (0-65535,空字符串表示不设置)
+pub fn parse_keepalive(value: &str) -> Result<Option<u16>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    value
+        .parse::<u16>()
+        .map(Some)
+        .map_err(|_| format!("PersistentKeepalive 无效 (应为 0-65535): {}", value))
+}
+
+// 校验隧道配置中的 PersistentKeepalive
+// peers 中的值已由类型保证在 0-65535 范围内,这里只需校验旧格式的字符串字段
+pub fn validate_tunnel_keepalive(config: &TunnelConfig) -> Result<(), String> {
+    parse_keepalive(&config.persistent_keepalive)
+        .map(|_| ())
+        .map_err(|e| format!("Peer 配置错误: {}", e))
+}
+
+// 校验路由表和 fwmark (仅 Linux 使用)
+// 0 / 253 (default) / 255 (local) 为系统保留表;fwmark 为 0 表示关闭,应留空
+pub fn validate_tunnel_routing(config: &TunnelConfig) -> Result<(), String> {
+    if let Some(table) = config.route_table {
+        if matches!(table, 0 | 253 | 255) {
+            return Err(format!(
+                "路由表 {} 为系统保留表，请使用其他编号 (main 表为 254)",
+                table
+            ));
+        }
+    }
+    if config.fwmark == Some(0) {
+        return Err("fwmark 不能为 0，不需要时请留空".to_string());
+    }
+    Ok(())
+}
+
+// 校验 DNS 搜索域 (逗号分隔的域名)
+// 每段 1-63 个字母、数字或连字符,且不能以连字符开头或结尾;纯 IP 应填写到 DNS 服务器中
+pub fn validate_dns_search(value: &str) -> Result<(), String> {
+    for domain in split_config_values(value) {
+        let name = domain.strip_suffix('.').unwrap_or(&domain);
+        let valid = !name.is_empty()
+            && name.len() <= 253
+            && name.parse::<std::net::IpAddr>().is_err()
+            && name.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+        if !valid {
+            return Err(format!("DNS 搜索域格式无效: {}", domain));
+        }
+    }
+    Ok(())
+}
+
+// 拆分 wg-quick 的 DNS 行: IP 地址为 DNS 服务器,其余项为搜索域
+pub fn split_dns_line(value: &str) -> (Vec<String>, Vec<String>) {
+    split_config_values(value)
+        .into_iter()
+        .partition(|entry| entry.parse::<std::net::IpAddr>().is_ok())
+}
+
+// 校验隧道配置中的 DNS 搜索域
+pub fn validate_tunnel_dns(config: &TunnelConfig) -> Result<(), String> {
+    match config.dns_search.as_deref().map(str::trim) {
+        Some(search) if !search.is_empty() => validate_dns_search(search),
+        _ => Ok(()),
+    }
+}
+
+// 检查 AllowedIPs 为空的 Peer,返回警告信息
+// AllowedIPs 为空时该 Peer 不会收发任何流量,客户端模式下几乎总是配置错误
+pub fn check_empty_allowed_ips(config: &TunnelConfig) -> Vec<String> {
+    let hint = if config.mode == "client" {
+        "，客户端模式下这通常是配置错误"
+    } else {
+        ""
+    };
+
+    let mut warnings: Vec<String> = config
+        .peers
+        .iter()
+        .enumerate()
+        .filter(|(_, peer)| peer.allowed_ips.split(',').all(|ip| ip.trim().is_empty()))
+        .map(|(index, peer)| {
+            format!(
+                "Peer {} 的 AllowedIPs 为空，将不会收发任何流量{}",
+                peer_label(peer, index),
+                hint
+            )
+        })
+        .collect();
+
+    // 向后兼容的单个 Peer 字段
+    if config.peers.is_empty()
+        && !config.peer_public_key.trim().is_empty()
+        && config.allowed_ips.trim().is_empty()
+    {
+        warnings.push(format!(
+            "Peer 的 AllowedIPs 为空，将不会收发任何流量{}",
+            hint
+        ));
+    }
+
+    warnings
+}
+
+// 检查 AllowedIPs 冲突,返回警告信息
+// - 两个 Peer 的 AllowedIPs 重叠: 完全相同时后配置的 Peer 会覆盖前者,否则按最长前缀匹配
+// - Peer 的 AllowedIPs 比本机地址所在网段更小且包含本机地址: 该 Peer 占用了本机地址,
+//   发往该地址的流量会被本机接收 (0.0.0.0/0 或与本机网段相同的路由属于正常配置,不提示)
+pub fn check_allowed_ips_conflicts(config: &TunnelConfig) -> Vec<String> {
+    use crate::commands::diagnostics::{cidr_overlaps, parse_cidr};
+    use std::net::IpAddr;
+
+    let parse_list = |list: &str| -> Vec<(String, (IpAddr, u8))> {
+        list.split(',')
+            .map(str::trim)
+            .filter_map(|cidr| Some((cidr.to_string(), parse_cidr(cidr)?)))
+            .collect()
+    };
+
+    // (Peer 名称, 解析后的 AllowedIPs)
+    let peers: Vec<(String, Vec<(String, (IpAddr, u8))>)> = if config.peers.is_empty() {
+        vec![("Peer".to_string(), parse_list(&config.allowed_ips))]
+    } else {
+        config
+            .peers
+            .iter()
+            .enumerate()
+            .map(|(index, peer)| {
+                let label = format!("Peer {}", peer_label(peer, index));
+                (label, parse_list(&peer.allowed_ips))
+            })
+            .collect()
+    };
+
+    let mut warnings = Vec::new();
+
+    for (i, (label_a, nets_a)) in peers.iter().enumerate() {
+        for (label_b, nets_b) in peers.iter().skip(i + 1) {
+            for (cidr_a, net_a) in nets_a {
+                for (cidr_b, net_b) in nets_b.iter().filter(|(_, n)| cidr_overlaps(*net_a, *n)) {
+                    if net_a.1 == net_b.1 {
+                        warnings.push(format!(
+                            "{} 与 {} 的 AllowedIPs 重复 ({})，WireGuard 会将该网段路由到最后配置的 Peer",
+                            label_a, label_b, cidr_a
+                        ));
+                    } else {
+                        let narrower = if net_a.1 > net_b.1 { label_a } else { label_b };
+                        warnings.push(format!(
+                            "{} 的 {} 与 {} 的 {} 重叠，重叠部分的流量只会路由到 {}",
+                            label_a, cidr_a, label_b, cidr_b, narrower
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for (address, (local_ip, local_prefix)) in parse_list(&config.address) {
+        let host = (local_ip, if local_ip.is_ipv4() { 32 } else { 128 });
+        for (label, nets) in &peers {
+            for (cidr, net) in nets {
+                if net.1 > local_prefix && cidr_overlaps(*net, host) {
+                    warnings.push(format!(
+                        "{} 的 AllowedIPs {} 包含本机地址 {}，发往该地址的流量会被本机接收而不会发给 Peer",
+                        label, cidr, address
+                    ));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+// 检查私钥与 Peer 公钥是否填反
+// - 私钥推导出的公钥与某个 Peer 的公钥相同: Peer 指向了自己
+// - 私钥未经 clamp: wg genkey 生成的私钥总是 clamp 过的,未 clamp 通常说明误填了公钥
+pub fn check_key_mix_ups(config: &TunnelConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let private_key = config.private_key.trim();
+    if private_key.is_empty() || crate::keychain::is_keychain_ref(private_key) {
+        return warnings;
+    }
+
+    let Ok(public_key) = crate::commands::key_management::compute_public_key(private_key) else {
+        return warnings;
+    };
+
+    for (index, peer) in config.peers.iter().enumerate() {
+        if peer.public_key.trim() == public_key {
+            warnings.push(format!(
+                "Peer {} 的公钥与本机私钥推导出的公钥相同，Peer 指向了自己，私钥和 Peer 公钥可能填反了",
+                peer_label(peer, index)
+            ));
+        }
+    }
+
+    // 向后兼容的单个 Peer 字段
+    if config.peers.is_empty() && config.peer_public_key.trim() == public_key {
+        warnings.push(
+            "Peer 公钥与本机私钥推导出的公钥相同，Peer 指向了自己，私钥和 Peer 公钥可能填反了"
+                .to_string(),
+        );
+    }
+
+    if let Ok(bytes) = BASE64.decode(private_key) {
+        if bytes.len() == 32 && (bytes[0] & 7 != 0 || bytes[31] & 0xc0 != 0x40) {
+            warnings.push("私钥看起来未经 clamp 处理，可能误填了公钥而不是私钥".to_string());
+        }
+    }
+
+    warnings
+}
+
+// WireGuard 封装开销: 外层 IPv6 头 40 + UDP 8 + WireGuard 32
+// IPv4 外层头只有 20 字节,但 wg-quick 不区分地址族,统一按 80 字节计算
+pub(crate) const WG_OVERHEAD_V6: u32 = 80;
+// IPv6 要求的最小链路 MTU,也是纯 IPv6 链路上常见的路径 MTU
+const MIN_IPV6_MTU: u32 = 1280;
+
+// 取 endpoint 的 IP 地址: IP 字面量直接解析,域名通过 DNS 解析 (会阻塞当前线程)
+pub fn endpoint_ip(endpoint: &str) -> Option<std::net::IpAddr> {
+    let endpoint = endpoint.trim();
+    if endpoint.is_empty() {
+        return None;
+    }
+    if let Ok(addr) = endpoint.parse::<std::net::SocketAddr>() {
+        return Some(addr.ip());
+    }
+    resolve_endpoint(endpoint)
+        .ok()?
+        .parse::<std::net::SocketAddr>()
+        .ok()
+        .map(|addr| addr.ip())
+}
+
+// 检查 MTU 在小 MTU 链路上是否会导致分片丢包
+// 外层包走 IPv6 (Endpoint 解析为 IPv6 地址) 时才可能落在 1280 MTU 的纯 IPv6 链路上;
+// MTU 为空时由客户端自动计算,不检查
+// 建议值为 1280 - 80 = 1200,使加密后的包不超过 1280 的路径 MTU
+pub fn check_mtu(mtu: &str, endpoint_ips: &[std::net::IpAddr]) -> Option<String> {
+    let mtu: u32 = mtu.trim().parse().ok()?;
+
+    let ipv6_endpoint = endpoint_ips.iter().any(|ip| ip.is_ipv6());
+    if !ipv6_endpoint || mtu + WG_OVERHEAD_V6 <= MIN_IPV6_MTU {
+        return None;
+    }
+
+    Some(format!(
+        "Endpoint 为 IPv6 地址，MTU {} 加上 WireGuard 开销 {} 字节共 {} 字节，超过纯 IPv6 链路常见的 {} 路径 MTU，可能因分片导致丢包，建议将 MTU 设为 {}",
+        mtu,
+        WG_OVERHEAD_V6,
+        mtu + WG_OVERHEAD_V6,
+        MIN_IPV6_MTU,
+        MIN_IPV6_MTU - WG_OVERHEAD_V6
+    ))
+}
+
+// 检查 AllowedIPs 是否会把受保护网段 (如管理网络) 的流量路由进隧道
+// 远程管理的服务器上启动这样的隧道后,SSH 等管理连接会断开
+pub fn check_protected_subnets(config: &TunnelConfig, protected_subnets: &[String]) -> Vec<String> {
+    use crate::commands::diagnostics::{cidr_overlaps, parse_cidr};
+
+    let protected: Vec<_> = protected_subnets
+        .iter()
+        .map(|s| s.trim())
+        .filter_map(|s| Some((s, parse_cidr(s)?)))
+        .collect();
+    if protected.is_empty() {
+        return Vec::new();
+    }
+
+    let peers: Vec<(String, &str)> = if config.peers.is_empty() {
+        vec![("Peer".to_string(), config.allowed_ips.as_str())]
+    } else {
+        config
+            .peers
+            .iter()
+            .enumerate()
+            .map(|(index, peer)| {
+                let label = format!("Peer {}", peer_label(peer, index));
+                (label, peer.allowed_ips.as_str())
+            })
+            .collect()
+    };
+
+    let mut warnings = Vec::new();
+    for (label, allowed_ips) in peers {
+        for cidr in allowed_ips.split(',').map(str::trim) {
+            let Some(net) = parse_cidr(cidr) else {
+                continue;
+            };
+            for (subnet, protected_net) in &protected {
+                if cidr_overlaps(net, *protected_net) {
+                    warnings.push(format!(
+                        "{} 的 AllowedIPs {} 包含受保护网段 {}，启动隧道后该网段的流量 (如 SSH 管理连接) 会被路由进隧道",
+                        label, cidr, subnet
+                    ));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+// 读取偏好设置中的受保护网段,读取失败时视为未配置
+fn load_protected_subnets(app: &tauri::AppHandle) -> Vec<String> {
+    crate::commands::persistence::load_preferences(app.clone())
+        .map(|p| p.protected_subnets)
+        .unwrap_or_default()
+}
+
+// 汇总隧道配置的所有警告,protected_subnets 为偏好设置中的受保护网段
+// MTU 检查会解析 endpoint 域名,异步上下文中应通过 config_warnings 调用
+pub fn collect_config_warnings(config: &TunnelConfig, protected_subnets: &[String]) -> Vec<String> {
+    let mut warnings = check_empty_allowed_ips(config);
+    warnings.extend(check_allowed_ips_conflicts(config));
+    warnings.extend(check_key_mix_ups(config));
+
+    // MTU 为空时不检查,也就不必解析 endpoint
+    if !config.mtu.trim().is_empty() {
+        let endpoint_ips: Vec<std::net::IpAddr> = config
+            .peers
+            .iter()
+            .filter_map(|peer| peer.endpoint.as_deref())
+            .chain(std::iter::once(config.endpoint.as_str()))
+            .filter_map(endpoint_ip)
+            .collect();
+        warnings.extend(check_mtu(&config.mtu, &endpoint_ips));
+    }
+    warnings.extend(check_protected_subnets(config, protected_subnets));
+
+    warnings
+}
+
+// 在阻塞线程池中汇总配置警告,避免 endpoint 的 DNS 解析阻塞异步运行时
+pub async fn config_warnings(app: &tauri::AppHandle, config: &TunnelConfig) -> Vec<String> {
+    let config = config.clone();
+    let protected_subnets = load_protected_subnets(app);
+    tokio::task::spawn_blocking(move || collect_config_warnings(&config, &protected_subnets))
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("汇总配置警告失败: {}", e);
+            Vec::new()
+        })
+}
+
+// 校验隧道配置: 格式错误返回 Err,可能的配置问题作为警告返回
+#[tauri::command]
+pub async fn validate_tunnel_config(
+    app: tauri::AppHandle,
+    config: TunnelConfig,
+) -> Result<Vec<String>, String> {
+    validate_tunnel_endpoints(&config)?;
+    validate_tunnel_keepalive(&config)?;
+    validate_tunnel_routing(&config)?;
+    validate_tunnel_dns(&config)?;
+
+    Ok(config_warnings(&app, &config).await)
+}
+
+// 校验 endpoint 端口 (供前端实时校验)
+#[tauri::command]
+pub fn validate_endpoint(endpoint: String) -> Result<u16, String> {
+    parse_endpoint_port(&endpoint)
+}
+
 // 解析 endpoint: 如果包含域名,解析为 IP 地址
 pub fn resolve_endpoint(endpoint: &str) -> Result<String, String> {
     use std::net::ToSocketAddrs;
@@ -201,6 +781,46 @@ pub fn resolve_endpoint(endpoint: &str) -> Result<String, String> {
     }
 }
 
+// 将 wireguard-go UAPI 响应中的 errno 转换为可读的错误信息
+// 返回 None 表示成功 (errno=0 或响应中没有 errno)
+pub fn uapi_error_message(response: &str) -> Option<String> {
+    let raw = response
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("errno="))?
+        .trim();
+
+    let code: i64 = match raw.parse() {
+        Ok(code) => code,
+        Err(_) => return Some(format!("未知错误 (Unknown error) [errno={}]", raw)),
+    };
+
+    // wireguard-go 返回负的 errno,这里统一取绝对值
+    let description = match code.abs() {
+        0 => return None,
+        5 => "I/O 错误，接口可能已关闭 (I/O error)",
+        22 => "配置无效，请检查密钥、端口或 AllowedIPs 格式 (Invalid argument)",
+        // Linux 为 98, macOS 为 48
+        48 | 98 => "监听端口已被占用 (Address already in use)",
+        // Linux 为 71, macOS 为 100
+        71 | 100 => "UAPI 协议错误 (Protocol error)",
+        55 => "wireguard-go 内部错误 (Unknown error)",
+        _ => "未知错误 (Unknown error)",
+    };
+
+    Some(format!("{} [errno={}]", description, raw))
+}
+
+// 隐藏 UAPI 响应中的私钥和预共享密钥,其余内容原样保留
+pub fn redact_uapi_dump(raw: &str) -> String {
+    raw.split('\n')
+        .map(|line| match line.split_once('=') {
+            Some((key @ ("private_key" | "preshared_key"), _)) => format!("{}=(已隐藏)", key),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // 解析接口状态
 pub fn parse_interface_status(status: &str) -> (u64, u64, Option<i64>) {
     let mut tx_bytes = 0u64;
@@ -320,6 +940,10 @@ pub struct InterfaceConfig {
     pub private_key: String,
     pub listen_port: Option<u16>,
     pub fwmark: Option<u32>,
+    #[serde(default)]
+    pub route_table: Option<u32>,
+    #[serde(default)]
+    pub kill_switch: bool,
     pub replace_peers: bool,
     pub peers: Vec<PeerConfig>,
 }
@@ -337,6 +961,8 @@ pub struct TunnelPeerConfig {
     pub persistent_keepalive: Option<u16>,
     #[serde(default)]
     pub remark: Option<String>, // 备注信息，用于标识客户端
+    #[serde(default)]
+    pub name: Option<String>, // 友好名称，与 Peer 统计一起显示
     // 运行时统计信息（保存配置时会为 0，运行时获取真实数据）
     #[serde(default)]
     pub tx_bytes: u64, // 上传流量
@@ -344,6 +970,9 @@ pub struct TunnelPeerConfig {
     pub rx_bytes: u64, // 下载流量
     #[serde(default)]
     pub last_handshake: Option<i64>, // 最后握手时间
+    // 到期时间 (毫秒时间戳),WireGuard 本身不支持过期,由应用在到期后移除该 Peer
+    #[serde(default)]
+    pub expires_at: Option<i64>,
 }
 
 // 隧道配置(用户创建的配置)
@@ -355,11 +984,35 @@ pub struct TunnelConfig {
     #[serde(default)]
     pub mode: String,
     // Interface 配置
-    pub private_key: String,
+    pub private_key: String, // 开启钥匙串存储时为 "keychain:<id>" 引用
+    // 是否将私钥保存到系统钥匙串
+    #[serde(default)]
+    pub store_key_in_keychain: bool,
+    // 分配的接口名称,新建隧道时选择不冲突的名称;旧配置为空时在启动时分配
+    #[serde(default)]
+    pub interface_name: Option<String>,
     pub address: String,
     pub listen_port: String, // 空字符串表示自动
     pub dns: String,
+    // DNS 搜索域,生成配置时追加到 DNS 行 (逗号分隔)
+    #[serde(default)]
+    pub dns_search: Option<String>,
     pub mtu: String,
+    // 路由表 (仅 Linux 守护进程),为空时使用 main 表
+    #[serde(default)]
+    pub route_table: Option<u32>,
+    // WireGuard 出站数据包的 fwmark (仅 Linux),与 route_table 同时设置时添加策略路由规则
+    #[serde(default)]
+    pub fwmark: Option<u32>,
+    // 隧道意外断开时阻止流量绕过隧道 (Linux 使用 iptables,macOS 使用 pf)
+    #[serde(default)]
+    pub kill_switch: bool,
+    // 开机自动启动 (Linux 由守护进程拉起,macOS 通过登录项启动应用)
+    #[serde(default)]
+    pub auto_start: bool,
+    // 网络切换 (默认网关变化) 后自动重连,重新解析 endpoint 并重新下发 Peer 配置
+    #[serde(default)]
+    pub reconnect_on_network_change: bool,
     // 服务端的公网 IP 或域名（仅服务端）
     #[serde(default)]
     pub server_endpoint: String,
@@ -384,6 +1037,37 @@ pub struct TunnelConfig {
     pub created_at: i64,
 }
 
+// 单个 Peer 的运行时统计（各平台通用，字段与守护进程的 PeerStatsIpc 一致）
+// public_key 为 base64 格式，last_handshake 为 unix 时间戳（秒）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerStats {
+    pub public_key: String,
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+    pub last_handshake: Option<i64>,
+    // 隧道配置中该 Peer 的友好名称
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+// 将 parse_peer_stats_from_uapi 的结果转换为按公钥排序的列表
+pub fn peer_stats_list(
+    stats: std::collections::HashMap<String, (u64, u64, Option<i64>)>,
+) -> Vec<PeerStats> {
+    let mut list: Vec<PeerStats> = stats
+        .into_iter()
+        .map(|(public_key, (tx_bytes, rx_bytes, handshake))| PeerStats {
+            public_key,
+            tx_bytes,
+            rx_bytes,
+            last_handshake: handshake,
+            name: None,
+        })
+        .collect();
+    list.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+    list
+}
+
 // 隧道状态
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TunnelStatus {
@@ -411,54 +1095,18 @@ pub struct TunnelStatus {
     // 接口名称（用于 peer 统计推送）
     #[serde(default)]
     pub interface_name: String,
+    #[serde(default)]
+    pub auto_start: bool,
+    #[serde(default)]
+    pub reconnect_on_network_change: bool,
 }
 
-// 启动隧道
-#[tauri::command]
-pub async fn start_tunnel(tunnel_id: String, app: tauri::AppHandle) -> Result<(), String> {
-    // 检查隧道是否已在运行
-    {
-        let processes = TUNNEL_PROCESSES.lock().await;
-        if processes.contains_key(&tunnel_id) {
-            return Err("隧道已在运行中".to_string());
-        }
-    }
-
-    // 额外检查:如果可能生成的接口已存在,说明有残留进程
-    let potential_interface = generate_interface_name(&tunnel_id);
-    if interface_exists(&potential_interface) {
-        return Err(format!(
-            "接口 {} 已存在,可能有残留进程。请先手动停止或删除该接口",
-            potential_interface
-        ));
-    }
-
-    // 从隧道配置目录加载配置
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
-
-    let config_file = app_data_dir
-        .join("tunnels")
-        .join(format!("{}.json", tunnel_id));
-
-    if !config_file.exists() {
-        return Err("隧道配置不存在".to_string());
-    }
-
-    let content =
-        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
-
-    let tunnel_config: TunnelConfig =
-        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
-
-    // 生成接口名称
-    let interface_name = generate_interface_name(&tunnel_id);
-
-    log::info!("interface name: {}", interface_name);
-
-    // 构建 InterfaceConfig
+// 根据隧道配置构建 InterfaceConfig
+// replace_peers 为 false 时只追加/更新 Peer,保留接口上通过其他方式添加的 Peer
+pub fn build_interface_config(
+    tunnel_config: &TunnelConfig,
+    replace_peers: bool,
+) -> InterfaceConfig {
     let listen_port = if tunnel_config.listen_port.is_empty() {
         None
     } else {
@@ -471,6 +1119,12 @@ pub async fn start_tunnel(tunnel_id: String, app: tauri::AppHandle) -> Result<()
     // 优先使用新的 peers 数组
     if !tunnel_config.peers.is_empty() {
         for tunnel_peer in &tunnel_config.peers {
+            // 已过期的 Peer 不再下发
+            if is_peer_expired(tunnel_peer.expires_at) {
+                log::info!("跳过已过期的 Peer: {}", tunnel_peer.public_key);
+                continue;
+            }
+
             let allowed_ips: Vec<String> = tunnel_peer
                 .allowed_ips
                 .split(',')
@@ -526,13 +1180,285 @@ pub async fn start_tunnel(tunnel_id: String, app: tauri::AppHandle) -> Result<()
         });
     }
 
-    let interface_config = InterfaceConfig {
+    InterfaceConfig {
         private_key: tunnel_config.private_key.clone(),
         listen_port,
-        fwmark: None,
-        replace_peers: true,
+        fwmark: tunnel_config.fwmark,
+        route_table: tunnel_config.route_table,
+        kill_switch: tunnel_config.kill_switch,
+        replace_peers,
         peers,
-    };
+    }
+}
+
+// 拆分以逗号、分号或空白分隔的多个值 (Address / DNS)
+fn split_config_values(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+// 将隧道配置序列化为 wg-quick 格式 (.conf)
+// line_ending: Windows 官方客户端使用 "\r\n",其他平台使用 "\n"
+pub fn build_wg_quick_config(
+    tunnel_config: &TunnelConfig,
+    interface_config: &InterfaceConfig,
+    line_ending: &str,
+) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    lines.push("[Interface]".to_string());
+    lines.push(format!(
+        "PrivateKey = {}",
+        interface_config.private_key.trim()
+    ));
+
+    for address in split_config_values(&tunnel_config.address) {
+        lines.push(format!("Address = {}", address));
+    }
+
+    if let Some(port) = interface_config.listen_port {
+        lines.push(format!("ListenPort = {}", port));
+    }
+
+    let mut dns_lines: Vec<String> = Vec::new();
+    if !tunnel_config.dns.trim().is_empty() {
+        for dns in split_config_values(&tunnel_config.dns) {
+            dns_lines.push(format!("DNS = {}", dns));
+        }
+    }
+    // wg-quick 将 DNS 行中的非 IP 项视为搜索域
+    if let Some(search) = tunnel_config.dns_search.as_deref() {
+        let domains = split_config_values(search).join(", ");
+        if !domains.is_empty() {
+            match dns_lines.last_mut() {
+                Some(last) => last.push_str(&format!(", {}", domains)),
+                None => dns_lines.push(format!("DNS = {}", domains)),
+            }
+        }
+    }
+    lines.extend(dns_lines);
+
+    if !tunnel_config.mtu.trim().is_empty() {
+        lines.push(format!("MTU = {}", tunnel_config.mtu.trim()));
+    }
+
+    lines.push(String::new());
+
+    for peer in &interface_config.peers {
+        lines.push("[Peer]".to_string());
+        lines.push(format!("PublicKey = {}", peer.public_key.trim()));
+
+        if let Some(ref psk) = peer.preshared_key {
+            if !psk.trim().is_empty() {
+                lines.push(format!("PresharedKey = {}", psk.trim()));
+            }
+        }
+
+        if let Some(ref endpoint) = peer.endpoint {
+            if !endpoint.trim().is_empty() {
+                lines.push(format!("Endpoint = {}", endpoint.trim()));
+            }
+        }
+
+        if !peer.allowed_ips.is_empty() {
+            let ips = peer
+                .allowed_ips
+                .iter()
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if !ips.is_empty() {
+                lines.push(format!("AllowedIPs = {}", ips));
+            }
+        }
+
+        if let Some(keepalive) = peer.persistent_keepalive {
+            lines.push(format!("PersistentKeepalive = {}", keepalive));
+        }
+
+        lines.push(String::new());
+    }
+
+    lines.join(line_ending)
+}
+
+// 获取 wireguard-go sidecar 的路径（仅 Unix 平台需要）
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn wireguard_go_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    #[cfg(target_os = "macos")]
+    {
+        // 在 macOS 上，外部二进制文件位于 Contents/MacOS 目录
+        let exe_dir = app
+            .path()
+            .resolve("wireguard-go", tauri::path::BaseDirectory::Executable)
+            .ok()
+            .and_then(|p| p.parent().map(|parent| parent.to_path_buf()));
+
+        if let Some(dir) = exe_dir {
+            // 生产环境：应用打包后，wireguard-go 在 Contents/MacOS 目录
+            Ok(dir.join("wireguard-go"))
+        } else {
+            // 开发环境：直接在 target/debug 或 target/release 目录
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+                .map(|p| p.join("wireguard-go"))
+                .ok_or_else(|| "无法获取 wireguard-go 路径".to_string())
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // 在 Linux 上，优先使用 Resource 目录（生产环境）
+        // 如果失败，则使用开发环境的 target 目录
+        if let Ok(path) = app
+            .path()
+            .resolve("wireguard-go", tauri::path::BaseDirectory::Resource)
+        {
+            Ok(path)
+        } else {
+            // 开发环境回退方案
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+                .map(|p| p.join("wireguard-go"))
+                .ok_or_else(|| "无法获取 wireguard-go 路径".to_string())
+        }
+    }
+}
+
+// 运行隧道所需的外部组件检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PrereqStatus {
+    pub wireguard_go_found: bool,
+    pub wireguard_go_path: Option<String>,
+    // 仅 Windows 需要官方客户端的 wireguard.exe / wg.exe,Unix 平台通过 UAPI 直接配置,始终为 true
+    pub wg_tools_found: bool,
+    // 仅 Linux 需要守护进程,其他平台始终为 false
+    pub daemon_running: bool,
+}
+
+// 检查 wireguard-go / WireGuard 工具和守护进程是否可用,启动隧道前提示用户安装
+#[tauri::command]
+pub async fn check_prerequisites(app: tauri::AppHandle) -> Result<PrereqStatus, String> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        let sidecar = wireguard_go_path(&app)
+            .ok()
+            .filter(|path| path.exists())
+            .map(|path| path.to_string_lossy().to_string());
+
+        // 找不到 sidecar 时使用守护进程的查找路径
+        #[cfg(target_os = "linux")]
+        let sidecar = sidecar.or_else(|| crate::daemon::find_wireguard_go().ok());
+
+        #[cfg(target_os = "linux")]
+        let daemon_running =
+            tokio::task::spawn_blocking(crate::daemon_ipc::IpcClient::is_daemon_running)
+                .await
+                .map_err(|e| format!("检查守护进程状态失败: {}", e))?;
+        #[cfg(target_os = "macos")]
+        let daemon_running = false;
+
+        Ok(PrereqStatus {
+            wireguard_go_found: sidecar.is_some(),
+            wireguard_go_path: sidecar,
+            wg_tools_found: true,
+            daemon_running,
+        })
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = app;
+        let tools = crate::tunnel_windows::locate_wireguard_tools().ok();
+        Ok(PrereqStatus {
+            wireguard_go_found: tools.is_some(),
+            wireguard_go_path: tools
+                .as_ref()
+                .map(|(wireguard, _)| wireguard.to_string_lossy().to_string()),
+            wg_tools_found: tools.is_some(),
+            daemon_running: false,
+        })
+    }
+}
+
+// 启动隧道
+#[tauri::command]
+pub async fn start_tunnel(tunnel_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    // 检查隧道是否已在运行
+    {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        if processes.contains_key(&tunnel_id) {
+            return Err("隧道已在运行中".to_string());
+        }
+    }
+
+    // 新接口的计数器从 0 开始,旧基线不再适用
+    STATS_BASELINES.lock().await.remove(&tunnel_id);
+    HANDSHAKE_HISTORY.lock().await.remove(&tunnel_id);
+
+    // 从隧道配置目录加载配置
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    // 旧配置没有接口名称,启动前分配不冲突的名称并保存
+    if tunnel_config.interface_name.is_none() {
+        let name = allocate_interface_name(&app_data_dir.join("tunnels"), &tunnel_id)?;
+        tunnel_config.interface_name = Some(name);
+        let json = serde_json::to_string_pretty(&tunnel_config)
+            .map_err(|e| format!("序列化隧道配置失败: {}", e))?;
+        std::fs::write(&config_file, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+        register_interface_name(&tunnel_config);
+    }
+
+    // 额外检查:如果接口已存在,说明有残留进程
+    let interface_name = generate_interface_name(&tunnel_id);
+    if interface_exists(&interface_name) {
+        return Err(format!(
+            "接口 {} 已存在,可能有残留进程。请先手动停止或删除该接口",
+            interface_name
+        ));
+    }
+
+    // 从钥匙串解析私钥
+    crate::keychain::resolve_private_key(&mut tunnel_config)?;
+
+    for warning in config_warnings(&app, &tunnel_config).await {
+        log::warn!("隧道 {} 配置警告: {}", tunnel_config.name, warning);
+    }
+
+    log::info!("interface name: {}", interface_name);
+
+    // 构建 InterfaceConfig (首次启动时替换所有 Peer)
+    let interface_config = build_interface_config(&tunnel_config, true);
+
+    // 监听端口被其他程序占用时提前报告占用进程,避免在 UAPI 阶段才失败
+    if let Some(port) = interface_config.listen_port {
+        if let Some(conflict) = crate::commands::diagnostics::listen_port_conflict(port) {
+            return Err(conflict);
+        }
+    }
 
     // 收集所有需要配置的路由
     let mut all_routes: Vec<String> = Vec::new();
@@ -552,48 +1478,7 @@ pub async fn start_tunnel(tunnel_id: String, app: tauri::AppHandle) -> Result<()
 
     // 获取 sidecar 路径（仅 Unix 平台需要）
     #[cfg(any(target_os = "macos", target_os = "linux"))]
-    let sidecar_path = {
-        #[cfg(target_os = "macos")]
-        {
-            // 在 macOS 上，外部二进制文件位于 Contents/MacOS 目录
-            let exe_dir = app
-                .path()
-                .resolve("wireguard-go", tauri::path::BaseDirectory::Executable)
-                .ok()
-                .and_then(|p| p.parent().map(|parent| parent.to_path_buf()));
-
-            if let Some(dir) = exe_dir {
-                // 生产环境：应用打包后，wireguard-go 在 Contents/MacOS 目录
-                dir.join("wireguard-go")
-            } else {
-                // 开发环境：直接在 target/debug 或 target/release 目录
-                std::env::current_exe()
-                    .ok()
-                    .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
-                    .map(|p| p.join("wireguard-go"))
-                    .ok_or_else(|| "无法获取 wireguard-go 路径".to_string())?
-            }
-        }
-
-        #[cfg(target_os = "linux")]
-        {
-            // 在 Linux 上，优先使用 Resource 目录（生产环境）
-            // 如果失败，则使用开发环境的 target 目录
-            if let Ok(path) = app
-                .path()
-                .resolve("wireguard-go", tauri::path::BaseDirectory::Resource)
-            {
-                path
-            } else {
-                // 开发环境回退方案
-                std::env::current_exe()
-                    .ok()
-                    .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
-                    .map(|p| p.join("wireguard-go"))
-                    .ok_or_else(|| "无法获取 wireguard-go 路径".to_string())?
-            }
-        }
-    };
+    let sidecar_path = wireguard_go_path(&app)?;
 
     #[cfg(any(target_os = "macos", target_os = "linux"))]
     let sidecar_path_str = sidecar_path
@@ -643,6 +1528,7 @@ pub async fn stop_tunnel(tunnel_id: String) -> Result<(), String> {
             configs.remove(&tunnel_id);
             log::info!("已清理隧道配置,endpoint 刷新任务将自动停止");
         }
+        STATS_BASELINES.lock().await.remove(&tunnel_id);
 
         child
             .kill(&tunnel_id)
@@ -670,6 +1556,45 @@ pub async fn stop_tunnel(tunnel_id: String) -> Result<(), String> {
     }
 }
 
+// 减去重置时记录的流量基线
+async fn apply_stats_baseline(tunnel_id: &str, tx_bytes: u64, rx_bytes: u64) -> (u64, u64) {
+    match STATS_BASELINES.lock().await.get(tunnel_id) {
+        Some((base_tx, base_rx)) => (
+            tx_bytes.saturating_sub(*base_tx),
+            rx_bytes.saturating_sub(*base_rx),
+        ),
+        None => (tx_bytes, rx_bytes),
+    }
+}
+
+// 重置隧道的流量统计 (只记录基线,不重建接口)
+#[tauri::command]
+pub async fn reset_tunnel_stats(tunnel_id: String) -> Result<(), String> {
+    let is_running = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.contains_key(&tunnel_id)
+    };
+    if !is_running {
+        return Err("隧道未运行".to_string());
+    }
+
+    let interface_name = generate_interface_name(&tunnel_id);
+    let (tx_bytes, rx_bytes, _) = get_tunnel_status_impl(&tunnel_id, &interface_name).await;
+
+    STATS_BASELINES
+        .lock()
+        .await
+        .insert(tunnel_id.clone(), (tx_bytes, rx_bytes));
+
+    log::info!(
+        "隧道 {} 流量统计已重置 (基线 tx={}, rx={})",
+        tunnel_id,
+        tx_bytes,
+        rx_bytes
+    );
+    Ok(())
+}
+
 // 获取隧道列表 (已废弃,使用 get_all_tunnel_configs 替代)
 // 保留此函数以保持向后兼容
 #[tauri::command]
@@ -702,9 +1627,13 @@ pub async fn get_tunnel_details(
     let content =
         std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
 
-    let tunnel_config: TunnelConfig =
+    let mut tunnel_config: TunnelConfig =
         serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
 
+    if let Err(e) = crate::keychain::resolve_private_key(&mut tunnel_config) {
+        log::warn!("{}", e);
+    }
+
     // 检查隧道是否在运行
     let is_in_process_list = {
         let processes = TUNNEL_PROCESSES.lock().await;
@@ -718,7 +1647,9 @@ pub async fn get_tunnel_details(
 
     // 如果运行中,获取实时状态
     let (tx_bytes, rx_bytes, last_handshake) = if is_running {
-        get_tunnel_status_impl(&tunnel_id, &interface_name).await
+        let (tx, rx, handshake) = get_tunnel_status_impl(&tunnel_id, &interface_name).await;
+        let (tx, rx) = apply_stats_baseline(&tunnel_id, tx, rx).await;
+        (tx, rx, handshake)
     } else {
         (0, 0, None)
     };
@@ -746,7 +1677,9 @@ pub async fn get_tunnel_details(
     };
 
     // 计算公钥 (如果有私钥的话)
-    let public_key = if !tunnel_config.private_key.is_empty() {
+    let public_key = if !tunnel_config.private_key.is_empty()
+        && !crate::keychain::is_keychain_ref(&tunnel_config.private_key)
+    {
         private_key_to_public(tunnel_config.private_key.clone()).ok()
     } else {
         None
@@ -877,6 +1810,8 @@ pub async fn get_tunnel_details(
         server_allowed_ips: tunnel_config.server_allowed_ips.clone(),
         peers: peers_with_stats,
         interface_name,
+        auto_start: tunnel_config.auto_start,
+        reconnect_on_network_change: tunnel_config.reconnect_on_network_change,
     })
 }
 
@@ -886,7 +1821,8 @@ pub async fn get_tunnel_details(
 #[tauri::command]
 pub async fn save_tunnel_config(
     app: tauri::AppHandle,
-    config: TunnelConfig,
+    mut config: TunnelConfig,
+    allow_duplicate: Option<bool>,
 ) -> Result<(), String> {
     let app_data_dir = app
         .path()
@@ -896,75 +1832,722 @@ pub async fn save_tunnel_config(
     let tunnels_dir = app_data_dir.join("tunnels");
     std::fs::create_dir_all(&tunnels_dir).map_err(|e| format!("创建隧道目录失败: {}", e))?;
 
+    // 规范化密钥并提前校验 endpoint 端口,避免启动时才在 UAPI 阶段失败
+    normalize_tunnel_keys(&mut config)?;
+    validate_tunnel_endpoints(&config)?;
+    validate_tunnel_keepalive(&config)?;
+    validate_tunnel_routing(&config)?;
+    validate_tunnel_dns(&config)?;
+    if !allow_duplicate.unwrap_or(false) {
+        check_tunnel_name_unique(&tunnels_dir, &config)?;
+    }
+    for warning in config_warnings(&app, &config).await {
+        log::warn!("隧道 {} 配置警告: {}", config.name, warning);
+    }
+
     let file_path = tunnels_dir.join(format!("{}.json", config.id));
+    let saved: Option<TunnelConfig> = std::fs::read_to_string(&file_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    // 表单不包含接口名称,沿用已保存的名称;新建隧道分配不冲突的名称
+    if config.interface_name.is_none() {
+        config.interface_name = match &saved {
+            Some(saved) => saved.interface_name.clone(),
+            None => Some(allocate_interface_name(&tunnels_dir, &config.id)?),
+        };
+    }
+
+    // 关闭钥匙串存储时取回明文私钥,写入配置后再删除钥匙串条目
+    let remove_from_keychain = !config.store_key_in_keychain
+        && saved
+            .as_ref()
+            .is_some_and(|saved| crate::keychain::is_keychain_ref(&saved.private_key));
+    if !config.store_key_in_keychain {
+        crate::keychain::resolve_private_key(&mut config)?;
+    }
+
+    // 按需将私钥转存到系统钥匙串
+    crate::keychain::store_private_key(&mut config);
+
     let json =
         serde_json::to_string_pretty(&config).map_err(|e| format!("序列化隧道配置失败: {}", e))?;
 
     std::fs::write(&file_path, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+    register_interface_name(&config);
+    if remove_from_keychain {
+        crate::keychain::delete_private_key(&config.id);
+    }
+
+    // 配置变更后更新自动启动使用的配置副本
+    if config.auto_start {
+        if let Err(e) = sync_tunnel_autostart(&app, &config.id, true).await {
+            log::warn!("更新隧道 {} 的自动启动配置失败: {}", config.name, e);
+        }
+    }
 
     Ok(())
 }
 
-// 获取隧道完整配置(用于编辑)
-#[tauri::command]
-pub async fn get_tunnel_config(
-    app: tauri::AppHandle,
-    tunnel_id: String,
-) -> Result<TunnelConfig, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+// 隧道最后可用配置快照的文件名后缀 (tunnels/<id>.lkg.json)
+pub const LKG_SUFFIX: &str = ".lkg.json";
 
-    let config_file = app_data_dir
-        .join("tunnels")
-        .join(format!("{}.json", tunnel_id));
+// 是否为隧道配置文件 (排除最后可用配置快照)
+pub fn is_tunnel_config_file(path: &std::path::Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(".json") && !n.ends_with(LKG_SUFFIX))
+}
 
-    if !config_file.exists() {
-        return Err("隧道配置不存在".to_string());
+// 检查隧道名称是否与其他隧道重复
+// 名称未修改时不检查,避免已存在的重名配置无法更新
+fn check_tunnel_name_unique(
+    tunnels_dir: &std::path::Path,
+    config: &TunnelConfig,
+) -> Result<(), String> {
+    let name = config.name.trim();
+    let Ok(entries) = std::fs::read_dir(tunnels_dir) else {
+        return Ok(());
+    };
+
+    let existing: Vec<TunnelConfig> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_tunnel_config_file(path))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    if existing
+        .iter()
+        .any(|t| t.id == config.id && t.name.trim() == name)
+    {
+        return Ok(());
     }
 
-    let content =
-        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    match existing
+        .iter()
+        .find(|t| t.id != config.id && t.name.trim() == name)
+    {
+        Some(other) => Err(format!("已存在同名隧道: {} (ID: {})", name, other.id)),
+        None => Ok(()),
+    }
+}
 
-    let tunnel_config: TunnelConfig =
-        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+// 解析标准 wg-quick .conf 内容为隧道配置
+// 支持多个 [Peer] 段,忽略注释、空行以及 PostUp/Table 等应用不使用的字段
+pub fn parse_wg_quick_conf(content: &str, name: &str) -> Result<TunnelConfig, String> {
+    #[derive(PartialEq)]
+    enum Section {
+        None,
+        Interface,
+        Peer,
+    }
 
-    Ok(tunnel_config)
-}
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut config = TunnelConfig {
+        id: now.to_string(),
+        name: name.trim().to_string(),
+        mode: String::new(),
+        private_key: String::new(),
+        store_key_in_keychain: false,
+        interface_name: None,
+        address: String::new(),
+        listen_port: String::new(),
+        dns: String::new(),
+        dns_search: None,
+        mtu: String::new(),
+        route_table: None,
+        fwmark: None,
+        kill_switch: false,
+        auto_start: false,
+        reconnect_on_network_change: false,
+        server_endpoint: String::new(),
+        server_allowed_ips: String::new(),
+        peers: Vec::new(),
+        peer_public_key: String::new(),
+        preshared_key: String::new(),
+        endpoint: String::new(),
+        allowed_ips: String::new(),
+        persistent_keepalive: String::new(),
+        created_at: now,
+    };
+    if config.name.is_empty() {
+        return Err("隧道名称不能为空".to_string());
+    }
 
-// 删除隧道配置
-#[tauri::command]
-pub async fn delete_tunnel_config(app: tauri::AppHandle, tunnel_id: String) -> Result<(), String> {
-    // 确保隧道未运行
-    {
-        let processes = TUNNEL_PROCESSES.lock().await;
-        if processes.contains_key(&tunnel_id) {
-            return Err("请先停止隧道再删除配置".to_string());
+    let mut section = Section::None;
+    let mut has_interface = false;
+
+    // 同一字段出现多次时 (如多行 Address) 按 wg-quick 的规则合并
+    fn append(target: &mut String, value: &str) {
+        if !target.is_empty() {
+            target.push_str(", ");
         }
+        target.push_str(value);
     }
 
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+    // 紧挨 [Peer] 的注释 (上一行或同一行) 作为 Peer 名称
+    let mut pending_name: Option<String> = None;
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        let comment = raw_line
+            .split_once('#')
+            .and_then(|(_, c)| peer_name_from_comment(c));
+        if line.is_empty() {
+            pending_name = comment;
+            // [Peer] 下一行的注释
+            if section == Section::Peer {
+                if let Some(peer) = config.peers.last_mut() {
+                    if peer.name.is_none() && peer.public_key.is_empty() {
+                        peer.name = pending_name.take();
+                    }
+                }
+            }
+            continue;
+        }
 
-    let file_path = app_data_dir
-        .join("tunnels")
-        .join(format!("{}.json", tunnel_id));
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if comment.is_some() {
+                pending_name = comment;
+            }
+            section = match header.trim().to_ascii_lowercase().as_str() {
+                "interface" if has_interface => {
+                    return Err(format!("第 {} 行: [Interface] 只能出现一次", line_no));
+                }
+                "interface" => {
+                    has_interface = true;
+                    Section::Interface
+                }
+                "peer" => {
+                    config.peers.push(TunnelPeerConfig {
+                        public_key: String::new(),
+                        client_private_key: None,
+                        preshared_key: None,
+                        endpoint: None,
+                        address: None,
+                        allowed_ips: String::new(),
+                        persistent_keepalive: None,
+                        remark: None,
+                        name: pending_name.take(),
+                        tx_bytes: 0,
+                        rx_bytes: 0,
+                        last_handshake: None,
+                        expires_at: None,
+                    });
+                    Section::Peer
+                }
+                _ => return Err(format!("第 {} 行: 未知的配置段 {}", line_no, line)),
+            };
+            pending_name = None;
+            continue;
+        }
+        pending_name = None;
+
+        let (key, value) = line
+            .split_once('=')
+            .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim()))
+            .ok_or_else(|| format!("第 {} 行: 格式错误,应为 键 = 值", line_no))?;
+        let invalid = |field: &str| format!("第 {} 行: {} 无效: {}", line_no, field, value);
+
+        match section {
+            Section::None => {
+                return Err(format!(
+                    "第 {} 行: 配置项必须位于 [Interface] 或 [Peer] 段内",
+                    line_no
+                ));
+            }
+            Section::Interface => match key.as_str() {
+                "privatekey" => config.private_key = value.to_string(),
+                "address" => append(&mut config.address, value),
+                "listenport" => {
+                    value.parse::<u16>().map_err(|_| invalid("ListenPort"))?;
+                    config.listen_port = value.to_string();
+                }
+                "dns" => {
+                    let (servers, domains) = split_dns_line(value);
+                    if !servers.is_empty() {
+                        append(&mut config.dns, &servers.join(", "));
+                    }
+                    if !domains.is_empty() {
+                        let search = config.dns_search.get_or_insert_with(String::new);
+                        append(search, &domains.join(", "));
+                    }
+                }
+                "mtu" => {
+                    value.parse::<u16>().map_err(|_| invalid("MTU"))?;
+                    config.mtu = value.to_string();
+                }
+                // Table = off / auto 时使用默认行为
+                "table" if value == "off" || value == "auto" => config.route_table = None,
+                "table" => config.route_table = Some(value.parse().map_err(|_| invalid("Table"))?),
+                "fwmark" if value == "off" => config.fwmark = None,
+                "fwmark" => {
+                    let mark = match value.strip_prefix("0x") {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => value.parse(),
+                    };
+                    config.fwmark = Some(mark.map_err(|_| invalid("FwMark"))?);
+                }
+                _ => log::info!("导入配置: 忽略 Interface 字段 {} (第 {} 行)", key, line_no),
+            },
+            Section::Peer => {
+                let peer = config.peers.last_mut().expect("Peer 段已创建");
+                match key.as_str() {
+                    "publickey" => peer.public_key = value.to_string(),
+                    "presharedkey" => peer.preshared_key = Some(value.to_string()),
+                    "endpoint" => {
+                        parse_endpoint_port(value)
+                            .map_err(|e| format!("第 {} 行: {}", line_no, e))?;
+                        peer.endpoint = Some(value.to_string());
+                    }
+                    "allowedips" => append(&mut peer.allowed_ips, value),
+                    "persistentkeepalive" => {
+                        // wg-quick 中 off 表示关闭
+                        peer.persistent_keepalive = if value.eq_ignore_ascii_case("off") {
+                            None
+                        } else {
+                            parse_keepalive(value)
+                                .map_err(|e| format!("第 {} 行: {}", line_no, e))?
+                        };
+                    }
+                    _ => log::info!("导入配置: 忽略 Peer 字段 {} (第 {} 行)", key, line_no),
+                }
+            }
+        }
+    }
 
-    if file_path.exists() {
-        std::fs::remove_file(&file_path).map_err(|e| format!("删除隧道配置失败: {}", e))?;
+    if !has_interface {
+        return Err("配置中缺少 [Interface] 段".to_string());
+    }
+    if config.private_key.is_empty() {
+        return Err("[Interface] 中缺少 PrivateKey".to_string());
+    }
+    if let Some(index) = config.peers.iter().position(|p| p.public_key.is_empty()) {
+        return Err(format!("第 {} 个 [Peer] 缺少 PublicKey", index + 1));
     }
 
-    Ok(())
+    // 与前端导入逻辑一致: 多个 Peer 视为服务端,否则为客户端
+    if config.peers.len() > 1 {
+        config.mode = "server".to_string();
+        config.server_allowed_ips = "0.0.0.0/0".to_string();
+    } else {
+        config.mode = "client".to_string();
+    }
+    if config.mtu.is_empty() {
+        config.mtu = "1420".to_string();
+    }
+
+    Ok(config)
 }
 
-// 获取所有隧道配置列表 (包括运行和停止的)
-#[tauri::command]
-pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelStatus>, String> {
-    let app_data_dir = app
-        .path()
+// 从注释中提取 Peer 名称,支持 "# alice" 和 "# Name = alice" 两种写法
+// 被注释掉的配置项 (如 "# PersistentKeepalive = 25") 不视为名称
+fn peer_name_from_comment(comment: &str) -> Option<String> {
+    let comment = comment.trim().trim_start_matches('#').trim();
+    let name = match comment.split_once(['=', ':']) {
+        Some((key, value))
+            if matches!(
+                key.trim().to_ascii_lowercase().as_str(),
+                "name" | "friendlyname" | "friendly_name"
+            ) =>
+        {
+            value.trim()
+        }
+        _ if comment.contains('=') => return None,
+        _ => comment,
+    };
+    Some(name.to_string()).filter(|n| !n.is_empty())
+}
+
+// 从 .conf 内容导入隧道,生成新的 id 并保存
+#[tauri::command]
+pub async fn import_tunnel_from_conf(
+    app: tauri::AppHandle,
+    conf_content: String,
+    name: String,
+) -> Result<TunnelConfig, String> {
+    let config = parse_wg_quick_conf(&conf_content, &name)?;
+    save_tunnel_config(app, config.clone(), None).await?;
+    log::info!(
+        "已从 .conf 导入隧道: {} ({} 个 Peer)",
+        config.name,
+        config.peers.len()
+    );
+    Ok(config)
+}
+
+// 复制隧道配置,生成新的 ID 和创建时间,返回新隧道的 ID
+// regenerate_keys 为 true 时为副本生成新的接口密钥对,对端需要改用新的公钥
+#[tauri::command]
+pub async fn duplicate_tunnel_config(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    new_name: String,
+    regenerate_keys: Option<bool>,
+) -> Result<String, String> {
+    let mut config = get_tunnel_config(app.clone(), tunnel_id).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    config.id = now.to_string();
+    config.interface_name = None;
+    config.name = new_name.trim().to_string();
+    config.created_at = now;
+    // 开机自启需要单独注册,副本默认不启用
+    config.auto_start = false;
+
+    if config.name.is_empty() {
+        return Err("隧道名称不能为空".to_string());
+    }
+
+    if regenerate_keys.unwrap_or(false) {
+        let keypair = crate::commands::key_management::generate_keypair()?;
+        config.private_key = keypair.private_key;
+        log::info!(
+            "隧道副本 {} 使用新的公钥: {}",
+            config.name,
+            keypair.public_key
+        );
+    }
+
+    let new_id = config.id.clone();
+    save_tunnel_config(app, config, None).await?;
+
+    log::info!("已复制隧道配置: {}", new_id);
+    Ok(new_id)
+}
+
+// 判断 Peer 是否已过期
+pub fn is_peer_expired(expires_at: Option<i64>) -> bool {
+    expires_at.is_some_and(|t| t <= chrono::Utc::now().timestamp_millis())
+}
+
+// 启动 Peer 过期检查任务,每分钟检查一次
+pub fn start_peer_expiry_task(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            enforce_peer_expiry(&app).await;
+        }
+    });
+}
+
+// 从运行中的服务端隧道移除已过期的 Peer,并标记过期的历史记录
+async fn enforce_peer_expiry(app: &tauri::AppHandle) {
+    let running: Vec<String> = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.keys().cloned().collect()
+    };
+
+    for tunnel_id in running {
+        let tunnel_config = match get_tunnel_config(app.clone(), tunnel_id.clone()).await {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("过期检查读取隧道配置失败: {}: {}", tunnel_id, e);
+                continue;
+            }
+        };
+
+        if tunnel_config.mode != "server" {
+            continue;
+        }
+
+        let interface_name = generate_interface_name(&tunnel_id);
+        for peer in &tunnel_config.peers {
+            let key = format!("{}:{}", tunnel_id, peer.public_key);
+
+            if !is_peer_expired(peer.expires_at) {
+                // 到期时间被延长后允许再次移除
+                REMOVED_EXPIRED_PEERS.lock().await.remove(&key);
+                continue;
+            }
+
+            if REMOVED_EXPIRED_PEERS.lock().await.contains(&key) {
+                continue;
+            }
+
+            match remove_peer(&tunnel_id, &interface_name, &peer.public_key).await {
+                Ok(()) => {
+                    log::info!(
+                        "已移除过期 Peer: 隧道 {}, Peer {}",
+                        tunnel_config.name,
+                        peer.public_key
+                    );
+                    REMOVED_EXPIRED_PEERS.lock().await.insert(key);
+
+                    // 同步移除保存的运行配置,避免 endpoint 刷新任务重新添加该 Peer
+                    let mut configs = TUNNEL_CONFIGS.lock().await;
+                    if let Some((_, config)) = configs.get_mut(&tunnel_id) {
+                        config.peers.retain(|p| p.public_key != peer.public_key);
+                    }
+                }
+                Err(e) => log::warn!("移除过期 Peer 失败: {}", e),
+            }
+        }
+    }
+
+    if let Err(e) = crate::commands::history_service::mark_expired_history(app) {
+        log::warn!("标记过期历史记录失败: {}", e);
+    }
+}
+
+// 握手超过该时长(秒)视为连接失效
+const HANDSHAKE_STALE_SECS: i64 = 180;
+
+// 每个隧道最多保留的握手记录数 (正常约 2 分钟一次握手,约可覆盖 1 天)
+const HANDSHAKE_HISTORY_LIMIT: usize = 720;
+
+#[derive(Clone, Serialize)]
+struct TunnelStatusChanged {
+    tunnel_id: String,
+    status: String, // running, connecting, stale, stopped
+    last_handshake: Option<i64>,
+}
+
+// 启动隧道状态监控任务,每 10 秒检查一次运行中的隧道,
+// 状态变化时推送 tunnel-status-changed 事件,前端无需轮询
+pub fn start_tunnel_status_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
+        // 上次推送的状态,只保存仍在进程列表中的隧道
+        let mut last_status: HashMap<String, String> = HashMap::new();
+        loop {
+            interval.tick().await;
+            check_tunnel_status_changes(&app, &mut last_status).await;
+        }
+    });
+}
+
+async fn check_tunnel_status_changes(
+    app: &tauri::AppHandle,
+    last_status: &mut HashMap<String, String>,
+) {
+    let running: Vec<String> = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.keys().cloned().collect()
+    };
+
+    // 已停止或已删除的隧道: 推送一次 stopped 后不再跟踪
+    let gone: Vec<String> = last_status
+        .keys()
+        .filter(|id| !running.contains(id))
+        .cloned()
+        .collect();
+    for tunnel_id in gone {
+        if last_status.remove(&tunnel_id).as_deref() != Some("stopped") {
+            emit_tunnel_status(app, &tunnel_id, "stopped", None);
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for tunnel_id in running {
+        let interface_name = generate_interface_name(&tunnel_id);
+        let (status, last_handshake) = if !interface_exists(&interface_name) {
+            // 进程已退出但未通过 stop_tunnel 停止
+            ("stopped", None)
+        } else {
+            let (_, _, last_handshake) = get_tunnel_status_impl(&tunnel_id, &interface_name).await;
+            if let Some(ts) = last_handshake.filter(|ts| *ts > 0) {
+                record_handshake(&tunnel_id, ts).await;
+            }
+            let status = match last_handshake {
+                None | Some(0) => "connecting",
+                Some(ts) if now - ts > HANDSHAKE_STALE_SECS => "stale",
+                Some(_) => "running",
+            };
+            (status, last_handshake)
+        };
+
+        if last_status.get(&tunnel_id).map(String::as_str) != Some(status) {
+            log::info!("隧道 {} 状态变化: {}", tunnel_id, status);
+            emit_tunnel_status(app, &tunnel_id, status, last_handshake);
+            last_status.insert(tunnel_id, status.to_string());
+        }
+    }
+}
+
+// 记录新的握手时间戳 (与上一条相同时忽略)
+async fn record_handshake(tunnel_id: &str, timestamp: i64) {
+    let mut history = HANDSHAKE_HISTORY.lock().await;
+    let entries = history.entry(tunnel_id.to_string()).or_default();
+    if entries.back().is_some_and(|last| *last >= timestamp) {
+        return;
+    }
+    entries.push_back(timestamp);
+    while entries.len() > HANDSHAKE_HISTORY_LIMIT {
+        entries.pop_front();
+    }
+}
+
+#[derive(Serialize)]
+pub struct TunnelReliability {
+    pub handshake_gaps: Vec<i64>, // 相邻两次握手的间隔 (秒)
+    pub max_gap: i64,
+    pub avg_gap: f64,
+}
+
+// 统计最近 window_secs 秒内相邻握手的间隔,用于量化连接是否频繁中断
+// 数据来自状态监控任务的采样 (10 秒一次),间隔明显超过 2 分钟说明握手出现停滞
+#[tauri::command]
+pub async fn get_tunnel_reliability(
+    tunnel_id: String,
+    window_secs: u64,
+) -> Result<TunnelReliability, String> {
+    let since = chrono::Utc::now().timestamp() - window_secs as i64;
+    let timestamps: Vec<i64> = {
+        let history = HANDSHAKE_HISTORY.lock().await;
+        let entries = history
+            .get(&tunnel_id)
+            .ok_or_else(|| "暂无该隧道的握手记录，请确认隧道已启动".to_string())?;
+        entries.iter().copied().filter(|ts| *ts >= since).collect()
+    };
+
+    let handshake_gaps: Vec<i64> = timestamps.windows(2).map(|w| w[1] - w[0]).collect();
+    let max_gap = handshake_gaps.iter().copied().max().unwrap_or(0);
+    let avg_gap = if handshake_gaps.is_empty() {
+        0.0
+    } else {
+        handshake_gaps.iter().sum::<i64>() as f64 / handshake_gaps.len() as f64
+    };
+
+    Ok(TunnelReliability {
+        handshake_gaps,
+        max_gap,
+        avg_gap,
+    })
+}
+
+fn emit_tunnel_status(
+    app: &tauri::AppHandle,
+    tunnel_id: &str,
+    status: &str,
+    last_handshake: Option<i64>,
+) {
+    let payload = TunnelStatusChanged {
+        tunnel_id: tunnel_id.to_string(),
+        status: status.to_string(),
+        last_handshake,
+    };
+    if let Err(e) = app.emit("tunnel-status-changed", payload) {
+        log::warn!("推送隧道状态变化失败: {}", e);
+    }
+}
+
+// 获取隧道完整配置(用于编辑)
+#[tauri::command]
+pub async fn get_tunnel_config(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+) -> Result<TunnelConfig, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    crate::keychain::resolve_private_key(&mut tunnel_config)?;
+
+    Ok(tunnel_config)
+}
+
+// 将已保存的隧道导出为 wg-quick 格式的 .conf 内容 (已过期的 Peer 不会导出)
+#[tauri::command]
+pub async fn export_tunnel_to_conf(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+) -> Result<String, String> {
+    let tunnel_config = get_tunnel_config(app, tunnel_id).await?;
+    let interface_config = build_interface_config(&tunnel_config, true);
+
+    let line_ending = if cfg!(target_os = "windows") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    Ok(build_wg_quick_config(
+        &tunnel_config,
+        &interface_config,
+        line_ending,
+    ))
+}
+
+// 删除隧道配置
+#[tauri::command]
+pub async fn delete_tunnel_config(app: tauri::AppHandle, tunnel_id: String) -> Result<(), String> {
+    // 确保隧道未运行
+    {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        if processes.contains_key(&tunnel_id) {
+            return Err("请先停止隧道再删除配置".to_string());
+        }
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let file_path = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    let mut was_autostart = false;
+    if file_path.exists() {
+        // 清理钥匙串中的私钥
+        if let Ok(content) = std::fs::read_to_string(&file_path) {
+            if let Ok(tunnel_config) = serde_json::from_str::<TunnelConfig>(&content) {
+                if crate::keychain::is_keychain_ref(&tunnel_config.private_key) {
+                    crate::keychain::delete_private_key(&tunnel_config.id);
+                }
+                was_autostart = tunnel_config.auto_start;
+            }
+        }
+
+        std::fs::remove_file(&file_path).map_err(|e| format!("删除隧道配置失败: {}", e))?;
+    }
+
+    // 移除自动启动的配置副本或登录项
+    if was_autostart {
+        if let Err(e) = sync_tunnel_autostart(&app, &tunnel_id, false).await {
+            log::warn!("取消隧道 {} 的开机自动启动失败: {}", tunnel_id, e);
+        }
+    }
+
+    let lkg_path = app_data_dir
+        .join("tunnels")
+        .join(format!("{}{}", tunnel_id, LKG_SUFFIX));
+    if lkg_path.exists() {
+        if let Err(e) = std::fs::remove_file(&lkg_path) {
+            log::warn!("删除最后可用配置失败: {}", e);
+        }
+    }
+
+    HANDSHAKE_HISTORY.lock().await.remove(&tunnel_id);
+
+    Ok(())
+}
+
+// 获取所有隧道配置列表 (包括运行和停止的)
+#[tauri::command]
+pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelStatus>, String> {
+    let app_data_dir = app
+        .path()
         .app_data_dir()
         .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
 
@@ -979,6 +2562,10 @@ pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelS
 
     let mut tunnels = Vec::new();
 
+    // GUI 重启后进程列表为空,先登记守护进程中仍在运行的隧道
+    #[cfg(target_os = "linux")]
+    crate::tunnel_linux::sync_daemon_state_once().await;
+
     // 获取运行中的隧道 ID 列表
     let running_tunnels: Vec<String> = {
         let processes = TUNNEL_PROCESSES.lock().await;
@@ -992,7 +2579,7 @@ pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelS
     for entry in entries {
         if let Ok(entry) = entry {
             let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            if is_tunnel_config_file(&path) {
                 if let Ok(content) = std::fs::read_to_string(&path) {
                     match serde_json::from_str::<TunnelConfig>(&content) {
                         Ok(tunnel_config) => {
@@ -1011,7 +2598,12 @@ pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelS
                             let is_running = is_in_process_list || interface_exists;
 
                             let (tx_bytes, rx_bytes, last_handshake) = if is_running {
-                                get_tunnel_status_impl(&tunnel_config.id, &interface_name).await
+                                let (tx, rx, handshake) =
+                                    get_tunnel_status_impl(&tunnel_config.id, &interface_name)
+                                        .await;
+                                let (tx, rx) =
+                                    apply_stats_baseline(&tunnel_config.id, tx, rx).await;
+                                (tx, rx, handshake)
                             } else {
                                 (0, 0, None)
                             };
@@ -1061,6 +2653,9 @@ pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelS
                                 server_allowed_ips: tunnel_config.server_allowed_ips.clone(),
                                 peers: tunnel_config.peers.clone(),
                                 interface_name: interface_name.clone(),
+                                auto_start: tunnel_config.auto_start,
+                                reconnect_on_network_change: tunnel_config
+                                    .reconnect_on_network_change,
                             };
 
                             tunnels.push(tunnel_status);
@@ -1080,6 +2675,1040 @@ pub async fn get_all_tunnel_configs(app: tauri::AppHandle) -> Result<Vec<TunnelS
     Ok(tunnels)
 }
 
+// 转义 HTML 特殊字符
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// 格式化字节数
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
+
+// 格式化距上次握手的时间
+fn format_handshake_age(last_handshake: Option<i64>, now: i64) -> String {
+    let Some(ts) = last_handshake.filter(|ts| *ts > 0) else {
+        return "从未".to_string();
+    };
+    let diff = (now - ts).max(0);
+    match diff {
+        0..=119 => format!("{} 秒前", diff),
+        120..=3599 => format!("{} 分钟前", diff / 60),
+        3600..=86399 => format!("{} 小时前", diff / 3600),
+        _ => format!("{} 天前", diff / 86400),
+    }
+}
+
+// 导出隧道状态的静态 HTML 页面,便于分享给没有安装应用的同事
+// 只包含名称、运行状态、握手时间和流量,不包含地址、密钥等信息
+#[tauri::command]
+pub async fn export_status_html(app: tauri::AppHandle) -> Result<String, String> {
+    let tunnels = get_all_tunnel_configs(app).await?;
+    let now = chrono::Local::now();
+
+    let rows: String = tunnels
+        .iter()
+        .map(|tunnel| {
+            let (status_class, status_text) = match tunnel.status.as_str() {
+                "running" => ("up", "运行中"),
+                "stopped" => ("down", "已停止"),
+                _ => ("pending", "连接中"),
+            };
+            let running = tunnel.status == "running";
+            format!(
+                "<tr><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&tunnel.name),
+                if tunnel.mode == "client" { "客户端" } else { "服务端" },
+                status_class,
+                status_text,
+                if running {
+                    format_handshake_age(tunnel.last_handshake, now.timestamp())
+                } else {
+                    "-".to_string()
+                },
+                if running { format_bytes(tunnel.tx_bytes) } else { "-".to_string() },
+                if running { format_bytes(tunnel.rx_bytes) } else { "-".to_string() },
+            )
+        })
+        .collect();
+
+    let running_count = tunnels.iter().filter(|t| t.status == "running").count();
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>WireVault 隧道状态</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", "PingFang SC", sans-serif; margin: 2rem; color: #333; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border: 1px solid #ddd; padding: 0.5rem 0.75rem; text-align: left; }}
+th {{ background: #f5f5f5; }}
+.up {{ color: #27ae60; font-weight: bold; }}
+.down {{ color: #c0392b; }}
+.pending {{ color: #f39c12; }}
+.meta {{ color: #888; font-size: 0.9rem; }}
+</style>
+</head>
+<body>
+<h1>WireVault 隧道状态</h1>
+<p class="meta">生成时间: {} · 共 {} 个隧道，{} 个运行中</p>
+<table>
+<thead><tr><th>名称</th><th>模式</th><th>状态</th><th>最后握手</th><th>上传</th><th>下载</th></tr></thead>
+<tbody>
+{}</tbody>
+</table>
+</body>
+</html>
+"#,
+        now.format("%Y-%m-%d %H:%M:%S"),
+        tunnels.len(),
+        running_count,
+        rows
+    ))
+}
+
+// 将旧格式的单个 Peer 字段批量迁移为 peers 数组，返回迁移的隧道数量
+#[tauri::command]
+pub async fn migrate_all_tunnels_to_peers_array(app: tauri::AppHandle) -> Result<usize, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let tunnels_dir = app_data_dir.join("tunnels");
+    if !tunnels_dir.exists() {
+        return Ok(0);
+    }
+
+    let entries =
+        std::fs::read_dir(&tunnels_dir).map_err(|e| format!("读取隧道目录失败: {}", e))?;
+
+    let mut migrated = 0;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !is_tunnel_config_file(&path) {
+            continue;
+        }
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::warn!("读取配置失败: {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        let mut tunnel_config: TunnelConfig = match serde_json::from_str(&content) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("解析配置失败: {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        // 已使用新格式或没有旧 Peer 数据的配置无需迁移
+        if !tunnel_config.peers.is_empty() || tunnel_config.peer_public_key.is_empty() {
+            continue;
+        }
+
+        let non_empty = |s: &str| {
+            if s.trim().is_empty() {
+                None
+            } else {
+                Some(s.trim().to_string())
+            }
+        };
+
+        tunnel_config.peers.push(TunnelPeerConfig {
+            public_key: tunnel_config.peer_public_key.trim().to_string(),
+            client_private_key: None,
+            preshared_key: non_empty(&tunnel_config.preshared_key),
+            endpoint: non_empty(&tunnel_config.endpoint),
+            address: None,
+            allowed_ips: tunnel_config.allowed_ips.trim().to_string(),
+            persistent_keepalive: tunnel_config.persistent_keepalive.trim().parse().ok(),
+            remark: None,
+            name: None,
+            tx_bytes: 0,
+            rx_bytes: 0,
+            last_handshake: None,
+            expires_at: None,
+        });
+
+        if let Err(e) = validate_tunnel_endpoints(&tunnel_config) {
+            log::warn!("迁移后的配置校验失败: id={}: {}", tunnel_config.id, e);
+        }
+
+        // 清空旧字段
+        tunnel_config.peer_public_key.clear();
+        tunnel_config.preshared_key.clear();
+        tunnel_config.endpoint.clear();
+        tunnel_config.allowed_ips.clear();
+        tunnel_config.persistent_keepalive.clear();
+
+        let json = serde_json::to_string_pretty(&tunnel_config)
+            .map_err(|e| format!("序列化隧道配置失败: {}", e))?;
+        std::fs::write(&path, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+
+        log::info!("已迁移隧道配置: id={}", tunnel_config.id);
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+// 在线修改隧道监听端口(运行中的隧道无需重启)
+#[tauri::command]
+pub async fn update_listen_port(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    port: u16,
+) -> Result<(), String> {
+    if port == 0 {
+        return Err("监听端口必须在 1-65535 之间".to_string());
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    if tunnel_config.listen_port.trim() == port.to_string() {
+        return Ok(());
+    }
+
+    // 检查端口是否被占用,尽量给出占用端口的进程
+    if let Some(conflict) = crate::commands::diagnostics::listen_port_conflict(port) {
+        return Err(conflict);
+    }
+
+    let is_running = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.contains_key(&tunnel_id)
+    };
+
+    if is_running {
+        let interface_name = generate_interface_name(&tunnel_id);
+        set_listen_port(&tunnel_id, &interface_name, port).await?;
+
+        // 同步更新运行时配置(endpoint 刷新任务会重新下发该配置)
+        let mut configs = TUNNEL_CONFIGS.lock().await;
+        if let Some((_, interface_config)) = configs.get_mut(&tunnel_id) {
+            interface_config.listen_port = Some(port);
+        }
+
+        log::info!("隧道 {} 监听端口已在线修改为 {}", tunnel_id, port);
+    }
+
+    tunnel_config.listen_port = port.to_string();
+    let json = serde_json::to_string_pretty(&tunnel_config)
+        .map_err(|e| format!("序列化隧道配置失败: {}", e))?;
+    std::fs::write(&config_file, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+
+    Ok(())
+}
+
+// 读取所有标记为开机自动启动的隧道 ID
+#[cfg(target_os = "macos")]
+fn autostart_tunnel_ids(app: &tauri::AppHandle) -> Vec<String> {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(app_data_dir.join("tunnels")) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_tunnel_config_file(path))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str::<TunnelConfig>(&content).ok())
+        .filter(|config| config.auto_start)
+        .map(|config| config.id)
+        .collect()
+}
+
+// 将隧道的开机自动启动设置同步到系统
+// Linux: 守护进程保存一份配置副本,开机后由守护进程直接启动隧道
+// macOS: 有隧道需要自动启动时注册登录项,登录时以 --autostart 启动应用并拉起这些隧道
+async fn sync_tunnel_autostart(
+    app: &tauri::AppHandle,
+    tunnel_id: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let ipc_config = if enabled {
+            let tunnel_config = get_tunnel_config(app.clone(), tunnel_id.to_string()).await?;
+            Some(crate::tunnel_linux::build_autostart_config(&tunnel_config))
+        } else {
+            None
+        };
+
+        let tunnel_id = tunnel_id.to_string();
+        tokio::task::spawn_blocking(move || {
+            crate::daemon_ipc::IpcClient::set_autostart(&tunnel_id, ipc_config.as_ref())
+        })
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = (tunnel_id, enabled);
+        crate::tunnel_macos::set_autostart_agent(!autostart_tunnel_ids(app).is_empty())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = (app, tunnel_id, enabled);
+        Err("Windows 暂不支持开机自动启动".to_string())
+    }
+}
+
+// 设置隧道开机自动启动,关闭时同时移除守护进程中的配置副本或登录项
+#[tauri::command]
+pub async fn set_tunnel_autostart(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    tunnel_config.auto_start = enabled;
+    let json = serde_json::to_string_pretty(&tunnel_config)
+        .map_err(|e| format!("序列化隧道配置失败: {}", e))?;
+    std::fs::write(&config_file, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+
+    // 同步失败时恢复原配置,避免标记与系统状态不一致
+    if let Err(e) = sync_tunnel_autostart(&app, &tunnel_id, enabled).await {
+        if let Err(restore_err) = std::fs::write(&config_file, &content) {
+            log::error!("恢复隧道配置失败: {}", restore_err);
+        }
+        return Err(format!("设置开机自动启动失败: {}", e));
+    }
+
+    log::info!("隧道 {} 开机自动启动: {}", tunnel_config.name, enabled);
+    Ok(())
+}
+
+// macOS: 由登录项以 --autostart 启动应用时,拉起标记为自动启动的隧道
+#[cfg(target_os = "macos")]
+pub fn start_autostart_tunnels(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        for tunnel_id in autostart_tunnel_ids(&app) {
+            match start_tunnel(tunnel_id.clone(), app.clone()).await {
+                Ok(()) => log::info!("已自动启动隧道 {}", tunnel_id),
+                Err(e) => log::error!("自动启动隧道 {} 失败: {}", tunnel_id, e),
+            }
+        }
+    });
+}
+
+// 设置隧道在网络切换后是否自动重连
+#[tauri::command]
+pub async fn set_reconnect_on_network_change(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    tunnel_config.reconnect_on_network_change = enabled;
+    let json = serde_json::to_string_pretty(&tunnel_config)
+        .map_err(|e| format!("序列化隧道配置失败: {}", e))?;
+    std::fs::write(&config_file, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+
+    log::info!(
+        "隧道 {} 网络切换后自动重连: {}",
+        tunnel_config.name,
+        enabled
+    );
+    Ok(())
+}
+
+// 网络切换后重连标记为自动重连的运行中隧道
+// 不停止隧道,只重新下发配置: 重新解析 endpoint 域名并更新 Peer,
+// 接口、路由和 kill switch 规则保持不变,切换网络期间流量不会绕过隧道
+pub async fn reconnect_tunnels_after_network_change(app: &tauri::AppHandle) {
+    let running: Vec<String> = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.keys().cloned().collect()
+    };
+
+    for tunnel_id in running {
+        let enabled = match get_tunnel_config(app.clone(), tunnel_id.clone()).await {
+            Ok(config) => config.reconnect_on_network_change,
+            Err(e) => {
+                log::warn!("读取隧道 {} 配置失败: {}", tunnel_id, e);
+                false
+            }
+        };
+        if !enabled {
+            continue;
+        }
+
+        log::info!("网络已切换,重新连接隧道 {}", tunnel_id);
+        match apply_tunnel_config(app.clone(), tunnel_id.clone(), Some(false)).await {
+            Ok(()) => log::info!("隧道 {} 已重新连接", tunnel_id),
+            Err(e) => log::error!("网络切换后重新下发隧道 {} 配置失败: {}", tunnel_id, e),
+        }
+    }
+}
+
+// 内置配置方案: 针对不同网络类型的 keepalive / MTU / DNS 推荐值
+#[derive(Serialize, Debug, Clone)]
+pub struct ConfigProfile {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    // None 表示关闭 keepalive
+    pub persistent_keepalive: Option<u16>,
+    pub mtu: u32,
+    // 仅在隧道未配置 DNS 时填入
+    pub dns: Option<&'static str>,
+}
+
+const CONFIG_PROFILES: [ConfigProfile; 4] = [
+    ConfigProfile {
+        id: "mobile",
+        name: "移动网络",
+        description: "4G/5G 等运营商网络,NAT 超时短且路径 MTU 较小",
+        persistent_keepalive: Some(25),
+        mtu: 1280,
+        dns: Some("1.1.1.1, 8.8.8.8"),
+    },
+    ConfigProfile {
+        id: "broadband",
+        name: "家庭宽带",
+        description: "位于路由器 NAT 之后的普通宽带",
+        persistent_keepalive: Some(25),
+        mtu: 1420,
+        dns: None,
+    },
+    ConfigProfile {
+        id: "pppoe",
+        name: "PPPoE 拨号",
+        description: "PPPoE 链路 MTU 为 1492,需相应减小隧道 MTU",
+        persistent_keepalive: Some(25),
+        mtu: 1412,
+        dns: None,
+    },
+    ConfigProfile {
+        id: "lan",
+        name: "局域网",
+        description: "同一局域网内直连,无需保活",
+        persistent_keepalive: None,
+        mtu: 1420,
+        dns: None,
+    },
+];
+
+// 列出内置配置方案
+#[tauri::command]
+pub fn list_config_profiles() -> Vec<ConfigProfile> {
+    CONFIG_PROFILES.to_vec()
+}
+
+// 将配置方案应用到已有隧道并重新保存,运行中的隧道需重启后生效
+#[tauri::command]
+pub async fn apply_config_profile(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    profile: String,
+) -> Result<TunnelConfig, String> {
+    let profile = CONFIG_PROFILES
+        .iter()
+        .find(|p| p.id == profile)
+        .ok_or_else(|| format!("未知的配置方案: {}", profile))?;
+
+    let mut tunnel_config = get_tunnel_config(app.clone(), tunnel_id).await?;
+
+    tunnel_config.mtu = profile.mtu.to_string();
+    if let Some(dns) = profile.dns {
+        if tunnel_config.dns.trim().is_empty() {
+            tunnel_config.dns = dns.to_string();
+        }
+    }
+
+    for peer in &mut tunnel_config.peers {
+        peer.persistent_keepalive = profile.persistent_keepalive;
+    }
+    tunnel_config.persistent_keepalive = profile
+        .persistent_keepalive
+        .map(|k| k.to_string())
+        .unwrap_or_default();
+
+    save_tunnel_config(app, tunnel_config.clone(), None).await?;
+
+    log::info!(
+        "隧道 {} 已应用配置方案 {}",
+        tunnel_config.name,
+        profile.name
+    );
+    Ok(tunnel_config)
+}
+
+// 读取运行中隧道的原始 UAPI get=1 响应,用于排查握手等问题
+// 私钥和预共享密钥会被隐藏
+#[tauri::command]
+pub async fn dump_uapi_state(tunnel_id: String) -> Result<String, String> {
+    let is_running = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.contains_key(&tunnel_id)
+    };
+    if !is_running {
+        return Err("隧道未运行".to_string());
+    }
+
+    let interface_name = generate_interface_name(&tunnel_id);
+    let raw = read_uapi_dump(&tunnel_id, &interface_name)
+        .await
+        .map_err(|e| format!("读取 UAPI 状态失败: {}", e))?;
+
+    Ok(redact_uapi_dump(&raw))
+}
+
+// 获取运行中隧道每个 Peer 的流量和握手信息,并附上配置中的 Peer 名称
+#[tauri::command]
+pub async fn get_tunnel_peer_stats(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+) -> Result<Vec<PeerStats>, String> {
+    let is_running = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.contains_key(&tunnel_id)
+    };
+    if !is_running {
+        return Err("隧道未运行".to_string());
+    }
+
+    let interface_name = generate_interface_name(&tunnel_id);
+    let mut stats = get_peer_stats_impl(&tunnel_id, &interface_name).await;
+
+    if let Ok(config) = get_tunnel_config(app, tunnel_id).await {
+        for stat in &mut stats {
+            stat.name = config
+                .peers
+                .iter()
+                .find(|p| p.public_key == stat.public_key)
+                .and_then(|p| p.name.clone());
+        }
+    }
+
+    Ok(stats)
+}
+
+// 设置 Peer 的友好名称,名称为空时清除
+#[tauri::command]
+pub async fn set_peer_name(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    public_key: String,
+    name: Option<String>,
+) -> Result<(), String> {
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if name.as_deref().is_some_and(|n| n.contains(['\n', '\r'])) {
+        return Err("Peer 名称不能包含换行".to_string());
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    // 直接修改保存的配置,保留钥匙串中的私钥引用
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    let peer = tunnel_config
+        .peers
+        .iter_mut()
+        .find(|p| p.public_key == public_key)
+        .ok_or_else(|| "隧道中不存在该 Peer".to_string())?;
+    peer.name = name;
+
+    let json = serde_json::to_string_pretty(&tunnel_config)
+        .map_err(|e| format!("序列化隧道配置失败: {}", e))?;
+    std::fs::write(&config_file, json).map_err(|e| format!("保存隧道配置失败: {}", e))?;
+
+    log::info!("已更新隧道 {} 的 Peer 名称", tunnel_id);
+    Ok(())
+}
+
+// 导出运行中隧道每个 Peer 的流量为 CSV,用于用量统计和计费
+// 名称优先使用历史记录中的备注,其次使用隧道配置中的 Peer 备注
+#[tauri::command]
+pub async fn export_peer_traffic_csv(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    path: String,
+) -> Result<(), String> {
+    use crate::commands::history_service::csv_field;
+
+    let stats = get_tunnel_peer_stats(app.clone(), tunnel_id.clone()).await?;
+
+    let mut names: HashMap<String, String> = HashMap::new();
+    if let Ok(config) = get_tunnel_config(app.clone(), tunnel_id.clone()).await {
+        for peer in config.peers {
+            if let Some(remark) = peer.remark.filter(|r| !r.trim().is_empty()) {
+                names.insert(peer.public_key, remark);
+            }
+        }
+    }
+    match crate::commands::history_service::get_history_list(app) {
+        Ok(history) => {
+            for item in history {
+                if !item.public_key.is_empty() && !item.peer_comment.trim().is_empty() {
+                    names.insert(item.public_key, item.peer_comment);
+                }
+            }
+        }
+        Err(e) => log::warn!("读取历史记录失败,Peer 名称将留空: {}", e),
+    }
+    // 用户设置的友好名称优先
+    for peer in &stats {
+        if let Some(name) = &peer.name {
+            names.insert(peer.public_key.clone(), name.clone());
+        }
+    }
+
+    let mut lines = vec!["name,public_key,tx_bytes,rx_bytes,last_handshake".to_string()];
+    for peer in &stats {
+        let name = names
+            .get(&peer.public_key)
+            .map(String::as_str)
+            .unwrap_or("");
+        let last_handshake = peer
+            .last_handshake
+            .filter(|ts| *ts > 0)
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default();
+        lines.push(format!(
+            "{},{},{},{},{}",
+            csv_field(name),
+            csv_field(&peer.public_key),
+            peer.tx_bytes,
+            peer.rx_bytes,
+            last_handshake
+        ));
+    }
+
+    std::fs::write(&path, lines.join("\n") + "\n")
+        .map_err(|e| format!("保存 CSV 文件失败: {}", e))?;
+
+    log::info!("已导出 {} 个 Peer 的流量到 {}", stats.len(), path);
+    Ok(())
+}
+
+// wireguard-go 的输出日志路径,每个接口单独一个文件,避免多个隧道互相覆盖
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub fn wireguard_go_log_path(interface_name: &str) -> String {
+    format!("/tmp/wireguard-go-{}.log", interface_name)
+}
+
+// 读取隧道对应的 wireguard-go 日志的最后 lines 行 (默认 100 行)
+// filter 不为空时只保留包含该关键字的行 (不区分大小写)
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[tauri::command]
+pub fn get_wireguard_go_log(
+    tunnel_id: String,
+    lines: Option<usize>,
+    filter: Option<String>,
+) -> Result<String, String> {
+    let interface_name = generate_interface_name(&tunnel_id);
+    let log_path = wireguard_go_log_path(&interface_name);
+
+    let content = std::fs::read_to_string(&log_path)
+        .map_err(|e| format!("读取 wireguard-go 日志失败: {}", e))?;
+
+    let keyword = filter
+        .map(|f| f.trim().to_lowercase())
+        .filter(|f| !f.is_empty());
+    let matched: Vec<&str> = content
+        .lines()
+        .filter(|line| match &keyword {
+            Some(keyword) => line.to_lowercase().contains(keyword),
+            None => true,
+        })
+        .collect();
+
+    let start = matched.len().saturating_sub(lines.unwrap_or(100));
+    Ok(matched[start..].join("\n"))
+}
+
+// 读取运行中隧道接口实际生效的 MTU,用于确认配置的 MTU 是否被系统或驱动覆盖
+#[tauri::command]
+pub async fn get_interface_mtu(tunnel_id: String) -> Result<u32, String> {
+    let is_running = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.contains_key(&tunnel_id)
+    };
+    if !is_running {
+        return Err("隧道未运行".to_string());
+    }
+
+    let interface_name = generate_interface_name(&tunnel_id);
+    get_interface_mtu_impl(&tunnel_id, &interface_name).await
+}
+
+// 将已保存的配置下发到运行中的隧道
+// replace_peers 默认为 false: 只追加/更新 Peer,不会断开通过其他方式添加的 Peer
+#[tauri::command]
+pub async fn apply_tunnel_config(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    replace_peers: Option<bool>,
+) -> Result<(), String> {
+    {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        if !processes.contains_key(&tunnel_id) {
+            return Err("隧道未运行".to_string());
+        }
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    let config_file = app_data_dir
+        .join("tunnels")
+        .join(format!("{}.json", tunnel_id));
+
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    let mut tunnel_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    crate::keychain::resolve_private_key(&mut tunnel_config)?;
+
+    let replace_peers = replace_peers.unwrap_or(false);
+    let interface_name = generate_interface_name(&tunnel_id);
+    let interface_config = build_interface_config(&tunnel_config, replace_peers);
+
+    apply_interface_config(
+        &tunnel_id,
+        &interface_name,
+        &tunnel_config.address,
+        interface_config.clone(),
+    )
+    .await?;
+
+    // 同步更新运行时配置(endpoint 刷新任务使用)
+    {
+        let mut configs = TUNNEL_CONFIGS.lock().await;
+        if let Some(entry) = configs.get_mut(&tunnel_id) {
+            entry.1 = interface_config;
+        }
+    }
+
+    log::info!(
+        "隧道 {} 配置已下发 (replace_peers={})",
+        tunnel_id,
+        replace_peers
+    );
+
+    Ok(())
+}
+
+// 将隧道当前保存的配置标记为最后可用配置,作为回滚目标
+#[tauri::command]
+pub async fn mark_last_known_good(app: tauri::AppHandle, tunnel_id: String) -> Result<(), String> {
+    let tunnels_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?
+        .join("tunnels");
+
+    let config_file = tunnels_dir.join(format!("{}.json", tunnel_id));
+    if !config_file.exists() {
+        return Err("隧道配置不存在".to_string());
+    }
+
+    // 原样保存文件内容,保留钥匙串中的私钥引用
+    let content =
+        std::fs::read_to_string(&config_file).map_err(|e| format!("读取配置失败: {}", e))?;
+    serde_json::from_str::<TunnelConfig>(&content).map_err(|e| format!("解析配置失败: {}", e))?;
+
+    std::fs::write(
+        tunnels_dir.join(format!("{}{}", tunnel_id, LKG_SUFFIX)),
+        content,
+    )
+    .map_err(|e| format!("保存最后可用配置失败: {}", e))?;
+
+    log::info!("已保存隧道 {} 的最后可用配置", tunnel_id);
+    Ok(())
+}
+
+// 将隧道恢复为最后可用配置,隧道运行中时在线下发 (替换全部 Peer)
+// 地址、DNS、MTU 等接口参数需要重启隧道后生效
+#[tauri::command]
+pub async fn rollback_tunnel(app: tauri::AppHandle, tunnel_id: String) -> Result<(), String> {
+    let tunnels_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?
+        .join("tunnels");
+
+    let lkg_file = tunnels_dir.join(format!("{}{}", tunnel_id, LKG_SUFFIX));
+    if !lkg_file.exists() {
+        return Err("该隧道没有保存最后可用配置".to_string());
+    }
+
+    let content =
+        std::fs::read_to_string(&lkg_file).map_err(|e| format!("读取最后可用配置失败: {}", e))?;
+    let lkg_config: TunnelConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析最后可用配置失败: {}", e))?;
+    if lkg_config.id != tunnel_id {
+        return Err("最后可用配置与隧道不匹配".to_string());
+    }
+
+    std::fs::write(tunnels_dir.join(format!("{}.json", tunnel_id)), content)
+        .map_err(|e| format!("恢复隧道配置失败: {}", e))?;
+
+    let is_running = {
+        let processes = TUNNEL_PROCESSES.lock().await;
+        processes.contains_key(&tunnel_id)
+    };
+    if is_running {
+        apply_tunnel_config(app, tunnel_id.clone(), Some(true)).await?;
+    }
+
+    log::info!(
+        "隧道 {} 已回滚到最后可用配置 (运行中: {})",
+        tunnel_id,
+        is_running
+    );
+    Ok(())
+}
+
+// 预览隧道启动时将使用的接口名称
+// 传入 tunnel_id 时返回该隧道对应的名称;否则返回新建隧道时将分配的名称
+#[tauri::command]
+pub async fn next_interface_name(
+    app: tauri::AppHandle,
+    tunnel_id: Option<String>,
+) -> Result<String, String> {
+    if let Some(id) = tunnel_id.filter(|id| !id.is_empty()) {
+        return Ok(generate_interface_name(&id));
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
+
+    // 新隧道的 ID 为毫秒时间戳
+    let id = chrono::Utc::now().timestamp_millis().to_string();
+    allocate_interface_name(&app_data_dir.join("tunnels"), &id)
+}
+
+// wireguard-go 进程信息
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WireguardProcessInfo {
+    pub pid: i32,
+    pub interface: String,
+    pub cmd: String,
+    pub tracked: bool, // 是否为应用当前管理的隧道
+}
+
+// 列出系统中所有 wireguard-go 进程,并标记是否由应用管理
+#[tauri::command]
+pub async fn list_wireguard_processes() -> Result<Vec<WireguardProcessInfo>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Err("Windows 平台由官方 WireGuard 服务管理,不使用 wireguard-go 进程".to_string())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = std::process::Command::new("ps")
+            .args(["-axo", "pid=,command="])
+            .output()
+            .map_err(|e| format!("执行 ps 失败: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "获取进程列表失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // 当前管理的隧道对应的接口名和 PID
+        let (tracked_interfaces, tracked_pids): (Vec<String>, Vec<i32>) = {
+            let processes = TUNNEL_PROCESSES.lock().await;
+            let interfaces = processes
+                .keys()
+                .map(|id| generate_interface_name(id))
+                .collect();
+            let pids = processes
+                .values()
+                .filter_map(|handle| match handle {
+                    ProcessHandle::PrivilegedProcess(pid) if *pid > 0 => Some(*pid),
+                    ProcessHandle::StdProcess(child) => Some(child.id() as i32),
+                    ProcessHandle::PrivilegedProcess(_) => None,
+                })
+                .collect();
+            (interfaces, pids)
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut result = Vec::new();
+
+        for line in stdout.lines() {
+            let line = line.trim();
+            let Some((pid_str, cmd)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let cmd = cmd.trim();
+
+            // 只匹配可执行文件名为 wireguard-go 的进程
+            let mut args = cmd.split_whitespace();
+            let is_wireguard_go = args
+                .next()
+                .map(|exe| exe.rsplit('/').next() == Some("wireguard-go"))
+                .unwrap_or(false);
+            if !is_wireguard_go {
+                continue;
+            }
+
+            let Ok(pid) = pid_str.parse::<i32>() else {
+                continue;
+            };
+
+            // 接口名为最后一个非选项参数
+            let interface = args
+                .filter(|arg| !arg.starts_with('-'))
+                .last()
+                .unwrap_or("")
+                .to_string();
+
+            let tracked = tracked_pids.contains(&pid) || tracked_interfaces.contains(&interface);
+
+            result.push(WireguardProcessInfo {
+                pid,
+                interface,
+                cmd: cmd.to_string(),
+                tracked,
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+// 终止残留的 wireguard-go 进程(需要管理员权限)
+#[tauri::command]
+pub async fn kill_wireguard_process(pid: i32) -> Result<(), String> {
+    // 只允许终止 wireguard-go 进程,且不能是应用正在管理的隧道
+    let processes = list_wireguard_processes().await?;
+    let process = processes
+        .iter()
+        .find(|p| p.pid == pid)
+        .ok_or_else(|| format!("未找到 PID 为 {} 的 wireguard-go 进程", pid))?;
+
+    if process.tracked {
+        return Err("该进程属于正在运行的隧道,请通过停止隧道来关闭".to_string());
+    }
+
+    log::info!("终止残留的 wireguard-go 进程: pid={}, interface={}", pid, process.interface);
+
+    #[cfg(target_os = "macos")]
+    {
+        crate::tunnel_macos::stop_wireguard_macos(pid, Some(process.interface.as_str()))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        crate::tunnel_linux::stop_wireguard_linux(pid, "")
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Err("Windows 平台不支持此操作".to_string())
+    }
+}
+
 // Peer 统计数据推送命令
 #[tauri::command]
 pub async fn start_peer_stats_watcher(
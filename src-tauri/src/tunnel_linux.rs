@@ -1,34 +1,25 @@
+use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::Emitter;
 
-use crate::daemon_ipc::{IpcClient, PeerConfigIpc, TunnelConfigIpc};
+use crate::daemon_ipc::{DaemonStateIpc, IpcClient, PeerConfigIpc, TunnelConfigIpc};
 use crate::tunnel::{
     base64_to_hex, generate_interface_name, interface_exists, parse_interface_status,
-    resolve_endpoint, InterfaceConfig, PeerConfig, ProcessHandle, TunnelConfig,
-    TunnelStatus, TUNNEL_CONFIGS, TUNNEL_PROCESSES,
+    parse_peer_stats_from_uapi, resolve_endpoint, uapi_error_message, InterfaceConfig, PeerConfig,
+    PeerStats, ProcessHandle, TunnelConfig, TunnelStatus, TUNNEL_CONFIGS, TUNNEL_PROCESSES,
 };
 
-// Linux: 使用守护进程方式管理 WireGuard (新方法)
-// 通过 Unix Socket 与 root 守护进程通信
-pub fn start_wireguard_linux_daemon(
+// 将 InterfaceConfig 转换为守护进程 IPC 配置
+fn build_ipc_config(
     config: &InterfaceConfig,
     tunnel_id: &str,
     interface: &str,
     address: &str,
     wireguard_go_path: &str,
-) -> Result<ProcessHandle, String> {
-    log::info!("使用守护进程启动 WireGuard 隧道 (Linux)...");
-    log::info!("传递给守护进程的 wireguard-go 路径: {}", wireguard_go_path);
-
-    // 检查守护进程是否运行
-    if !IpcClient::is_daemon_running() {
-        return Err(
-            "WireGuard 守护进程未运行。请先启动守护进程: sudo systemctl start wire-vault-daemon"
-                .to_string(),
-        );
-    }
-
-    // 构建 IPC 配置
+) -> TunnelConfigIpc {
     let peers: Vec<PeerConfigIpc> = config
         .peers
         .iter()
@@ -41,7 +32,7 @@ pub fn start_wireguard_linux_daemon(
         })
         .collect();
 
-    let tunnel_config = TunnelConfigIpc {
+    TunnelConfigIpc {
         tunnel_id: tunnel_id.to_string(),
         interface_name: interface.to_string(),
         private_key: config.private_key.clone(),
@@ -50,7 +41,36 @@ pub fn start_wireguard_linux_daemon(
         peers,
         wireguard_go_path: wireguard_go_path.to_string(),
         socket_dir: None, // 使用默认的 /var/run/wireguard
-    };
+        replace_peers: config.replace_peers,
+        fwmark: config.fwmark,
+        route_table: config.route_table,
+        kill_switch: config.kill_switch,
+    }
+}
+
+// Linux: 使用守护进程方式管理 WireGuard (新方法)
+// 通过 Unix Socket 与 root 守护进程通信
+pub fn start_wireguard_linux_daemon(
+    config: &InterfaceConfig,
+    tunnel_id: &str,
+    interface: &str,
+    address: &str,
+    wireguard_go_path: &str,
+) -> Result<ProcessHandle, String> {
+    log::info!("使用守护进程启动 WireGuard 隧道 (Linux)...");
+    log::info!("传递给守护进程的 wireguard-go 路径: {}", wireguard_go_path);
+
+    // 检查守护进程是否运行
+    if !IpcClient::is_daemon_running() {
+        return Err(
+            "WireGuard 守护进程未运行。请先启动守护进程: sudo systemctl start wire-vault-daemon"
+                .to_string(),
+        );
+    }
+    IpcClient::check_daemon_compatibility()?;
+
+    // 构建 IPC 配置
+    let tunnel_config = build_ipc_config(config, tunnel_id, interface, address, wireguard_go_path);
 
     // 发送启动请求
     IpcClient::start_tunnel(tunnel_config)?;
@@ -85,8 +105,10 @@ pub fn start_wireguard_linux_legacy(
 
     // Linux 方案:以 root 运行 wireguard-go,然后手动修改 socket 目录权限
     let mut shell_script = format!(
-        "'{}' -f '{}' > /tmp/wireguard-go.log 2>&1 & WG_PID=$! && sleep 2",
-        escaped_wg_path, escaped_interface
+        "'{}' -f '{}' > '{}' 2>&1 & WG_PID=$! && sleep 2",
+        escaped_wg_path,
+        escaped_interface,
+        crate::tunnel::wireguard_go_log_path(&escaped_interface)
     );
 
     // 配置每个 IP 地址
@@ -289,25 +311,98 @@ pub async fn configure_interface(
         .read_to_string(&mut response)
         .map_err(|e| format!("读取响应失败: {}", e))?;
 
-    if response.contains("errno=") && !response.contains("errno=0") {
-        Err(format!("配置失败: {}", response))
+    if let Some(message) = uapi_error_message(&response) {
+        Err(format!("配置失败: {}", message))
     } else {
         Ok("配置应用成功".to_string())
     }
 }
 
+// 以 root 运行 GUI 时可直接访问 wireguard-go 创建的 UAPI socket
+fn direct_uapi_socket(interface: &str) -> Option<String> {
+    if !nix::unistd::Uid::effective().is_root() {
+        return None;
+    }
+
+    let socket_path = format!("/var/run/wireguard/{}.sock", interface);
+    if std::path::Path::new(&socket_path).exists() {
+        Some(socket_path)
+    } else {
+        None
+    }
+}
+
+// 通过 UAPI socket 直接读取接口状态 (阻塞)
+fn read_uapi_status(socket_path: &str) -> Result<String, String> {
+    let mut stream =
+        UnixStream::connect(socket_path).map_err(|e| format!("无法连接到 socket: {}", e))?;
+
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(1)))
+        .map_err(|e| format!("设置超时失败: {}", e))?;
+
+    stream
+        .write_all(b"get=1\n\n")
+        .map_err(|e| format!("写入失败: {}", e))?;
+
+    // 读取直到遇到双换行符或超时
+    let mut response = String::new();
+    let mut buffer = [0u8; 4096];
+
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                response.push_str(&String::from_utf8_lossy(&buffer[..n]));
+                if response.contains("\n\n") {
+                    break;
+                }
+            }
+            Err(ref e)
+                if e.kind() == std::io::ErrorKind::WouldBlock
+                    || e.kind() == std::io::ErrorKind::TimedOut =>
+            {
+                if !response.is_empty() {
+                    break;
+                }
+                return Err("读取超时".to_string());
+            }
+            Err(e) => return Err(format!("读取失败: {}", e)),
+        }
+    }
+
+    if let Some(message) = uapi_error_message(&response) {
+        return Err(format!("获取状态失败: {}", message));
+    }
+
+    Ok(response)
+}
+
 // Linux: 获取接口状态
-pub async fn get_interface_status(_interface: String) -> Result<String, String> {
-    // Linux 守护进程模式下，普通用户无法访问 root 创建的 socket
-    // 需要通过 IPC 获取状态
-    Err("Linux 平台请使用守护进程 IPC 获取状态".to_string())
+pub async fn get_interface_status(interface: String) -> Result<String, String> {
+    // 守护进程模式下，普通用户无法访问 root 创建的 socket，需要通过 IPC 获取状态
+    // 以 root 运行 GUI 时可以直接读取
+    let socket_path = direct_uapi_socket(&interface)
+        .ok_or_else(|| "Linux 平台请使用守护进程 IPC 获取状态".to_string())?;
+
+    tokio::task::spawn_blocking(move || read_uapi_status(&socket_path))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
 }
 
 // Linux: 获取隧道状态的实现
 pub async fn get_tunnel_status_impl(
     tunnel_id: &str,
-    _interface_name: &str,
+    interface_name: &str,
 ) -> (u64, u64, Option<i64>) {
+    // 以 root 运行时优先直接读取 UAPI，失败再回退到守护进程
+    if direct_uapi_socket(interface_name).is_some() {
+        match get_interface_status(interface_name.to_string()).await {
+            Ok(status_str) => return parse_interface_status(&status_str),
+            Err(e) => log::warn!("直接读取接口状态失败，回退到守护进程: {}", e),
+        }
+    }
+
     log::info!("通过守护进程获取接口状态...");
     let tunnel_id = tunnel_id.to_string();
     // 使用 spawn_blocking 避免阻塞异步运行时
@@ -329,6 +424,231 @@ pub async fn get_tunnel_status_impl(
     }
 }
 
+// Linux: 将配置下发到运行中的隧道（通过守护进程）
+pub async fn apply_interface_config(
+    tunnel_id: &str,
+    interface: &str,
+    address: &str,
+    config: InterfaceConfig,
+) -> Result<(), String> {
+    let ipc_config = build_ipc_config(&config, tunnel_id, interface, address, "");
+    tokio::task::spawn_blocking(move || IpcClient::apply_config(ipc_config))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+// Linux: 在线修改监听端口（通过守护进程）
+pub async fn set_listen_port(tunnel_id: &str, _interface: &str, port: u16) -> Result<(), String> {
+    let tunnel_id = tunnel_id.to_string();
+    tokio::task::spawn_blocking(move || IpcClient::set_listen_port(&tunnel_id, port))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+// Linux: 从运行中的接口移除 Peer（通过守护进程）
+pub async fn remove_peer(
+    tunnel_id: &str,
+    _interface: &str,
+    public_key: &str,
+) -> Result<(), String> {
+    let tunnel_id = tunnel_id.to_string();
+    let public_key = public_key.to_string();
+    tokio::task::spawn_blocking(move || IpcClient::remove_peer(&tunnel_id, &public_key))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+// Linux: 读取原始 UAPI 响应（root 时直接访问 socket，否则通过守护进程）
+pub async fn read_uapi_dump(tunnel_id: &str, interface: &str) -> Result<String, String> {
+    if direct_uapi_socket(interface).is_some() {
+        return get_interface_status(interface.to_string()).await;
+    }
+
+    let tunnel_id = tunnel_id.to_string();
+    tokio::task::spawn_blocking(move || IpcClient::dump_uapi_state(&tunnel_id))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+// Linux: 获取每个 Peer 的统计列表
+pub async fn get_peer_stats_impl(tunnel_id: &str, interface_name: &str) -> Vec<PeerStats> {
+    // 以 root 运行时优先直接读取 UAPI，失败再回退到守护进程
+    if direct_uapi_socket(interface_name).is_some() {
+        match get_interface_status(interface_name.to_string()).await {
+            Ok(status_str) => {
+                return crate::tunnel::peer_stats_list(parse_peer_stats_from_uapi(&status_str))
+            }
+            Err(e) => log::warn!("直接读取 Peer 统计失败，回退到守护进程: {}", e),
+        }
+    }
+
+    let tunnel_id = tunnel_id.to_string();
+    let result = tokio::task::spawn_blocking(move || IpcClient::get_peer_stats(&tunnel_id)).await;
+
+    match result {
+        Ok(Ok(stats)) => stats
+            .into_iter()
+            .map(|s| PeerStats {
+                public_key: s.public_key,
+                tx_bytes: s.tx_bytes,
+                rx_bytes: s.rx_bytes,
+                last_handshake: s.last_handshake,
+                name: None,
+            })
+            .collect(),
+        Ok(Err(e)) => {
+            log::warn!("获取 Peer 统计失败: {}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            log::warn!("任务执行失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Linux: 通过守护进程 (netlink) 读取接口当前生效的 MTU
+pub async fn get_interface_mtu_impl(tunnel_id: &str, _interface_name: &str) -> Result<u32, String> {
+    let tunnel_id = tunnel_id.to_string();
+    tokio::task::spawn_blocking(move || IpcClient::get_interface_mtu(&tunnel_id))
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+lazy_static::lazy_static! {
+    // 守护进程流式推送的停止标记 (tunnel_id -> 是否停止)
+    static ref PEER_STATS_STREAMS: std::sync::Mutex<HashMap<String, Arc<AtomicBool>>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+// Linux: 订阅守护进程的 peer 统计流,每帧以 peer-stats-stream 事件转发给前端
+// 流结束 (隧道停止、守护进程断开或手动停止) 时发出 peer-stats-stream-ended 事件
+#[tauri::command]
+pub fn start_peer_stats_stream(
+    app: tauri::AppHandle,
+    tunnel_id: String,
+    interval_ms: Option<u64>,
+) -> Result<(), String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut streams = PEER_STATS_STREAMS
+            .lock()
+            .map_err(|e| format!("获取推送状态失败: {}", e))?;
+        if streams.contains_key(&tunnel_id) {
+            log::info!("隧道 {} 的统计流已在运行", tunnel_id);
+            return Ok(());
+        }
+        streams.insert(tunnel_id.clone(), stop.clone());
+    }
+
+    std::thread::spawn(move || {
+        let interval_ms = interval_ms.unwrap_or(1000);
+        let result = IpcClient::stream_peer_stats(&tunnel_id, interval_ms, |stats| {
+            if stop.load(Ordering::Relaxed) {
+                return false;
+            }
+            let payload = serde_json::json!({ "tunnel_id": tunnel_id, "stats": stats });
+            if let Err(e) = app.emit("peer-stats-stream", payload) {
+                log::error!("发出 peer-stats-stream 事件失败: {}", e);
+            }
+            true
+        });
+
+        let error = result.err();
+        if let Some(e) = &error {
+            log::warn!("隧道 {} 的统计流中断: {}", tunnel_id, e);
+        }
+
+        // 只移除自己的标记,避免误删停止后重新启动的流
+        if let Ok(mut streams) = PEER_STATS_STREAMS.lock() {
+            if matches!(streams.get(&tunnel_id), Some(s) if Arc::ptr_eq(s, &stop)) {
+                streams.remove(&tunnel_id);
+            }
+        }
+
+        let payload = serde_json::json!({ "tunnel_id": tunnel_id, "error": error });
+        if let Err(e) = app.emit("peer-stats-stream-ended", payload) {
+            log::error!("发出 peer-stats-stream-ended 事件失败: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+// Linux: 停止订阅 peer 统计流,下一帧到达时断开与守护进程的连接
+#[tauri::command]
+pub fn stop_peer_stats_stream(tunnel_id: String) -> Result<(), String> {
+    let mut streams = PEER_STATS_STREAMS
+        .lock()
+        .map_err(|e| format!("获取推送状态失败: {}", e))?;
+    if let Some(stop) = streams.remove(&tunnel_id) {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+// Linux: 构建交给守护进程保存的开机自动启动配置 (与启动隧道时的 IPC 配置一致)
+// 不包含 wireguard-go 路径,开机时由守护进程查找安装的副本
+pub fn build_autostart_config(tunnel_config: &TunnelConfig) -> TunnelConfigIpc {
+    let interface_config = crate::tunnel::build_interface_config(tunnel_config, true);
+    build_ipc_config(
+        &interface_config,
+        &tunnel_config.id,
+        &generate_interface_name(&tunnel_config.id),
+        &tunnel_config.address,
+        "",
+    )
+}
+
+// 守护进程中的隧道是否已登记到进程列表 (每次启动 GUI 成功同步一次即可)
+static DAEMON_STATE_SYNCED: AtomicBool = AtomicBool::new(false);
+
+// Linux: 将守护进程仍在运行、但不在进程列表中的隧道登记为运行中,返回新登记的隧道 ID
+// GUI 崩溃或重启后 TUNNEL_PROCESSES 为空,而守护进程管理的隧道仍在运行
+#[tauri::command]
+pub async fn sync_daemon_state() -> Result<Vec<String>, String> {
+    let tunnel_ids = tokio::task::spawn_blocking(IpcClient::list_tunnels)
+        .await
+        .map_err(|e| format!("查询守护进程隧道列表失败: {}", e))??;
+
+    let mut adopted = Vec::new();
+    {
+        let mut processes = TUNNEL_PROCESSES.lock().await;
+        for tunnel_id in tunnel_ids {
+            if !processes.contains_key(&tunnel_id) {
+                // 与启动时相同,PID = -1 表示由守护进程管理
+                processes.insert(tunnel_id.clone(), ProcessHandle::PrivilegedProcess(-1));
+                adopted.push(tunnel_id);
+            }
+        }
+    }
+
+    if !adopted.is_empty() {
+        log::info!("已从守护进程恢复运行中的隧道: {:?}", adopted);
+    }
+    DAEMON_STATE_SYNCED.store(true, Ordering::Relaxed);
+
+    Ok(adopted)
+}
+
+// Linux: GUI 启动后首次获取隧道列表时与守护进程同步一次,守护进程未运行时下次再试
+pub async fn sync_daemon_state_once() {
+    if DAEMON_STATE_SYNCED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Err(e) = sync_daemon_state().await {
+        log::warn!("同步守护进程隧道状态失败: {}", e);
+    }
+}
+
+// Linux: 导出守护进程中的隧道状态 (接口、socket、Peer 数量和 wireguard-go 版本),用于问题反馈
+#[tauri::command]
+pub async fn get_daemon_state() -> Result<DaemonStateIpc, String> {
+    tokio::task::spawn_blocking(IpcClient::dump_state)
+        .await
+        .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
 // Linux: 启动隧道的平台特定部分
 pub async fn start_tunnel_platform(
     tunnel_id: String,
@@ -425,5 +745,5 @@ pub async fn cleanup_stale_tunnel(interface_name: &str) -> Result<(), String> {
 
 // Linux 不需要 endpoint 刷新任务（守护进程处理）
 pub fn start_endpoint_refresh_task(_tunnel_id: String, _interface: String) {
-    // Linux 守护进程模式下，endpoint 刷新应该在守护进程内部实现
+    // GUI 无法访问 root 权限的 UAPI socket，endpoint 刷新由守护进程在启动隧道时开启
 }
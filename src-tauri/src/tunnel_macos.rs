@@ -4,16 +4,23 @@ use std::os::unix::net::UnixStream;
 
 use crate::tunnel::{
     base64_to_hex, interface_exists, parse_interface_status,
-    resolve_endpoint, InterfaceConfig, ProcessHandle, TunnelConfig,
+    resolve_endpoint, uapi_error_message, InterfaceConfig, ProcessHandle, TunnelConfig,
     TUNNEL_CONFIGS, TUNNEL_PROCESSES,
 };
 
+lazy_static::lazy_static! {
+    // 启用 kill switch 的隧道接口 -> pf 规则当前放行的 endpoint
+    static ref KILL_SWITCH_ENDPOINTS: tokio::sync::Mutex<HashMap<String, Vec<std::net::SocketAddr>>> =
+        tokio::sync::Mutex::new(HashMap::new());
+}
+
 // macOS 启动 WireGuard 隧道（一次性权限请求完成所有操作）
 pub fn start_wireguard_macos(
     wireguard_path: &str,
     interface_name: &str,
     ip_address: &str,
     routes: &[String],
+    kill_switch_endpoints: Option<&[std::net::SocketAddr]>,
 ) -> Result<ProcessHandle, String> {
     // 创建一个完整的 shell 脚本，在一次权限请求中完成所有操作：
     // 1. 启动 wireguard-go
@@ -37,9 +44,10 @@ pub fn start_wireguard_macos(
     // 构建完整的 shell 脚本
     // 启动 wireguard-go 并修改 socket 权限
     let mut shell_script = format!(
-        "'{}' -f '{}' > /tmp/wireguard-go.log 2>&1 & WG_PID=$! && sleep 1 && /usr/sbin/chown '{}' /var/run/wireguard/{}.sock",
+        "'{}' -f '{}' > '{}' 2>&1 & WG_PID=$! && sleep 1 && /usr/sbin/chown '{}' /var/run/wireguard/{}.sock",
         escaped_wg_path,
         escaped_interface,
+        crate::tunnel::wireguard_go_log_path(&escaped_interface),
         escaped_user,
         escaped_interface
     );
@@ -143,6 +151,14 @@ pub fn start_wireguard_macos(
         }
     }
 
+    // 启用 kill switch 时加载 pf 规则
+    if let Some(endpoints) = kill_switch_endpoints {
+        shell_script.push_str(&format!(
+            " && {} && (/sbin/pfctl -e > /dev/null 2>&1 || true)",
+            kill_switch_pf_load_command(interface_name, endpoints)
+        ));
+    }
+
     // 最后输出 PID
     shell_script.push_str(" && echo $WG_PID");
 
@@ -179,12 +195,126 @@ pub fn start_wireguard_macos(
     Ok(ProcessHandle::PrivilegedProcess(pid))
 }
 
+// kill switch 使用的 pf anchor,位于系统默认 pf.conf 会加载的 com.apple/* 下
+fn kill_switch_anchor(interface_name: &str) -> String {
+    format!("com.apple/wirevault-{}", interface_name)
+}
+
+// kill switch 的 pf 规则: 除回环接口、隧道接口和发往 Peer endpoint 的 UDP 流量外,拒绝所有出站流量
+// wireguard-go 意外退出时规则保留,直到隧道被停止
+fn kill_switch_pf_rules(interface_name: &str, endpoints: &[std::net::SocketAddr]) -> Vec<String> {
+    let mut rules = vec![
+        "pass out quick on lo0 all".to_string(),
+        format!("pass out quick on {} all", interface_name),
+    ];
+    for endpoint in endpoints {
+        rules.push(format!(
+            "pass out quick proto udp from any to {} port {}",
+            endpoint.ip(),
+            endpoint.port()
+        ));
+    }
+    rules.push("block return out quick all".to_string());
+    rules
+}
+
+// 加载 kill switch pf 规则的 shell 命令,pfctl -f 会整体替换 anchor 中的规则
+fn kill_switch_pf_load_command(interface_name: &str, endpoints: &[std::net::SocketAddr]) -> String {
+    let rules = kill_switch_pf_rules(interface_name, endpoints)
+        .iter()
+        .map(|rule| format!("'{}'", rule.replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "printf '%s\\n' {} | /sbin/pfctl -a '{}' -f - > /dev/null 2>&1",
+        rules,
+        kill_switch_anchor(&interface_name.replace('\'', "'\\''"))
+    )
+}
+
+// 将各 Peer 的 endpoint 域名解析为 IP,kill switch 和 UAPI 使用同一份解析结果
+fn resolve_peer_endpoints(config: &InterfaceConfig) -> Result<InterfaceConfig, String> {
+    let mut resolved = config.clone();
+    for peer in &mut resolved.peers {
+        if let Some(endpoint) = peer.endpoint.as_mut().filter(|e| !e.is_empty()) {
+            *endpoint = resolve_endpoint(endpoint)
+                .map_err(|e| format!("无法解析 endpoint {}: {}", endpoint, e))?;
+        }
+    }
+    Ok(resolved)
+}
+
+// 从 UAPI get 响应中读取各 Peer 当前使用的 endpoint
+fn parse_uapi_endpoints(status: &str) -> Vec<std::net::SocketAddr> {
+    let mut endpoints: Vec<std::net::SocketAddr> = status
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("endpoint=")?.parse().ok())
+        .collect();
+    endpoints.sort();
+    endpoints.dedup();
+    endpoints
+}
+
+// endpoint 变化后按 wireguard-go 当前使用的地址重新加载 kill switch 的 pf 规则
+// 修改 pf 规则需要管理员权限,只在放行的地址确实变化时请求
+async fn refresh_kill_switch_endpoints(interface_name: &str) {
+    let mut kill_switches = KILL_SWITCH_ENDPOINTS.lock().await;
+    let Some(current) = kill_switches.get_mut(interface_name) else {
+        return;
+    };
+
+    let endpoints = match get_interface_status(interface_name.to_string()).await {
+        Ok(status) => parse_uapi_endpoints(&status),
+        Err(e) => {
+            log::warn!("读取接口 {} 的 endpoint 失败: {}", interface_name, e);
+            return;
+        }
+    };
+    if endpoints == *current {
+        return;
+    }
+
+    let shell_command = kill_switch_pf_load_command(interface_name, &endpoints);
+    let applescript = format!(
+        "do shell script \"{}\" with administrator privileges",
+        shell_command.replace('\"', "\\\"")
+    );
+    let output = tokio::task::spawn_blocking(move || {
+        std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(&applescript)
+            .output()
+    })
+    .await;
+
+    match output {
+        Ok(Ok(output)) if output.status.success() => {
+            log::info!("已更新 kill switch 放行的 endpoint: {:?}", endpoints);
+            *current = endpoints;
+        }
+        Ok(Ok(output)) => log::warn!(
+            "更新 kill switch 规则失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(Err(e)) => log::warn!("执行 osascript 失败: {}", e),
+        Err(e) => log::warn!("任务执行失败: {}", e),
+    }
+}
+
 // macOS 停止 WireGuard 进程
-pub fn stop_wireguard_macos(pid: i32) -> Result<(), String> {
+// 先清空 kill switch 的 pf 规则 (未启用时为空操作),再终止进程
+pub fn stop_wireguard_macos(pid: i32, interface_name: Option<&str>) -> Result<(), String> {
     log::info!("请求管理员权限以停止隧道进程 (PID: {})...", pid);
 
     // 使用 SIGKILL (-9) 确保进程被强制终止
-    let shell_command = format!("/bin/kill -9 {}", pid);
+    let mut shell_command = format!("/bin/kill -9 {}", pid);
+    if let Some(interface_name) = interface_name {
+        shell_command = format!(
+            "/sbin/pfctl -a '{}' -F all > /dev/null 2>&1; {}",
+            kill_switch_anchor(&interface_name.replace('\'', "'\\''")),
+            shell_command
+        );
+    }
 
     let applescript = format!(
         "do shell script \"{}\" with administrator privileges",
@@ -337,8 +467,8 @@ pub async fn configure_interface(
 
         log::info!("UAPI 响应:\n{}", response);
 
-        if response.contains("errno=") && !response.contains("errno=0") {
-            Err(format!("配置失败: {}", response))
+        if let Some(message) = uapi_error_message(&response) {
+            Err(format!("配置失败: {}", message))
         } else {
             Ok("配置应用成功".to_string())
         }
@@ -400,6 +530,94 @@ pub async fn get_interface_status(interface: String) -> Result<String, String> {
     .map_err(|e| format!("任务执行失败: {}", e))?
 }
 
+// macOS: 将配置下发到运行中的隧道
+pub async fn apply_interface_config(
+    _tunnel_id: &str,
+    interface: &str,
+    _address: &str,
+    config: InterfaceConfig,
+) -> Result<(), String> {
+    configure_interface(interface.to_string(), config).await?;
+
+    // 重新下发时 endpoint 会重新解析,同步 kill switch 放行的地址
+    refresh_kill_switch_endpoints(interface).await;
+    Ok(())
+}
+
+// 向接口的 UAPI socket 发送 set 命令并检查 errno
+async fn uapi_set(interface: &str, command: String) -> Result<(), String> {
+    let socket_path = format!("/var/run/wireguard/{}.sock", interface);
+
+    tokio::task::spawn_blocking(move || {
+        let mut stream =
+            UnixStream::connect(&socket_path).map_err(|e| format!("无法连接到 socket: {}", e))?;
+
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .map_err(|e| format!("设置超时失败: {}", e))?;
+
+        stream
+            .write_all(format!("set=1\n{}\n", command).as_bytes())
+            .map_err(|e| format!("发送配置失败: {}", e))?;
+
+        let mut response = String::new();
+        let mut buffer = [0u8; 1024];
+
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => {
+                    response.push_str(&String::from_utf8_lossy(&buffer[..n]));
+                    if response.contains("\n\n") || response.contains("errno=") {
+                        break;
+                    }
+                }
+                Err(ref e)
+                    if e.kind() == std::io::ErrorKind::WouldBlock
+                        || e.kind() == std::io::ErrorKind::TimedOut =>
+                {
+                    if !response.is_empty() {
+                        break;
+                    }
+                    return Err("读取响应超时".to_string());
+                }
+                Err(e) => return Err(format!("读取响应失败: {}", e)),
+            }
+        }
+
+        match uapi_error_message(&response) {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+// macOS: 在线修改监听端口（通过 UAPI）
+pub async fn set_listen_port(_tunnel_id: &str, interface: &str, port: u16) -> Result<(), String> {
+    uapi_set(interface, format!("listen_port={}\n", port))
+        .await
+        .map_err(|e| format!("修改监听端口失败: {}", e))
+}
+
+// macOS: 从运行中的接口移除 Peer（通过 UAPI）
+pub async fn remove_peer(
+    _tunnel_id: &str,
+    interface: &str,
+    public_key: &str,
+) -> Result<(), String> {
+    let command = format!("public_key={}\nremove=true\n", base64_to_hex(public_key)?);
+    uapi_set(interface, command)
+        .await
+        .map_err(|e| format!("移除 Peer 失败: {}", e))
+}
+
+// macOS: 读取原始 UAPI 响应（直接访问 socket）
+pub async fn read_uapi_dump(_tunnel_id: &str, interface: &str) -> Result<String, String> {
+    get_interface_status(interface.to_string()).await
+}
+
 // macOS: 获取隧道状态的实现
 pub async fn get_tunnel_status_impl(
     _tunnel_id: &str,
@@ -427,6 +645,46 @@ pub async fn get_macos_peer_stats(
     Ok(peer_stats)
 }
 
+// macOS: 获取每个 Peer 的统计列表（UAPI 中的十六进制公钥已转换为 base64）
+pub async fn get_peer_stats_impl(
+    _tunnel_id: &str,
+    interface_name: &str,
+) -> Vec<crate::tunnel::PeerStats> {
+    match get_macos_peer_stats(interface_name).await {
+        Ok(stats) => crate::tunnel::peer_stats_list(stats),
+        Err(e) => {
+            log::warn!("获取 Peer 统计失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// macOS: 通过 ifconfig 读取接口当前生效的 MTU (输出形如 "utun5: flags=... mtu 1420")
+pub async fn get_interface_mtu_impl(_tunnel_id: &str, interface_name: &str) -> Result<u32, String> {
+    let output = std::process::Command::new("/sbin/ifconfig")
+        .arg(interface_name)
+        .output()
+        .map_err(|e| format!("执行 ifconfig 失败: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("读取接口信息失败: {}", stderr.trim()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut tokens = stdout.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token == "mtu" {
+            return tokens
+                .next()
+                .and_then(|v| v.parse::<u32>().ok())
+                .ok_or_else(|| "解析 MTU 失败".to_string());
+        }
+    }
+
+    Err(format!("接口 {} 缺少 MTU 信息", interface_name))
+}
+
 // macOS: 启动隧道的平台特定部分
 pub async fn start_tunnel_platform(
     tunnel_id: String,
@@ -436,11 +694,27 @@ pub async fn start_tunnel_platform(
     all_routes: Vec<String>,
     sidecar_path_str: &str,
 ) -> Result<(), String> {
+    // 先解析 endpoint 域名,kill switch 放行的 IP 与下发到 UAPI 的 IP 一致
+    let resolved_config = {
+        let interface_config = interface_config.clone();
+        tokio::task::spawn_blocking(move || resolve_peer_endpoints(&interface_config))
+            .await
+            .map_err(|e| format!("任务执行失败: {}", e))??
+    };
+    let kill_switch_endpoints: Vec<std::net::SocketAddr> = resolved_config
+        .peers
+        .iter()
+        .filter_map(|peer| peer.endpoint.as_deref()?.parse().ok())
+        .collect();
+
     let process_handle = start_wireguard_macos(
         sidecar_path_str,
         &interface_name,
         &tunnel_config.address,
         &all_routes,
+        tunnel_config
+            .kill_switch
+            .then_some(kill_switch_endpoints.as_slice()),
     )
     .map_err(|e| format!("启动隧道失败: {}", e))?;
 
@@ -473,10 +747,20 @@ pub async fn start_tunnel_platform(
     }
 
     // macOS: 需要 GUI 应用自己配置接口（因为使用的是特权提升方式，不是守护进程）
-    match configure_interface(interface_name.clone(), interface_config.clone()).await {
+    match configure_interface(interface_name.clone(), resolved_config).await {
         Ok(_) => {
             log::info!("接口配置成功");
 
+            // 记录 kill switch 放行的地址,未启用时清除同名接口之前的记录
+            {
+                let mut kill_switches = KILL_SWITCH_ENDPOINTS.lock().await;
+                if tunnel_config.kill_switch {
+                    kill_switches.insert(interface_name.clone(), kill_switch_endpoints);
+                } else {
+                    kill_switches.remove(&interface_name);
+                }
+            }
+
             // 保存隧道配置(用于定期更新 endpoint)
             {
                 let mut configs = TUNNEL_CONFIGS.lock().await;
@@ -503,8 +787,12 @@ pub async fn start_tunnel_platform(
 
 // macOS: 停止隧道的清理逻辑
 pub async fn cleanup_stale_tunnel(interface_name: &str) -> Result<(), String> {
-    // 使用 osascript 请求管理员权限来杀死进程
-    let shell_command = format!("/usr/bin/pkill -9 -f 'wireguard-go.*{}'", interface_name);
+    // 使用 osascript 请求管理员权限来杀死进程,同时清空 kill switch 的 pf 规则
+    let shell_command = format!(
+        "/sbin/pfctl -a '{}' -F all > /dev/null 2>&1; /usr/bin/pkill -9 -f 'wireguard-go.*{}'",
+        kill_switch_anchor(interface_name),
+        interface_name
+    );
 
     let applescript = format!(
         "do shell script \"{}\" with administrator privileges",
@@ -659,13 +947,14 @@ pub fn start_endpoint_refresh_task(tunnel_id: String, interface: String) {
 
                                 match result {
                                     Ok(Ok(response)) => {
-                                        if response.contains("errno=0") || response.is_empty() {
+                                        if let Some(message) = uapi_error_message(&response) {
+                                            log::warn!("更新 endpoint 失败: {}", message);
+                                        } else {
                                             log::info!("成功更新 endpoint: {}", resolved_endpoint);
                                             // 保存新的 endpoint,下次对比时使用
                                             last_resolved_endpoints
                                                 .insert(peer.public_key.clone(), resolved_endpoint);
-                                        } else {
-                                            log::warn!("更新 endpoint 返回: {}", response);
+                                            refresh_kill_switch_endpoints(&interface).await;
                                         }
                                     }
                                     Ok(Err(e)) => {
@@ -686,3 +975,54 @@ pub fn start_endpoint_refresh_task(tunnel_id: String, interface: String) {
         }
     });
 }
+
+// macOS: 开机自动启动的登录项 (LaunchAgent),登录时以 --autostart 参数启动应用
+const AUTOSTART_AGENT_LABEL: &str = "com.wirevault.autostart";
+
+// macOS: 注册或移除自动启动登录项,有任一隧道需要自动启动时注册
+pub fn set_autostart_agent(enabled: bool) -> Result<(), String> {
+    let home = std::env::var_os("HOME").ok_or_else(|| "无法确定用户主目录".to_string())?;
+    let agents_dir = std::path::PathBuf::from(home).join("Library/LaunchAgents");
+    let agent_path = agents_dir.join(format!("{}.plist", AUTOSTART_AGENT_LABEL));
+
+    if !enabled {
+        if agent_path.exists() {
+            std::fs::remove_file(&agent_path).map_err(|e| format!("删除登录项失败: {}", e))?;
+            log::info!("已移除自动启动登录项: {:?}", agent_path);
+        }
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe().map_err(|e| format!("获取应用路径失败: {}", e))?;
+    let exe = exe
+        .to_string_lossy()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+
+    let plist = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>--autostart</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        AUTOSTART_AGENT_LABEL, exe
+    );
+
+    std::fs::create_dir_all(&agents_dir).map_err(|e| format!("创建登录项目录失败: {}", e))?;
+    std::fs::write(&agent_path, plist).map_err(|e| format!("写入登录项失败: {}", e))?;
+    log::info!("已注册自动启动登录项: {:?}", agent_path);
+
+    Ok(())
+}
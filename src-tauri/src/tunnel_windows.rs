@@ -4,7 +4,9 @@ use std::path::{Path, PathBuf};
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
-use crate::tunnel::{InterfaceConfig, ProcessHandle, TunnelConfig, TUNNEL_PROCESSES};
+use crate::tunnel::{
+    build_wg_quick_config, InterfaceConfig, ProcessHandle, TunnelConfig, TUNNEL_PROCESSES,
+};
 
 // Windows 创建进程标志：CREATE_NO_WINDOW = 0x08000000
 // 用于隐藏控制台窗口
@@ -102,85 +104,6 @@ pub fn locate_wireguard_tools() -> Result<(PathBuf, PathBuf), String> {
     Ok((wireguard, wg))
 }
 
-fn split_config_values(value: &str) -> Vec<String> {
-    value
-        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .collect()
-}
-
-fn build_windows_config_content(
-    tunnel_config: &TunnelConfig,
-    interface_config: &InterfaceConfig,
-) -> String {
-    let mut lines: Vec<String> = Vec::new();
-    lines.push("[Interface]".to_string());
-    lines.push(format!(
-        "PrivateKey = {}",
-        interface_config.private_key.trim()
-    ));
-
-    for address in split_config_values(&tunnel_config.address) {
-        lines.push(format!("Address = {}", address));
-    }
-
-    if let Some(port) = interface_config.listen_port {
-        lines.push(format!("ListenPort = {}", port));
-    }
-
-    if !tunnel_config.dns.trim().is_empty() {
-        for dns in split_config_values(&tunnel_config.dns) {
-            lines.push(format!("DNS = {}", dns));
-        }
-    }
-
-    if !tunnel_config.mtu.trim().is_empty() {
-        lines.push(format!("MTU = {}", tunnel_config.mtu.trim()));
-    }
-
-    lines.push(String::new());
-
-    for peer in &interface_config.peers {
-        lines.push("[Peer]".to_string());
-        lines.push(format!("PublicKey = {}", peer.public_key.trim()));
-
-        if let Some(ref psk) = peer.preshared_key {
-            if !psk.trim().is_empty() {
-                lines.push(format!("PresharedKey = {}", psk.trim()));
-            }
-        }
-
-        if let Some(ref endpoint) = peer.endpoint {
-            if !endpoint.trim().is_empty() {
-                lines.push(format!("Endpoint = {}", endpoint.trim()));
-            }
-        }
-
-        if !peer.allowed_ips.is_empty() {
-            let ips = peer
-                .allowed_ips
-                .iter()
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .collect::<Vec<_>>()
-                .join(", ");
-            if !ips.is_empty() {
-                lines.push(format!("AllowedIPs = {}", ips));
-            }
-        }
-
-        if let Some(keepalive) = peer.persistent_keepalive {
-            lines.push(format!("PersistentKeepalive = {}", keepalive));
-        }
-
-        lines.push(String::new());
-    }
-
-    lines.join("\r\n")
-}
-
 fn extract_service_name_from_output(output: &str) -> Option<String> {
     if let Some(pos) = output.find("WireGuardTunnel$") {
         let tail = &output[pos..];
@@ -218,7 +141,7 @@ pub fn start_wireguard_windows(
     let config_path = tunnels_dir.join(config_file_name);
     log::info!("配置文件路径: {:?}", config_path);
 
-    let config_content = build_windows_config_content(tunnel_config, interface_config);
+    let config_content = build_wg_quick_config(tunnel_config, interface_config, "\r\n");
     log::info!("生成的配置内容:\n{}", config_content);
 
     std::fs::write(&config_path, &config_content)
@@ -523,6 +446,106 @@ pub async fn get_tunnel_status_impl(
     get_windows_interface_counters(interface_name).unwrap_or((0, 0, None))
 }
 
+// Windows: 将配置下发到运行中的隧道
+pub async fn apply_interface_config(
+    _tunnel_id: &str,
+    interface: &str,
+    _address: &str,
+    config: InterfaceConfig,
+) -> Result<(), String> {
+    configure_interface(interface.to_string(), config)
+        .await
+        .map(|_| ())
+}
+
+// Windows: 在线修改监听端口（通过 wg.exe set）
+pub async fn set_listen_port(_tunnel_id: &str, interface: &str, port: u16) -> Result<(), String> {
+    let (_, wg_path) = locate_wireguard_tools()?;
+    let output = std::process::Command::new(&wg_path)
+        .args(["set", interface, "listen-port", &port.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("执行 wg.exe 失败: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("修改监听端口失败: {}", stderr.trim()));
+    }
+
+    Ok(())
+}
+
+// Windows: 从运行中的接口移除 Peer（通过 wg.exe set）
+pub async fn remove_peer(
+    _tunnel_id: &str,
+    interface: &str,
+    public_key: &str,
+) -> Result<(), String> {
+    let (_, wg_path) = locate_wireguard_tools()?;
+    let output = std::process::Command::new(&wg_path)
+        .args(["set", interface, "peer", public_key, "remove"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("执行 wg.exe 失败: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("移除 Peer 失败: {}", stderr.trim()));
+    }
+
+    Ok(())
+}
+
+// Windows: 隧道由 WireGuard 服务管理，无法访问 UAPI socket
+pub async fn read_uapi_dump(_tunnel_id: &str, _interface: &str) -> Result<String, String> {
+    Err("Windows 平台不支持读取 UAPI 状态，请使用 wg.exe show 查看".to_string())
+}
+
+// Windows: 获取每个 Peer 的统计列表
+// wg.exe dump 的握手列是绝对 unix 时间戳，与 UAPI 的 last_handshake_time_sec 含义相同，
+// 0 表示从未握手（解析为 None），"多久之前"由前端统一换算
+pub async fn get_peer_stats_impl(
+    _tunnel_id: &str,
+    interface_name: &str,
+) -> Vec<crate::tunnel::PeerStats> {
+    match get_interface_status(interface_name.to_string()).await {
+        Ok(dump) => crate::tunnel::peer_stats_list(parse_windows_dump_per_peer(&dump)),
+        Err(e) => {
+            log::warn!("获取 Peer 统计失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Windows: 通过 netsh 读取接口当前生效的 MTU
+// 输出的每行依次为 MTU、MediaSenseState、Bytes In、Bytes Out、接口名
+pub async fn get_interface_mtu_impl(_tunnel_id: &str, interface_name: &str) -> Result<u32, String> {
+    let output = std::process::Command::new("netsh")
+        .args(["interface", "ipv4", "show", "subinterfaces"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("执行 netsh 失败: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("读取接口信息失败: {}", stderr.trim()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let mtu = columns.next()?.parse::<u32>().ok()?;
+            // 接口名可能包含空格,跳过中间三列后取剩余部分
+            let name = columns.skip(3).collect::<Vec<_>>().join(" ");
+            Some((mtu, name))
+        })
+        .find(|(_, name)| name == interface_name)
+        .map(|(mtu, _)| mtu)
+        .ok_or_else(|| format!("未找到接口 {}", interface_name))
+}
+
 // Windows: 启动隧道的平台特定部分
 pub async fn start_tunnel_platform(
     tunnel_id: String,
@@ -532,6 +555,11 @@ pub async fn start_tunnel_platform(
     _all_routes: Vec<String>,
     tunnels_dir: &Path,
 ) -> Result<(), String> {
+    // 官方客户端在 AllowedIPs 包含 0.0.0.0/0 时会自动阻止隧道外的流量,无需额外规则
+    if tunnel_config.kill_switch {
+        log::info!("Windows 平台的 kill switch 由官方 WireGuard 客户端在全局路由时自动启用");
+    }
+
     let process_handle =
         start_wireguard_windows(&tunnel_id, tunnel_config, interface_config, tunnels_dir)
             .map_err(|e| format!("启动隧道失败: {}", e))?;
@@ -1,20 +1,49 @@
+use crate::sync::SyncBackend;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 use url::Url;
 
-/// WebDAV 配置结构
+/// WebDAV 配置结构 (同时用于 SFTP 同步)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WebDavConfig {
     pub enabled: bool,
-    pub server_url: String,
+    #[serde(default = "default_backend_type")]
+    pub backend_type: String, // 同步方式: "webdav", "sftp"
+    pub server_url: String, // SFTP 使用 sftp://host[:port]/path 格式
     pub username: String,
-    pub password: String,
+    pub password: String, // SFTP 使用私钥时作为私钥密码
+    #[serde(default)]
+    pub ssh_key_path: String, // SFTP 私钥路径,为空时使用密码认证
+    #[serde(default)]
+    pub encryption_password: Option<String>, // 设置后上传前加密文件内容
+    #[serde(default)]
+    pub conflict_strategy: ConflictStrategy, // 双向同步时两端都修改过的文件如何处理
     pub sync_interval: u64, // 同步间隔(秒)
     #[serde(default)]
     pub auto_sync_enabled: bool, // 自动同步开关
 }
 
+fn default_backend_type() -> String {
+    "webdav".to_string()
+}
+
+/// 双向同步冲突处理策略 (上次同步后本地和远程都被修改过时)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// 修改时间较新的一方覆盖另一方
+    #[default]
+    NewerWins,
+    /// 始终以本地文件为准
+    LocalWins,
+    /// 始终以远程文件为准
+    RemoteWins,
+    /// 内容不同时将远程文件另存为 conflicts/<dir>/<name>.remote-<timestamp>.json,再上传本地文件
+    KeepBoth,
+}
+
 /// 最后同步信息
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LastSyncInfo {
@@ -24,21 +53,42 @@ pub struct LastSyncInfo {
     pub servers_downloaded: usize, // 下载的服务端配置数量
     pub history_uploaded: usize,   // 上传的历史记录数量
     pub history_downloaded: usize, // 下载的历史记录数量
+    #[serde(default)]
+    pub files: HashMap<String, SyncFileState>, // 上次双向同步后各文件的状态,键为 "servers/<文件名>"
+}
+
+/// 上次双向同步后文件两端的修改时间,用于判断哪一端在此之后被修改过
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncFileState {
+    pub local_modified: i64,
+    pub remote_modified: i64,
 }
 
 impl Default for WebDavConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            backend_type: default_backend_type(),
             server_url: String::new(),
             username: String::new(),
             password: String::new(),
+            ssh_key_path: String::new(),
+            encryption_password: None,
+            conflict_strategy: ConflictStrategy::default(),
             sync_interval: 300,       // 默认 5 分钟
             auto_sync_enabled: false, // 默认关闭自动同步
         }
     }
 }
 
+/// 远程文件信息
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteFileInfo {
+    pub name: String,
+    pub size: Option<u64>,     // 文件大小(字节)
+    pub modified: Option<i64>, // 最后修改时间戳
+}
+
 /// WebDAV 客户端
 pub struct WebDavClient {
     client: Client,
@@ -184,8 +234,11 @@ impl WebDavClient {
         }
     }
 
-    /// 列出目录内容
-    pub async fn list_directory(&self, remote_path: &str) -> Result<Vec<String>, String> {
+    /// 列出目录内容，包含文件大小和修改时间
+    pub async fn list_directory_detailed(
+        &self,
+        remote_path: &str,
+    ) -> Result<Vec<RemoteFileInfo>, String> {
         let url = self.build_url(&format!("{}/", remote_path.trim_end_matches('/')))?;
 
         let propfind_body = r#"<?xml version="1.0" encoding="utf-8"?>
@@ -300,42 +353,83 @@ impl WebDavClient {
     }
 
     /// 解析 PROPFIND 响应
-    fn parse_propfind_response(&self, xml: &str, base_path: &str) -> Result<Vec<String>, String> {
+    fn parse_propfind_response(
+        &self,
+        xml: &str,
+        base_path: &str,
+    ) -> Result<Vec<RemoteFileInfo>, String> {
         use quick_xml::events::Event;
         use quick_xml::Reader;
 
         let mut reader = Reader::from_str(xml);
         reader.config_mut().trim_text(true);
 
+        // 当前正在读取的属性元素
+        #[derive(PartialEq)]
+        enum Field {
+            None,
+            Href,
+            ContentLength,
+            LastModified,
+        }
+
+        let is_element = |name: &[u8], local: &str| {
+            name == format!("d:{}", local).as_bytes() || name == format!("D:{}", local).as_bytes()
+        };
+
         let mut files = Vec::new();
+        let mut field = Field::None;
         let mut current_href = String::new();
-        let mut in_href = false;
+        let mut current_length = String::new();
+        let mut current_modified = String::new();
 
         let mut buf = Vec::new();
         loop {
             match reader.read_event_into(&mut buf) {
                 Ok(Event::Start(e)) => {
-                    if e.name().as_ref() == b"d:href" || e.name().as_ref() == b"D:href" {
-                        in_href = true;
+                    let name = e.name();
+                    if is_element(name.as_ref(), "response") {
                         current_href.clear();
+                        current_length.clear();
+                        current_modified.clear();
+                    } else if is_element(name.as_ref(), "href") {
+                        field = Field::Href;
+                    } else if is_element(name.as_ref(), "getcontentlength") {
+                        field = Field::ContentLength;
+                    } else if is_element(name.as_ref(), "getlastmodified") {
+                        field = Field::LastModified;
                     }
                 }
                 Ok(Event::Text(e)) => {
-                    if in_href {
-                        current_href.push_str(&String::from_utf8_lossy(&e));
+                    let text = String::from_utf8_lossy(&e);
+                    match field {
+                        Field::Href => current_href.push_str(&text),
+                        Field::ContentLength => current_length.push_str(&text),
+                        Field::LastModified => current_modified.push_str(&text),
+                        Field::None => {}
                     }
                 }
                 Ok(Event::End(e)) => {
-                    if e.name().as_ref() == b"d:href" || e.name().as_ref() == b"D:href" {
-                        in_href = false;
+                    let name = e.name();
+                    if is_element(name.as_ref(), "response") {
                         if !current_href.is_empty() && !current_href.ends_with(base_path) {
-                            // 提取文件名
+                            // 提取文件名 (目录的 href 以 / 结尾，会被跳过)
                             if let Some(filename) = current_href.split('/').last() {
                                 if !filename.is_empty() {
-                                    files.push(filename.to_string());
+                                    files.push(RemoteFileInfo {
+                                        name: filename.to_string(),
+                                        size: current_length.trim().parse::<u64>().ok(),
+                                        modified: chrono::DateTime::parse_from_rfc2822(
+                                            current_modified.trim(),
+                                        )
+                                        .ok()
+                                        .map(|dt| dt.timestamp()),
+                                    });
                                 }
                             }
                         }
+                    } else if field != Field::None {
+                        field = Field::None;
                     }
                 }
                 Ok(Event::Eof) => break,
@@ -404,3 +498,37 @@ impl WebDavClient {
         }
     }
 }
+
+#[async_trait::async_trait]
+impl SyncBackend for WebDavClient {
+    async fn test_connection(&self) -> Result<(), String> {
+        WebDavClient::test_connection(self).await
+    }
+
+    async fn upload_file(&self, local_path: &Path, remote_path: &str) -> Result<(), String> {
+        WebDavClient::upload_file(self, local_path, remote_path).await
+    }
+
+    async fn download_file(&self, remote_path: &str, local_path: &Path) -> Result<(), String> {
+        WebDavClient::download_file(self, remote_path, local_path).await
+    }
+
+    async fn delete_file(&self, remote_path: &str) -> Result<(), String> {
+        WebDavClient::delete_file(self, remote_path).await
+    }
+
+    async fn create_directory(&self, remote_path: &str) -> Result<(), String> {
+        WebDavClient::create_directory(self, remote_path).await
+    }
+
+    async fn list_directory_detailed(
+        &self,
+        remote_path: &str,
+    ) -> Result<Vec<RemoteFileInfo>, String> {
+        WebDavClient::list_directory_detailed(self, remote_path).await
+    }
+
+    async fn get_last_modified(&self, remote_path: &str) -> Result<Option<i64>, String> {
+        WebDavClient::get_last_modified(self, remote_path).await
+    }
+}
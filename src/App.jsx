@@ -110,6 +110,28 @@ function App() {
           console.error("迁移旧配置失败:", err);
         }
 
+        // 检查数据目录是否可写，提前提示只读或空间不足
+        try {
+          const dataDirStatus = await invoke("check_data_dir_writable");
+          if (!dataDirStatus.writable) {
+            showToast("应用数据目录不可写，配置将无法保存，请检查磁盘权限", "error");
+          } else if (dataDirStatus.free_bytes !== null && dataDirStatus.free_bytes < 50 * 1024 * 1024) {
+            showToast("磁盘可用空间不足 50 MB，保存配置可能失败", "warning");
+          }
+        } catch (err) {
+          console.error("检查数据目录失败:", err);
+        }
+
+        // 检查提权方式，启动隧道需要管理员权限
+        try {
+          const elevation = await invoke("check_elevation_backend");
+          if (!elevation.available) {
+            showToast("未找到可用的提权工具（Linux 需安装 polkit 或 sudo），将无法启动隧道", "warning");
+          }
+        } catch (err) {
+          console.error("检查提权方式失败:", err);
+        }
+
         const dir = ".";
         setWorkDir(dir);
 
@@ -1,3 +1,4 @@
+import { Fragment, useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
 import { openUrl } from "@tauri-apps/plugin-opener";
@@ -13,10 +14,24 @@ function ConfigTabs({
   allPeerConfigs,
   mikrotikConfigContent,
   openwrtConfigContent,
+  pfsenseConfigContent,
+  jsonConfigContent,
   publicKey,
   onShowToast,
   onSavePeerConfig,
 }) {
+  // 导出格式列表由后端提供，新增格式时无需修改这里
+  const [exportFormats, setExportFormats] = useState([]);
+
+  useEffect(() => {
+    invoke("list_export_formats")
+      .then(setExportFormats)
+      .catch((err) => console.error("获取导出格式失败:", err));
+  }, []);
+
+  // 标准 WireGuard 配置的标签页 id 为 wireguard
+  const tabIdOf = (format) => (format.id === "wg" ? "wireguard" : format.id);
+
   // 复制到剪贴板
   const handleCopyToClipboard = async (content, name) => {
     try {
@@ -107,6 +122,46 @@ function ConfigTabs({
     }
   };
 
+  // 保存 pfSense 配置文件
+  const handleSavePfsenseConfig = async () => {
+    try {
+      const filePath = await save({
+        defaultPath: `pfsense_peer.xml`,
+        filters: [{
+          name: 'XML 文件',
+          extensions: ['xml']
+        }]
+      });
+
+      if (filePath) {
+        await invoke("save_config_to_path", { content: pfsenseConfigContent, filePath });
+        onShowToast("pfSense 配置文件已保存", "success");
+      }
+    } catch (err) {
+      onShowToast("保存失败: " + err, "error");
+    }
+  };
+
+  // 保存 JSON 配置文件
+  const handleSaveJsonConfig = async () => {
+    try {
+      const filePath = await save({
+        defaultPath: `${interfaceName || 'peer'}.json`,
+        filters: [{
+          name: 'JSON 文件',
+          extensions: ['json']
+        }]
+      });
+
+      if (filePath) {
+        await invoke("save_config_to_path", { content: jsonConfigContent, filePath });
+        onShowToast("JSON 配置文件已保存", "success");
+      }
+    } catch (err) {
+      onShowToast("保存失败: " + err, "error");
+    }
+  };
+
   // 打开外部链接
   const handleOpenExternalLink = async (url) => {
     try {
@@ -121,42 +176,25 @@ function ConfigTabs({
     <>
       {/* 标签页导航 */}
       <div className="tabs-nav">
-        <button
-          className={`tab-button ${activeTab === "wireguard" ? "active" : ""}`}
-          onClick={() => onSetActiveTab("wireguard")}
-        >
-          WireGuard
-        </button>
-        <button
-          className={`tab-button ${activeTab === "qrcode" ? "active" : ""}`}
-          onClick={() => onSetActiveTab("qrcode")}
-        >
-          二维码
-        </button>
-        <button
-          className={`tab-button ${activeTab === "surge" ? "active" : ""}`}
-          onClick={() => onSetActiveTab("surge")}
-        >
-          Surge
-        </button>
-        <button
-          className={`tab-button ${activeTab === "ikuai" ? "active" : ""}`}
-          onClick={() => onSetActiveTab("ikuai")}
-        >
-          爱快
-        </button>
-        <button
-          className={`tab-button ${activeTab === "mikrotik" ? "active" : ""}`}
-          onClick={() => onSetActiveTab("mikrotik")}
-        >
-          MikroTik
-        </button>
-        <button
-          className={`tab-button ${activeTab === "openwrt" ? "active" : ""}`}
-          onClick={() => onSetActiveTab("openwrt")}
-        >
-          OpenWrt
-        </button>
+        {exportFormats.map((format) => (
+          <Fragment key={format.id}>
+            <button
+              className={`tab-button ${activeTab === tabIdOf(format) ? "active" : ""}`}
+              onClick={() => onSetActiveTab(tabIdOf(format))}
+            >
+              {format.label}
+            </button>
+            {/* 二维码紧跟在 WireGuard 配置之后 */}
+            {format.id === "wg" && (
+              <button
+                className={`tab-button ${activeTab === "qrcode" ? "active" : ""}`}
+                onClick={() => onSetActiveTab("qrcode")}
+              >
+                二维码
+              </button>
+            )}
+          </Fragment>
+        ))}
       </div>
 
       {/* 标签页内容 */}
@@ -356,6 +394,68 @@ function ConfigTabs({
             </div>
           </div>
         )}
+
+        {/* pfSense 配置 */}
+        {activeTab === "pfsense" && (
+          <div className="tab-panel">
+            <div className="config-result">
+              <div className="config-header">
+                <h3>pfSense WireGuard Peer 配置</h3>
+                <div className="button-group-inline">
+                  <button onClick={() => handleCopyToClipboard(pfsenseConfigContent, "pfSense 配置")} className="btn-save">
+                    📋 复制
+                  </button>
+                  <button onClick={handleSavePfsenseConfig} className="btn-save">
+                    💾 另存为...
+                  </button>
+                </div>
+              </div>
+              <pre className="config-content">{pfsenseConfigContent}</pre>
+            </div>
+
+            <div className="info-row">
+              <div className="success-info">
+                <h4>📋 使用说明</h4>
+                <ol>
+                  <li>备份 pfSense 配置：Diagnostics → Backup &amp; Restore</li>
+                  <li>在备份的 config.xml 中找到 WireGuard 的 <code>&lt;peers&gt;</code> 节点</li>
+                  <li>将上方生成的 <code>&lt;item&gt;</code> 粘贴到 <code>&lt;peers&gt;</code> 中并恢复配置</li>
+                  <li>确认 <code>tun</code> 与 pfSense 中的隧道名称一致</li>
+                </ol>
+              </div>
+
+              <div className="hint-box">
+                <h4>💡 注意事项</h4>
+                <p>• 需要安装 WireGuard 软件包（System → Package Manager）</p>
+                <p>• 恢复配置前务必备份当前配置</p>
+                <p><strong>参考文档：</strong><span onClick={() => handleOpenExternalLink("https://docs.netgate.com/pfsense/en/latest/vpn/wireguard/index.html")} style={{ color: "var(--primary-color)", marginLeft: "0.5rem", cursor: "pointer", textDecoration: "underline" }}>pfSense 官方文档</span></p>
+              </div>
+            </div>
+          </div>
+        )}
+
+        {/* JSON 配置 */}
+        {activeTab === "json" && (
+          <div className="tab-panel">
+            <div className="config-result">
+              <div className="config-header">
+                <h3>JSON Peer 配置</h3>
+                <div className="button-group-inline">
+                  <button onClick={() => handleCopyToClipboard(jsonConfigContent, "JSON 配置")} className="btn-save">
+                    📋 复制
+                  </button>
+                  <button onClick={handleSaveJsonConfig} className="btn-save">
+                    💾 另存为...
+                  </button>
+                </div>
+              </div>
+              <pre className="config-content">{jsonConfigContent}</pre>
+              <p className="hint">
+                🤖 供自动化脚本或配置管理工具读取，未填写的可选字段不会输出
+              </p>
+            </div>
+          </div>
+        )}
       </div>
     </>
   );
@@ -5,7 +5,9 @@ import './style.css';
 
 function DaemonPanel({ isOpen, onClose, onShowToast }) {
   const [daemonStatus, setDaemonStatus] = useState(null);
+  const [initSystem, setInitSystem] = useState(null);
   const [daemonLogs, setDaemonLogs] = useState('');
+  const [wgGoVersions, setWgGoVersions] = useState(null);
   const [loading, setLoading] = useState(false);
   const [confirmDialog, setConfirmDialog] = useState({
     isOpen: false,
@@ -24,10 +26,32 @@ function DaemonPanel({ isOpen, onClose, onShowToast }) {
     }
   };
 
+  // 检测 init 系统
+  const loadInitSystem = async () => {
+    try {
+      const init = await invoke('detect_init_system');
+      setInitSystem(init);
+    } catch (error) {
+      console.error('检测 init 系统失败:', error);
+    }
+  };
+
+  // 对比应用自带和守护进程使用的 wireguard-go 版本
+  const loadWgGoVersions = async () => {
+    try {
+      const versions = await invoke('compare_wireguard_go_versions');
+      setWgGoVersions(versions);
+    } catch (error) {
+      console.error('获取 wireguard-go 版本失败:', error);
+    }
+  };
+
   // 初始加载状态
   useEffect(() => {
     if (isOpen) {
+      loadInitSystem();
       loadDaemonStatus();
+      loadWgGoVersions();
       // 每 2 秒刷新一次状态
       const interval = setInterval(loadDaemonStatus, 2000);
       return () => clearInterval(interval);
@@ -138,6 +162,36 @@ function DaemonPanel({ isOpen, onClose, onShowToast }) {
     }
   };
 
+  // 修复 service 文件（应用更新后可执行文件路径变化时使用）
+  const handleRepairDaemon = async () => {
+    setLoading(true);
+    try {
+      await invoke('repair_daemon_unit');
+      onShowToast('守护进程 service 文件已修复并重启', 'success');
+      await loadDaemonStatus();
+    } catch (error) {
+      onShowToast(`修复失败: ${error}`, 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // 修复数据目录权限（以 root 运行过 GUI 后残留 root 所有的文件）
+  const handleFixPermissions = async () => {
+    setLoading(true);
+    try {
+      const repaired = await invoke('check_and_fix_data_permissions');
+      onShowToast(
+        repaired.length > 0 ? `已修复 ${repaired.length} 个文件的权限` : '数据目录权限正常',
+        'success'
+      );
+    } catch (error) {
+      onShowToast(`修复权限失败: ${error}`, 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
   // 启用开机自启
   const handleEnableDaemon = async () => {
     setLoading(true);
@@ -179,6 +233,20 @@ function DaemonPanel({ isOpen, onClose, onShowToast }) {
     }
   };
 
+  // 复制守护进程中的隧道状态，用于问题反馈（不含私钥）
+  const handleCopyDaemonState = async () => {
+    setLoading(true);
+    try {
+      const state = await invoke('get_daemon_state');
+      await navigator.clipboard.writeText(JSON.stringify(state, null, 2));
+      onShowToast(`已复制守护进程状态 (${state.tunnels.length} 个隧道)`, 'success');
+    } catch (error) {
+      onShowToast(`获取守护进程状态失败: ${error}`, 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
   if (!isOpen) return null;
 
   return (
@@ -209,6 +277,16 @@ function DaemonPanel({ isOpen, onClose, onShowToast }) {
                   <strong>版本:</strong> {daemonStatus.version}
                 </div>
               )}
+              {daemonStatus.installed && wgGoVersions?.bundled && wgGoVersions?.daemon_installed && !wgGoVersions.match && (
+                <div className="daemon-status-item">
+                  <strong>wireguard-go:</strong> ⚠️ 守护进程使用 {wgGoVersions.daemon_installed}，应用自带 {wgGoVersions.bundled}，建议重新安装守护进程
+                </div>
+              )}
+              {daemonStatus.installed && daemonStatus.socket_state === 'stale' && (
+                <div className="daemon-status-item">
+                  <strong>Socket:</strong> ⚠️ 已失效，请删除 /var/run/wire-vault-daemon.sock 后重启守护进程
+                </div>
+              )}
             </div>
           )}
 
@@ -216,9 +294,15 @@ function DaemonPanel({ isOpen, onClose, onShowToast }) {
           {daemonStatus && (
             <div className="daemon-actions">
               {!daemonStatus.installed ? (
-                <button onClick={handleInstallDaemon} className="btn-primary" disabled={loading}>
-                  📦 安装守护进程
-                </button>
+                initSystem && initSystem !== 'systemd' && initSystem !== 'openrc' ? (
+                  <div className="daemon-status-item">
+                    ⚠️ 当前系统未使用 systemd 或 OpenRC (检测到: {initSystem})，暂不支持安装守护进程
+                  </div>
+                ) : (
+                  <button onClick={handleInstallDaemon} className="btn-primary" disabled={loading}>
+                    📦 安装守护进程
+                  </button>
+                )
               ) : (
                 <>
                   <div className="daemon-actions-row">
@@ -234,6 +318,17 @@ function DaemonPanel({ isOpen, onClose, onShowToast }) {
                     <button onClick={handleViewDaemonLogs} className="btn-secondary" disabled={loading}>
                       📋 查看日志
                     </button>
+                    <button onClick={handleCopyDaemonState} className="btn-secondary" disabled={loading || !daemonStatus.running}>
+                      📄 复制状态
+                    </button>
+                    {initSystem === 'systemd' && (
+                      <button onClick={handleRepairDaemon} className="btn-secondary" disabled={loading}>
+                        🔧 修复服务
+                      </button>
+                    )}
+                    <button onClick={handleFixPermissions} className="btn-secondary" disabled={loading}>
+                      🔑 修复数据权限
+                    </button>
                   </div>
                   <div className="daemon-actions-row">
                     <button onClick={handleEnableDaemon} className="btn-secondary" disabled={loading || daemonStatus.enabled}>
@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
+import { useState } from "react";
 import "./style.css";
 import ConfigTabs from "../ConfigTabs";
 
@@ -9,9 +10,24 @@ function HistoryDetailModal({
   onSetActiveTab,
   onClose,
   onShowToast,
+  onTagsChanged,
 }) {
+  const [tagsInput, setTagsInput] = useState((history?.tags || []).join(", "));
+
   if (!history) return null;
 
+  // 保存标签（逗号分隔）
+  const handleSaveTags = async () => {
+    try {
+      const tags = tagsInput.split(/[,，]/).map(tag => tag.trim()).filter(Boolean);
+      await invoke("set_history_tags", { id: history.id, tags });
+      onShowToast("标签已保存", "success");
+      onTagsChanged?.();
+    } catch (err) {
+      onShowToast("保存标签失败: " + err, "error");
+    }
+  };
+
 
   // 保存配置的函数
   const handleSaveConfig = async (content, defaultFileName, filterName, extensions) => {
@@ -41,6 +57,8 @@ function HistoryDetailModal({
     allPeerConfigs: [history.ikuai_config], // 将爱快配置作为数组传递
     mikrotikConfigContent: history.mikrotik_config,
     openwrtConfigContent: history.openwrt_config,
+    pfsenseConfigContent: history.pfsense_config,
+    jsonConfigContent: history.json_config,
     publicKey: history.public_key,
     onShowToast,
     onSavePeerConfig: async () => {
@@ -64,7 +82,17 @@ function HistoryDetailModal({
         </div>
 
         <div className="modal-body">
-          
+          <div className="history-tags-editor">
+            <input
+              type="text"
+              value={tagsInput}
+              onChange={(e) => setTagsInput(e.target.value)}
+              placeholder="标签，多个用逗号分隔"
+            />
+            <button onClick={handleSaveTags} className="btn-secondary">
+              保存标签
+            </button>
+          </div>
 
           {/* 使用 ConfigTabs 组件渲染标签页内容 */}
           <div className="tabs-content">
@@ -0,0 +1,120 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+import "./style.css";
+
+const emptyMember = () => ({ name: "", address: "", endpoint: "" });
+
+// 全互联 (mesh) 配置生成：每个成员连接其他所有成员
+function MeshGeneratorModal({ isOpen, onClose, onShowToast }) {
+  const [members, setMembers] = useState([emptyMember(), emptyMember()]);
+  const [results, setResults] = useState([]);
+  const [generating, setGenerating] = useState(false);
+
+  if (!isOpen) return null;
+
+  const updateMember = (index, field, value) => {
+    setMembers(members.map((m, i) => (i === index ? { ...m, [field]: value } : m)));
+  };
+
+  const handleGenerate = async () => {
+    setGenerating(true);
+    try {
+      const configs = await invoke("generate_mesh", { members });
+      setResults(configs);
+      onShowToast(`已生成 ${configs.length} 个成员的配置`, "success");
+    } catch (err) {
+      onShowToast("生成 Mesh 配置失败: " + err, "error");
+    } finally {
+      setGenerating(false);
+    }
+  };
+
+  const handleSave = async (result) => {
+    try {
+      const filePath = await save({
+        defaultPath: `${result.name}.conf`,
+        filters: [{ name: "WireGuard 配置", extensions: ["conf"] }],
+      });
+      if (filePath) {
+        await invoke("save_config_to_path", { content: result.conf, filePath });
+        onShowToast(`${result.name} 的配置已保存`, "success");
+      }
+    } catch (err) {
+      onShowToast("保存失败: " + err, "error");
+    }
+  };
+
+  const handleClose = () => {
+    // 配置中包含私钥，关闭时清空
+    setResults([]);
+    onClose();
+  };
+
+  return (
+    <div className="mesh-overlay">
+      <div className="mesh-dialog" onClick={(e) => e.stopPropagation()}>
+        <div className="mesh-header">
+          <h3>生成 Mesh 配置</h3>
+          <button onClick={handleClose} className="btn-close">✕</button>
+        </div>
+        <div className="mesh-body">
+          <p className="hint">
+            为每个成员生成密钥对，每个成员的配置中包含其他所有成员。没有公网地址的成员可留空 Endpoint。
+          </p>
+          {members.map((member, index) => (
+            <div key={index} className="mesh-member-row">
+              <input
+                type="text"
+                placeholder="名称"
+                value={member.name}
+                onChange={(e) => updateMember(index, "name", e.target.value)}
+              />
+              <input
+                type="text"
+                placeholder="VPN 地址，如 10.0.0.1/24"
+                value={member.address}
+                onChange={(e) => updateMember(index, "address", e.target.value)}
+              />
+              <input
+                type="text"
+                placeholder="Endpoint，如 1.2.3.4:51820（可选）"
+                value={member.endpoint}
+                onChange={(e) => updateMember(index, "endpoint", e.target.value)}
+              />
+              <button
+                className="btn-secondary"
+                onClick={() => setMembers(members.filter((_, i) => i !== index))}
+                disabled={members.length <= 2}
+              >
+                删除
+              </button>
+            </div>
+          ))}
+          <div className="button-group">
+            <button className="btn-secondary" onClick={() => setMembers([...members, emptyMember()])}>
+              添加成员
+            </button>
+            <button className="btn-primary" onClick={handleGenerate} disabled={generating}>
+              {generating ? "生成中..." : "生成配置"}
+            </button>
+          </div>
+
+          {results.map((result) => (
+            <div key={result.name} className="mesh-result">
+              <div className="config-header">
+                <h4>{result.name}</h4>
+                <button className="btn-save" onClick={() => handleSave(result)}>
+                  💾 另存为...
+                </button>
+              </div>
+              <pre className="config-content">{result.conf}</pre>
+            </div>
+          ))}
+        </div>
+      </div>
+    </div>
+  );
+}
+
+export default MeshGeneratorModal;
@@ -0,0 +1,31 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * 监听后端推送的配置目录变化（WebDAV 同步或手动修改文件后触发）
+ * @param {Function} onChange - 回调函数，接收发生变化的目录名列表
+ * @example
+ * useConfigsChangedListener((dirs) => {
+ *   // dirs 例如 ["tunnels", "history"]
+ *   if (dirs.includes('tunnels')) loadTunnels();
+ * });
+ */
+export function useConfigsChangedListener(onChange) {
+  useEffect(() => {
+    let unlisten;
+
+    listen('configs-changed', (event) => {
+      if (onChange && typeof onChange === 'function') {
+        onChange(event.payload || []);
+      }
+    }).then((fn) => {
+      unlisten = fn;
+    }).catch((error) => {
+      console.error('监听 configs-changed 事件失败:', error);
+    });
+
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, [onChange]);
+}
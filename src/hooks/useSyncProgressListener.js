@@ -0,0 +1,31 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * 监听后端推送的同步进度（双向同步时每个文件上传/下载前触发）
+ * @param {Function} onProgress - 回调函数，接收 { current, total, filename, direction }
+ * @example
+ * useSyncProgressListener(({ current, total, filename, direction }) => {
+ *   // direction 为 upload / download
+ *   console.log(`${current}/${total} ${filename}`);
+ * });
+ */
+export function useSyncProgressListener(onProgress) {
+  useEffect(() => {
+    let unlisten;
+
+    listen('sync-progress', (event) => {
+      if (event.payload && onProgress && typeof onProgress === 'function') {
+        onProgress(event.payload);
+      }
+    }).then((fn) => {
+      unlisten = fn;
+    }).catch((error) => {
+      console.error('监听 sync-progress 事件失败:', error);
+    });
+
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, [onProgress]);
+}
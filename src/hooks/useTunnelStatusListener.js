@@ -0,0 +1,31 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * 监听后端推送的隧道状态变化（握手超时、进程退出等）
+ * @param {Function} onChange - 回调函数，接收 { tunnel_id, status, last_handshake }
+ * @example
+ * useTunnelStatusListener(({ tunnel_id, status }) => {
+ *   // status 为 running / connecting / stale / stopped
+ *   console.log(tunnel_id, status);
+ * });
+ */
+export function useTunnelStatusListener(onChange) {
+  useEffect(() => {
+    let unlisten;
+
+    listen('tunnel-status-changed', (event) => {
+      if (event.payload && onChange && typeof onChange === 'function') {
+        onChange(event.payload);
+      }
+    }).then((fn) => {
+      unlisten = fn;
+    }).catch((error) => {
+      console.error('监听 tunnel-status-changed 事件失败:', error);
+    });
+
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, [onChange]);
+}
@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
 import Stepper from "../../components/Stepper";
 import ConfigTabs from "../../components/ConfigTabs";
+import MeshGeneratorModal from "../../components/MeshGeneratorModal";
 import "./style.css";
 
 export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
@@ -13,6 +14,9 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
   const [address, setAddress] = useState("");
   const [listenPort, setListenPort] = useState("");
   const [dns, setDns] = useState("");
+  const [dnsSearch, setDnsSearch] = useState("");
+  const [mtu, setMtu] = useState("");
+  const [showMeshGenerator, setShowMeshGenerator] = useState(false);
 
   // 对端配置（持久化）
   const [peerPublicKey, setPeerPublicKey] = useState("");
@@ -20,6 +24,10 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
   const [endpoint, setEndpoint] = useState("");
   const [allowedIps, setAllowedIps] = useState("0.0.0.0/0,::/0");
   const [keepalive, setKeepalive] = useState("25");
+  const [excludeEndpointRoute, setExcludeEndpointRoute] = useState(false);
+  const [rateLimitUp, setRateLimitUp] = useState("");
+  const [rateLimitDown, setRateLimitDown] = useState("");
+  const [expiresAt, setExpiresAt] = useState(""); // 到期时间 (datetime-local)，为空表示永久有效
 
   // 爱快配置（持久化）
   const [peerId, setpeerId] = useState(1);
@@ -33,7 +41,11 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
   const [surgeConfigContent, setSurgeConfigContent] = useState("");
   const [mikrotikConfigContent, setMikrotikConfigContent] = useState("");
   const [openwrtConfigContent, setOpenwrtConfigContent] = useState("");
+  const [pfsenseConfigContent, setPfsenseConfigContent] = useState("");
+  const [jsonConfigContent, setJsonConfigContent] = useState("");
   const [qrcodeDataUrl, setQrcodeDataUrl] = useState("");
+  const [configPreview, setConfigPreview] = useState("");
+  const [previewWarnings, setPreviewWarnings] = useState([]); // 预览中无效字段的提示
   const [workDir, setWorkDir] = useState("");
 
   // 累积的 peer 配置列表
@@ -113,6 +125,19 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
     }
   };
 
+  // 私钥输入框失焦时校验格式
+  const handlePrivateKeyBlur = async () => {
+    if (!privateKey) return;
+    try {
+      const ok = await invoke("validate_wireguard_key", { key: privateKey, keyType: "private" });
+      if (!ok) {
+        onShowToast("私钥看起来未经 clamp 处理，可能误填了公钥", "warning");
+      }
+    } catch (err) {
+      onShowToast(err, "error");
+    }
+  };
+
   // 验证步骤
   const validateStep1 = () => {
     if (!selectedServerId) {
@@ -141,6 +166,18 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
       return false;
     }
 
+    for (const [label, value] of [["上传限速", rateLimitUp], ["下载限速", rateLimitDown]]) {
+      if (value !== "" && (!Number.isInteger(Number(value)) || Number(value) <= 0)) {
+        onShowToast(`${label}必须为正整数 (Mbps)`, "warning");
+        return false;
+      }
+    }
+
+    if (expiresAt && new Date(expiresAt).getTime() <= Date.now()) {
+      onShowToast("到期时间必须晚于当前时间", "warning");
+      return false;
+    }
+
     if (!privateKey.trim()) {
       onShowToast("请生成或输入私钥", "warning");
       return false;
@@ -234,32 +271,70 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
     setStep(step - 1);
   };
 
+  // 组装传给后端的配置
+  const buildConfig = () => ({
+    interface_name: interfaceName,
+    private_key: privateKey,
+    address: address,
+    listen_port: listenPort || null,
+    dns: dns || null,
+    dns_search: dnsSearch.trim() || null,
+    mtu: mtu.trim() || null,
+    peer_public_key: peerPublicKey,
+    preshared_key: presharedKey || null,
+    endpoint: endpoint,
+    allowed_ips: allowedIps,
+    persistent_keepalive: keepalive || null,
+    exclude_endpoint_route: excludeEndpointRoute,
+    rate_limit_up: rateLimitUp ? Number(rateLimitUp) : null,
+    rate_limit_down: rateLimitDown ? Number(rateLimitDown) : null,
+    peer_id: peerId,
+    peer_interface: peerInterface,
+    peer_comment: peerComment,
+  });
+
+  // 填写客户端配置时实时预览 WireGuard 配置
+  useEffect(() => {
+    if (step !== 2) return;
+
+    const timer = setTimeout(async () => {
+      try {
+        const preview = await invoke("preview_wg_config", { config: buildConfig() });
+        setConfigPreview(preview.content);
+        setPreviewWarnings(preview.warnings);
+      } catch (err) {
+        setConfigPreview(`# 预览失败: ${err}`);
+        setPreviewWarnings([]);
+      }
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [step, interfaceName, privateKey, address, listenPort, dns, dnsSearch, mtu, peerPublicKey,
+    presharedKey, endpoint, allowedIps, keepalive, excludeEndpointRoute]);
+
   // 生成配置
   const handleGenerate = async () => {
     try {
       setLoading(true);
       onShowToast("正在生成配置...", "info");
 
-      const config = {
-        interface_name: interfaceName,
-        private_key: privateKey,
-        address: address,
-        listen_port: listenPort || null,
-        dns: dns || null,
-        peer_public_key: peerPublicKey,
-        preshared_key: presharedKey || null,
-        endpoint: endpoint,
-        allowed_ips: allowedIps,
-        persistent_keepalive: keepalive || null,
-        peer_id: peerId,
-        peer_interface: peerInterface,
-        peer_comment: peerComment,
-      };
+      const config = buildConfig();
+
+      // 提示 MTU 在 IPv6 链路上可能分片（不阻止生成）
+      const mtuWarnings = await invoke("check_config_mtu", { config });
+      mtuWarnings.forEach((warning) => onShowToast(warning, "warning"));
 
       const wgConfig = await invoke("generate_wg_config", { config, workDir });
       setWgConfigContent(wgConfig);
 
-      const ikuaiConfig = await invoke("generate_ikuai_config", { config, workDir });
+      // 爱快 Peer 配置不支持限速，生成时去掉限速并提示用户在流控分流中单独设置
+      const hasRateLimit = config.rate_limit_up !== null || config.rate_limit_down !== null;
+      const ikuaiConfig = await invoke("generate_ikuai_config", {
+        config: { ...config, rate_limit_up: null, rate_limit_down: null },
+        workDir,
+      });
+      if (hasRateLimit) {
+        onShowToast("爱快配置不包含限速，请在爱快的流控分流中为该客户端单独设置", "warning");
+      }
 
       const surgeConfig = await invoke("generate_surge_config", { config, workDir });
       setSurgeConfigContent(surgeConfig);
@@ -270,6 +345,12 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
       const openwrtConfig = await invoke("generate_openwrt_config", { config, workDir });
       setOpenwrtConfigContent(openwrtConfig);
 
+      const pfsenseConfig = await invoke("generate_pfsense_config", { config, workDir });
+      setPfsenseConfigContent(pfsenseConfig);
+
+      const jsonConfig = await invoke("generate_json_config", { config });
+      setJsonConfigContent(jsonConfig);
+
       setAllPeerConfigs(prev => [...prev, ikuaiConfig]);
 
       try {
@@ -301,9 +382,12 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
           surge_config: surgeConfig,
           mikrotik_config: mikrotikConfig,
           openwrt_config: openwrtConfig,
+          pfsense_config: pfsenseConfig,
+          json_config: jsonConfig,
           public_key: publicKey,
           server_id: selectedServerId,
           server_name: selectedServerName,
+          expires_at: expiresAt ? new Date(expiresAt).getTime() : null,
         };
         await invoke("save_to_history", { entry: historyEntry });
       } catch (err) {
@@ -360,8 +444,12 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
     setAddress("");
     setListenPort("");
     setDns("");
+    setMtu("");
 
     setpeerComment("");
+    setRateLimitUp("");
+    setRateLimitDown("");
+    setExpiresAt("");
 
     setWgConfigContent("");
     setSurgeConfigContent("");
@@ -389,6 +477,11 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
 
   return (
     <>
+      <MeshGeneratorModal
+        isOpen={showMeshGenerator}
+        onClose={() => setShowMeshGenerator(false)}
+        onShowToast={onShowToast}
+      />
 
       {/* 主内容区域 */}
       <div className={step === 3 ? 'config-content-wrapper config-success' : 'config-content-wrapper '}>
@@ -399,6 +492,9 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
 
             <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
               <h2>选择 WireGuard 服务端</h2>
+              <button className="btn-secondary" onClick={() => setShowMeshGenerator(true)}>
+                生成 Mesh 配置
+              </button>
             </div>
             <div className="hint-box">
               💡 请选择要连接的 WireGuard 服务端，或点击"服务端管理"新建一个
@@ -527,6 +623,7 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
                   type="text"
                   value={privateKey}
                   onChange={(e) => handlePrivateKeyChange(e.target.value)}
+                  onBlur={handlePrivateKeyBlur}
                   placeholder="粘贴已有私钥或点击生成"
                 />
                 <button onClick={handleGenerateKeypair} disabled={loading} className="btn-generate">
@@ -610,6 +707,83 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
                 />
               </div>
             </div>
+            <div className="form-row">
+              <div className="form-group">
+                <label>DNS 搜索域（可选）</label>
+                <input
+                  type="text"
+                  value={dnsSearch}
+                  onChange={(e) => setDnsSearch(e.target.value)}
+                  placeholder="corp.internal"
+                />
+              </div>
+
+              <div className="form-group">
+                <label>MTU（可选）</label>
+                <input
+                  type="number"
+                  min="576"
+                  value={mtu}
+                  onChange={(e) => setMtu(e.target.value)}
+                  placeholder="自动"
+                />
+              </div>
+            </div>
+            <div className="form-group">
+              <label>
+                <input
+                  type="checkbox"
+                  checked={excludeEndpointRoute}
+                  onChange={(e) => setExcludeEndpointRoute(e.target.checked)}
+                />
+                {" "}全局路由时排除服务端地址
+              </label>
+              <small>客户端与服务端在同一局域网时，避免握手包被路由进隧道形成环路（会将 AllowedIPs 拆分为不含服务端 IP 的多个网段）</small>
+            </div>
+            <div className="form-row">
+              <div className="form-group">
+                <label>上传限速 Mbps（可选）</label>
+                <input
+                  type="number"
+                  min="1"
+                  value={rateLimitUp}
+                  onChange={(e) => setRateLimitUp(e.target.value)}
+                  placeholder="不限制"
+                />
+              </div>
+
+              <div className="form-group">
+                <label>下载限速 Mbps（可选）</label>
+                <input
+                  type="number"
+                  min="1"
+                  value={rateLimitDown}
+                  onChange={(e) => setRateLimitDown(e.target.value)}
+                  placeholder="不限制"
+                />
+              </div>
+            </div>
+            <small>仅用于 MikroTik 配置（生成简单队列），爱快需在流控分流中单独设置</small>
+            <div className="form-group">
+              <label>到期时间（可选）</label>
+              <input
+                type="datetime-local"
+                value={expiresAt}
+                onChange={(e) => setExpiresAt(e.target.value)}
+              />
+              <small>到期后历史记录会标记为已过期，留空表示永久有效</small>
+            </div>
+            {configPreview && (
+              <div className="form-group">
+                <label>配置预览</label>
+                <pre className="config-content">{configPreview}</pre>
+                {previewWarnings.map((warning) => (
+                  <small key={warning} style={{ color: "#e67e22" }}>
+                    ⚠️ {warning}
+                  </small>
+                ))}
+              </div>
+            )}
             <div className="button-group">
               <button onClick={handlePrev} className="btn-secondary">
                 上一步
@@ -637,6 +811,8 @@ export default function ConfigGeneratorView({ onShowToast, onNavPage }) {
               allPeerConfigs={allPeerConfigs}
               mikrotikConfigContent={mikrotikConfigContent}
               openwrtConfigContent={openwrtConfigContent}
+              pfsenseConfigContent={pfsenseConfigContent}
+              jsonConfigContent={jsonConfigContent}
               publicKey={publicKey}
               onShowToast={onShowToast}
               onSavePeerConfig={handleSavePeerConfig}
@@ -1,10 +1,14 @@
 import { invoke } from "@tauri-apps/api/core";
-import { save } from "@tauri-apps/plugin-dialog";
-import { useState, useEffect } from "react";
+import { open, save } from "@tauri-apps/plugin-dialog";
+import { useState, useEffect, useCallback } from "react";
 import { useToast } from "../../hooks/useToast";
+import { useConfigsChangedListener } from "../../hooks/useConfigsChangedListener";
 import HistoryDetailModal from "../../components/HistoryDetailModal";
 import "./style.css";
 
+// 历史记录每页条数
+const HISTORY_PAGE_SIZE = 50;
+
 function HistoryView({
   onSetConfirmDialogConfig,
   onSetShowConfirmDialog,
@@ -12,7 +16,13 @@ function HistoryView({
   const { messages, showToast, removeToast } = useToast();
   const [serverList, setServerList] = useState([]);
   const [historyList, setHistoryList] = useState([]);
+  const [historyTotal, setHistoryTotal] = useState(0);
   const [selectedServerId, setSelectedServerId] = useState("");
+  const [searchQuery, setSearchQuery] = useState("");
+  const [selectedTags, setSelectedTags] = useState([]);
+  const [allTags, setAllTags] = useState([]); // 所有记录中出现过的标签（包括未加载的页）
+  const [filteredHistoryList, setFilteredHistoryList] = useState([]);
+  const [compressHistory, setCompressHistory] = useState(false);
 
   // 弹窗相关状态
   const [showModal, setShowModal] = useState(false);
@@ -23,8 +33,50 @@ function HistoryView({
   useEffect(() => {
     loadServers();
     loadHistoryList();
+    loadPreferences();
+  }, []);
+
+  // 历史记录或服务端文件在外部发生变化（如 WebDAV 同步）时重新加载
+  const handleConfigsChanged = useCallback(async (dirs) => {
+    try {
+      if (dirs.includes("history")) {
+        const [items, total] = await invoke("get_history_page", { offset: 0, limit: HISTORY_PAGE_SIZE });
+        setHistoryList(items);
+        setHistoryTotal(total);
+        setAllTags(await invoke("list_history_tags"));
+      }
+      if (dirs.includes("servers")) {
+        setServerList(await invoke("get_server_list"));
+      }
+    } catch (err) {
+      console.error("刷新历史记录失败:", err);
+    }
   }, []);
 
+  useConfigsChangedListener(handleConfigsChanged);
+
+  const loadPreferences = async () => {
+    try {
+      const preferences = await invoke("load_preferences");
+      setCompressHistory(preferences.compress_history);
+    } catch (err) {
+      console.error("加载偏好设置失败:", err);
+    }
+  };
+
+  // 切换历史记录压缩存储（只影响之后保存的记录）
+  const handleToggleCompress = async (enabled) => {
+    try {
+      const preferences = await invoke("load_preferences");
+      await invoke("save_preferences", {
+        preferences: { ...preferences, compress_history: enabled },
+      });
+      setCompressHistory(enabled);
+    } catch (err) {
+      showToast("保存设置失败: " + err, "error");
+    }
+  };
+
   const loadServers = async () => {
     try {
       const list = await invoke("get_server_list");
@@ -33,11 +85,33 @@ function HistoryView({
       console.error("加载服务端列表失败:", err);
     }
   };
-  // 获取筛选后的历史记录
-  const filteredHistoryList = selectedServerId
-    ? historyList.filter(item => item.server_id === selectedServerId)
-    : historyList;
-  
+  // 按关键字、服务端和标签筛选历史记录（由后端搜索）
+  useEffect(() => {
+    if (!searchQuery.trim() && !selectedServerId && selectedTags.length === 0) {
+      setFilteredHistoryList(historyList);
+      return;
+    }
+
+    const timer = setTimeout(async () => {
+      try {
+        setFilteredHistoryList(await invoke("search_history", {
+          query: searchQuery,
+          serverId: selectedServerId || null,
+          tags: selectedTags,
+        }));
+      } catch (err) {
+        console.error("搜索历史记录失败:", err);
+      }
+    }, 200);
+    return () => clearTimeout(timer);
+  }, [historyList, searchQuery, selectedServerId, selectedTags]);
+
+  const handleToggleTag = (tag) => {
+    setSelectedTags(prev =>
+      prev.includes(tag) ? prev.filter(t => t !== tag) : [...prev, tag]
+    );
+  };
+
   
   // 删除历史记录
   const handleDeleteHistory = async (id) => {
@@ -58,9 +132,10 @@ function HistoryView({
         return;
       }
 
-      // 获取所有历史记录的详细信息
+      // 获取所有历史记录的详细信息（列表只加载了部分页）
+      const allItems = await invoke("get_history_list");
       const allPeers = [];
-      for (const item of historyList) {
+      for (const item of allItems) {
         try {
           const detail = await invoke("get_history_detail", { id: item.id });
           allPeers.push(detail.ikuai_config);
@@ -100,7 +175,7 @@ function HistoryView({
     console.log("handleClearCache");
     onSetConfirmDialogConfig({
       title: "⚠️ 清空历史记录",
-      message: `确定要清空所有历史记录吗？\n\n这会删除：\n• 所有历史记录（共 ${historyList.length} 条）\n\n注意：服务端配置不会被删除\n此操作不可恢复！`,
+      message: `确定要清空所有历史记录吗？\n\n这会删除：\n• 所有历史记录（共 ${historyTotal} 条）\n\n注意：服务端配置不会被删除\n此操作不可恢复！`,
       onConfirm: confirmClearCache,
     });
     onSetShowConfirmDialog(true);
@@ -140,13 +215,54 @@ function HistoryView({
 
       if (filePath) {
         await invoke("export_all_configs_zip", { zipPath: filePath });
-        showToast(`已导出 ${historyList.length} 条配置到 ZIP 文件`, "success");
+        showToast(`已导出 ${historyTotal} 条配置到 ZIP 文件`, "success");
       }
     } catch (err) {
       showToast("导出 ZIP 失败: " + err, "error");
     }
   };
 
+  // 从导出的 ZIP 恢复历史记录
+  const handleImportZip = async () => {
+    try {
+      const filePath = await open({
+        title: '选择导出的 ZIP 文件',
+        multiple: false,
+        filters: [{
+          name: 'ZIP 压缩包',
+          extensions: ['zip']
+        }]
+      });
+      if (!filePath) return;
+
+      const count = await invoke("import_history_zip", { zipPath: filePath });
+      showToast(count > 0 ? `已导入 ${count} 条历史记录` : "没有需要导入的新记录", "success");
+      await loadHistoryList();
+    } catch (err) {
+      showToast("导入 ZIP 失败: " + err, "error");
+    }
+  };
+
+  // 导出历史记录为 CSV 表格
+  const handleExportCsv = async () => {
+    try {
+      const filePath = await save({
+        defaultPath: 'wireguard-history.csv',
+        filters: [{
+          name: 'CSV 表格',
+          extensions: ['csv']
+        }]
+      });
+
+      if (filePath) {
+        await invoke("export_history_csv", { csvPath: filePath });
+        showToast(`已导出 ${historyTotal} 条记录到 CSV 文件`, "success");
+      }
+    } catch (err) {
+      showToast("导出 CSV 失败: " + err, "error");
+    }
+  };
+
   // 查看历史记录详情（打开弹窗）
   const handleViewHistory = async (id) => {
     try {
@@ -171,22 +287,54 @@ function HistoryView({
   // 加载历史记录列表
   const loadHistoryList = async (serverId = null) => {
     try {
-      let list;
       if (serverId) {
-        list = await invoke("get_history_list_by_server", { serverId });
+        const list = await invoke("get_history_list_by_server", { serverId });
+        setHistoryList(list);
+        setHistoryTotal(list.length);
       } else {
-        list = await invoke("get_history_list");
+        // 重新加载时保留已加载的页数
+        const limit = Math.max(HISTORY_PAGE_SIZE, historyList.length);
+        const [items, total] = await invoke("get_history_page", { offset: 0, limit });
+        setHistoryList(items);
+        setHistoryTotal(total);
       }
-      setHistoryList(list);
+      setAllTags(await invoke("list_history_tags"));
     } catch (err) {
       console.error("加载历史记录失败:", err);
     }
   };
+
+  // 加载下一页历史记录
+  const handleLoadMore = async () => {
+    try {
+      const [items, total] = await invoke("get_history_page", {
+        offset: historyList.length,
+        limit: HISTORY_PAGE_SIZE,
+      });
+      setHistoryList(prev => [...prev, ...items]);
+      setHistoryTotal(total);
+    } catch (err) {
+      showToast("加载历史记录失败: " + err, "error");
+    }
+  };
   
   return (
     <div className="form-section">
       <div className="history-header">
         <h2>历史记录</h2>
+        <div className="history-header-actions">
+          <button onClick={handleImportZip} className="btn-secondary" style={{ fontSize: "0.8rem", padding: "0.3rem 0.6rem" }}>
+            导入 ZIP
+          </button>
+          <label style={{ fontSize: "0.85rem" }}>
+            <input
+              type="checkbox"
+              checked={compressHistory}
+              onChange={(e) => handleToggleCompress(e.target.checked)}
+            />
+            {" "}压缩存储新记录
+          </label>
+        </div>
       </div>
 
       {historyList.length === 0 ? (
@@ -215,10 +363,35 @@ function HistoryView({
             </div>
           )}
 
+          <div className="form-group history-filter">
+            <label>搜索</label>
+            <input
+              type="text"
+              value={searchQuery}
+              onChange={(e) => setSearchQuery(e.target.value)}
+              placeholder="备注、接口名称、地址、公钥或标签"
+            />
+            {allTags.length > 0 && (
+              <div className="history-tags">
+                {allTags.map(tag => (
+                  <button
+                    key={tag}
+                    type="button"
+                    className={selectedTags.includes(tag) ? "history-tag history-tag-active" : "history-tag"}
+                    onClick={() => handleToggleTag(tag)}
+                  >
+                    {tag}
+                  </button>
+                ))}
+              </div>
+            )}
+          </div>
+
           <div className="history-actions">
             <p className="history-hint">
-              共 {historyList.length} 条记录
-              {selectedServerId && ` | 筛选后: ${filteredHistoryList.length} 条`}
+              共 {historyTotal} 条记录
+              {(selectedServerId || searchQuery.trim() || selectedTags.length > 0) &&
+                ` | 筛选后: ${filteredHistoryList.length} 条`}
             </p>
             <div className="history-actions-buttons">
               <button onClick={handleClearCache} className="btn-primary" style={{ fontSize: "0.8rem", padding: "0.3rem 0.6rem" }}>
@@ -229,6 +402,9 @@ function HistoryView({
                   <button onClick={handleExportAllZip} className="btn-generate" style={{ fontSize: "0.8rem", padding: "0.3rem 0.6rem" }}>
                     导出 ZIP
                   </button>
+                  <button onClick={handleExportCsv} className="btn-generate" style={{ fontSize: "0.8rem", padding: "0.3rem 0.6rem" }}>
+                    导出 CSV
+                  </button>
                   <button onClick={handleExportAllPeers} className="btn-generate" style={{ fontSize: "0.8rem", padding: "0.3rem 0.6rem" }}>
                     导出 Peers
                   </button>
@@ -269,11 +445,30 @@ function HistoryView({
                 </div>
                 <div className="history-card-meta">
                   {item.interface_name} | {item.address} | {new Date(item.timestamp).toLocaleString()}
+                  {item.tags && item.tags.length > 0 && (
+                    <span style={{ marginLeft: "0.5rem" }}>| 标签: {item.tags.join(", ")}</span>
+                  )}
+                  {item.expired ? (
+                    <span style={{ color: "#e74c3c", marginLeft: "0.5rem" }}>已过期</span>
+                  ) : item.expires_at ? (
+                    <span style={{ marginLeft: "0.5rem" }}>
+                      | 到期: {new Date(item.expires_at).toLocaleString()}
+                    </span>
+                  ) : null}
                 </div>
               </div>
             ))}
           </div>
 
+          {!selectedServerId && !searchQuery.trim() && selectedTags.length === 0 &&
+            historyList.length < historyTotal && (
+            <div className="history-load-more">
+              <button onClick={handleLoadMore} className="btn-secondary">
+                加载更多 ({historyList.length}/{historyTotal})
+              </button>
+            </div>
+          )}
+
           {/* 历史记录详情弹窗 */}
           {showModal && selectedHistory && (
             <HistoryDetailModal
@@ -284,7 +479,8 @@ function HistoryView({
                 setShowModal(false);
                 setSelectedHistory(null);
               }}
-              showToast={showToast}
+              onShowToast={showToast}
+              onTagsChanged={loadHistoryList}
             />
           )}
         </>
@@ -1,11 +1,15 @@
-import { useState } from "react";
+import { useState, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { useConfigsChangedListener } from "../../hooks/useConfigsChangedListener";
 import ConfirmDialog from "../../components/ConfirmDialog";
 
 function ServerManagementView({
   onShowToast,
 }) {
   const [serverList, setServerList] = useState([]);
+  const [fingerprints, setFingerprints] = useState({});
+  const [resolutions, setResolutions] = useState({}); // server_id -> Endpoint 解析结果
+  const [resolving, setResolving] = useState(false);
   const [selectedServer, setSelectedServer] = useState(null);
   const [showForm, setShowForm] = useState(false);
   const [isEditing, setIsEditing] = useState(false);
@@ -35,6 +39,13 @@ function ServerManagementView({
     try {
       const list = await invoke("get_server_list");
       setServerList(list);
+
+      try {
+        const fps = await invoke("get_server_fingerprints");
+        setFingerprints(Object.fromEntries(fps.map((fp) => [fp.id, fp.peer_public_key_fp])));
+      } catch (err) {
+        console.error("获取服务端指纹失败:", err);
+      }
     } catch (err) {
       console.error("加载服务端列表失败:", err);
       onShowToast("加载服务端列表失败: " + err, "error");
@@ -46,6 +57,18 @@ function ServerManagementView({
     loadServerList();
   }, []);
 
+  // 服务端文件在外部发生变化（如 WebDAV 同步）时重新加载
+  const handleConfigsChanged = useCallback(async (dirs) => {
+    if (!dirs.includes("servers")) return;
+    try {
+      setServerList(await invoke("get_server_list"));
+    } catch (err) {
+      console.error("刷新服务端列表失败:", err);
+    }
+  }, []);
+
+  useConfigsChangedListener(handleConfigsChanged);
+
   // 查看服务端详情
   const handleViewServer = async (id) => {
     try {
@@ -56,6 +79,38 @@ function ServerManagementView({
     }
   };
 
+  // 从本机探测服务端端口 (不代表外网可达性)
+  const handleProbePort = async (id) => {
+    try {
+      onShowToast("正在从本机探测端口...", "info");
+      const result = await invoke("probe_server_port_locally", { serverId: id });
+      onShowToast(
+        `${result.resolved_ip}:${result.port} ${result.port_open ? "未被拒绝" : "不可达"} - ${result.tested}`,
+        result.port_open ? "success" : "warning"
+      );
+    } catch (err) {
+      onShowToast("检测失败: " + err, "error");
+    }
+  };
+
+  // 解析所有服务端的 Endpoint
+  const handleResolveAll = async () => {
+    setResolving(true);
+    try {
+      const results = await invoke("resolve_all_server_endpoints");
+      setResolutions(Object.fromEntries(results.map((r) => [r.server_id, r])));
+      const failed = results.filter((r) => !r.ok).length;
+      onShowToast(
+        failed === 0 ? `全部 ${results.length} 个 Endpoint 解析成功` : `${failed} 个 Endpoint 解析失败`,
+        failed === 0 ? "success" : "warning"
+      );
+    } catch (err) {
+      onShowToast("解析 Endpoint 失败: " + err, "error");
+    } finally {
+      setResolving(false);
+    }
+  };
+
   // 新建服务端
   const handleNewServer = () => {
     setFormData({
@@ -433,6 +488,11 @@ function ServerManagementView({
                    清空所有服务端
                 </button>
               )}
+              {serverList.length > 0 && (
+                <button onClick={handleResolveAll} className="btn-secondary" style={{ fontSize: "0.8rem", padding: "0.4rem 0.7rem" }} disabled={resolving}>
+                  {resolving ? "解析中..." : "解析全部 Endpoint"}
+                </button>
+              )}
               <button onClick={handleNewServer} className="btn-primary" style={{ fontSize: "0.9rem" }}>
                 新建服务端
               </button>
@@ -463,6 +523,14 @@ function ServerManagementView({
                         <strong style={{ fontSize: "1rem" }}>{server.name}</strong>
                         <div style={{ fontSize: "0.85rem", color: "var(--text-muted)", marginTop: "0.25rem" }}>
                           {server.endpoint} | Peer ID 计数: {server.next_peer_id}
+                          {fingerprints[server.id] && ` | 公钥指纹: ${fingerprints[server.id]}`}
+                          {resolutions[server.id] && (
+                            <span style={{ color: resolutions[server.id].ok ? "var(--text-muted)" : "#e74c3c" }}>
+                              {resolutions[server.id].ok
+                                ? ` | 解析: ${resolutions[server.id].resolved_ip}`
+                                : ` | 解析失败: ${resolutions[server.id].error}`}
+                            </span>
+                          )}
                         </div>
                       </div>
                       <div style={{ display: "flex", gap: "0.5rem" }}>
@@ -476,6 +544,17 @@ function ServerManagementView({
                         >
                           编辑
                         </button>
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation();
+                            handleProbePort(server.id);
+                          }}
+                          className="btn-generate"
+                          style={{ fontSize: "0.75rem", padding: "0.2rem 0.5rem" }}
+                          title="从本机向服务端端口发送 UDP 探测包，结果不代表外网可达性"
+                        >
+                          检测
+                        </button>
                         <button
                           onClick={(e) => {
                             e.stopPropagation();
@@ -23,7 +23,8 @@ function PeerConfigModal({
   }
 
   const peer = tunnel.peers[peerIndex];
-  const peerTitle = peer?.remark ? `${peer.remark} 的配置` : `Peer ${peerIndex + 1} 配置`;
+  const peerLabel = peer?.name || peer?.remark;
+  const peerTitle = peerLabel ? `${peerLabel} 的配置` : `Peer ${peerIndex + 1} 配置`;
 
   return (
     <div className="modal-overlay">
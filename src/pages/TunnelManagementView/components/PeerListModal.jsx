@@ -1,9 +1,11 @@
 import { useState, useRef, useEffect } from 'react';
 
-function PeerListModal({ tunnel, onClose, onViewPeerConfig, formatBytes, formatTime }) {
+function PeerListModal({ tunnel, onClose, onViewPeerConfig, onExportCsv, onRenamePeer, formatBytes, formatTime }) {
   const modalBodyRef = useRef(null);
   const scrollPositionRef = useRef(0);
   const [prevPeerCount, setPrevPeerCount] = useState(0);
+  const [editingKey, setEditingKey] = useState(null); // 正在重命名的 Peer 公钥
+  const [editingName, setEditingName] = useState('');
 
   // 保存滚动位置
   useEffect(() => {
@@ -44,14 +46,33 @@ function PeerListModal({ tunnel, onClose, onViewPeerConfig, formatBytes, formatT
     return null;
   }
 
+  const startRename = (peer) => {
+    setEditingKey(peer.public_key);
+    setEditingName(peer.name || '');
+  };
+
+  const submitRename = async () => {
+    const saved = await onRenamePeer(editingKey, editingName.trim());
+    if (saved) {
+      setEditingKey(null);
+    }
+  };
+
   return (
     <div className="modal-overlay">
       <div className="modal-content" onClick={(e) => e.stopPropagation()}>
         <div className="modal-header">
           <h3>Peer 列表 - {tunnel.name}</h3>
-          <button onClick={onClose} className="btn-close">
-            ✕
-          </button>
+          <div style={{ display: 'flex', gap: '0.5rem', alignItems: 'center' }}>
+            {onExportCsv && tunnel.peers && tunnel.peers.length > 0 && (
+              <button onClick={() => onExportCsv(tunnel)} className="btn-secondary">
+                导出流量 CSV
+              </button>
+            )}
+            <button onClick={onClose} className="btn-close">
+              ✕
+            </button>
+          </div>
         </div>
         <div className="modal-body" ref={modalBodyRef}>
           {tunnel.peers && tunnel.peers.length > 0 ? (
@@ -59,18 +80,45 @@ function PeerListModal({ tunnel, onClose, onViewPeerConfig, formatBytes, formatT
               {tunnel.peers.map((peer, index) => (
                 <div key={peer.public_key || index} className="peer-list-item">
                   <div className="peer-list-item-header">
-                    <h4>
-                      {peer.remark ? `${peer.remark}` : `Peer ${index + 1}`}
-                      {peer.remark && <span style={{ fontSize: '0.9em', color: '#999', marginLeft: '8px' }}>({peer.address || 'N/A'})</span>}
-                    </h4>
-                    <button
-                      onClick={() => onViewPeerConfig(index)}
-                      className="btn-secondary"
-                    >
-                      查看配置
-                    </button>
+                    {editingKey === peer.public_key ? (
+                      <div style={{ display: 'flex', gap: '0.5rem', alignItems: 'center', flex: 1 }}>
+                        <input
+                          type="text"
+                          value={editingName}
+                          onChange={(e) => setEditingName(e.target.value)}
+                          onKeyDown={(e) => e.key === 'Enter' && submitRename()}
+                          placeholder="留空则清除名称"
+                          autoFocus
+                        />
+                        <button onClick={submitRename} className="btn-primary">保存</button>
+                        <button onClick={() => setEditingKey(null)} className="btn-secondary">取消</button>
+                      </div>
+                    ) : (
+                      <h4>
+                        {peer.name || peer.remark || `Peer ${index + 1}`}
+                        {(peer.name || peer.remark) && <span style={{ fontSize: '0.9em', color: '#999', marginLeft: '8px' }}>({peer.address || 'N/A'})</span>}
+                      </h4>
+                    )}
+                    <div style={{ display: 'flex', gap: '0.5rem' }}>
+                      {onRenamePeer && editingKey !== peer.public_key && (
+                        <button onClick={() => startRename(peer)} className="btn-secondary">
+                          重命名
+                        </button>
+                      )}
+                      <button
+                        onClick={() => onViewPeerConfig(index)}
+                        className="btn-secondary"
+                      >
+                        查看配置
+                      </button>
+                    </div>
                   </div>
                   <div className="peer-list-item-body">
+                    {peer.name && (
+                      <div className="detail-group">
+                        <div><label>公钥:</label><code style={{ fontSize: '0.75rem', wordBreak: 'break-all' }}>{peer.public_key}</code></div>
+                      </div>
+                    )}
                     <div className="detail-group">
                       
                       <div><label>上传流量:</label>{formatBytes ? formatBytes(peer.tx_bytes || 0) : '0 B'}</div>
@@ -83,6 +131,16 @@ function PeerListModal({ tunnel, onClose, onViewPeerConfig, formatBytes, formatT
                       
                       <div><label>上次握手:</label>{formatTime ? formatTime(peer.last_handshake) : '从未'}</div>
                     </div>
+                    {peer.expires_at && (
+                      <div className="detail-group">
+                        <div>
+                          <label>到期时间:</label>
+                          {peer.expires_at <= Date.now()
+                            ? <span style={{ color: '#e74c3c' }}>已过期</span>
+                            : new Date(peer.expires_at).toLocaleString('zh-CN')}
+                        </div>
+                      </div>
+                    )}
                   </div>
                 </div>
               ))}
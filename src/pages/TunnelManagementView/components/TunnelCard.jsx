@@ -6,6 +6,12 @@ function TunnelCard({
   onEdit,
   onViewDetails,
   onViewPeerList,
+  onResetStats,
+  onMarkLastKnownGood,
+  onRollback,
+  onToggleAutostart,
+  onToggleReconnect,
+  onDuplicate,
   onDelete,
   formatBytes,
   formatTime,
@@ -20,6 +26,11 @@ function TunnelCard({
              tunnel.status === 'stopped' ? '🔴 已停止' :
              '🟡 连接中'}
           </span>
+          {tunnel.status === 'running' && tunnel.stale && (
+            <span className="tunnel-status status-connecting" title="超过 3 分钟未完成握手">
+              ⚠️ 握手超时
+            </span>
+          )}
         </div>
         <div className="tunnel-actions-inline">
           {tunnel.status === 'running' ? (
@@ -55,6 +66,45 @@ function TunnelCard({
           >
             详情
           </button>
+          <button
+            onClick={() => onDuplicate(tunnel)}
+            className="btn-secondary"
+            disabled={loading}
+            title="复制为新隧道并生成新的密钥对"
+          >
+            复制
+          </button>
+          <button
+            onClick={() => onMarkLastKnownGood(tunnel.id)}
+            className="btn-secondary"
+            title="将当前配置保存为最后可用配置，修改出错时可以回滚"
+          >
+            保存快照
+          </button>
+          <button
+            onClick={() => onRollback(tunnel.id)}
+            className="btn-secondary"
+            disabled={loading}
+            title="恢复到最后可用配置，隧道运行中时会在线更新 Peer"
+          >
+            回滚
+          </button>
+          <button
+            onClick={() => onToggleAutostart(tunnel.id, !tunnel.auto_start)}
+            className="btn-secondary"
+            disabled={loading}
+            title="开机后无需打开应用即可自动启动该隧道"
+          >
+            {tunnel.auto_start ? '取消自启' : '开机自启'}
+          </button>
+          <button
+            onClick={() => onToggleReconnect(tunnel.id, !tunnel.reconnect_on_network_change)}
+            className="btn-secondary"
+            disabled={loading}
+            title="切换 Wi-Fi 或有线网络后自动重新连接该隧道"
+          >
+            {tunnel.reconnect_on_network_change ? '取消自动重连' : '切网重连'}
+          </button>
           {tunnel.mode === 'server' && tunnel.peers && tunnel.peers.length > 0 && (
             <button
               onClick={() => onViewPeerList(tunnel.id)}
@@ -97,6 +147,13 @@ function TunnelCard({
               <span className="stat-label">最后握手:</span>
               <span className="stat-value">{formatTime(tunnel.last_handshake)}</span>
             </div>
+            <button
+              onClick={() => onResetStats(tunnel.id)}
+              className="btn-secondary"
+              title="将上传/下载流量清零，从现在开始重新统计"
+            >
+              重置统计
+            </button>
           </>
         )}
       </div>
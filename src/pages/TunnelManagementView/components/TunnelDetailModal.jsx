@@ -1,8 +1,92 @@
-function TunnelDetailModal({ tunnel, onClose, formatBytes, formatTime }) {
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+function TunnelDetailModal({ tunnel, onClose, formatBytes, formatTime, onExportConf, onProfileApplied, showWireguardGoLog }) {
+  const [uapiDump, setUapiDump] = useState(null);
+  const [profiles, setProfiles] = useState([]);
+  const [selectedProfile, setSelectedProfile] = useState('');
+  const [profileMessage, setProfileMessage] = useState('');
+  const [mtuCheck, setMtuCheck] = useState(null);
+  const [wgGoLog, setWgGoLog] = useState(null);
+  const [wgGoLogFilter, setWgGoLogFilter] = useState('');
+
+  useEffect(() => {
+    invoke('list_config_profiles')
+      .then(setProfiles)
+      .catch((err) => console.error('获取配置方案失败:', err));
+  }, []);
+
+  // 切换隧道时清空上次读取的 UAPI 状态
+  useEffect(() => {
+    setUapiDump(null);
+    setWgGoLog(null);
+    setSelectedProfile('');
+    setProfileMessage('');
+  }, [tunnel?.id]);
+
+  // 运行中的隧道读取接口实际 MTU，与配置值对比，确认 MTU 设置已生效
+  useEffect(() => {
+    setMtuCheck(null);
+    if (!tunnel || tunnel.status !== 'running') {
+      return;
+    }
+    Promise.all([
+      invoke('get_interface_mtu', { tunnelId: tunnel.id }),
+      invoke('get_tunnel_config', { tunnelId: tunnel.id }),
+    ])
+      .then(([actual, config]) => {
+        const configured = parseInt(config.mtu, 10);
+        setMtuCheck({ actual, configured: Number.isNaN(configured) ? null : configured });
+      })
+      .catch((error) => setMtuCheck({ error: String(error) }));
+  }, [tunnel?.id, tunnel?.status]);
+
   if (!tunnel) {
     return null;
   }
 
+  // 读取原始 UAPI 状态（私钥已隐藏），用于排查握手问题
+  const handleDumpUapi = async () => {
+    try {
+      const dump = await invoke('dump_uapi_state', { tunnelId: tunnel.id });
+      setUapiDump(dump);
+    } catch (error) {
+      setUapiDump(`读取失败: ${error}`);
+    }
+  };
+
+  // 读取该隧道的 wireguard-go 日志（最后 200 行，可按关键字过滤）
+  const handleLoadWgGoLog = async () => {
+    try {
+      const log = await invoke('get_wireguard_go_log', {
+        tunnelId: tunnel.id,
+        lines: 200,
+        filter: wgGoLogFilter || null,
+      });
+      setWgGoLog(log || '（无匹配的日志）');
+    } catch (error) {
+      setWgGoLog(`读取失败: ${error}`);
+    }
+  };
+
+  // 应用内置配置方案（keepalive / MTU / DNS）
+  const handleApplyProfile = async () => {
+    if (!selectedProfile) {
+      return;
+    }
+    try {
+      await invoke('apply_config_profile', { tunnelId: tunnel.id, profile: selectedProfile });
+      setProfileMessage(
+        tunnel.status === 'running' ? '已应用，重启隧道后生效' : '已应用'
+      );
+      onProfileApplied?.();
+    } catch (error) {
+      setProfileMessage(`应用失败: ${error}`);
+    }
+  };
+
+  const currentProfile = profiles.find((p) => p.id === selectedProfile);
+
   return (
     <div className="modal-overlay">
       <div className="modal-content" onClick={(e) => e.stopPropagation()}>
@@ -53,6 +137,35 @@ function TunnelDetailModal({ tunnel, onClose, formatBytes, formatTime }) {
             <label>公钥:</label>
             <div className="code-block">{tunnel.public_key}</div>
           </div>
+          <div className="detail-group">
+            <label>配置方案:</label>
+            <div>
+              <select value={selectedProfile} onChange={(e) => setSelectedProfile(e.target.value)}>
+                <option value="">选择网络类型</option>
+                {profiles.map((p) => (
+                  <option key={p.id} value={p.id}>
+                    {p.name}
+                  </option>
+                ))}
+              </select>
+              <button
+                onClick={handleApplyProfile}
+                className="btn-secondary"
+                disabled={!selectedProfile}
+                style={{ marginLeft: '0.5rem' }}
+              >
+                应用
+              </button>
+              {currentProfile && (
+                <small style={{ display: 'block', marginTop: '0.25rem', color: 'var(--text-muted)' }}>
+                  {currentProfile.description}：MTU {currentProfile.mtu}，
+                  Keepalive {currentProfile.persistent_keepalive ?? '关闭'}
+                  {currentProfile.dns ? `，DNS ${currentProfile.dns}（仅在未配置时填入）` : ''}
+                </small>
+              )}
+              {profileMessage && <small style={{ display: 'block', marginTop: '0.25rem' }}>{profileMessage}</small>}
+            </div>
+          </div>
           {tunnel.status === 'running' && (
             <>
               <div className="detail-group">
@@ -67,10 +180,67 @@ function TunnelDetailModal({ tunnel, onClose, formatBytes, formatTime }) {
                 <label>最后握手:</label>
                 <div>{formatTime(tunnel.last_handshake)}</div>
               </div>
+              {mtuCheck && (
+                <div className="detail-group">
+                  <label>接口 MTU:</label>
+                  <div>
+                    {mtuCheck.error ? (
+                      `读取失败: ${mtuCheck.error}`
+                    ) : (
+                      <>
+                        {mtuCheck.actual}
+                        {mtuCheck.configured !== null && mtuCheck.configured !== mtuCheck.actual && (
+                          <span style={{ color: 'var(--error-color)', marginLeft: '0.5rem' }}>
+                            ⚠️ 与配置值 {mtuCheck.configured} 不一致，MTU 设置可能被系统或驱动覆盖
+                          </span>
+                        )}
+                      </>
+                    )}
+                  </div>
+                </div>
+              )}
+              {uapiDump !== null && (
+                <div className="detail-group">
+                  <label>UAPI 状态:</label>
+                  <pre style={{ maxHeight: '300px', overflow: 'auto', fontSize: '0.8rem', whiteSpace: 'pre-wrap' }}>
+                    {uapiDump}
+                  </pre>
+                </div>
+              )}
             </>
           )}
+          {wgGoLog !== null && (
+            <div className="detail-group">
+              <label>wireguard-go 日志:</label>
+              <div>
+                <input
+                  type="text"
+                  value={wgGoLogFilter}
+                  onChange={(e) => setWgGoLogFilter(e.target.value)}
+                  onKeyDown={(e) => e.key === 'Enter' && handleLoadWgGoLog()}
+                  placeholder="按关键字过滤，回车刷新"
+                />
+                <pre style={{ maxHeight: '300px', overflow: 'auto', fontSize: '0.8rem', whiteSpace: 'pre-wrap' }}>
+                  {wgGoLog}
+                </pre>
+              </div>
+            </div>
+          )}
         </div>
         <div className="modal-footer">
+          <button onClick={() => onExportConf(tunnel)} className="btn-secondary">
+            导出 .conf
+          </button>
+          {showWireguardGoLog && (
+            <button onClick={handleLoadWgGoLog} className="btn-secondary">
+              wireguard-go 日志
+            </button>
+          )}
+          {tunnel.status === 'running' && (
+            <button onClick={handleDumpUapi} className="btn-secondary">
+              查看 UAPI 状态
+            </button>
+          )}
           <button onClick={onClose} className="btn-primary">
             关闭
           </button>
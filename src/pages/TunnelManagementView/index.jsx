@@ -5,6 +5,8 @@ import jsQR from 'jsqr';
 import ConfirmDialog from '../../components/ConfirmDialog';
 import DaemonPanel from '../../components/DaemonPanel';
 import { usePeerStatsListener } from '../../hooks/usePeerStatsListener';
+import { useConfigsChangedListener } from '../../hooks/useConfigsChangedListener';
+import { useTunnelStatusListener } from '../../hooks/useTunnelStatusListener';
 import {
   PeerConfigModal,
   PeerListModal,
@@ -15,6 +17,14 @@ import {
 import './style.css';
 
 // 解析 WireGuard 配置文件内容
+// 从注释中提取 Peer 名称，支持 "# alice" 和 "# Name = alice"，被注释掉的配置项不视为名称
+const peerNameFromComment = (comment) => {
+  const text = comment.replace(/^#+/, '').trim();
+  const match = text.match(/^(name|friendly_?name)\s*[=:]\s*(.*)$/i);
+  if (match) return match[2].trim();
+  return text.includes('=') ? '' : text;
+};
+
 const parseWireGuardConfig = (content) => {
   const config = {
     privateKey: '',
@@ -27,12 +37,19 @@ const parseWireGuardConfig = (content) => {
   const lines = content.split('\n');
   let currentSection = null;
   let currentPeer = null;
+  let pendingName = ''; // 紧挨 [Peer] 的注释作为 Peer 名称
 
   for (const line of lines) {
     const trimmed = line.trim();
 
     // 跳过空行和注释
     if (!trimmed || trimmed.startsWith('#')) {
+      pendingName = trimmed ? peerNameFromComment(trimmed) : '';
+      // [Peer] 下一行的注释
+      if (pendingName && currentPeer && !currentPeer.name && !currentPeer.publicKey) {
+        currentPeer.name = pendingName;
+        pendingName = '';
+      }
       continue;
     }
 
@@ -53,9 +70,12 @@ const parseWireGuardConfig = (content) => {
         address: '',
         allowedIps: '0.0.0.0/0',
         persistentKeepalive: 25,
+        name: pendingName,
       };
+      pendingName = '';
       continue;
     }
+    pendingName = '';
 
     // 解析键值对
     const [key, ...valueParts] = trimmed.split('=').map(s => s.trim());
@@ -462,12 +482,19 @@ function TunnelManagementView({ onShowToast }) {
   const [editingConfig, setEditingConfig] = useState(null);
   const [localPublicKey, setLocalPublicKey] = useState(''); // 本地公钥
   const [isLinux, setIsLinux] = useState(false); // 是否为 Linux 系统
+  const [isWindows, setIsWindows] = useState(false); // 是否为 Windows 系统
   const [allLocalIps, setAllLocalIps] = useState([]); // 所有设备本地IP列表
+  const [detectingMtu, setDetectingMtu] = useState(false);
+  const [protectedSubnets, setProtectedSubnets] = useState(''); // 受保护网段（偏好设置，所有隧道共用）
+  const [endpointCandidates, setEndpointCandidates] = useState([]); // 推荐的服务端 Endpoint
 
   // 守护进程管理状态 (仅 Linux)
   const [daemonStatus, setDaemonStatus] = useState(null);
   const [showDaemonPanel, setShowDaemonPanel] = useState(false);
 
+  // 外部组件检查结果（wireguard-go / WireGuard 客户端 / 守护进程）
+  const [prereqStatus, setPrereqStatus] = useState(null);
+
   // Peer 列表模态框状态
   const [showPeerList, setShowPeerList] = useState(false);
   const [peerListTunnel, setPeerListTunnel] = useState(null);
@@ -490,7 +517,14 @@ function TunnelManagementView({ onShowToast }) {
     address: '',
     listenPort: '',
     dns: '223.5.5.5, 223.6.6.6, 2400:3200::1, 2400:3200:baba::1',
+    dnsSearch: '', // DNS 搜索域（可选）
     mtu: '1420',
+    routeTable: '', // 路由表（仅 Linux，可选）
+    fwmark: '', // fwmark（仅 Linux，可选）
+    storeKeyInKeychain: false, // 私钥保存到系统钥匙串
+    killSwitch: false, // 隧道断开时阻止流量绕过隧道（Linux / macOS）
+    autoStart: false, // 开机自动启动（在隧道卡片上设置）
+    reconnectOnNetworkChange: false, // 网络切换后自动重连（在隧道卡片上设置）
     serverEndpoint: '', // 服务端的公网 IP 或域名（仅服务端）
     serverAllowedIps: '0.0.0.0/0', // 服务端允许客户端访问的网络范围（仅服务端）
     // Peer 配置 - 支持多个 Peer (服务端) 或单个 Peer (客户端)
@@ -503,6 +537,7 @@ function TunnelManagementView({ onShowToast }) {
   // 快速添加客户端备注输入对话框状态
   const [showRemarkInput, setShowRemarkInput] = useState(false);
   const [tempRemark, setTempRemark] = useState('');
+  const [tempExpiresAt, setTempExpiresAt] = useState(''); // 到期时间 (datetime-local)，为空表示永久有效
 
 
 
@@ -512,6 +547,7 @@ function TunnelManagementView({ onShowToast }) {
       const platformName = await invoke('get_platform');
       console.log('当前操作系统:', platformName);
       setIsLinux(platformName === 'linux');
+      setIsWindows(platformName === 'windows');
     };
 
     const fetchAllLocalIps = async () => {
@@ -532,8 +568,18 @@ function TunnelManagementView({ onShowToast }) {
       }
     };
 
+    const loadProtectedSubnets = async () => {
+      try {
+        const preferences = await invoke('load_preferences');
+        setProtectedSubnets((preferences.protected_subnets || []).join(', '));
+      } catch (error) {
+        console.error('加载偏好设置失败:', error);
+      }
+    };
+
     checkPlatform();
     fetchAllLocalIps();
+    loadProtectedSubnets();
   }, []);
 
   // 加载守护进程状态 (仅 Linux)
@@ -548,6 +594,19 @@ function TunnelManagementView({ onShowToast }) {
     }
   };
 
+  // 检查运行隧道所需的外部组件，守护进程状态变化后重新检查
+  useEffect(() => {
+    const checkPrerequisites = async () => {
+      try {
+        const status = await invoke('check_prerequisites');
+        setPrereqStatus(status);
+      } catch (error) {
+        console.error('检查运行环境失败:', error);
+      }
+    };
+    checkPrerequisites();
+  }, [daemonStatus?.running]);
+
   // 初始加载隧道列表（仅一次）
   useEffect(() => {
     loadTunnels();
@@ -594,12 +653,30 @@ function TunnelManagementView({ onShowToast }) {
     }
   };
 
+  // 输入框失焦时校验密钥格式,空值不提示
+  const handleValidateKey = async (key, keyType) => {
+    if (!key) return true;
+    try {
+      const ok = await invoke('validate_wireguard_key', { key, keyType });
+      if (!ok) {
+        onShowToast('私钥看起来未经 clamp 处理，可能误填了公钥', 'warning');
+      }
+      return true;
+    } catch (error) {
+      onShowToast(error, 'error');
+      return false;
+    }
+  };
+
   // 从私钥计算公钥
   const handleCalculatePublicKey = async () => {
     if (!config.privateKey || config.privateKey.trim() === '') {
       // 私钥为空时静默返回,不显示提示
       return;
     }
+    if (!(await handleValidateKey(config.privateKey, 'private'))) {
+      return;
+    }
     try {
       const publicKey = await invoke('private_key_to_public', { privateKey: config.privateKey });
       setLocalPublicKey(publicKey);
@@ -644,6 +721,44 @@ function TunnelManagementView({ onShowToast }) {
     }
   };
 
+  // 获取推荐的服务端 Endpoint (公网 IP 和局域网 IP，附带推荐端口)
+  const handleSuggestEndpoint = async () => {
+    try {
+      onShowToast('正在检测可用地址...', 'info');
+      const candidates = await invoke('suggest_server_endpoint');
+      setEndpointCandidates(candidates);
+    } catch (error) {
+      onShowToast('检测服务端地址失败: ' + error, 'error');
+    }
+  };
+
+  // 选择推荐的 Endpoint，未填写监听端口时一并使用推荐端口
+  const handlePickEndpoint = (candidate) => {
+    const match = candidate.match(/^\[?(.+?)\]?:(\d+)$/);
+    if (!match) return;
+    const [, host, port] = match;
+    setConfig({
+      ...config,
+      serverEndpoint: host,
+      listenPort: config.listenPort || port,
+    });
+    setEndpointCandidates([]);
+  };
+
+  // 测试 Endpoint 连通性 (TCP 连接计时)
+  const handlePingEndpoint = async (endpoint) => {
+    if (!endpoint) {
+      onShowToast('请先填写服务端地址', 'warning');
+      return;
+    }
+    try {
+      const ms = await invoke('ping_endpoint', { endpoint });
+      onShowToast(`${endpoint} 延迟: ${ms} ms`, 'success');
+    } catch (error) {
+      onShowToast('测试延迟失败: ' + error, 'error');
+    }
+  };
+
   // 添加 Peer
   const handleAddPeer = () => {
     setConfig({
@@ -666,6 +781,7 @@ function TunnelManagementView({ onShowToast }) {
   const handleQuickAddClient = () => {
     // 显示备注输入对话框
     setTempRemark('');
+    setTempExpiresAt('');
     setShowRemarkInput(true);
   };
 
@@ -678,6 +794,13 @@ function TunnelManagementView({ onShowToast }) {
         return;
       }
 
+      // 检查到期时间
+      const expiresAt = tempExpiresAt ? new Date(tempExpiresAt).getTime() : null;
+      if (expiresAt !== null && (isNaN(expiresAt) || expiresAt <= Date.now())) {
+        onShowToast('到期时间必须晚于当前时间', 'warning');
+        return;
+      }
+
       setShowRemarkInput(false);
 
       // 为客户端生成密钥对
@@ -736,6 +859,7 @@ function TunnelManagementView({ onShowToast }) {
         allowedIps: clientIp, // 服务端模式下，这里应该是客户端的 VPN IP，这样服务端才能路由到客户端
         persistentKeepalive: 0, // 服务端默认为 0，不需要保持连接
         remark: tempRemark.trim(), // 备注信息
+        expiresAt, // 到期时间 (毫秒时间戳)，到期后自动从接口移除
       };
 
       setConfig({
@@ -745,6 +869,7 @@ function TunnelManagementView({ onShowToast }) {
 
       onShowToast(`客户端"${tempRemark.trim()}"已添加，密钥对和预共享密钥已自动生成`, 'success');
       setTempRemark('');
+      setTempExpiresAt('');
     } catch (error) {
       onShowToast('快速添加客户端失败: ' + error, 'error');
     }
@@ -1027,6 +1152,13 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
           onShowToast('请输入服务端地址 (Endpoint)', 'warning');
           return;
         }
+        const keepalive = peer.persistentKeepalive;
+        if (keepalive !== '' && keepalive !== null && keepalive !== undefined &&
+            (!Number.isInteger(Number(keepalive)) || Number(keepalive) < 0 || Number(keepalive) > 65535)) {
+          const peerLabel = config.mode === 'server' ? `Peer ${i + 1}` : '服务端';
+          onShowToast(`${peerLabel}: PersistentKeepalive 应为 0-65535`, 'warning');
+          return;
+        }
       }
 
       setLoading(true);
@@ -1037,10 +1169,17 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
         name: config.name,
         mode: config.mode, // 保存模式信息
         private_key: config.privateKey,
+        store_key_in_keychain: config.storeKeyInKeychain,
         address: config.address,
         listen_port: String(config.listenPort || ''), // 确保是字符串
         dns: config.dns || '',
+        dns_search: config.dnsSearch.trim() || null,
         mtu: String(config.mtu || '1420'), // 确保是字符串
+        route_table: config.routeTable !== '' ? Number(config.routeTable) : null,
+        fwmark: config.fwmark !== '' ? Number(config.fwmark) : null,
+        kill_switch: config.killSwitch,
+        auto_start: config.autoStart,
+        reconnect_on_network_change: config.reconnectOnNetworkChange,
         server_endpoint: config.serverEndpoint || '', // 服务端的公网地址
         server_allowed_ips: config.serverAllowedIps || '0.0.0.0/0', // 服务端允许客户端访问的网络范围
         peers: config.peers.map(peer => ({
@@ -1050,12 +1189,26 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
           endpoint: peer.endpoint || null,
           address: peer.address || null, // 客户端的 VPN IP 地址
           allowed_ips: peer.allowedIps,
-          persistent_keepalive: peer.persistentKeepalive || null,
+          persistent_keepalive: peer.persistentKeepalive ? Number(peer.persistentKeepalive) : null,
           remark: peer.remark || null, // 备注信息
+          name: peer.name || null, // 友好名称
+          expires_at: peer.expiresAt || null, // 到期时间
         })),
         created_at: Date.now(),
       };
 
+      // 提示可能的配置问题（不阻止保存）
+      const warnings = await invoke('validate_tunnel_config', { config: tunnelConfig });
+      warnings.forEach((warning) => onShowToast(warning, 'warning'));
+
+      // 客户端模式下提示 DNS 泄露风险
+      if (tunnelConfig.mode === 'client') {
+        const dnsAnalysis = await invoke('analyze_dns_config', { config: tunnelConfig });
+        if (dnsAnalysis.leak_risk !== 'low') {
+          dnsAnalysis.notes.forEach((note) => onShowToast(note, 'warning'));
+        }
+      }
+
       await invoke('save_tunnel_config', { config: tunnelConfig });
       onShowToast('隧道配置已保存', 'success');
       setShowConfigForm(false);
@@ -1077,7 +1230,14 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
       address: '',
       listenPort: '',
       dns: '223.5.5.5, 223.6.6.6, 2400:3200::1, 2400:3200:baba::1',
+      dnsSearch: '',
       mtu: '1420',
+      routeTable: '',
+      fwmark: '',
+      storeKeyInKeychain: false,
+      killSwitch: false,
+      autoStart: false,
+      reconnectOnNetworkChange: false,
       serverEndpoint: '', // 重置服务端公网地址
       serverAllowedIps: '0.0.0.0/0', // 重置服务端 AllowedIPs
       peers: [],
@@ -1102,8 +1262,10 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
             endpoint: p.endpoint || '',
             address: p.address || '', // 客户端的 VPN IP 地址
             allowedIps: p.allowed_ips || '0.0.0.0/0',
-            persistentKeepalive: p.persistent_keepalive || 25,
+            persistentKeepalive: p.persistent_keepalive ?? 0, // 保留每个 Peer 各自的值 (0 表示关闭)
             remark: p.remark || '', // 备注信息
+            name: p.name || '', // 友好名称
+            expiresAt: p.expires_at ?? null, // 到期时间
           }))
         : [];
 
@@ -1111,10 +1273,17 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
         name: fullConfig.name,
         mode: fullConfig.mode || 'server', // 默认为 server 模式
         privateKey: fullConfig.private_key || '',
+        storeKeyInKeychain: fullConfig.store_key_in_keychain || false,
         address: fullConfig.address || '',
         listenPort: fullConfig.listen_port || '',
         dns: fullConfig.dns || '',
+        dnsSearch: fullConfig.dns_search || '',
         mtu: fullConfig.mtu || '1420',
+        routeTable: fullConfig.route_table ?? '',
+        fwmark: fullConfig.fwmark ?? '',
+        killSwitch: fullConfig.kill_switch || false,
+        autoStart: fullConfig.auto_start || false,
+        reconnectOnNetworkChange: fullConfig.reconnect_on_network_change || false,
         serverEndpoint: fullConfig.server_endpoint || '', // 加载服务端公网地址
         serverAllowedIps: fullConfig.server_allowed_ips || '0.0.0.0/0', // 加载服务端 AllowedIPs
         peers,
@@ -1168,6 +1337,183 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
     }
   };
 
+  // 导出隧道状态 HTML 页面（不含密钥、地址等信息）
+  const handleExportStatusHtml = async () => {
+    try {
+      const html = await invoke('export_status_html');
+      const filePath = await save({
+        defaultPath: 'wirevault-status.html',
+        filters: [{ name: 'HTML 页面', extensions: ['html'] }],
+      });
+      if (filePath) {
+        await invoke('save_config_to_path', { content: html, filePath });
+        onShowToast('状态页面已导出', 'success');
+      }
+    } catch (error) {
+      onShowToast('导出状态页面失败: ' + error, 'error');
+    }
+  };
+
+  // 导出隧道为 wg-quick 格式的 .conf 文件（包含私钥，用于备份或迁移）
+  const handleExportConf = async (tunnel) => {
+    try {
+      const content = await invoke('export_tunnel_to_conf', { tunnelId: tunnel.id });
+      const filePath = await save({
+        defaultPath: `${tunnel.name || tunnel.id}.conf`,
+        filters: [{ name: 'WireGuard 配置', extensions: ['conf'] }],
+      });
+      if (filePath) {
+        await invoke('save_config_to_path', { content, filePath });
+        onShowToast('配置已导出，文件包含私钥，请妥善保管', 'success');
+      }
+    } catch (error) {
+      onShowToast('导出配置失败: ' + error, 'error');
+    }
+  };
+
+  // 重置隧道流量统计（不重建接口）
+  const handleResetStats = async (tunnelId) => {
+    try {
+      await invoke('reset_tunnel_stats', { tunnelId });
+      onShowToast('流量统计已重置', 'success');
+      await loadTunnels();
+    } catch (error) {
+      onShowToast('重置流量统计失败: ' + error, 'error');
+    }
+  };
+
+  // 保存最后可用配置快照
+  const handleMarkLastKnownGood = async (tunnelId) => {
+    try {
+      await invoke('mark_last_known_good', { tunnelId });
+      onShowToast('已将当前配置保存为最后可用配置', 'success');
+    } catch (error) {
+      onShowToast('保存最后可用配置失败: ' + error, 'error');
+    }
+  };
+
+  // 探测到 Peer Endpoint 的路径 MTU，填入建议的隧道 MTU
+  const handleDetectMtu = async () => {
+    const endpoint = config.peers.map((peer) => peer.endpoint).find((e) => e && e.trim());
+    if (!endpoint) {
+      onShowToast('请先填写 Peer 的 Endpoint', 'warning');
+      return;
+    }
+    try {
+      setDetectingMtu(true);
+      const mtu = await invoke('detect_optimal_mtu', { endpoint });
+      setConfig((prev) => ({ ...prev, mtu: String(mtu) }));
+      onShowToast(`建议 MTU: ${mtu}`, 'success');
+    } catch (error) {
+      onShowToast('检测 MTU 失败: ' + error, 'error');
+    } finally {
+      setDetectingMtu(false);
+    }
+  };
+
+  // 检查监听端口是否被其他程序占用
+  const handleCheckListenPort = async () => {
+    const port = Number(config.listenPort);
+    if (!Number.isInteger(port) || port < 1 || port > 65535) return;
+    try {
+      const conflict = await invoke('check_listen_port', { port });
+      if (conflict) {
+        onShowToast(conflict, 'warning');
+      }
+    } catch (error) {
+      console.error('检查监听端口失败:', error);
+    }
+  };
+
+  // 设置开机自动启动
+  const handleToggleAutostart = async (tunnelId, enabled) => {
+    try {
+      setLoading(true);
+      await invoke('set_tunnel_autostart', { tunnelId, enabled });
+      onShowToast(enabled ? '已开启开机自动启动' : '已关闭开机自动启动', 'success');
+      await loadTunnels();
+    } catch (error) {
+      onShowToast('设置开机自动启动失败: ' + error, 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // 保存受保护网段（AllowedIPs 包含这些网段时校验会发出警告）
+  const handleSaveProtectedSubnets = async () => {
+    try {
+      const preferences = await invoke('load_preferences');
+      const subnets = protectedSubnets.split(',').map((s) => s.trim()).filter(Boolean);
+      await invoke('save_preferences', {
+        preferences: { ...preferences, protected_subnets: subnets },
+      });
+    } catch (error) {
+      onShowToast('保存受保护网段失败: ' + error, 'error');
+    }
+  };
+
+  // 设置网络切换后自动重连
+  const handleToggleReconnect = async (tunnelId, enabled) => {
+    try {
+      setLoading(true);
+      await invoke('set_reconnect_on_network_change', { tunnelId, enabled });
+      onShowToast(enabled ? '已开启网络切换后自动重连' : '已关闭网络切换后自动重连', 'success');
+      await loadTunnels();
+    } catch (error) {
+      onShowToast('设置自动重连失败: ' + error, 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // 回滚到最后可用配置
+  const handleRollbackTunnel = (tunnelId) => {
+    setConfirmDialog({
+      isOpen: true,
+      title: '回滚隧道配置',
+      message: '确定要恢复到最后可用配置吗？当前配置将被覆盖。隧道运行中时 Peer 会在线更新，地址、DNS 等需要重启隧道后生效。',
+      onConfirm: async () => {
+        setConfirmDialog({ ...confirmDialog, isOpen: false });
+        try {
+          setLoading(true);
+          await invoke('rollback_tunnel', { tunnelId });
+          onShowToast('已回滚到最后可用配置', 'success');
+          await loadTunnels();
+        } catch (error) {
+          onShowToast('回滚失败: ' + error, 'error');
+        } finally {
+          setLoading(false);
+        }
+      },
+    });
+  };
+
+  // 复制隧道配置（生成新的接口密钥对）
+  const handleDuplicateTunnel = (tunnel) => {
+    setConfirmDialog({
+      isOpen: true,
+      title: '复制隧道',
+      message: `确定要复制隧道 "${tunnel.name}" 吗？副本会使用新的密钥对，需要在对端添加副本的公钥。`,
+      onConfirm: async () => {
+        setConfirmDialog({ ...confirmDialog, isOpen: false });
+        try {
+          setLoading(true);
+          await invoke('duplicate_tunnel_config', {
+            tunnelId: tunnel.id,
+            newName: `${tunnel.name} 副本`,
+            regenerateKeys: true,
+          });
+          onShowToast('已复制隧道配置', 'success');
+          await loadTunnels();
+        } catch (error) {
+          onShowToast('复制隧道失败: ' + error, 'error');
+        } finally {
+          setLoading(false);
+        }
+      },
+    });
+  };
+
   // 删除隧道配置
   const handleDeleteTunnel = (tunnelId) => {
     setConfirmDialog({
@@ -1227,6 +1573,75 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
   // 监听 peer 统计更新事件
   usePeerStatsListener(handlePeerStatsUpdate);
 
+  // 隧道配置文件在外部发生变化（如 WebDAV 同步）时重新加载列表
+  const handleConfigsChanged = useCallback(async (dirs) => {
+    if (!dirs.includes('tunnels')) return;
+    try {
+      const list = await invoke('get_all_tunnel_configs');
+      setTunnels(list);
+    } catch (error) {
+      console.error('刷新隧道列表失败:', error);
+    }
+  }, []);
+
+  useConfigsChangedListener(handleConfigsChanged);
+
+  // 后端检测到隧道状态变化（握手超时、进程退出）时更新列表
+  const handleTunnelStatusChanged = useCallback(async ({ tunnel_id, status, last_handshake }) => {
+    if (status === 'stopped') {
+      try {
+        const list = await invoke('get_all_tunnel_configs');
+        setTunnels(list);
+      } catch (error) {
+        console.error('刷新隧道列表失败:', error);
+      }
+      return;
+    }
+
+    setTunnels(prev => prev.map(t =>
+      t.id === tunnel_id
+        ? { ...t, last_handshake: last_handshake ?? t.last_handshake, stale: status === 'stale' }
+        : t
+    ));
+  }, []);
+
+  useTunnelStatusListener(handleTunnelStatusChanged);
+
+  // 导出每个 Peer 的流量为 CSV
+  const handleExportPeerTraffic = async (tunnel) => {
+    try {
+      const path = await save({
+        defaultPath: `${tunnel.name}-traffic.csv`,
+        filters: [{ name: 'CSV', extensions: ['csv'] }],
+      });
+      if (!path) return;
+
+      await invoke('export_peer_traffic_csv', { tunnelId: tunnel.id, path });
+      onShowToast('流量统计已导出', 'success');
+    } catch (error) {
+      onShowToast('导出流量统计失败: ' + error, 'error');
+    }
+  };
+
+  // 设置 Peer 的友好名称，返回是否保存成功
+  const handleRenamePeer = async (publicKey, name) => {
+    if (!peerListTunnel) return false;
+    try {
+      await invoke('set_peer_name', { tunnelId: peerListTunnel.id, publicKey, name: name || null });
+      setPeerListTunnel((prev) => prev && {
+        ...prev,
+        peers: prev.peers.map((peer) =>
+          peer.public_key === publicKey ? { ...peer, name: name || null } : peer
+        ),
+      });
+      onShowToast(name ? `Peer 已重命名为"${name}"` : 'Peer 名称已清除', 'success');
+      return true;
+    } catch (error) {
+      onShowToast('设置 Peer 名称失败: ' + error, 'error');
+      return false;
+    }
+  };
+
   // 查看 Peer 列表
   const handleViewPeerList = async (tunnelId) => {
     try {
@@ -1313,6 +1728,14 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
         >
           🔄 刷新
         </button>
+        <button
+          onClick={handleExportStatusHtml}
+          className="btn-secondary"
+          disabled={loading || tunnels.length === 0}
+          title="导出不含密钥和地址的隧道状态页面"
+        >
+          📄 导出状态页
+        </button>
         {/* Linux 守护进程管理按钮 */}
         {isLinux && daemonStatus && (
           <button
@@ -1325,6 +1748,27 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
         )}
       </div>
 
+      {/* 缺少外部组件时提示，避免启动隧道时才看到平台相关的错误 */}
+      {prereqStatus && (
+        (isWindows && !prereqStatus.wg_tools_found) ||
+        (!isWindows && !prereqStatus.wireguard_go_found) ||
+        (isLinux && !prereqStatus.daemon_running)
+      ) && (
+        <div className="prereq-banner">
+          <strong>⚠️ 请先安装 WireGuard</strong>
+          <ul>
+            {isWindows && !prereqStatus.wg_tools_found && (
+              <li>未找到 wireguard.exe / wg.exe，请先安装官方 WireGuard 客户端</li>
+            )}
+            {!isWindows && !prereqStatus.wireguard_go_found && (
+              <li>未找到 wireguard-go，请重新安装应用或手动安装 wireguard-go</li>
+            )}
+            {isLinux && !prereqStatus.daemon_running && (
+              <li>守护进程未运行，请在“守护进程”面板中安装并启动</li>
+            )}
+          </ul>
+        </div>
+      )}
 
       {tunnels.length === 0 ? (
         <div className="empty-state">
@@ -1352,6 +1796,12 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
               onEdit={handleEditTunnel}
               onViewDetails={handleViewDetails}
               onViewPeerList={handleViewPeerList}
+              onResetStats={handleResetStats}
+              onMarkLastKnownGood={handleMarkLastKnownGood}
+              onRollback={handleRollbackTunnel}
+              onToggleAutostart={handleToggleAutostart}
+              onToggleReconnect={handleToggleReconnect}
+              onDuplicate={handleDuplicateTunnel}
               onDelete={handleDeleteTunnel}
               formatBytes={formatBytes}
               formatTime={formatTime}
@@ -1456,6 +1906,15 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                       生成密钥
                     </button>
                   </div>
+                  <label>
+                    <input
+                      type="checkbox"
+                      checked={config.storeKeyInKeychain}
+                      onChange={(e) => setConfig({ ...config, storeKeyInKeychain: e.target.checked })}
+                    />
+                    {" "}保存到系统钥匙串
+                  </label>
+                  <small>配置文件中只保存引用，钥匙串不可用时回退为明文存储</small>
                 </div>
 
                 {/* 显示公钥 */}
@@ -1544,6 +2003,7 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                         type="number"
                         value={config.listenPort}
                         onChange={(e) => setConfig({ ...config, listenPort: e.target.value })}
+                        onBlur={handleCheckListenPort}
                         placeholder="请输入监听端口"
                       />
                       <button
@@ -1561,13 +2021,75 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                   </div>
                   <div className="form-group">
                     <label>MTU</label>
-                    <input
-                      type="number"
-                      value={config.mtu}
-                      onChange={(e) => setConfig({ ...config, mtu: e.target.value })}
-                      placeholder="1420"
-                    />
+                    <div className="input-with-button">
+                      <input
+                        type="number"
+                        value={config.mtu}
+                        onChange={(e) => setConfig({ ...config, mtu: e.target.value })}
+                        placeholder="1420"
+                      />
+                      <button
+                        onClick={handleDetectMtu}
+                        className="btn-inline"
+                        type="button"
+                        disabled={detectingMtu}
+                        title="按到第一个 Peer Endpoint 的路径 MTU 计算"
+                      >
+                        {detectingMtu ? '检测中...' : '自动检测'}
+                      </button>
+                    </div>
+                  </div>
+                </div>
+
+                {/* 策略路由（仅 Linux 守护进程支持） */}
+                {isLinux && (
+                  <div className="form-row">
+                    <div className="form-group">
+                      <label>路由表 (可选)</label>
+                      <input
+                        type="number"
+                        value={config.routeTable}
+                        onChange={(e) => setConfig({ ...config, routeTable: e.target.value })}
+                        placeholder="留空使用 main 表"
+                      />
+                    </div>
+                    <div className="form-group">
+                      <label>FwMark (可选)</label>
+                      <input
+                        type="number"
+                        value={config.fwmark}
+                        onChange={(e) => setConfig({ ...config, fwmark: e.target.value })}
+                        placeholder="与路由表同时设置时添加策略路由规则"
+                      />
+                    </div>
+                  </div>
+                )}
+
+                {/* Kill Switch（Windows 由官方客户端在全局路由时自动启用） */}
+                {!isWindows && config.mode === 'client' && (
+                  <div className="form-group">
+                    <label>
+                      <input
+                        type="checkbox"
+                        checked={config.killSwitch}
+                        onChange={(e) => setConfig({ ...config, killSwitch: e.target.checked })}
+                      />
+                      {" "}Kill Switch
+                    </label>
+                    <small>隧道意外断开时阻止所有不经过隧道的出站流量（发往 Peer Endpoint 的流量除外），停止隧道后恢复</small>
                   </div>
+                )}
+
+                <div className="form-group">
+                  <label>受保护网段（可选，所有隧道共用）</label>
+                  <input
+                    type="text"
+                    value={protectedSubnets}
+                    onChange={(e) => setProtectedSubnets(e.target.value)}
+                    onBlur={handleSaveProtectedSubnets}
+                    placeholder="例如: 192.168.10.0/24（SSH 管理网络）"
+                  />
+                  <small>AllowedIPs 包含这些网段时保存前会发出警告，避免远程管理时隧道接管 SSH 流量导致断连</small>
                 </div>
 
                 <div className="form-group">
@@ -1581,6 +2103,17 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                   <small>多个 DNS 用逗号分隔（支持 IPv4 和 IPv6）</small>
                 </div>
 
+                <div className="form-group">
+                  <label>DNS 搜索域（可选）</label>
+                  <input
+                    type="text"
+                    value={config.dnsSearch}
+                    onChange={(e) => setConfig({ ...config, dnsSearch: e.target.value })}
+                    placeholder="例如: corp.internal"
+                  />
+                  <small>追加到配置文件的 DNS 行，多个域名用逗号分隔</small>
+                </div>
+
                 {/* 服务端特定的配置 */}
                 {config.mode === 'server' && (
                   <>
@@ -1600,7 +2133,29 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                         >
                           获取公网IP
                         </button>
+                        <button
+                          type="button"
+                          className="btn-inline"
+                          onClick={handleSuggestEndpoint}
+                          title="检测公网 IP 和局域网 IP，并推荐一个空闲的监听端口"
+                        >
+                          推荐地址
+                        </button>
                       </div>
+                      {endpointCandidates.length > 0 && (
+                        <div style={{ display: 'flex', flexWrap: 'wrap', gap: '0.5rem', marginTop: '0.5rem' }}>
+                          {endpointCandidates.map((candidate) => (
+                            <button
+                              key={candidate}
+                              type="button"
+                              className="btn-secondary"
+                              onClick={() => handlePickEndpoint(candidate)}
+                            >
+                              {candidate}
+                            </button>
+                          ))}
+                        </div>
+                      )}
                       <small>用于客户端连接，生成的客户端配置会自动带入此地址，请输入公网 IP 或域名</small>
                     </div>
                     <div className="form-group">
@@ -1715,6 +2270,7 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                               type="text"
                               value={peer.publicKey}
                               onChange={(e) => handleUpdatePeer(index, 'publicKey', e.target.value)}
+                              onBlur={(e) => handleValidateKey(e.target.value, 'public')}
                               placeholder="输入对端的公钥"
                               className="monospace-input"
                             />
@@ -1727,6 +2283,7 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                                 type="text"
                                 value={peer.presharedKey}
                                 onChange={(e) => handleUpdatePeer(index, 'presharedKey', e.target.value)}
+                                onBlur={(e) => handleValidateKey(e.target.value, 'preshared')}
                                 placeholder="点击生成或手动输入"
                                 className="monospace-input"
                               />
@@ -1785,6 +2342,7 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                             type="text"
                             value={config.peers[0]?.publicKey || ''}
                             onChange={(e) => handleUpdatePeer(0, 'publicKey', e.target.value)}
+                            onBlur={(e) => handleValidateKey(e.target.value, 'public')}
                             placeholder="输入服务端的公钥"
                             className="monospace-input"
                           />
@@ -1797,6 +2355,7 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                               type="text"
                               value={config.peers[0]?.presharedKey || ''}
                               onChange={(e) => handleUpdatePeer(0, 'presharedKey', e.target.value)}
+                              onBlur={(e) => handleValidateKey(e.target.value, 'preshared')}
                               placeholder="点击生成或手动输入"
                               className="monospace-input"
                             />
@@ -1812,12 +2371,21 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
 
                         <div className="form-group">
                           <label>服务端地址 (Endpoint) *</label>
-                          <input
-                            type="text"
-                            value={config.peers[0]?.endpoint || ''}
-                            onChange={(e) => handleUpdatePeer(0, 'endpoint', e.target.value)}
-                            placeholder="例如: vpn.example.com:51820"
-                          />
+                          <div className="input-with-button">
+                            <input
+                              type="text"
+                              value={config.peers[0]?.endpoint || ''}
+                              onChange={(e) => handleUpdatePeer(0, 'endpoint', e.target.value)}
+                              placeholder="例如: vpn.example.com:51820"
+                            />
+                            <button
+                              type="button"
+                              className="btn-inline"
+                              onClick={() => handlePingEndpoint(config.peers[0]?.endpoint)}
+                            >
+                              测试延迟
+                            </button>
+                          </div>
                           <small>格式: 域名或IP:端口</small>
                         </div>
 
@@ -1836,7 +2404,7 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                           <label>保持连接 (PersistentKeepalive)</label>
                           <input
                             type="number"
-                            value={config.peers[0]?.persistentKeepalive || 25}
+                            value={config.peers[0]?.persistentKeepalive ?? 0}
                             onChange={(e) => handleUpdatePeer(0, 'persistentKeepalive', parseInt(e.target.value) || 0)}
                             placeholder="25"
                           />
@@ -1884,6 +2452,9 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
         onClose={() => setSelectedTunnel(null)}
         formatBytes={formatBytes}
         formatTime={formatTime}
+        onExportConf={handleExportConf}
+        onProfileApplied={loadTunnels}
+        showWireguardGoLog={!isWindows}
       />
 
 {/* 隧道模式选择对话框 */}
@@ -1927,6 +2498,15 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
                   }}
                 />
               </div>
+              <div className="form-group">
+                <label>到期时间（可选）</label>
+                <input
+                  type="datetime-local"
+                  value={tempExpiresAt}
+                  onChange={(e) => setTempExpiresAt(e.target.value)}
+                />
+                <small style={{ color: '#666' }}>到期后该客户端会自动从运行中的隧道移除，留空表示永久有效</small>
+              </div>
             </div>
             <div className="modal-footer">
               <button onClick={() => setShowRemarkInput(false)} className="btn-secondary">
@@ -1947,6 +2527,8 @@ peer = (public-key = ${targetTunnel.public_key || ''}, allowed-ips = ${serverAll
           tunnel={peerListTunnel}
           onClose={handleClosePeerList}
           onViewPeerConfig={(index) => setSelectedPeerForConfig(index)}
+          onExportCsv={handleExportPeerTraffic}
+          onRenamePeer={handleRenamePeer}
           formatBytes={formatBytes}
           formatTime={formatTime}
         />
@@ -1,6 +1,8 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { save, open } from '@tauri-apps/plugin-dialog';
 import { useToast } from '../../hooks/useToast';
+import { useSyncProgressListener } from '../../hooks/useSyncProgressListener';
 import Toast from '../../components/Toast';
 import './style.css';
 
@@ -8,9 +10,13 @@ function WebDavSettingsView({  onConfigChange }) {
   const { messages, showToast, removeToast } = useToast();
   const [config, setConfig] = useState({
     enabled: false,
+    backend_type: 'webdav', // webdav 或 sftp
     server_url: '',
     username: '',
     password: '',
+    ssh_key_path: '', // SFTP 私钥路径，为空时使用密码认证
+    encryption_password: '', // 设置后上传前加密配置文件
+    conflict_strategy: 'newer_wins', // 双向同步冲突处理策略
     sync_interval: 300,
     auto_sync_enabled: false,
   });
@@ -19,14 +25,27 @@ function WebDavSettingsView({  onConfigChange }) {
   const [testing, setTesting] = useState(false);
   const [syncing, setSyncing] = useState(false);
   const [testResult, setTestResult] = useState(null);
+  const [urlIssues, setUrlIssues] = useState([]); // 服务器地址校验结果
+  const [deletionRecords, setDeletionRecords] = useState(null); // 待同步的删除记录
+  const [exportSecrets, setExportSecrets] = useState(false); // 导出设置时包含密码等敏感信息
+  const [profiles, setProfiles] = useState([]); // 已保存的命名配置
+  const [profileName, setProfileName] = useState(''); // 选中或新建的配置名称
   const [syncResult, setSyncResult] = useState(null);
+  const [syncProgress, setSyncProgress] = useState(null); // 当前同步进度
   const [lastSyncInfo, setLastSyncInfo] = useState(null); // 最后同步信息
   const [currentTime, setCurrentTime] = useState(Date.now()); // 用于触发时间更新
 
+  // 同步进度事件
+  const handleSyncProgress = useCallback((progress) => {
+    setSyncProgress(progress);
+  }, []);
+  useSyncProgressListener(handleSyncProgress);
+
   // 加载配置和同步信息
   useEffect(() => {
     loadConfig();
     loadLastSyncInfo();
+    loadProfiles();
   }, []);
 
   
@@ -55,6 +74,80 @@ function WebDavSettingsView({  onConfigChange }) {
     }
   };
 
+  // 服务器地址中包含凭据时自动拆分为各个字段
+  const handleServerUrlChange = async (value) => {
+    if (/^https?:\/\/[^/@\s]+:[^/@\s]+@/i.test(value.trim())) {
+      try {
+        const parsed = await invoke('parse_webdav_url', { input: value });
+        setConfig({
+          ...config,
+          server_url: parsed.server_url,
+          username: parsed.username,
+          password: parsed.password,
+        });
+        showToast('已从 URL 中解析出用户名和密码', 'success');
+        return;
+      } catch (error) {
+        showToast(`解析 URL 失败: ${error}`, 'error');
+      }
+    }
+    setConfig({ ...config, server_url: value });
+  };
+
+  // 离开服务器地址输入框时校验配置
+  const handleServerUrlBlur = async () => {
+    if (!config.server_url.trim()) {
+      setUrlIssues([]);
+      return;
+    }
+    try {
+      const warnings = await invoke('validate_webdav_config', { config: { ...config, enabled: false } });
+      setUrlIssues(warnings);
+    } catch (error) {
+      setUrlIssues([String(error)]);
+    }
+  };
+
+  const loadProfiles = async () => {
+    try {
+      setProfiles(await invoke('list_webdav_profiles'));
+    } catch (error) {
+      console.error('加载 WebDAV 配置列表失败:', error);
+    }
+  };
+
+  // 将当前表单另存为命名配置
+  const handleSaveProfile = async () => {
+    try {
+      await invoke('save_webdav_profile', { name: profileName, config });
+      showToast(`配置 ${profileName.trim()} 已保存`, 'success');
+      loadProfiles();
+    } catch (error) {
+      showToast(`保存配置失败: ${error}`, 'error');
+    }
+  };
+
+  // 载入命名配置到表单，点击"保存配置"后生效
+  const handleLoadProfile = async () => {
+    try {
+      setConfig(await invoke('load_webdav_profile', { name: profileName }));
+      showToast(`已载入配置 ${profileName}，保存后生效`, 'info');
+    } catch (error) {
+      showToast(`载入配置失败: ${error}`, 'error');
+    }
+  };
+
+  const handleDeleteProfile = async () => {
+    try {
+      await invoke('delete_webdav_profile', { name: profileName });
+      showToast(`配置 ${profileName} 已删除`, 'success');
+      setProfileName('');
+      loadProfiles();
+    } catch (error) {
+      showToast(`删除配置失败: ${error}`, 'error');
+    }
+  };
+
   const loadLastSyncInfo = async () => {
     try {
       const syncInfo = await invoke('load_last_sync_info');
@@ -64,8 +157,15 @@ function WebDavSettingsView({  onConfigChange }) {
     }
   };
 
+  // SFTP 使用私钥认证时可以不填密码
+  const isSftp = config.backend_type === 'sftp';
+  const hasCredentials = Boolean(config.password || (isSftp && config.ssh_key_path));
+
   const handleSave = async () => {
     try {
+      const warnings = await invoke('validate_webdav_config', { config });
+      warnings.forEach((warning) => showToast(warning, 'warning'));
+
       await invoke('save_webdav_config', { config });
       showToast('配置保存成功！', 'success');
       setTestResult(null); // 清除测试结果
@@ -79,7 +179,7 @@ function WebDavSettingsView({  onConfigChange }) {
   };
 
   const handleTest = async () => {
-    if (!config.server_url || !config.username || !config.password) {
+    if (!config.server_url || !config.username || !hasCredentials) {
       showToast('请填写完整的服务器地址、用户名和密码', 'warning');
       return;
     }
@@ -97,6 +197,90 @@ function WebDavSettingsView({  onConfigChange }) {
     }
   };
 
+  // 测试所有已保存的 WebDAV 配置
+  const handleTestAll = async () => {
+    setTesting(true);
+    setTestResult(null);
+
+    try {
+      const results = await invoke('test_all_webdav_profiles');
+      if (results.length === 0) {
+        setTestResult({ success: false, message: '没有已保存的 WebDAV 配置' });
+        return;
+      }
+      const failed = results.filter(r => !r.ok);
+      setTestResult({
+        success: failed.length === 0,
+        message: failed.length === 0
+          ? `全部 ${results.length} 个配置连接成功`
+          : failed.map(r => `${r.name}: ${r.error}`).join('；'),
+      });
+    } catch (error) {
+      setTestResult({ success: false, message: `测试失败: ${error}` });
+    } finally {
+      setTesting(false);
+    }
+  };
+
+  // 导出应用设置（默认不包含密码、加密密码和私钥路径）
+  const handleExportSettings = async () => {
+    try {
+      const content = await invoke('export_settings', { includeSecrets: exportSecrets });
+      const filePath = await save({
+        defaultPath: 'wirevault-settings.json',
+        filters: [{ name: '设置文件', extensions: ['json'] }],
+      });
+      if (filePath) {
+        await invoke('save_config_to_path', { content, filePath });
+        showToast('设置已导出', 'success');
+      }
+    } catch (error) {
+      showToast('导出设置失败: ' + error, 'error');
+    }
+  };
+
+  // 导入应用设置
+  const handleImportSettings = async () => {
+    try {
+      const filePath = await open({
+        title: '选择设置文件',
+        filters: [{ name: '设置文件', extensions: ['json'] }],
+      });
+      if (!filePath) return;
+
+      const json = await invoke('read_file_content', { filePath });
+      await invoke('import_settings', { json });
+      await loadConfig();
+      if (onConfigChange) {
+        onConfigChange();
+      }
+      showToast('设置已导入', 'success');
+    } catch (error) {
+      showToast('导入设置失败: ' + error, 'error');
+    }
+  };
+
+  // 查看待同步的删除记录
+  const handleInspectDeletions = async () => {
+    try {
+      const records = await invoke('inspect_deletion_records');
+      setDeletionRecords(records);
+    } catch (error) {
+      showToast('读取删除记录失败: ' + error, 'error');
+    }
+  };
+
+  // 清除删除记录（同步异常时使用）
+  const handleClearDeletions = async () => {
+    try {
+      await invoke('clear_deletion_records_manual');
+      setDeletionRecords({ servers: [], history: [] });
+      showToast('删除记录已清除', 'success');
+    } catch (error) {
+      showToast('清除删除记录失败: ' + error, 'error');
+    }
+  };
+
   const handleSync = async () => {
     if (!config.enabled) {
       showToast('请先启用 WebDAV 同步并保存配置', 'warning');
@@ -105,6 +289,7 @@ function WebDavSettingsView({  onConfigChange }) {
 
     setSyncing(true);
     setSyncResult(null);
+    setSyncProgress(null);
 
     try {
       // 使用双向同步
@@ -125,6 +310,7 @@ function WebDavSettingsView({  onConfigChange }) {
       });
     } finally {
       setSyncing(false);
+      setSyncProgress(null);
     }
   };
 
@@ -208,18 +394,47 @@ function WebDavSettingsView({  onConfigChange }) {
               </label>
             </div>
 
+            <div className="webdav-form-group">
+              <label>同步方式</label>
+              <select
+                value={config.backend_type}
+                onChange={(e) => {
+                  setConfig({ ...config, backend_type: e.target.value });
+                  setUrlIssues([]);
+                }}
+                disabled={!config.enabled}
+              >
+                <option value="webdav">WebDAV</option>
+                <option value="sftp">SFTP (SSH)</option>
+              </select>
+            </div>
+
             <div className="webdav-form-group">
               <label>服务器地址</label>
               <input
                 type="text"
-                placeholder="https://your-webdav-server.com/dav"
+                placeholder={isSftp ? 'sftp://your-ssh-server.com:22/home/user/wirevault' : 'https://your-webdav-server.com/dav'}
                 value={config.server_url}
-                onChange={(e) => setConfig({ ...config, server_url: e.target.value })}
+                onChange={(e) => handleServerUrlChange(e.target.value)}
+                onBlur={handleServerUrlBlur}
                 disabled={!config.enabled}
               />
-              <small className="webdav-help-text">
-                WebDAV 服务器地址，例如：https://dav.example.com/remote.php/dav/files/username/
-              </small>
+              {urlIssues.map((issue) => (
+                <small key={issue} className="webdav-help-text" style={{ color: '#e67e22' }}>
+                  ⚠️ {issue}
+                </small>
+              ))}
+              {isSftp ? (
+                <small className="webdav-help-text">
+                  SFTP 地址，例如：sftp://example.com:22/home/user/wirevault （路径留空时使用登录用户的主目录）。
+                  首次使用前请先通过 ssh 命令连接一次，确认主机指纹已写入 known_hosts
+                </small>
+              ) : (
+                <small className="webdav-help-text">
+                  WebDAV 服务器地址，例如：https://dav.example.com/remote.php/dav/files/username/
+                  （也可直接粘贴 https://用户名:密码@服务器/路径 自动填写账号信息）
+                </small>
+              )}
             </div>
 
             <div className="webdav-form-group">
@@ -247,6 +462,53 @@ function WebDavSettingsView({  onConfigChange }) {
               </small>
             </div>
 
+            {isSftp && (
+              <div className="webdav-form-group">
+                <label>SSH 私钥路径（可选）</label>
+                <input
+                  type="text"
+                  placeholder="例如：/home/user/.ssh/id_ed25519"
+                  value={config.ssh_key_path}
+                  onChange={(e) => setConfig({ ...config, ssh_key_path: e.target.value })}
+                  disabled={!config.enabled}
+                />
+                <small className="webdav-help-text">
+                  填写后使用私钥认证，此时上方密码作为私钥密码（私钥未加密时留空）
+                </small>
+              </div>
+            )}
+
+            <div className="webdav-form-group">
+              <label>加密密码（可选）</label>
+              <input
+                type="password"
+                placeholder="留空则不加密"
+                value={config.encryption_password || ''}
+                onChange={(e) => setConfig({ ...config, encryption_password: e.target.value || null })}
+                disabled={!config.enabled}
+              />
+              <small className="webdav-help-text">
+                设置后配置文件在上传前加密，远程服务器只能看到密文。所有设备需使用相同的加密密码，忘记密码将无法恢复远程数据
+              </small>
+            </div>
+
+            <div className="webdav-form-group">
+              <label>冲突处理</label>
+              <select
+                value={config.conflict_strategy || 'newer_wins'}
+                onChange={(e) => setConfig({ ...config, conflict_strategy: e.target.value })}
+                disabled={!config.enabled}
+              >
+                <option value="newer_wins">修改时间较新的一方优先</option>
+                <option value="local_wins">以本地为准</option>
+                <option value="remote_wins">以远程为准</option>
+                <option value="keep_both">保留两份</option>
+              </select>
+              <small className="webdav-help-text">
+                本地和远程文件都被修改过时的处理方式。保留两份时远程版本会另存到 conflicts 目录下的 名称.remote-时间戳.json
+              </small>
+            </div>
+
             <div className="webdav-form-group">
               <label>自动同步间隔（秒）</label>
               <input
@@ -266,17 +528,66 @@ function WebDavSettingsView({  onConfigChange }) {
               <button
                 className="webdav-btn-primary"
                 onClick={handleSave}
-                disabled={!config.enabled || !config.server_url || !config.username || !config.password}
+                disabled={!config.enabled || !config.server_url || !config.username || !hasCredentials}
               >
                 保存配置
               </button>
               <button
                 className="webdav-btn-secondary"
                 onClick={handleTest}
-                disabled={testing || !config.server_url || !config.username || !config.password}
+                disabled={testing || !config.server_url || !config.username || !hasCredentials}
               >
                 {testing ? '测试中...' : '测试连接'}
               </button>
+              <button
+                className="webdav-btn-secondary"
+                onClick={handleTestAll}
+                disabled={testing}
+              >
+                测试全部配置
+              </button>
+            </div>
+
+            <div className="webdav-form-group">
+              <label>已保存的配置</label>
+              <input
+                type="text"
+                list="webdav-profiles"
+                placeholder="输入名称另存当前表单，或选择已保存的配置"
+                value={profileName}
+                onChange={(e) => setProfileName(e.target.value)}
+              />
+              <datalist id="webdav-profiles">
+                {profiles.map((name) => (
+                  <option key={name} value={name} />
+                ))}
+              </datalist>
+              <small className="webdav-help-text">
+                可保存多个服务器配置，"测试全部配置"会同时测试当前配置和所有已保存的配置
+              </small>
+            </div>
+            <div className="webdav-button-group">
+              <button
+                className="webdav-btn-secondary"
+                onClick={handleSaveProfile}
+                disabled={!profileName.trim() || !config.server_url}
+              >
+                另存为
+              </button>
+              <button
+                className="webdav-btn-secondary"
+                onClick={handleLoadProfile}
+                disabled={!profiles.includes(profileName)}
+              >
+                载入
+              </button>
+              <button
+                className="webdav-btn-secondary"
+                onClick={handleDeleteProfile}
+                disabled={!profiles.includes(profileName)}
+              >
+                删除
+              </button>
             </div>
 
             {/* 测试结果 */}
@@ -339,7 +650,57 @@ function WebDavSettingsView({  onConfigChange }) {
               >
                 {syncing ? '同步中...' : '立即同步'}
               </button>
+              <button className="webdav-btn-secondary" onClick={handleExportSettings}>
+                导出设置
+              </button>
+              <button className="webdav-btn-secondary" onClick={handleImportSettings}>
+                导入设置
+              </button>
+              <button className="webdav-btn-secondary" onClick={handleInspectDeletions}>
+                查看删除记录
+              </button>
             </div>
+            <label className="webdav-checkbox-label">
+              <input
+                type="checkbox"
+                checked={exportSecrets}
+                onChange={(e) => setExportSecrets(e.target.checked)}
+              />
+              <span>导出设置时包含密码、同步加密密码和 SSH 私钥路径</span>
+            </label>
+
+            {/* 同步进度 */}
+            {syncing && syncProgress && syncProgress.total > 0 && (
+              <div className="webdav-sync-progress">
+                <div className="webdav-progress-bar">
+                  <div
+                    className="webdav-progress-fill"
+                    style={{ width: `${Math.round((syncProgress.current / syncProgress.total) * 100)}%` }}
+                  />
+                </div>
+                <small className="webdav-help-text">
+                  {syncProgress.direction === 'upload' ? '↑ 上传' : '↓ 下载'} {syncProgress.filename}
+                  （{syncProgress.current}/{syncProgress.total}）
+                </small>
+              </div>
+            )}
+
+            {/* 待同步的删除记录 */}
+            {deletionRecords && (
+              <div className="webdav-form-group">
+                <small className="webdav-help-text">
+                  待同步删除：服务端配置 {deletionRecords.servers.length} 个，历史记录 {deletionRecords.history.length} 个
+                </small>
+                {[...deletionRecords.servers, ...deletionRecords.history].map((name) => (
+                  <small key={name} className="webdav-help-text">· {name}</small>
+                ))}
+                {(deletionRecords.servers.length > 0 || deletionRecords.history.length > 0) && (
+                  <button className="webdav-btn-secondary" onClick={handleClearDeletions}>
+                    清除删除记录
+                  </button>
+                )}
+              </div>
+            )}
 
             {/* 同步结果 */}
             {syncResult && (